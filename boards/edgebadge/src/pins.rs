@@ -37,8 +37,8 @@ define_pins!(
     pac: pac,
 
     /// Analog pin 0.  Can act as a true analog output
-    /// as it has a DAC (which is not currently supported
-    /// by this hal) as well as input.
+    /// as it has a DAC (see `hal::dac`)
+    /// as well as input.
     pin speaker = a2,
     /// enable speaker amplifier (todo even with built in buzzer?)
     pin speaker_enable = a27,
@@ -268,7 +268,7 @@ pub struct Sets {
     /// Battery Voltage
     pub battery: Battery,
 
-    /// Speaker (DAC not implemented in hal yet)
+    /// Speaker, driven by `VOUT0` of the DAC (see `hal::dac`)
     pub speaker: Speaker,
 
     /// SPI
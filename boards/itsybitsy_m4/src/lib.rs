@@ -34,8 +34,8 @@ hal::bsp_peripherals!(
 hal::bsp_pins!(
     PA02 {
         /// Analog pin 0.  Can act as a true analog output
-        /// as it has a DAC (which is not currently supported
-        /// by this hal) as well as input.
+        /// as it has a DAC (see `hal::dac`)
+        /// as well as input.
         name: a0
         aliases: {
             AlternateB: Analog0
@@ -43,8 +43,8 @@ hal::bsp_pins!(
     }
     PA05 {
         /// Analog pin 1.  Can act as a true analog output
-        /// as it has a DAC (which is not currently supported
-        /// by this hal) as well as input.
+        /// as it has a DAC (see `hal::dac`)
+        /// as well as input.
         name: a1
         aliases: {
             AlternateB: Analog1
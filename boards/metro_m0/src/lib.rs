@@ -22,8 +22,8 @@ pub mod pins {
     hal::bsp_pins!(
         PA02 {
             /// Analog pin 0.  Can act as a true analog output
-            /// as it has a DAC (which is not currently supported
-            /// by this hal) as well as input.
+            /// as it has a DAC (see `hal::dac`)
+            /// as well as input.
             name: a0
         }
         PB08 {
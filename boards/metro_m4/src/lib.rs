@@ -36,8 +36,8 @@ hal::bsp_peripherals!(
 hal::bsp_pins!(
     PA02 {
         /// Analog pin 0.  Can act as a true analog output
-        /// as it has a DAC (which is not currently supported
-        /// by this hal) as well as input.
+        /// as it has a DAC (see `hal::dac`)
+        /// as well as input.
         name: a0
     }
     PA05 {
@@ -41,8 +41,8 @@ pub mod aliases {
     bsp_pins!(
 
         /// Analog pin 0. Can act as a true analog output
-        /// as it has a DAC (which is not currently supported
-        /// by this hal) as well as input.
+        /// as it has a DAC (see `hal::dac`)
+        /// as well as input.
         PA02 {
             name: speaker
             aliases: {
@@ -546,7 +546,7 @@ pub struct Sets {
     /// Battery Voltage
     pub battery: Battery,
 
-    /// Speaker (DAC not implemented in hal yet)
+    /// Speaker, driven by `VOUT0` of the DAC (see `hal::dac`)
     pub speaker: Speaker,
 
     /// SPI (connected to SD Card)
@@ -193,6 +193,37 @@ declare_interrupts!(TC6);
 #[hal_cfg("tc7")]
 declare_interrupts!(TC7);
 
+// ----------  TCC Interrupts ---------- //
+#[hal_cfg("tcc0-d21")]
+declare_interrupts!(TCC0);
+
+#[hal_cfg("tcc1-d21")]
+declare_interrupts!(TCC1);
+
+#[hal_cfg("tcc2-d21")]
+declare_interrupts!(TCC2);
+
+#[hal_cfg("tcc0-d5x")]
+declare_multiple_interrupts!(TCC0: [TCC0_OTHER, TCC0_MC0, TCC0_MC1, TCC0_MC2, TCC0_MC3, TCC0_MC4, TCC0_MC5]);
+
+#[hal_cfg("tcc1-d5x")]
+declare_multiple_interrupts!(TCC1: [TCC1_OTHER, TCC1_MC0, TCC1_MC1, TCC1_MC2, TCC1_MC3]);
+
+#[hal_cfg("tcc2-d5x")]
+declare_multiple_interrupts!(TCC2: [TCC2_OTHER, TCC2_MC0, TCC2_MC1, TCC2_MC2]);
+
+#[hal_cfg("tcc3")]
+declare_multiple_interrupts!(TCC3: [TCC3_OTHER, TCC3_MC0, TCC3_MC1]);
+
+#[hal_cfg("tcc4")]
+declare_multiple_interrupts!(TCC4: [TCC4_OTHER, TCC4_MC0, TCC4_MC1]);
+
+// ----------  WDT Interrupt ---------- //
+declare_interrupts!(WDT);
+
+// ----------  RTC Interrupt ---------- //
+declare_interrupts!(RTC);
+
 // ----------  EIC Interrupt ---------- //
 #[hal_cfg(any("eic-d11", "eic-d21"))]
 declare_interrupts!(EIC);
@@ -214,6 +245,38 @@ declare_multiple_interrupts!(ADC0: [ADC0_RESRDY, ADC0_OTHER]);
 declare_multiple_interrupts!(ADC1: [ADC1_RESRDY, ADC1_OTHER]);
 #[hal_cfg(any("adc-d11", "adc-d21"))]
 declare_interrupts!(ADC);
+
+// ----------  AC Interrupt ---------- //
+#[hal_cfg("ac")]
+declare_interrupts!(AC);
+
+// ----------  SDHC Interrupts ---------- //
+#[hal_cfg("sdhc0")]
+declare_interrupts!(SDHC0);
+
+#[hal_cfg("sdhc1")]
+declare_interrupts!(SDHC1);
+
+// ----------  PDEC Interrupts ---------- //
+#[hal_cfg("pdec")]
+declare_multiple_interrupts!(PDEC: [PDEC_OTHER, PDEC_MC0, PDEC_MC1]);
+
+// ----------  FREQM Interrupt ---------- //
+#[hal_cfg("freqm")]
+declare_interrupts!(FREQM);
+
+// ----------  SUPC Interrupts ---------- //
+#[hal_cfg("supc")]
+declare_multiple_interrupts!(SUPC: [SUPC_OTHER, SUPC_BODDET]);
+
+// ----------  RAMECC Interrupt ---------- //
+#[hal_cfg("ramecc")]
+declare_interrupts!(RAMECC);
+
+// ----------  PAC Interrupt ---------- //
+#[hal_cfg("pac-d5x")]
+declare_interrupts!(PAC);
+
 /// An interrupt source that may have one or many interrupt bindings.
 ///
 /// This trait may implemented directly when multiple interrupt sources are
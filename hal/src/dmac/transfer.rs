@@ -577,6 +577,9 @@ where
         };
 
         let old_buffers = core::mem::replace(&mut self.buffers, new_buffers);
+        // Reset the complete flag before restarting, so that an interrupt
+        // handler may set it as soon as the new transfer is done
+        self.complete = false;
         self.chan.as_mut().restart();
         Ok((old_buffers.source, old_buffers.destination))
     }
@@ -602,6 +605,7 @@ where
         }
 
         let old_destination = core::mem::replace(&mut self.buffers.destination, destination);
+        self.complete = false;
         self.chan.as_mut().restart();
         Ok(old_destination)
     }
@@ -627,6 +631,7 @@ where
         }
 
         let old_source = core::mem::replace(&mut self.buffers.source, source);
+        self.complete = false;
         self.chan.as_mut().restart();
         Ok(old_source)
     }
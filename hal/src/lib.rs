@@ -4,6 +4,7 @@ pub use embedded_hal_1 as ehal;
 use embedded_hal_02 as ehal_02;
 pub use embedded_hal_nb as ehal_nb;
 pub use embedded_io;
+pub use embedded_storage;
 pub use fugit;
 pub use nb;
 pub use paste;
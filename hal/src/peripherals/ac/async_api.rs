@@ -0,0 +1,265 @@
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::*;
+use crate::async_hal::interrupts::{AC, Binding, Handler, InterruptSource};
+
+/// Interrupt handler for the AC peripheral.
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Sealed for InterruptHandler {}
+
+impl Handler<AC> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        let ac = unsafe { pac::Peripherals::steal().ac };
+
+        let flags_pending = Flags::from_bits_truncate(ac.intflag().read().bits());
+        let enabled_flags = Flags::from_bits_truncate(ac.intenset().read().bits());
+        let flags = flags_pending & enabled_flags;
+
+        // Disable the interrupts but don't clear the flags; they will be cleared
+        // when the future is next polled.
+        ac.intenclr().write(|w| unsafe { w.bits(flags.bits()) });
+
+        if flags.contains(Flags::COMP0) {
+            WAKERS[0].wake();
+        }
+        if flags.contains(Flags::COMP1) {
+            WAKERS[1].wake();
+        }
+        if flags.contains(Flags::WIN0) {
+            WAKERS[WINDOW_WAKER].wake();
+        }
+    }
+}
+
+/// Index of the window waker in [`WAKERS`]
+const WINDOW_WAKER: usize = 2;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_WAKER: AtomicWaker = AtomicWaker::new();
+static WAKERS: [AtomicWaker; 3] = [NEW_WAKER; 3];
+
+/// Wait until `is_interrupt` returns `true`, then clear the interrupt flag.
+///
+/// `enable` and `clear` must respectively enable and clear the interrupt
+/// flag being waited on.
+async fn wait_flag(
+    waker: &AtomicWaker,
+    is_interrupt: impl Fn() -> bool,
+    enable: impl Fn(),
+    clear: impl Fn(),
+) {
+    core::future::poll_fn(|cx| {
+        if is_interrupt() {
+            clear();
+            return Poll::Ready(());
+        }
+
+        waker.register(cx.waker());
+        enable();
+
+        if is_interrupt() {
+            clear();
+            return Poll::Ready(());
+        }
+
+        Poll::Pending
+    })
+    .await;
+}
+
+//==============================================================================
+// FutureComparator
+//==============================================================================
+
+/// A [`Comparator`] capable of `async` operations
+pub struct FutureComparator<Id: CmpId, P, N, O, F> {
+    inner: Comparator<Id, P, N, O>,
+    irqs: F,
+}
+
+impl<Id, P, N, O> Comparator<Id, P, N, O>
+where
+    Id: CmpId,
+    P: PositiveInput,
+    N: NegativeInput,
+{
+    /// Turn a [`Comparator`] into a [`FutureComparator`], allowing for the
+    /// use of async methods. You are required to provide the struct created
+    /// by the [`bind_interrupts`](crate::bind_interrupts) macro to prove
+    /// that the interrupt sources have been correctly configured. This
+    /// function will automatically enable the `AC` NVIC interrupt source.
+    pub fn into_future<F>(self, irqs: F) -> FutureComparator<Id, P, N, O, F>
+    where
+        F: Binding<AC, InterruptHandler>,
+    {
+        AC::unpend();
+        unsafe { AC::enable() };
+        FutureComparator { inner: self, irqs }
+    }
+}
+
+impl<Id, P, N, O, F> FutureComparator<Id, P, N, O, F>
+where
+    Id: CmpId,
+    P: PositiveInput,
+    N: NegativeInput,
+    F: Binding<AC, InterruptHandler>,
+{
+    /// Convert the [`FutureComparator`] back into a blocking [`Comparator`],
+    /// and return the IRQs
+    pub fn into_blocking(self) -> (Comparator<Id, P, N, O>, F) {
+        (self.inner, self.irqs)
+    }
+
+    /// Read the current comparator output
+    #[inline]
+    pub fn state(&self) -> bool {
+        self.inner.state()
+    }
+
+    /// Wait until the comparator output changes according to `interrupt`,
+    /// and return the new output state.
+    ///
+    /// In continuous mode, use [`InterruptSelect::Rising`],
+    /// [`InterruptSelect::Falling`] or [`InterruptSelect::Toggle`]. If the
+    /// interrupt condition differs from the current configuration, the
+    /// comparator is briefly disabled to reconfigure it.
+    pub async fn wait(&mut self, interrupt: InterruptSelect) -> bool {
+        self.inner.disable_interrupt();
+        self.set_interrupt_select(interrupt);
+        // Only wait for the changes which occur from now on
+        self.inner.clear_interrupt();
+        self.wait_interrupt().await;
+        self.inner.state()
+    }
+
+    /// Wait until the comparator output rises
+    #[inline]
+    pub async fn wait_for_rising_edge(&mut self) {
+        self.wait(InterruptSelect::Rising).await;
+    }
+
+    /// Wait until the comparator output falls
+    #[inline]
+    pub async fn wait_for_falling_edge(&mut self) {
+        self.wait(InterruptSelect::Falling).await;
+    }
+
+    /// Wait until the comparator output toggles, and return the new output
+    /// state
+    #[inline]
+    pub async fn wait_for_toggle(&mut self) -> bool {
+        self.wait(InterruptSelect::Toggle).await
+    }
+
+    /// Perform a comparison and return its result.
+    ///
+    /// In single-shot mode, this starts a new comparison and waits for its
+    /// completion. In continuous mode, this is equivalent to
+    /// [`state`](Self::state).
+    pub async fn compare(&mut self) -> bool {
+        if self.inner.config.single_shot {
+            self.inner.disable_interrupt();
+            self.set_interrupt_select(InterruptSelect::Eoc);
+            self.inner.clear_interrupt();
+            self.inner.start();
+            self.wait_interrupt().await;
+        }
+        self.inner.state()
+    }
+
+    fn set_interrupt_select(&mut self, interrupt: InterruptSelect) {
+        if self.inner.config.interrupt != interrupt {
+            self.inner.config.interrupt = interrupt;
+            self.inner.write_compctrl();
+        }
+    }
+
+    async fn wait_interrupt(&mut self) {
+        let ac = self.inner.chan.ac();
+        let mask = Id::FLAG.bits();
+        wait_flag(
+            &WAKERS[Id::ID],
+            || ac.intflag().read().bits() & mask != 0,
+            || ac.intenset().write(|w| unsafe { w.bits(mask) }),
+            || {
+                ac.intenclr().write(|w| unsafe { w.bits(mask) });
+                ac.intflag().write(|w| unsafe { w.bits(mask) });
+            },
+        )
+        .await;
+    }
+}
+
+//==============================================================================
+// FutureWindow
+//==============================================================================
+
+/// A [`Window`] capable of `async` operations
+pub struct FutureWindow<C0, C1, F> {
+    inner: Window<C0, C1>,
+    irqs: F,
+}
+
+impl<C0, C1> Window<C0, C1> {
+    /// Turn a [`Window`] into a [`FutureWindow`], allowing for the use of
+    /// async methods. You are required to provide the struct created by the
+    /// [`bind_interrupts`](crate::bind_interrupts) macro to prove that the
+    /// interrupt sources have been correctly configured. This function will
+    /// automatically enable the `AC` NVIC interrupt source.
+    pub fn into_future<F>(self, irqs: F) -> FutureWindow<C0, C1, F>
+    where
+        F: Binding<AC, InterruptHandler>,
+    {
+        AC::unpend();
+        unsafe { AC::enable() };
+        FutureWindow { inner: self, irqs }
+    }
+}
+
+impl<C0, C1, F> FutureWindow<C0, C1, F>
+where
+    F: Binding<AC, InterruptHandler>,
+{
+    /// Convert the [`FutureWindow`] back into a blocking [`Window`], and
+    /// return the IRQs
+    pub fn into_blocking(self) -> (Window<C0, C1>, F) {
+        (self.inner, self.irqs)
+    }
+
+    /// Read the position of the input signal relative to the window
+    #[inline]
+    pub fn state(&self) -> WindowState {
+        self.inner.state()
+    }
+
+    /// Wait until the window condition selected by `interrupt` occurs, and
+    /// return the position of the input signal relative to the window.
+    ///
+    /// This briefly disables the AC to reconfigure the window interrupt.
+    pub async fn wait(&mut self, interrupt: WindowInterrupt) -> WindowState {
+        self.inner.disable_interrupt();
+        self.inner.set_interrupt(interrupt);
+        self.inner.clear_interrupt();
+
+        let ac = self.inner.ac();
+        let mask = Flags::WIN0.bits();
+        wait_flag(
+            &WAKERS[WINDOW_WAKER],
+            || ac.intflag().read().bits() & mask != 0,
+            || ac.intenset().write(|w| unsafe { w.bits(mask) }),
+            || {
+                ac.intenclr().write(|w| unsafe { w.bits(mask) });
+                ac.intflag().write(|w| unsafe { w.bits(mask) });
+            },
+        )
+        .await;
+
+        self.inner.state()
+    }
+}
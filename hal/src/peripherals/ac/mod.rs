@@ -0,0 +1,753 @@
+//! # Analog Comparator
+//!
+//! This module provides an interface to the Analog Comparator (AC)
+//! peripheral. The AC contains a pair of comparators, each of which compares
+//! the voltage on its positive input to the voltage on its negative input.
+//!
+//! ## Inputs
+//!
+//! Each comparator takes a [`PositiveInput`] and a [`NegativeInput`]. The
+//! following inputs are available:
+//!
+//! * `AIN` pins, configured in [`AlternateB`](crate::gpio::AlternateB) mode
+//!   (positive and negative);
+//! * the [`VddScaler`], which outputs a fraction of VDDANA (negative only on
+//!   SAMD11/SAMD21, positive or negative on SAMx5x);
+//! * the internal [`Bandgap`] reference (negative only);
+//! * the [`DacOutput`] (negative only);
+//! * [`Gnd`] (negative only).
+//!
+//! ## Steps to create a [`Comparator`]
+//!
+//! 1. Create an [`Ac`] by calling [`Ac::new`]. This returns the controller,
+//!    along with the [`Channels`] representing each comparator.
+//! 1. Turn a [`Channel`] into a [`Comparator`] by calling
+//!    [`Channel::with_inputs`], providing the inputs and a
+//!    [`ComparatorConfig`].
+//!
+//! ```no_run
+//! let ac_dig = clocks.ac_dig(&gclk0).unwrap();
+//! let ac_ana = clocks.ac_ana(&gclk0).unwrap();
+//! let (ac, channels) = Ac::new(&mut peripherals.pm, &ac_dig, &ac_ana, peripherals.ac);
+//!
+//! // Compare AIN0 to half of VDDANA
+//! let ain0: Pin<_, AlternateB> = pins.pa04.into();
+//! let mut cmp0 = channels.0.with_inputs(
+//!     ain0,
+//!     VddScaler::new(31),
+//!     ComparatorConfig::default(),
+//! );
+//!
+//! let above = cmp0.state();
+//! ```
+//!
+//! ## Continuous and single-shot operation
+//!
+//! By default, comparators are continuously running, and their output can
+//! be read at any time with [`Comparator::state`]. Setting
+//! [`ComparatorConfig::single_shot`] powers the comparator down between
+//! comparisons. A comparison is then started and read back by calling
+//! [`Comparator::compare`], or started by an incoming event (see
+//! [`Comparator::enable_start_event`]).
+//!
+//! ## Window mode
+//!
+//! The two comparators can be combined into a [`Window`] by calling
+//! [`Comparator::into_window`]. In window mode, the inputs of the two
+//! comparators define the upper and lower bounds of a window, and the AC
+//! reports whether the common input signal is above, inside or below it. The
+//! signal of interest should be connected to the positive input of both
+//! comparators.
+//!
+//! ## Output to a pin
+//!
+//! On SAMD21 and SAMx5x chips, the output of a comparator can be routed to a
+//! `CMP` pin by calling `Comparator::with_output`. The SAMD11 `CMP` pins are
+//! not supported yet.
+//!
+//! ## `async` operation <span class="stab portability" title="Available on crate feature `async` only"><code>async</code></span>
+//!
+//! A [`Comparator`] or [`Window`] can be turned into a [`FutureComparator`] or
+//! [`FutureWindow`] by calling `into_future`, with a struct proving that the
+//! `AC` interrupt is bound to the AC [`InterruptHandler`] (refer to the
+//! module-level [`async_hal`](crate::async_hal) documentation for more
+//! information). The resulting types provide `async` methods that wait for a
+//! rising, falling or toggling comparator output.
+
+use core::marker::PhantomData;
+
+use atsamd_hal_macros::{hal_cfg, hal_macro_helper};
+
+#[hal_cfg(any("ac-d21", "ac-d5x"))]
+use crate::gpio::{AnyPin, Pin};
+use crate::pac;
+use crate::typelevel::{NoneT, Sealed};
+
+mod pin;
+pub use pin::*;
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
+#[hal_cfg(any("ac-d11", "ac-d21"))]
+use crate::clock::{AcAnaClock, AcDigClock};
+
+#[hal_cfg("ac-d5x")]
+use crate::clock::v2::{
+    apb::ApbClk,
+    pclk::{Pclk, PclkSourceId},
+    types::Ac as AcId,
+};
+
+pub use pac::ac::compctrl::{
+    Flenselect as Filter, Intselselect as InterruptSelect, Outselect as OutputMode,
+    Speedselect as Speed,
+};
+pub use pac::ac::statusa::Wstate0select as WindowState;
+pub use pac::ac::winctrl::Wintsel0select as WindowInterrupt;
+
+#[hal_cfg("ac-d5x")]
+pub use pac::ac::compctrl::Hystselect as HysteresisLevel;
+
+bitflags::bitflags! {
+    /// AC interrupt flags
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Flags: u8 {
+        /// Comparator 0
+        const COMP0 = 0x01;
+        /// Comparator 1
+        const COMP1 = 0x02;
+        /// Window 0
+        const WIN0 = 0x10;
+    }
+}
+
+//==============================================================================
+// Comparator IDs
+//==============================================================================
+
+/// Trait representing a comparator ID.
+pub trait CmpId: Sealed {
+    /// Index of the comparator
+    const ID: usize;
+    /// Interrupt flag of the comparator
+    const FLAG: Flags;
+}
+
+/// Type-level variant of [`CmpId`] for comparator 0
+pub enum Cmp0 {}
+impl Sealed for Cmp0 {}
+impl CmpId for Cmp0 {
+    const ID: usize = 0;
+    const FLAG: Flags = Flags::COMP0;
+}
+
+/// Type-level variant of [`CmpId`] for comparator 1
+pub enum Cmp1 {}
+impl Sealed for Cmp1 {}
+impl CmpId for Cmp1 {
+    const ID: usize = 1;
+    const FLAG: Flags = Flags::COMP1;
+}
+
+//==============================================================================
+// ComparatorConfig
+//==============================================================================
+
+/// Configuration of a [`Comparator`]
+#[hal_macro_helper]
+#[derive(Clone, Copy, Debug)]
+pub struct ComparatorConfig {
+    /// Only perform a comparison when requested, instead of continuously
+    pub single_shot: bool,
+    /// Swap the positive and negative inputs, inverting the output
+    pub swap: bool,
+    /// Propagation delay versus power consumption trade-off
+    pub speed: Speed,
+    /// Enable hysteresis
+    #[hal_cfg(any("ac-d11", "ac-d21"))]
+    pub hysteresis: bool,
+    /// Hysteresis level, or `None` to disable hysteresis
+    #[hal_cfg("ac-d5x")]
+    pub hysteresis: Option<HysteresisLevel>,
+    /// Digital filtering of the comparator output
+    pub filter: Filter,
+    /// Condition which sets the comparator interrupt flag
+    pub interrupt: InterruptSelect,
+    /// Keep the comparator running in standby sleep mode
+    #[hal_cfg("ac-d5x")]
+    pub run_in_standby: bool,
+}
+
+impl Default for ComparatorConfig {
+    #[hal_macro_helper]
+    fn default() -> Self {
+        Self {
+            single_shot: false,
+            swap: false,
+            speed: Speed::High,
+            #[hal_cfg(any("ac-d11", "ac-d21"))]
+            hysteresis: false,
+            #[hal_cfg("ac-d5x")]
+            hysteresis: None,
+            filter: Filter::Off,
+            interrupt: InterruptSelect::Toggle,
+            #[hal_cfg("ac-d5x")]
+            run_in_standby: false,
+        }
+    }
+}
+
+//==============================================================================
+// Ac
+//==============================================================================
+
+/// Analog Comparator controller
+#[hal_macro_helper]
+pub struct Ac {
+    ac: pac::Ac,
+    #[hal_cfg("ac-d5x")]
+    _apbclk: ApbClk<AcId>,
+}
+
+/// Handles to the individual comparators
+pub struct Channels(pub Channel<Cmp0>, pub Channel<Cmp1>);
+
+impl Ac {
+    /// Create and enable a new [`Ac`], and return handles to its comparators.
+    ///
+    /// The digital clock is used for output filtering and synchronization, and
+    /// the analog clock is used by comparators in single-shot mode.
+    #[hal_cfg(any("ac-d11", "ac-d21"))]
+    pub fn new(
+        pm: &mut pac::Pm,
+        _dig_clock: &AcDigClock,
+        _ana_clock: &AcAnaClock,
+        ac: pac::Ac,
+    ) -> (Self, Channels) {
+        pm.apbcmask().modify(|_, w| w.ac_().set_bit());
+        let ac = Self { ac };
+        ac.init();
+        (ac, Channels(Channel::new(), Channel::new()))
+    }
+
+    /// Create and enable a new [`Ac`], and return handles to its comparators.
+    ///
+    /// The [`new`](Self::new) function currently takes an `&` reference to a
+    /// [`Pclk`]. In the meantime, you must ensure that the PCLK is enabled for
+    /// the `Ac` struct's lifetime.
+    #[hal_cfg("ac-d5x")]
+    pub fn new<PS: PclkSourceId>(
+        ac: pac::Ac,
+        apbclk: ApbClk<AcId>,
+        _pclk: &Pclk<AcId, PS>,
+    ) -> (Self, Channels) {
+        let ac = Self {
+            ac,
+            _apbclk: apbclk,
+        };
+        ac.init();
+        (ac, Channels(Channel::new(), Channel::new()))
+    }
+
+    fn init(&self) {
+        self.swreset();
+        self.ac.ctrla().modify(|_, w| w.enable().set_bit());
+        sync(&self.ac);
+    }
+
+    fn swreset(&self) {
+        self.ac.ctrla().write(|w| w.swrst().set_bit());
+        // SWRST is synchronized, the reset is complete once it is no longer busy
+        sync(&self.ac);
+    }
+
+    /// Keep the comparators running in standby sleep mode
+    #[hal_cfg(any("ac-d11", "ac-d21"))]
+    pub fn run_in_standby(&mut self, enable: bool) {
+        with_disable(&self.ac, |ac| {
+            ac.ctrla().modify(|_, w| w.runstdby().bit(enable))
+        });
+    }
+
+    /// Read the interrupt flags of both comparators and the window
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.ac.intflag().read().bits())
+    }
+
+    /// Reset the AC and return the underlying resources.
+    ///
+    /// **Note**: The [`Channels`] struct is consumed by this method. Any
+    /// [`Comparator`] must first be turned back into a [`Channel`] by calling
+    /// [`Comparator::free`].
+    #[hal_cfg(any("ac-d11", "ac-d21"))]
+    pub fn free(self, pm: &mut pac::Pm, _channels: Channels) -> pac::Ac {
+        self.swreset();
+        pm.apbcmask().modify(|_, w| w.ac_().clear_bit());
+        self.ac
+    }
+
+    /// Reset the AC and return the underlying resources.
+    ///
+    /// **Note**: The [`Channels`] struct is consumed by this method. Any
+    /// [`Comparator`] must first be turned back into a [`Channel`] by calling
+    /// [`Comparator::free`].
+    #[hal_cfg("ac-d5x")]
+    pub fn free(self, _channels: Channels) -> (pac::Ac, ApbClk<AcId>) {
+        self.swreset();
+        (self.ac, self._apbclk)
+    }
+}
+
+/// Busy-wait until register synchronization is complete
+#[hal_macro_helper]
+#[inline]
+fn sync(ac: &pac::ac::RegisterBlock) {
+    #[hal_cfg(any("ac-d11", "ac-d21"))]
+    while ac.statusb().read().syncbusy().bit_is_set() {
+        core::hint::spin_loop();
+    }
+    #[hal_cfg("ac-d5x")]
+    while ac.syncbusy().read().bits() != 0 {
+        core::hint::spin_loop();
+    }
+}
+
+/// Run the provided closure with the AC disabled, then re-enable it if it was
+/// previously enabled. `EVCTRL` and `WINCTRL` may only be written while the
+/// AC is disabled.
+fn with_disable<R>(
+    ac: &pac::ac::RegisterBlock,
+    fun: impl FnOnce(&pac::ac::RegisterBlock) -> R,
+) -> R {
+    critical_section::with(|_| {
+        let enabled = ac.ctrla().read().enable().bit_is_set();
+        ac.ctrla().modify(|_, w| w.enable().clear_bit());
+        sync(ac);
+
+        let ret = fun(ac);
+
+        if enabled {
+            ac.ctrla().modify(|_, w| w.enable().set_bit());
+            sync(ac);
+        }
+        ret
+    })
+}
+
+//==============================================================================
+// Channel
+//==============================================================================
+
+/// Comparator channel
+///
+/// Use this struct to create a [`Comparator`] by calling
+/// [`with_inputs`](Self::with_inputs).
+pub struct Channel<Id: CmpId> {
+    _id: PhantomData<Id>,
+}
+
+impl<Id: CmpId> Channel<Id> {
+    fn new() -> Self {
+        Self { _id: PhantomData }
+    }
+
+    /// Borrow the AC registers.
+    ///
+    /// Each channel only accesses the registers (or bits) belonging to its
+    /// comparator. Shared read-modify-write registers are accessed in a
+    /// critical section.
+    #[inline]
+    fn ac(&self) -> &pac::ac::RegisterBlock {
+        unsafe { &*pac::Ac::ptr() }
+    }
+
+    /// Assign inputs to this [`Channel`], and turn it into an enabled
+    /// [`Comparator`].
+    pub fn with_inputs<P, N>(self, pos: P, neg: N, config: ComparatorConfig) -> Comparator<Id, P, N>
+    where
+        P: PositiveInput,
+        N: NegativeInput,
+    {
+        let mut comparator = Comparator {
+            chan: self,
+            pos,
+            neg,
+            out: NoneT,
+            config,
+        };
+        comparator.configure(config);
+        comparator
+    }
+}
+
+//==============================================================================
+// Comparator
+//==============================================================================
+
+/// A comparator, configured with its inputs and, optionally, an output pin
+pub struct Comparator<Id: CmpId, P, N, O = NoneT> {
+    chan: Channel<Id>,
+    pos: P,
+    neg: N,
+    out: O,
+    config: ComparatorConfig,
+}
+
+impl<Id, P, N, O> Comparator<Id, P, N, O>
+where
+    Id: CmpId,
+    P: PositiveInput,
+    N: NegativeInput,
+{
+    /// Reconfigure the comparator.
+    ///
+    /// This briefly disables the comparator, and waits until it has completed
+    /// its startup.
+    pub fn configure(&mut self, config: ComparatorConfig) {
+        self.config = config;
+        self.write_compctrl();
+    }
+
+    /// Write COMPCTRLn from the current configuration
+    #[hal_macro_helper]
+    fn write_compctrl(&mut self) {
+        let ac = self.chan.ac();
+        let config = self.config;
+
+        // COMPCTRLn is enable-protected by COMPCTRLn.ENABLE
+        ac.compctrl(Id::ID).modify(|_, w| w.enable().clear_bit());
+        sync(ac);
+
+        if let Some(value) = self.pos.scaler().or(self.neg.scaler()) {
+            ac.scaler(Id::ID)
+                .write(|w| unsafe { w.value().bits(value) });
+        }
+
+        // OUT is left untouched, as it is managed by `with_output`
+        ac.compctrl(Id::ID).modify(|_, w| {
+            w.muxpos().variant(P::MUXPOS);
+            w.muxneg().variant(N::MUXNEG);
+            w.single().bit(config.single_shot);
+            w.swap().bit(config.swap);
+            w.speed().variant(config.speed);
+            w.flen().variant(config.filter);
+            w.intsel().variant(config.interrupt);
+            #[hal_cfg(any("ac-d11", "ac-d21"))]
+            w.hyst().bit(config.hysteresis);
+            #[hal_cfg("ac-d5x")]
+            {
+                w.runstdby().bit(config.run_in_standby);
+                match config.hysteresis {
+                    Some(level) => {
+                        w.hysten().set_bit();
+                        w.hyst().variant(level);
+                    }
+                    None => {
+                        w.hysten().clear_bit();
+                    }
+                }
+            }
+            w
+        });
+        sync(ac);
+
+        ac.compctrl(Id::ID).modify(|_, w| w.enable().set_bit());
+        sync(ac);
+
+        if !config.single_shot {
+            while !self.is_ready() {
+                core::hint::spin_loop();
+            }
+        }
+    }
+
+    /// Check whether the comparator has completed its startup (in continuous
+    /// mode), or its last comparison (in single-shot mode)
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.chan.ac().statusb().read().bits() & (1 << Id::ID) != 0
+    }
+
+    /// Read the current comparator output.
+    ///
+    /// Returns `true` if the positive input is above the negative input (or
+    /// the opposite if the inputs are swapped). In single-shot mode, this
+    /// returns the result of the last comparison.
+    #[inline]
+    pub fn state(&self) -> bool {
+        self.chan.ac().statusa().read().bits() & (1 << Id::ID) != 0
+    }
+
+    /// Perform a comparison and return its result.
+    ///
+    /// In single-shot mode, this starts a new comparison and waits for it to
+    /// complete. In continuous mode, this is equivalent to
+    /// [`state`](Self::state).
+    pub fn compare(&mut self) -> bool {
+        if self.config.single_shot {
+            self.start();
+            while !self.is_ready() {
+                core::hint::spin_loop();
+            }
+        }
+        self.state()
+    }
+
+    /// Start a single-shot comparison
+    #[inline]
+    fn start(&mut self) {
+        self.chan
+            .ac()
+            .ctrlb()
+            .write(|w| unsafe { w.bits(1 << Id::ID) });
+    }
+
+    /// Enable the comparator interrupt
+    #[inline]
+    pub fn enable_interrupt(&mut self) {
+        self.chan
+            .ac()
+            .intenset()
+            .write(|w| unsafe { w.bits(Id::FLAG.bits()) });
+    }
+
+    /// Disable the comparator interrupt
+    #[inline]
+    pub fn disable_interrupt(&mut self) {
+        self.chan
+            .ac()
+            .intenclr()
+            .write(|w| unsafe { w.bits(Id::FLAG.bits()) });
+    }
+
+    /// Check whether the comparator interrupt flag is set
+    #[inline]
+    pub fn is_interrupt(&self) -> bool {
+        self.chan.ac().intflag().read().bits() & Id::FLAG.bits() != 0
+    }
+
+    /// Clear the comparator interrupt flag
+    #[inline]
+    pub fn clear_interrupt(&mut self) {
+        self.chan
+            .ac()
+            .intflag()
+            .write(|w| unsafe { w.bits(Id::FLAG.bits()) });
+    }
+
+    /// Emit an EVSYS event whenever the interrupt condition occurs.
+    ///
+    /// This briefly disables the AC.
+    pub fn enable_event_output(&mut self, enable: bool) {
+        // COMPEOn
+        self.modify_evctrl(1 << Id::ID, enable);
+    }
+
+    /// Start a single-shot comparison on each incoming EVSYS event.
+    ///
+    /// This briefly disables the AC.
+    pub fn enable_start_event(&mut self, enable: bool) {
+        // COMPEIn
+        self.modify_evctrl(1 << (Id::ID + 8), enable);
+    }
+
+    fn modify_evctrl(&mut self, mask: u16, enable: bool) {
+        with_disable(self.chan.ac(), |ac| {
+            ac.evctrl().modify(|r, w| {
+                let bits = if enable {
+                    r.bits() | mask
+                } else {
+                    r.bits() & !mask
+                };
+                unsafe { w.bits(bits) }
+            })
+        });
+    }
+
+    #[hal_cfg(any("ac-d21", "ac-d5x"))]
+    fn set_output_mode(&mut self, mode: OutputMode) {
+        let ac = self.chan.ac();
+        ac.compctrl(Id::ID).modify(|_, w| w.enable().clear_bit());
+        sync(ac);
+        ac.compctrl(Id::ID).modify(|_, w| w.out().variant(mode));
+        sync(ac);
+        ac.compctrl(Id::ID).modify(|_, w| w.enable().set_bit());
+        sync(ac);
+    }
+
+    /// Disable the comparator and release the underlying resources
+    pub fn free(self) -> (Channel<Id>, P, N, O) {
+        let ac = self.chan.ac();
+        ac.compctrl(Id::ID).write(|w| w.enable().clear_bit());
+        sync(ac);
+        (self.chan, self.pos, self.neg, self.out)
+    }
+}
+
+#[hal_cfg(any("ac-d21", "ac-d5x"))]
+impl<Id, P, N> Comparator<Id, P, N>
+where
+    Id: CmpId,
+    P: PositiveInput,
+    N: NegativeInput,
+{
+    /// Route the comparator output to a `CMP` pin.
+    ///
+    /// [`OutputMode::Sync`] outputs the filtered and synchronized output,
+    /// while [`OutputMode::Async`] directly outputs the comparator state.
+    pub fn with_output<I>(
+        self,
+        pin: impl AnyPin<Id = I>,
+        mode: OutputMode,
+    ) -> Comparator<Id, P, N, Pin<I, CmpOutputMode>>
+    where
+        I: CmpOutputPinId<Id>,
+    {
+        let mut comparator = Comparator {
+            chan: self.chan,
+            pos: self.pos,
+            neg: self.neg,
+            out: pin.into().into_mode(),
+            config: self.config,
+        };
+        comparator.set_output_mode(mode);
+        comparator
+    }
+}
+
+impl<P, N, O> Comparator<Cmp0, P, N, O>
+where
+    P: PositiveInput,
+    N: NegativeInput,
+{
+    /// Combine comparator 0 and comparator 1 into a [`Window`].
+    ///
+    /// The window interrupt flag is set according to `interrupt`.
+    pub fn into_window<P1, N1, O1>(
+        self,
+        other: Comparator<Cmp1, P1, N1, O1>,
+        interrupt: WindowInterrupt,
+    ) -> Window<Self, Comparator<Cmp1, P1, N1, O1>>
+    where
+        P1: PositiveInput,
+        N1: NegativeInput,
+    {
+        let mut window = Window {
+            cmp0: self,
+            cmp1: other,
+        };
+        window.enable(interrupt);
+        window
+    }
+}
+
+#[hal_cfg(any("ac-d21", "ac-d5x"))]
+impl<Id, P, N, I> Comparator<Id, P, N, Pin<I, CmpOutputMode>>
+where
+    Id: CmpId,
+    P: PositiveInput,
+    N: NegativeInput,
+    I: CmpOutputPinId<Id>,
+{
+    /// Stop routing the comparator output to its `CMP` pin, and return the
+    /// pin
+    pub fn without_output(mut self) -> (Comparator<Id, P, N>, Pin<I, CmpOutputMode>) {
+        self.set_output_mode(OutputMode::Off);
+        let comparator = Comparator {
+            chan: self.chan,
+            pos: self.pos,
+            neg: self.neg,
+            out: NoneT,
+            config: self.config,
+        };
+        (comparator, self.out)
+    }
+}
+
+//==============================================================================
+// Window
+//==============================================================================
+
+/// A pair of comparators operating in window mode
+pub struct Window<C0, C1> {
+    cmp0: C0,
+    cmp1: C1,
+}
+
+impl<C0, C1> Window<C0, C1> {
+    #[inline]
+    fn ac(&self) -> &pac::ac::RegisterBlock {
+        unsafe { &*pac::Ac::ptr() }
+    }
+
+    fn enable(&mut self, interrupt: WindowInterrupt) {
+        with_disable(self.ac(), |ac| {
+            ac.winctrl().write(|w| {
+                w.wen0().set_bit();
+                w.wintsel0().variant(interrupt)
+            })
+        });
+    }
+
+    /// Change the condition which sets the window interrupt flag.
+    ///
+    /// This briefly disables the AC.
+    pub fn set_interrupt(&mut self, interrupt: WindowInterrupt) {
+        self.enable(interrupt);
+    }
+
+    /// Read the position of the input signal relative to the window
+    #[inline]
+    pub fn state(&self) -> WindowState {
+        self.ac().statusa().read().wstate0().variant().unwrap()
+    }
+
+    /// Enable the window interrupt
+    #[inline]
+    pub fn enable_interrupt(&mut self) {
+        self.ac()
+            .intenset()
+            .write(|w| unsafe { w.bits(Flags::WIN0.bits()) });
+    }
+
+    /// Disable the window interrupt
+    #[inline]
+    pub fn disable_interrupt(&mut self) {
+        self.ac()
+            .intenclr()
+            .write(|w| unsafe { w.bits(Flags::WIN0.bits()) });
+    }
+
+    /// Check whether the window interrupt flag is set
+    #[inline]
+    pub fn is_interrupt(&self) -> bool {
+        self.ac().intflag().read().bits() & Flags::WIN0.bits() != 0
+    }
+
+    /// Clear the window interrupt flag
+    #[inline]
+    pub fn clear_interrupt(&mut self) {
+        self.ac()
+            .intflag()
+            .write(|w| unsafe { w.bits(Flags::WIN0.bits()) });
+    }
+
+    /// Emit an EVSYS event whenever the window interrupt condition occurs.
+    ///
+    /// This briefly disables the AC.
+    pub fn enable_event_output(&mut self, enable: bool) {
+        with_disable(self.ac(), |ac| {
+            ac.evctrl().modify(|_, w| w.wineo0().bit(enable))
+        });
+    }
+
+    /// Disable window mode, and return the two comparators
+    pub fn free(self) -> (C0, C1) {
+        with_disable(self.ac(), |ac| ac.winctrl().write(|w| w.wen0().clear_bit()));
+        (self.cmp0, self.cmp1)
+    }
+}
@@ -0,0 +1,195 @@
+//! Comparator inputs and outputs
+
+use atsamd_hal_macros::hal_cfg;
+
+#[hal_cfg(any("ac-d21", "ac-d5x"))]
+use super::CmpId;
+use crate::gpio::pin::*;
+use crate::pac::ac::compctrl::{Muxnegselect, Muxposselect};
+use crate::typelevel::Sealed;
+
+/// Input which can be connected to the positive input of a comparator
+pub trait PositiveInput: Sealed {
+    /// MUXPOS selection for this input
+    const MUXPOS: Muxposselect;
+
+    /// Value of the VDD scaler, if this input uses it
+    #[inline]
+    fn scaler(&self) -> Option<u8> {
+        None
+    }
+}
+
+/// Input which can be connected to the negative input of a comparator
+pub trait NegativeInput: Sealed {
+    /// MUXNEG selection for this input
+    const MUXNEG: Muxnegselect;
+
+    /// Value of the VDD scaler, if this input uses it
+    #[inline]
+    fn scaler(&self) -> Option<u8> {
+        None
+    }
+}
+
+//==============================================================================
+// AIN pins
+//==============================================================================
+
+/// Type-level trait for [`PinId`]s which can be used as `AIN` comparator
+/// inputs
+pub trait AinPinId: PinId {
+    /// MUXPOS selection for this pin
+    const MUXPOS: Muxposselect;
+    /// MUXNEG selection for this pin
+    const MUXNEG: Muxnegselect;
+}
+
+impl<I: AinPinId> PositiveInput for Pin<I, AlternateB> {
+    const MUXPOS: Muxposselect = I::MUXPOS;
+}
+
+impl<I: AinPinId> NegativeInput for Pin<I, AlternateB> {
+    const MUXNEG: Muxnegselect = I::MUXNEG;
+}
+
+macro_rules! ain_pins {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $PinId:ident: $AIN:ident
+        ),+
+        $(,)?
+    ) => {
+        $(
+            $( #[$cfg] )?
+            impl AinPinId for $PinId {
+                const MUXPOS: Muxposselect = Muxposselect::$AIN;
+                const MUXNEG: Muxnegselect = Muxnegselect::$AIN;
+            }
+        )+
+    };
+}
+
+ain_pins! {
+    #[hal_cfg("pa04")]
+    PA04: Pin0,
+    #[hal_cfg("pa05")]
+    PA05: Pin1,
+    #[hal_cfg("pa06")]
+    PA06: Pin2,
+    #[hal_cfg("pa07")]
+    PA07: Pin3,
+}
+
+//==============================================================================
+// Internal inputs
+//==============================================================================
+
+/// Scaled VDDANA input.
+///
+/// The output voltage is `VDDANA * (value + 1) / 64`.
+#[derive(Clone, Copy, Debug)]
+pub struct VddScaler {
+    value: u8,
+}
+
+impl VddScaler {
+    /// Create a new [`VddScaler`] input. Values above 63 are saturated.
+    #[inline]
+    pub fn new(value: u8) -> Self {
+        Self {
+            value: value.min(63),
+        }
+    }
+}
+
+impl Sealed for VddScaler {}
+
+impl NegativeInput for VddScaler {
+    const MUXNEG: Muxnegselect = Muxnegselect::Vscale;
+
+    #[inline]
+    fn scaler(&self) -> Option<u8> {
+        Some(self.value)
+    }
+}
+
+#[hal_cfg("ac-d5x")]
+impl PositiveInput for VddScaler {
+    const MUXPOS: Muxposselect = Muxposselect::Vscale;
+
+    #[inline]
+    fn scaler(&self) -> Option<u8> {
+        Some(self.value)
+    }
+}
+
+/// Ground input
+#[derive(Clone, Copy, Debug)]
+pub struct Gnd;
+impl Sealed for Gnd {}
+impl NegativeInput for Gnd {
+    const MUXNEG: Muxnegselect = Muxnegselect::Gnd;
+}
+
+/// Internal bandgap voltage reference input
+#[derive(Clone, Copy, Debug)]
+pub struct Bandgap;
+impl Sealed for Bandgap {}
+impl NegativeInput for Bandgap {
+    const MUXNEG: Muxnegselect = Muxnegselect::Bandgap;
+}
+
+/// DAC output input.
+///
+/// On SAMx5x, this is the output of DAC channel 0.
+#[derive(Clone, Copy, Debug)]
+pub struct DacOutput;
+impl Sealed for DacOutput {}
+impl NegativeInput for DacOutput {
+    const MUXNEG: Muxnegselect = Muxnegselect::Dac;
+}
+
+//==============================================================================
+// CMP pins
+//==============================================================================
+
+/// Pin mode of `CMP` output pins
+#[hal_cfg("ac-d21")]
+pub type CmpOutputMode = AlternateH;
+
+/// Pin mode of `CMP` output pins
+#[hal_cfg("ac-d5x")]
+pub type CmpOutputMode = AlternateM;
+
+/// Type-level trait for [`PinId`]s which can output the state of comparator
+/// `Id`
+#[hal_cfg(any("ac-d21", "ac-d5x"))]
+pub trait CmpOutputPinId<Id: CmpId>: PinId {}
+
+macro_rules! cmp_pins {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $PinId:ident: $Cmp:ident
+        ),+
+        $(,)?
+    ) => {
+        $(
+            $( #[$cfg] )?
+            impl CmpOutputPinId<super::$Cmp> for $PinId {}
+        )+
+    };
+}
+
+cmp_pins! {
+    #[hal_cfg(all("pa12", any("ac-d21", "ac-d5x")))]
+    PA12: Cmp0,
+    #[hal_cfg(all("pa13", any("ac-d21", "ac-d5x")))]
+    PA13: Cmp1,
+    #[hal_cfg(all("pa18", any("ac-d21", "ac-d5x")))]
+    PA18: Cmp0,
+    #[hal_cfg(all("pa19", any("ac-d21", "ac-d5x")))]
+    PA19: Cmp1,
+}
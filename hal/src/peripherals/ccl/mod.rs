@@ -0,0 +1,667 @@
+//! # Configurable Custom Logic
+//!
+//! The Configurable Custom Logic (CCL) peripheral found on SAMD5x/SAME5x chips
+//! provides four programmable Look-Up Tables (LUTs), which can be used to
+//! implement glue logic between pins and peripherals without CPU
+//! intervention.
+//!
+//! Each LUT is represented by a [`Lut`], obtained when creating the [`Ccl`].
+//! A LUT has three inputs, whose sources are selected with [`LutConfig`], and
+//! an 8-bit truth table. Inputs can also be connected to `CCL/IN` pins with
+//! [`Lut::with_input0`], [`Lut::with_input1`] and [`Lut::with_input2`], and
+//! the LUT output can be routed to a `CCL/OUT` pin with [`Lut::with_output`].
+//!
+//! ```no_run
+//! let apb_ccl = buses.apb.enable(tokens.apbs.ccl);
+//! let (mut ccl, luts) = Ccl::new(peripherals.ccl, apb_ccl);
+//!
+//! // OUT0 = IN0 XOR IN1
+//! let config = LutConfig::new(truth_table(|in0, in1, _| in0 ^ in1));
+//! let mut lut0 = luts
+//!     .0
+//!     .with_input0(pins.pa04)
+//!     .with_input1(pins.pa05)
+//!     .with_output(pins.pa07);
+//! lut0.configure(config);
+//! ```
+//!
+//! ## Sequential logic
+//!
+//! The outputs of each pair of LUTs (LUT0/LUT1 and LUT2/LUT3) can be fed to a
+//! sequential element (D or JK flip-flop, gated D latch or RS latch),
+//! configured with [`Ccl::set_sequential`]. The output of the sequential
+//! element replaces the output of the even LUT of the pair.
+//!
+//! For example, a Manchester encoder can be built by XOR-ing the output of a
+//! SERCOM in SPI mode ([`Input::Sercom`]) with the clock generated by a TC
+//! ([`Input::Tc`]), and resynchronizing the result with a D flip-flop.
+//!
+//! ## Clocking
+//!
+//! Purely combinational logic only requires the APB clock. The filter, edge
+//! detector and sequential elements are clocked by `GCLK_CCL`, which must be
+//! enabled through a [`Pclk`](crate::clock::v2::pclk::Pclk) while they are in
+//! use.
+//!
+//! ## Events
+//!
+//! Each LUT can use its EVSYS event input as a LUT input ([`Input::Event`]),
+//! and generate an event from its output ([`LutConfig::event_output`]). See
+//! the [`evsys`](crate::evsys) module for routing events.
+
+use core::marker::PhantomData;
+
+use atsamd_hal_macros::hal_cfg;
+
+use crate::clock::v2::{apb::ApbClk, types::Ccl as CclId};
+use crate::gpio::{AlternateN, AnyPin, Pin, PinId};
+use crate::pac;
+use crate::typelevel::{NoneT, Sealed};
+
+pub use crate::pac::ccl::lutctrl::Filtselselect as Filter;
+pub use crate::pac::ccl::seqctrl::Seqselselect as Sequential;
+
+use crate::pac::ccl::lutctrl::Insel0select;
+
+/// Compute a truth table from a boolean function of the three LUT inputs.
+///
+/// `f` is called with `(in0, in1, in2)` for each of the eight input
+/// combinations.
+pub fn truth_table(f: impl Fn(bool, bool, bool) -> bool) -> u8 {
+    (0..8).fold(0, |truth, i| {
+        let out = f(i & 0x1 != 0, i & 0x2 != 0, i & 0x4 != 0);
+        truth | ((out as u8) << i)
+    })
+}
+
+/// Source of a LUT input
+///
+/// `CCL/IN` pins are selected by assigning the pin to the LUT, with
+/// [`Lut::with_input0`], [`Lut::with_input1`] or [`Lut::with_input2`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Input {
+    /// The input is masked, and always reads as zero
+    Masked,
+    /// The output of the LUT itself (or of its sequential element)
+    Feedback,
+    /// The output of the next LUT (LUT0 for LUT3)
+    Link,
+    /// The EVSYS event input of the LUT
+    Event,
+    /// The output of comparator `x`, where `x` is the input index
+    Ac,
+    /// Waveform output `x` of TC `n`, where `x` is the input index and `n` the
+    /// LUT index
+    Tc,
+    /// Waveform output `x` of TC `n + 1`, where `x` is the input index and `n`
+    /// the LUT index
+    AltTc,
+    /// Waveform output `x` of TCC `n`, where `x` is the input index and `n` the
+    /// LUT index
+    Tcc,
+    /// SERCOM `n` output, where `n` is the LUT index
+    Sercom,
+}
+
+impl Input {
+    #[inline]
+    fn insel(self) -> Insel0select {
+        match self {
+            Input::Masked => Insel0select::Mask,
+            Input::Feedback => Insel0select::Feedback,
+            Input::Link => Insel0select::Link,
+            Input::Event => Insel0select::Event,
+            Input::Ac => Insel0select::Ac,
+            Input::Tc => Insel0select::Tc,
+            Input::AltTc => Insel0select::Alttc,
+            Input::Tcc => Insel0select::Tcc,
+            Input::Sercom => Insel0select::Sercom,
+        }
+    }
+}
+
+//==============================================================================
+// LutConfig
+//==============================================================================
+
+/// Configuration of a [`Lut`]
+#[derive(Clone, Copy, Debug)]
+pub struct LutConfig {
+    /// Truth table. Bit `i` is the output value when the inputs, read as
+    /// `in2:in1:in0`, equal `i`. See [`truth_table`].
+    pub truth: u8,
+    /// Source of each input. Ignored for inputs connected to a `CCL/IN` pin.
+    pub inputs: [Input; 3],
+    /// Filter applied to the LUT output
+    pub filter: Filter,
+    /// Enable the edge detector on the LUT output
+    pub edge_detect: bool,
+    /// Invert the EVSYS event input
+    pub invert_event_input: bool,
+    /// Generate an EVSYS event from the LUT output
+    pub event_output: bool,
+}
+
+impl Default for LutConfig {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl LutConfig {
+    /// Create a new [`LutConfig`] with the given truth table and all inputs
+    /// masked
+    #[inline]
+    pub fn new(truth: u8) -> Self {
+        Self {
+            truth,
+            inputs: [Input::Masked; 3],
+            filter: Filter::Disable,
+            edge_detect: false,
+            invert_event_input: false,
+            event_output: false,
+        }
+    }
+
+    /// Set the source of input `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than 2.
+    #[inline]
+    pub fn input(mut self, index: usize, input: Input) -> Self {
+        self.inputs[index] = input;
+        self
+    }
+
+    /// Set the output filter
+    #[inline]
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Enable or disable the output edge detector
+    #[inline]
+    pub fn edge_detect(mut self, edge_detect: bool) -> Self {
+        self.edge_detect = edge_detect;
+        self
+    }
+
+    /// Invert the EVSYS event input
+    #[inline]
+    pub fn invert_event_input(mut self, invert: bool) -> Self {
+        self.invert_event_input = invert;
+        self
+    }
+
+    /// Generate an EVSYS event from the LUT output
+    #[inline]
+    pub fn event_output(mut self, event_output: bool) -> Self {
+        self.event_output = event_output;
+        self
+    }
+}
+
+//==============================================================================
+// LUT IDs
+//==============================================================================
+
+/// Trait representing a LUT ID.
+pub trait LutId: Sealed {
+    /// Index of the LUT
+    const ID: usize;
+}
+
+/// Trait representing the even LUT of a pair sharing a sequential element.
+pub trait SeqLutId: LutId {
+    /// Index of the sequential element
+    const SEQ: usize;
+}
+
+macro_rules! luts {
+    ($($Lut:ident: $id:literal),+) => {
+        $(
+            #[doc = concat!("Type-level variant of [`LutId`] for LUT", $id)]
+            pub enum $Lut {}
+            impl Sealed for $Lut {}
+            impl LutId for $Lut {
+                const ID: usize = $id;
+            }
+        )+
+    };
+}
+
+luts!(Lut0: 0, Lut1: 1, Lut2: 2, Lut3: 3);
+
+impl SeqLutId for Lut0 {
+    const SEQ: usize = 0;
+}
+
+impl SeqLutId for Lut2 {
+    const SEQ: usize = 1;
+}
+
+//==============================================================================
+// Ccl
+//==============================================================================
+
+/// Configurable Custom Logic controller
+///
+/// Controls the settings shared by all LUTs.
+pub struct Ccl {
+    ccl: pac::Ccl,
+    apbclk: ApbClk<CclId>,
+}
+
+/// Handles to the individual LUTs
+pub struct Luts(pub Lut<Lut0>, pub Lut<Lut1>, pub Lut<Lut2>, pub Lut<Lut3>);
+
+impl Ccl {
+    /// Create and enable a new [`Ccl`], and return handles to its four LUTs.
+    pub fn new(ccl: pac::Ccl, apbclk: ApbClk<CclId>) -> (Self, Luts) {
+        ccl.ctrl().write(|w| w.swrst().set_bit());
+        while ccl.ctrl().read().swrst().bit_is_set() {
+            core::hint::spin_loop();
+        }
+        ccl.ctrl().write(|w| w.enable().set_bit());
+
+        let luts = Luts(Lut::new(), Lut::new(), Lut::new(), Lut::new());
+        (Self { ccl, apbclk }, luts)
+    }
+
+    /// Select the sequential element fed by the LUT pair starting at `Id`.
+    ///
+    /// This briefly disables the CCL.
+    pub fn set_sequential<Id: SeqLutId>(&mut self, sequential: Sequential) {
+        with_disable(&self.ccl, |ccl| {
+            ccl.seqctrl(Id::SEQ)
+                .write(|w| w.seqsel().variant(sequential));
+        });
+    }
+
+    /// Keep the CCL running in standby sleep mode
+    ///
+    /// This briefly disables the CCL.
+    pub fn run_in_standby(&mut self, run_in_standby: bool) {
+        with_disable(&self.ccl, |ccl| {
+            ccl.ctrl().modify(|_, w| w.runstdby().bit(run_in_standby));
+        });
+    }
+
+    /// Reset the CCL and return the underlying resources.
+    ///
+    /// **Note**: The [`Luts`] struct is consumed by this method. Any pins must
+    /// first be released by calling [`Lut::free`].
+    pub fn free(self, _luts: Luts) -> (pac::Ccl, ApbClk<CclId>) {
+        self.ccl.ctrl().write(|w| w.swrst().set_bit());
+        while self.ccl.ctrl().read().swrst().bit_is_set() {
+            core::hint::spin_loop();
+        }
+        (self.ccl, self.apbclk)
+    }
+}
+
+/// Run the provided closure with the CCL disabled, then re-enable it if it was
+/// previously enabled.
+fn with_disable<R>(
+    ccl: &pac::ccl::RegisterBlock,
+    fun: impl FnOnce(&pac::ccl::RegisterBlock) -> R,
+) -> R {
+    critical_section::with(|_| {
+        let enabled = ccl.ctrl().read().enable().bit_is_set();
+        ccl.ctrl().modify(|_, w| w.enable().clear_bit());
+        let ret = fun(ccl);
+        if enabled {
+            ccl.ctrl().modify(|_, w| w.enable().set_bit());
+        }
+        ret
+    })
+}
+
+//==============================================================================
+// Lut
+//==============================================================================
+
+/// Type-level trait for the types which can occupy a LUT input slot: either
+/// [`NoneT`], or a `CCL/IN` pin.
+pub trait OptionalInput: Sealed {
+    /// Whether the slot is occupied by a pin
+    const IS_PIN: bool;
+}
+
+impl OptionalInput for NoneT {
+    const IS_PIN: bool = false;
+}
+
+impl<I: PinId> OptionalInput for Pin<I, AlternateN> {
+    const IS_PIN: bool = true;
+}
+
+/// Look-Up Table
+///
+/// `I0`, `I1` and `I2` are the `CCL/IN` pins connected to each input, and `O`
+/// the `CCL/OUT` pin driven by the LUT output, or [`NoneT`] if unused.
+pub struct Lut<Id: LutId, I0 = NoneT, I1 = NoneT, I2 = NoneT, O = NoneT> {
+    config: LutConfig,
+    enabled: bool,
+    in0: I0,
+    in1: I1,
+    in2: I2,
+    out: O,
+    _id: PhantomData<Id>,
+}
+
+impl<Id: LutId> Lut<Id> {
+    fn new() -> Self {
+        Self {
+            config: LutConfig::default(),
+            enabled: false,
+            in0: NoneT,
+            in1: NoneT,
+            in2: NoneT,
+            out: NoneT,
+            _id: PhantomData,
+        }
+    }
+}
+
+impl<Id, I0, I1, I2, O> Lut<Id, I0, I1, I2, O>
+where
+    Id: LutId,
+    I0: OptionalInput,
+    I1: OptionalInput,
+    I2: OptionalInput,
+{
+    /// Borrow the CCL registers.
+    ///
+    /// Each LUT only ever accesses its own `LUTCTRL` register.
+    #[inline]
+    fn ccl(&self) -> &pac::ccl::RegisterBlock {
+        unsafe { &*pac::Ccl::ptr() }
+    }
+
+    /// Write the LUT configuration.
+    ///
+    /// `LUTCTRL` is enable-protected, so the CCL is briefly disabled while the
+    /// register is written.
+    fn write_lutctrl(&mut self) {
+        let config = self.config;
+        let insel = |index: usize, is_pin: bool| {
+            if is_pin {
+                Insel0select::Io
+            } else {
+                config.inputs[index].insel()
+            }
+        };
+        let insel0 = insel(0, I0::IS_PIN) as u8;
+        let insel1 = insel(1, I1::IS_PIN) as u8;
+        let insel2 = insel(2, I2::IS_PIN) as u8;
+        let event_input = config.inputs.contains(&Input::Event);
+
+        let enabled = self.enabled;
+        with_disable(self.ccl(), |ccl| {
+            ccl.lutctrl(Id::ID).write(|w| unsafe {
+                w.truth().bits(config.truth);
+                w.insel0().bits(insel0);
+                w.insel1().bits(insel1);
+                w.insel2().bits(insel2);
+                w.filtsel().variant(config.filter);
+                w.edgesel().bit(config.edge_detect);
+                w.lutei().bit(event_input);
+                w.invei().bit(config.invert_event_input);
+                w.luteo().bit(config.event_output);
+                w.enable().bit(enabled)
+            });
+        });
+    }
+
+    /// Configure and enable the LUT.
+    ///
+    /// This briefly disables the CCL.
+    pub fn configure(&mut self, config: LutConfig) {
+        self.config = config;
+        self.enabled = true;
+        self.write_lutctrl();
+    }
+
+    /// Return the current LUT configuration
+    #[inline]
+    pub fn config(&self) -> LutConfig {
+        self.config
+    }
+
+    /// Enable the LUT
+    #[inline]
+    pub fn enable(&mut self) {
+        self.enabled = true;
+        self.ccl()
+            .lutctrl(Id::ID)
+            .modify(|_, w| w.enable().set_bit());
+    }
+
+    /// Disable the LUT
+    #[inline]
+    pub fn disable(&mut self) {
+        self.enabled = false;
+        self.ccl()
+            .lutctrl(Id::ID)
+            .modify(|_, w| w.enable().clear_bit());
+    }
+
+    /// Disable the LUT, and return its pins.
+    ///
+    /// This briefly disables the CCL.
+    pub fn free(mut self) -> (Lut<Id>, I0, I1, I2, O) {
+        self.disable();
+        with_disable(self.ccl(), |ccl| ccl.lutctrl(Id::ID).reset());
+        (Lut::new(), self.in0, self.in1, self.in2, self.out)
+    }
+}
+
+impl<Id, I1, I2, O> Lut<Id, NoneT, I1, I2, O>
+where
+    Id: LutId,
+    I1: OptionalInput,
+    I2: OptionalInput,
+{
+    /// Connect input 0 of the LUT to a `CCL/IN` pin.
+    ///
+    /// This briefly disables the CCL.
+    pub fn with_input0<I>(self, pin: impl AnyPin<Id = I>) -> Lut<Id, Pin<I, AlternateN>, I1, I2, O>
+    where
+        I: InputPinId<Id, 0>,
+    {
+        let mut lut = Lut {
+            config: self.config,
+            enabled: self.enabled,
+            in0: pin.into().into_mode(),
+            in1: self.in1,
+            in2: self.in2,
+            out: self.out,
+            _id: PhantomData,
+        };
+        lut.write_lutctrl();
+        lut
+    }
+}
+
+impl<Id, I0, I2, O> Lut<Id, I0, NoneT, I2, O>
+where
+    Id: LutId,
+    I0: OptionalInput,
+    I2: OptionalInput,
+{
+    /// Connect input 1 of the LUT to a `CCL/IN` pin.
+    ///
+    /// This briefly disables the CCL.
+    pub fn with_input1<I>(self, pin: impl AnyPin<Id = I>) -> Lut<Id, I0, Pin<I, AlternateN>, I2, O>
+    where
+        I: InputPinId<Id, 1>,
+    {
+        let mut lut = Lut {
+            config: self.config,
+            enabled: self.enabled,
+            in0: self.in0,
+            in1: pin.into().into_mode(),
+            in2: self.in2,
+            out: self.out,
+            _id: PhantomData,
+        };
+        lut.write_lutctrl();
+        lut
+    }
+}
+
+impl<Id, I0, I1, O> Lut<Id, I0, I1, NoneT, O>
+where
+    Id: LutId,
+    I0: OptionalInput,
+    I1: OptionalInput,
+{
+    /// Connect input 2 of the LUT to a `CCL/IN` pin.
+    ///
+    /// This briefly disables the CCL.
+    pub fn with_input2<I>(self, pin: impl AnyPin<Id = I>) -> Lut<Id, I0, I1, Pin<I, AlternateN>, O>
+    where
+        I: InputPinId<Id, 2>,
+    {
+        let mut lut = Lut {
+            config: self.config,
+            enabled: self.enabled,
+            in0: self.in0,
+            in1: self.in1,
+            in2: pin.into().into_mode(),
+            out: self.out,
+            _id: PhantomData,
+        };
+        lut.write_lutctrl();
+        lut
+    }
+}
+
+impl<Id, I0, I1, I2> Lut<Id, I0, I1, I2, NoneT>
+where
+    Id: LutId,
+    I0: OptionalInput,
+    I1: OptionalInput,
+    I2: OptionalInput,
+{
+    /// Drive a `CCL/OUT` pin with the LUT output
+    pub fn with_output<I>(self, pin: impl AnyPin<Id = I>) -> Lut<Id, I0, I1, I2, Pin<I, AlternateN>>
+    where
+        I: OutputPinId<Id>,
+    {
+        Lut {
+            config: self.config,
+            enabled: self.enabled,
+            in0: self.in0,
+            in1: self.in1,
+            in2: self.in2,
+            out: pin.into().into_mode(),
+            _id: PhantomData,
+        }
+    }
+}
+
+//==============================================================================
+// Pins
+//==============================================================================
+
+/// Type-level trait for [`PinId`]s which can be used as input `N` of LUT `L`
+pub trait InputPinId<L: LutId, const N: usize>: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be driven by the output of LUT
+/// `L`
+pub trait OutputPinId<L: LutId>: PinId {}
+
+macro_rules! input_pins {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $PinId:ident: ($Lut:ident, $n:literal)
+        ),+
+        $(,)?
+    ) => {
+        $(
+            $( #[$cfg] )?
+            impl InputPinId<$Lut, $n> for crate::gpio::$PinId {}
+        )+
+    };
+}
+
+macro_rules! output_pins {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $PinId:ident: $Lut:ident
+        ),+
+        $(,)?
+    ) => {
+        $(
+            $( #[$cfg] )?
+            impl OutputPinId<$Lut> for crate::gpio::$PinId {}
+        )+
+    };
+}
+
+input_pins! {
+    #[hal_cfg("pa04")]
+    PA04: (Lut0, 0),
+    #[hal_cfg("pa05")]
+    PA05: (Lut0, 1),
+    #[hal_cfg("pa06")]
+    PA06: (Lut0, 2),
+    #[hal_cfg("pa08")]
+    PA08: (Lut1, 0),
+    #[hal_cfg("pa09")]
+    PA09: (Lut1, 1),
+    #[hal_cfg("pa10")]
+    PA10: (Lut1, 2),
+    #[hal_cfg("pa22")]
+    PA22: (Lut2, 0),
+    #[hal_cfg("pa23")]
+    PA23: (Lut2, 1),
+    #[hal_cfg("pa24")]
+    PA24: (Lut2, 2),
+    #[hal_cfg("pa30")]
+    PA30: (Lut1, 0),
+    #[hal_cfg("pb00")]
+    PB00: (Lut0, 1),
+    #[hal_cfg("pb01")]
+    PB01: (Lut0, 2),
+    #[hal_cfg("pb06")]
+    PB06: (Lut2, 0),
+    #[hal_cfg("pb07")]
+    PB07: (Lut2, 1),
+    #[hal_cfg("pb08")]
+    PB08: (Lut2, 2),
+    #[hal_cfg("pb14")]
+    PB14: (Lut3, 0),
+    #[hal_cfg("pb15")]
+    PB15: (Lut3, 1),
+    #[hal_cfg("pb16")]
+    PB16: (Lut3, 2),
+    #[hal_cfg("pb22")]
+    PB22: (Lut0, 0),
+}
+
+output_pins! {
+    #[hal_cfg("pa07")]
+    PA07: Lut0,
+    #[hal_cfg("pa11")]
+    PA11: Lut1,
+    #[hal_cfg("pa25")]
+    PA25: Lut2,
+    #[hal_cfg("pa31")]
+    PA31: Lut1,
+    #[hal_cfg("pb02")]
+    PB02: Lut0,
+    #[hal_cfg("pb09")]
+    PB09: Lut2,
+    #[hal_cfg("pb17")]
+    PB17: Lut3,
+    #[hal_cfg("pb23")]
+    PB23: Lut0,
+}
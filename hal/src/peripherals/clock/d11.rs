@@ -611,3 +611,24 @@ fn configure_and_enable_dfll48m(sysctrl: &mut Sysctrl, use_external_crystal: boo
 
     wait_for_dfllrdy(sysctrl);
 }
+
+/// Returns `true` if the generic `clock` is enabled and its generator runs in
+/// standby.
+///
+/// The GCLK registers are read through their indirect access, which selects
+/// the generic clock and the generator with an 8-bit write to their ID. This
+/// must not race with a [`GenericClockController`] method.
+pub(crate) fn runs_in_standby(clock: ClockId) -> bool {
+    // SAFETY: only the ID fields are written, which select the registers to
+    // read without changing the clock configuration.
+    let gclk = unsafe { &*Gclk::PTR };
+    unsafe { (gclk.clkctrl().as_ptr() as *mut u8).write_volatile(u8::from(clock)) };
+    let clkctrl = gclk.clkctrl().read();
+    if clkctrl.clken().bit_is_clear() {
+        return false;
+    }
+
+    unsafe { (gclk.genctrl().as_ptr() as *mut u8).write_volatile(clkctrl.r#gen().bits()) };
+    while gclk.status().read().syncbusy().bit_is_set() {}
+    gclk.genctrl().read().runstdby().bit_is_set()
+}
@@ -586,3 +586,17 @@ fn configure_usb_correction(oscctrl: &mut Oscctrl) {
     });
     while oscctrl.dfllsync().read().dfllctrlb().bit_is_set() {}
 }
+
+/// Returns `true` if the peripheral channel `clock` is enabled and its
+/// generator runs in standby.
+pub(crate) fn runs_in_standby(clock: ClockId) -> bool {
+    // SAFETY: the GCLK registers are only read
+    let gclk = unsafe { &*Gclk::PTR };
+    let pchctrl = gclk.pchctrl(u8::from(clock) as usize).read();
+    pchctrl.chen().bit_is_set()
+        && gclk
+            .genctrl(pchctrl.r#gen().bits() as usize)
+            .read()
+            .runstdby()
+            .bit_is_set()
+}
@@ -0,0 +1,290 @@
+//! # Cortex-M Cache Controller
+//!
+//! This module provides a driver for the Cortex-M Cache Controller (CMCC)
+//! found on SAMD5x/SAME5x chips. The CMCC is a 4 kB, 4-way set associative
+//! cache with 16-byte lines, sitting between the CPU and the flash, the QSPI
+//! memory and the other code regions. The SRAM is not cached.
+//!
+//! ```no_run
+//! let mut cmcc = Cmcc::new(peripherals.cmcc, clocks.ahbs.cmcc);
+//! cmcc.enable();
+//!
+//! cmcc.start_monitor(MonitorMode::DhitCount);
+//! run_benchmark();
+//! let data_hits = cmcc.monitor_count();
+//! ```
+//!
+//! ## Coherency
+//!
+//! The cache is not aware of writes to the cached memories. Cached data must
+//! be invalidated after the flash or the QSPI memory is modified, with
+//! [`Cmcc::invalidate_all`] or [`Cmcc::invalidate_range`].
+//! [`Nvm`](crate::nvm::Nvm) write and erase operations, and
+//! [`Qspi::into_xip`](crate::qspi::Qspi::into_xip), invalidate the cache
+//! automatically.
+//!
+//! ## Monitor
+//!
+//! The CMCC can count cycles, instruction cache hits or data cache hits, see
+//! [`MonitorMode`]. Comparing the number of hits during a piece of code with
+//! the number of cycles it took gives a rough idea of the cache efficiency.
+
+use crate::clock::v2::{ahb::AhbClk, types::Cmcc as CmccId};
+use crate::pac;
+use crate::pac::cmcc::RegisterBlock;
+
+pub use pac::cmcc::mcfg::Modeselect as MonitorMode;
+
+/// Size of a cache line, in bytes
+pub const LINE_SIZE: u32 = 16;
+
+/// Number of cache ways
+pub const NUM_WAYS: u8 = 4;
+
+/// Cache size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CacheSize {
+    /// 1 kB
+    _1kB,
+    /// 2 kB
+    _2kB,
+    /// 4 kB, the default
+    _4kB,
+}
+
+impl CacheSize {
+    /// Number of lines in each way
+    #[inline]
+    const fn sets(self) -> u32 {
+        let bytes = match self {
+            CacheSize::_1kB => 1024,
+            CacheSize::_2kB => 2048,
+            CacheSize::_4kB => 4096,
+        };
+        bytes / LINE_SIZE / NUM_WAYS as u32
+    }
+}
+
+/// CMCC configuration
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    size: CacheSize,
+    instruction_cache: bool,
+    data_cache: bool,
+}
+
+impl Config {
+    /// Create the default configuration: 4 kB, caching both instructions and
+    /// data
+    pub fn new() -> Self {
+        Self {
+            size: CacheSize::_4kB,
+            instruction_cache: true,
+            data_cache: true,
+        }
+    }
+
+    /// Set the cache size. Reducing it saves power.
+    pub fn size(mut self, size: CacheSize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Cache instruction fetches
+    pub fn instruction_cache(mut self, enable: bool) -> Self {
+        self.instruction_cache = enable;
+        self
+    }
+
+    /// Cache data accesses
+    pub fn data_cache(mut self, enable: bool) -> Self {
+        self.data_cache = enable;
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cortex-M Cache Controller
+pub struct Cmcc {
+    cmcc: pac::Cmcc,
+    ahbclk: AhbClk<CmccId>,
+}
+
+impl Cmcc {
+    /// Create the CMCC driver. The cache state is not changed.
+    pub fn new(cmcc: pac::Cmcc, ahbclk: AhbClk<CmccId>) -> Self {
+        Self { cmcc, ahbclk }
+    }
+
+    #[inline]
+    fn regs(&self) -> &RegisterBlock {
+        &self.cmcc
+    }
+
+    /// Enable the cache
+    #[inline]
+    pub fn enable(&mut self) {
+        self.regs().ctrl().write(|w| w.cen().set_bit());
+    }
+
+    /// Disable the cache, and wait until it is disabled
+    #[inline]
+    pub fn disable(&mut self) {
+        disable(self.regs());
+    }
+
+    /// Check whether the cache is enabled
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.regs().sr().read().csts().bit_is_set()
+    }
+
+    /// Configure the cache. The cache is disabled during the change, and
+    /// invalidated.
+    pub fn configure(&mut self, config: Config) {
+        self.with_disabled(|regs| {
+            regs.cfg().write(|w| {
+                match config.size {
+                    CacheSize::_1kB => w.csizesw().conf_csize_1kb(),
+                    CacheSize::_2kB => w.csizesw().conf_csize_2kb(),
+                    CacheSize::_4kB => w.csizesw().conf_csize_4kb(),
+                };
+                w.icdis().bit(!config.instruction_cache);
+                w.dcdis().bit(!config.data_cache)
+            });
+            regs.maint0().write(|w| w.invall().set_bit());
+        });
+    }
+
+    /// Return the configured cache size
+    #[inline]
+    pub fn size(&self) -> CacheSize {
+        let cfg = self.regs().cfg().read();
+        if cfg.csizesw().is_conf_csize_1kb() {
+            CacheSize::_1kB
+        } else if cfg.csizesw().is_conf_csize_2kb() {
+            CacheSize::_2kB
+        } else {
+            CacheSize::_4kB
+        }
+    }
+
+    /// Invalidate the whole cache
+    #[inline]
+    pub fn invalidate_all(&mut self) {
+        self.with_disabled(|regs| regs.maint0().write(|w| w.invall().set_bit()));
+    }
+
+    /// Invalidate the lines which may hold data from `len` bytes starting at
+    /// `address`
+    ///
+    /// Invalidating a range larger than the cache is equivalent to
+    /// [`invalidate_all`](Self::invalidate_all).
+    pub fn invalidate_range(&mut self, address: u32, len: u32) {
+        let sets = self.size().sets();
+        let first = address / LINE_SIZE;
+        let last = (address + len.max(1) - 1) / LINE_SIZE;
+        if last - first + 1 >= sets {
+            self.invalidate_all();
+            return;
+        }
+
+        self.with_disabled(|regs| {
+            for line in first..=last {
+                let index = (line % sets) as u8;
+                for way in 0..NUM_WAYS {
+                    // SAFETY: The index is smaller than the number of sets, and
+                    // the way is in range.
+                    regs.maint1()
+                        .write(|w| unsafe { w.index().bits(index).way().bits(way) });
+                }
+            }
+        });
+    }
+
+    /// Lock the ways set in `mask`, bit `n` standing for way `n`. Locked ways
+    /// are not refilled, so the data they hold stays in the cache.
+    ///
+    /// At least one way must stay unlocked for the cache to be useful.
+    #[inline]
+    pub fn lock_ways(&mut self, mask: u8) {
+        // SAFETY: Every 4-bit value is valid
+        self.regs()
+            .lckway()
+            .write(|w| unsafe { w.lckway().bits(mask & 0xF) });
+    }
+
+    /// Return the mask of locked ways
+    #[inline]
+    pub fn locked_ways(&self) -> u8 {
+        self.regs().lckway().read().lckway().bits()
+    }
+
+    /// Reset the monitor counter, and start counting `mode` events
+    #[inline]
+    pub fn start_monitor(&mut self, mode: MonitorMode) {
+        let regs = self.regs();
+        regs.men().write(|w| w.menable().clear_bit());
+        regs.mcfg().write(|w| w.mode().variant(mode));
+        regs.mctrl().write(|w| w.swrst().set_bit());
+        regs.men().write(|w| w.menable().set_bit());
+    }
+
+    /// Stop the monitor. The counter keeps its value.
+    #[inline]
+    pub fn stop_monitor(&mut self) {
+        self.regs().men().write(|w| w.menable().clear_bit());
+    }
+
+    /// Read the monitor counter
+    #[inline]
+    pub fn monitor_count(&self) -> u32 {
+        self.regs().msr().read().event_cnt().bits()
+    }
+
+    /// Run `f` with the cache disabled, then restore its state
+    fn with_disabled(&mut self, f: impl FnOnce(&RegisterBlock)) {
+        let enabled = self.is_enabled();
+        let regs = self.regs();
+        disable(regs);
+        f(regs);
+        if enabled {
+            regs.ctrl().write(|w| w.cen().set_bit());
+        }
+    }
+
+    /// Return the underlying resources. The cache state is not changed.
+    pub fn free(self) -> (pac::Cmcc, AhbClk<CmccId>) {
+        (self.cmcc, self.ahbclk)
+    }
+}
+
+fn disable(regs: &RegisterBlock) {
+    regs.ctrl().write(|w| w.cen().clear_bit());
+    while regs.sr().read().csts().bit_is_set() {}
+}
+
+/// Invalidate the whole cache without owning the CMCC
+///
+/// Used by the drivers modifying cached memories, to keep the cache coherent.
+pub(crate) fn invalidate_all() {
+    critical_section::with(|_| {
+        // SAFETY: Invalidating the cache has no visible effect except on
+        // performance, and the critical section prevents a concurrent
+        // maintenance operation from this core. The cache is restored to its
+        // previous state.
+        let cmcc = unsafe { pac::Cmcc::steal() };
+        let enabled = cmcc.sr().read().csts().bit_is_set();
+        disable(&cmcc);
+        cmcc.maint0().write(|w| w.invall().set_bit());
+        if enabled {
+            cmcc.ctrl().write(|w| w.cen().set_bit());
+        }
+    });
+}
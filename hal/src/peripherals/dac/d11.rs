@@ -0,0 +1,315 @@
+//! # Digital-to-Analog Converter
+//!
+//! This module provides an interface to the 10-bit Digital-to-Analog Converter
+//! (DAC) found on SAMD11 and SAMD21 chips. The DAC has a single output,
+//! `VOUT`, which is routed to `PA02`.
+//!
+//! ```no_run
+//! let dac_clock = clocks.dac(&gclk0).unwrap();
+//! let mut dac = Dac::new(
+//!     &mut peripherals.pm,
+//!     &dac_clock,
+//!     peripherals.dac,
+//!     pins.pa02,
+//!     Reference::Avcc,
+//! );
+//!
+//! // Output mid-scale
+//! dac.write(512);
+//! ```
+//!
+//! ## DMA
+//!
+//! With the `dma` feature enabled, a [`Dac`] can be consumed by
+//! [`Dac::send_with_dma`] to stream a buffer of samples to the output. The
+//! rate at which samples are produced is set by the DMA trigger source. Either
+//! trigger the DMA channel directly from a timer overflow (eg
+//! `TriggerSource::Tc3Ovf`), or enable [`Dac::enable_start_event`] so that
+//! the DMAC fills `DATABUF` on `TriggerSource::DacEmpty`, and an EVSYS event
+//! (typically a timer overflow) loads each sample into the DAC.
+
+use crate::clock::DacClock;
+use crate::gpio::{AlternateB, AnyPin, PA02, Pin};
+use crate::pac::{self, Pm};
+
+pub use crate::pac::dac::ctrlb::Refselselect as Reference;
+
+/// Maximum value that can be written to the DAC
+pub const MAX_VALUE: u16 = (1 << 10) - 1;
+
+bitflags::bitflags! {
+    /// DAC interrupt flags
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Flags: u8 {
+        /// Data buffer underrun
+        const UNDERRUN = 0x01;
+        /// Data buffer empty
+        const EMPTY = 0x02;
+        /// Synchronization ready
+        const SYNCRDY = 0x04;
+    }
+}
+
+/// Digital-to-Analog Converter
+pub struct Dac {
+    dac: pac::Dac,
+    pin: Pin<PA02, AlternateB>,
+    start_event: bool,
+}
+
+impl Dac {
+    /// Create and enable a new [`Dac`].
+    ///
+    /// The DAC output is connected to `VOUT` (`PA02`), which is configured in
+    /// alternate mode B.
+    pub fn new(
+        pm: &mut Pm,
+        _clock: &DacClock,
+        dac: pac::Dac,
+        pin: impl AnyPin<Id = PA02>,
+        reference: Reference,
+    ) -> Self {
+        pm.apbcmask().modify(|_, w| w.dac_().set_bit());
+
+        dac.ctrla().write(|w| w.swrst().set_bit());
+        while dac.ctrla().read().swrst().bit_is_set() || Self::is_syncing(&dac) {
+            core::hint::spin_loop();
+        }
+
+        dac.ctrlb().write(|w| {
+            w.refsel().variant(reference);
+            // Enable the external output to drive VOUT
+            w.eoen().set_bit()
+        });
+
+        let mut dac = Self {
+            dac,
+            pin: pin.into().into_alternate(),
+            start_event: false,
+        };
+        dac.enable();
+        dac
+    }
+
+    #[inline]
+    fn is_syncing(dac: &pac::Dac) -> bool {
+        dac.status().read().syncbusy().bit_is_set()
+    }
+
+    #[inline]
+    fn sync(&self) {
+        while Self::is_syncing(&self.dac) {
+            core::hint::spin_loop();
+        }
+    }
+
+    #[inline]
+    fn enable(&mut self) {
+        self.dac.ctrla().modify(|_, w| w.enable().set_bit());
+        self.sync();
+    }
+
+    #[inline]
+    fn disable(&mut self) {
+        self.dac.ctrla().modify(|_, w| w.enable().clear_bit());
+        self.sync();
+    }
+
+    /// Run the provided closure with the DAC disabled. CTRLB and EVCTRL are
+    /// enable-protected, and may only be written while the DAC is disabled.
+    fn with_disable(&mut self, fun: impl FnOnce(&pac::Dac)) {
+        self.disable();
+        fun(&self.dac);
+        self.enable();
+    }
+
+    /// Change the voltage reference
+    pub fn set_reference(&mut self, reference: Reference) {
+        self.with_disable(|dac| dac.ctrlb().modify(|_, w| w.refsel().variant(reference)));
+    }
+
+    /// Connect the DAC output to the internal analog comparator and ADC
+    /// inputs, in addition to `VOUT`.
+    pub fn enable_internal_output(&mut self, enable: bool) {
+        self.with_disable(|dac| dac.ctrlb().modify(|_, w| w.ioen().bit(enable)));
+    }
+
+    /// Disable the voltage pump.
+    ///
+    /// The voltage pump may be disabled to save power when VDDANA is higher
+    /// than 2.5V.
+    pub fn disable_voltage_pump(&mut self, disable: bool) {
+        self.with_disable(|dac| dac.ctrlb().modify(|_, w| w.vpd().bit(disable)));
+    }
+
+    /// Allow writing `DATABUF` without waiting for synchronization.
+    ///
+    /// This is useful to reduce latency when the DAC is fed by DMA.
+    pub fn bypass_databuf_write_protection(&mut self, enable: bool) {
+        self.with_disable(|dac| dac.ctrlb().modify(|_, w| w.bdwp().bit(enable)));
+    }
+
+    /// Keep the DAC running in standby sleep mode
+    pub fn run_in_standby(&mut self, enable: bool) {
+        self.with_disable(|dac| dac.ctrla().modify(|_, w| w.runstdby().bit(enable)));
+    }
+
+    /// Start a conversion of `DATABUF` on each incoming EVSYS event.
+    ///
+    /// When enabled, samples should be written to `DATABUF` (which
+    /// [`write_buffered`](Self::write_buffered) and DMA transfers will do),
+    /// and are only output once the START event is received. The DAC will
+    /// also emit an EMPTY event and DMA trigger once `DATABUF` has been
+    /// consumed.
+    pub fn enable_start_event(&mut self, enable: bool) {
+        self.with_disable(|dac| {
+            dac.evctrl().modify(|_, w| {
+                w.startei().bit(enable);
+                w.emptyeo().bit(enable)
+            })
+        });
+        self.start_event = enable;
+    }
+
+    /// Write a 10-bit value to the DAC output.
+    ///
+    /// Values larger than [`MAX_VALUE`] are saturated.
+    #[inline]
+    pub fn write(&mut self, value: u16) {
+        self.sync();
+        self.dac
+            .data()
+            .write(|w| unsafe { w.data().bits(value.min(MAX_VALUE)) });
+    }
+
+    /// Write a 10-bit value to the data buffer. It will be output on the next
+    /// START event. See [`enable_start_event`](Self::enable_start_event).
+    ///
+    /// Values larger than [`MAX_VALUE`] are saturated.
+    #[inline]
+    pub fn write_buffered(&mut self, value: u16) {
+        self.sync();
+        self.dac
+            .databuf()
+            .write(|w| unsafe { w.databuf().bits(value.min(MAX_VALUE)) });
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.dac.intflag().read().bits())
+    }
+
+    /// Clear the specified interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.dac
+            .intflag()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable the specified interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.dac
+            .intenset()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the specified interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.dac
+            .intenclr()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Pointer to the register written by DMA transfers
+    #[cfg(feature = "dma")]
+    #[inline]
+    fn dma_target(&self) -> *mut u16 {
+        if self.start_event {
+            self.dac.databuf().as_ptr()
+        } else {
+            self.dac.data().as_ptr()
+        }
+    }
+
+    /// Disable the DAC and return the underlying resources
+    pub fn free(mut self, pm: &mut Pm) -> (pac::Dac, Pin<PA02, AlternateB>) {
+        self.disable();
+        self.dac.ctrla().write(|w| w.swrst().set_bit());
+        while self.dac.ctrla().read().swrst().bit_is_set() {
+            core::hint::spin_loop();
+        }
+        pm.apbcmask().modify(|_, w| w.dac_().clear_bit());
+        (self.dac, self.pin)
+    }
+}
+
+#[cfg(feature = "dma")]
+mod dma {
+    use super::*;
+    use crate::dmac::{
+        self, AnyChannel, Buffer, Busy, Channel, InterruptFlags, Ready, Transfer, TriggerAction,
+        TriggerSource, transfer::BufferPair,
+    };
+
+    unsafe impl Buffer for Dac {
+        type Beat = u16;
+
+        #[inline]
+        fn dma_ptr(&mut self) -> *mut Self::Beat {
+            self.dma_target()
+        }
+
+        #[inline]
+        fn incrementing(&self) -> bool {
+            false
+        }
+
+        #[inline]
+        fn buffer_len(&self) -> usize {
+            1
+        }
+    }
+
+    impl Dac {
+        /// The DMA trigger source issued when `DATABUF` is empty
+        pub const DMA_EMPTY_TRIGGER: TriggerSource = TriggerSource::DacEmpty;
+
+        /// Transform a [`Dac`] into a DMA [`Transfer`] and start streaming the
+        /// provided buffer to the output.
+        ///
+        /// One sample is written each time `trigger` fires. Use a timer
+        /// overflow trigger to write directly to `DATA`, or
+        /// [`DMA_EMPTY_TRIGGER`](Self::DMA_EMPTY_TRIGGER) together with
+        /// [`enable_start_event`](Self::enable_start_event) to fill
+        /// `DATABUF` and let EVSYS pace the conversions.
+        ///
+        /// Samples are not range-checked; values should not exceed
+        /// [`MAX_VALUE`]. Set `circular` to loop over the buffer
+        /// indefinitely, eg for a periodic waveform.
+        #[inline]
+        pub fn send_with_dma<Ch, B>(
+            self,
+            buf: B,
+            mut channel: Ch,
+            trigger: TriggerSource,
+            circular: bool,
+        ) -> Transfer<Channel<Ch::Id, Busy>, BufferPair<B, Self>>
+        where
+            Ch: AnyChannel<Status = Ready>,
+            B: Buffer<Beat = u16> + 'static,
+        {
+            channel
+                .as_mut()
+                .enable_interrupts(InterruptFlags::new().with_tcmpl(true));
+
+            // SAFETY: This is safe because of the `'static` bound check for `B`,
+            // and the fact that the buffer length of a `Dac` is always 1.
+            let xfer = unsafe { dmac::Transfer::new_unchecked(channel, buf, self, circular) };
+            xfer.begin(trigger, TriggerAction::Beat)
+        }
+    }
+}
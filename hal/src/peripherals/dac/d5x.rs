@@ -0,0 +1,597 @@
+//! # Digital-to-Analog Converter
+//!
+//! This module provides an interface to the dual-channel, 12-bit
+//! Digital-to-Analog Converter (DAC) found on SAMD5x/SAME5x chips.
+//!
+//! The DAC has two outputs, `VOUT0` on `PA02` and `VOUT1` on `PA05`. Each
+//! output is represented by a [`Channel`], obtained when creating the [`Dac`].
+//! A [`Channel`] becomes an [`Output`] once it is given ownership of its pin
+//! by calling [`Channel::with_pin`].
+//!
+//! ```no_run
+//! let apb_dac = buses.apb.enable(tokens.apbs.dac);
+//! let (pclk_dac, _gclk0) = Pclk::enable(tokens.pclks.dac, clocks.gclk0);
+//!
+//! let (mut dac, channels) = Dac::new(peripherals.dac, apb_dac, &pclk_dac, Reference::Vddana).unwrap();
+//! let mut out0 = channels.0.with_pin(pins.pa02, OutputConfig::default());
+//!
+//! // Output mid-scale
+//! out0.write(2048);
+//! ```
+//!
+//! ## Channel configuration
+//!
+//! Each output can be configured with [`OutputConfig`], including
+//! - dithering, in which case the four LSBs of each sample are used as dither
+//!   bits;
+//! - an automatic refresh period, used to maintain the output voltage when no
+//!   new conversions are started;
+//! - the oversampling ratio of the interpolation filter.
+//!
+//! The `DACCTRLx` registers are enable-protected. Configuring an [`Output`]
+//! therefore briefly disables the whole DAC, which will interrupt any ongoing
+//! conversion on the other channel.
+//!
+//! ## DMA
+//!
+//! With the `dma` feature enabled, an [`Output`] can be consumed by
+//! [`Output::send_with_dma`] to stream a buffer of samples. The rate at which
+//! samples are produced is set by the DMA trigger source. Either trigger the
+//! DMA channel directly from a timer overflow (eg `TriggerSource::Tc4Ovf`), or
+//! enable [`Output::enable_start_event`] so that the DMAC fills `DATABUF` on
+//! the channel's EMPTY trigger and an EVSYS event (typically a timer overflow)
+//! loads each sample into the DAC.
+
+use core::marker::PhantomData;
+
+use crate::clock::v2::{
+    apb::ApbClk,
+    pclk::{Pclk, PclkSourceId},
+    types::Dac as DacId,
+};
+use crate::gpio::{AlternateB, AnyPin, PA02, PA05, Pin, PinId};
+use crate::pac;
+use crate::typelevel::Sealed;
+
+pub use crate::pac::dac::ctrlb::Refselselect as Reference;
+pub use crate::pac::dac::dacctrl::{Osrselect as Oversampling, Refreshselect as Refresh};
+
+use crate::pac::dac::dacctrl::Cctrlselect;
+
+/// Maximum value that can be written to the DAC, when dithering is disabled
+pub const MAX_VALUE: u16 = (1 << 12) - 1;
+
+/// Maximum frequency of `GCLK_DAC`
+const MAX_CLOCK_FREQ: u32 = 12_000_000;
+
+/// Errors that may occur when creating a [`Dac`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// Clock too fast.
+    ///
+    /// The DAC requires that it's fed a GCLK that does not exceed 12 MHz.
+    ClockTooFast,
+}
+
+bitflags::bitflags! {
+    /// DAC interrupt flags
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Flags: u8 {
+        /// Channel 0 underrun
+        const UNDERRUN0 = 0x01;
+        /// Channel 1 underrun
+        const UNDERRUN1 = 0x02;
+        /// Channel 0 data buffer empty
+        const EMPTY0 = 0x04;
+        /// Channel 1 data buffer empty
+        const EMPTY1 = 0x08;
+        /// Channel 0 filter result ready
+        const RESRDY0 = 0x10;
+        /// Channel 1 filter result ready
+        const RESRDY1 = 0x20;
+        /// Channel 0 filter overrun
+        const OVERRUN0 = 0x40;
+        /// Channel 1 filter overrun
+        const OVERRUN1 = 0x80;
+    }
+}
+
+//==============================================================================
+// Channel IDs
+//==============================================================================
+
+/// Trait representing a DAC channel ID.
+pub trait ChId: Sealed {
+    /// Index of the channel
+    const ID: usize;
+    /// Pin connected to this channel's output
+    type PinId: PinId;
+}
+
+/// Type-level variant of [`ChId`] for `VOUT0`
+pub enum Ch0 {}
+impl Sealed for Ch0 {}
+impl ChId for Ch0 {
+    const ID: usize = 0;
+    type PinId = PA02;
+}
+
+/// Type-level variant of [`ChId`] for `VOUT1`
+pub enum Ch1 {}
+impl Sealed for Ch1 {}
+impl ChId for Ch1 {
+    const ID: usize = 1;
+    type PinId = PA05;
+}
+
+//==============================================================================
+// OutputConfig
+//==============================================================================
+
+/// Configuration of a DAC [`Output`]
+#[derive(Clone, Copy, Debug)]
+pub struct OutputConfig {
+    /// Left-adjust the sample data
+    pub left_adjust: bool,
+    /// Enable dithering. The four LSBs of each sample are used as dither
+    /// bits, and the sample data is left-adjusted.
+    pub dither: bool,
+    /// Automatic refresh period. [`Refresh::Refresh0`] disables refresh.
+    pub refresh: Refresh,
+    /// Oversampling ratio of the interpolation filter
+    pub oversampling: Oversampling,
+    /// Keep the output running in standby sleep mode
+    pub run_in_standby: bool,
+    /// Enable the external filter, which requires an external capacitor on
+    /// the output
+    pub external_filter: bool,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            left_adjust: false,
+            dither: false,
+            refresh: Refresh::Refresh0,
+            oversampling: Oversampling::Osr1,
+            run_in_standby: false,
+            external_filter: false,
+        }
+    }
+}
+
+impl OutputConfig {
+    /// Enable or disable dithering
+    #[inline]
+    pub fn dither(mut self, dither: bool) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    /// Set the automatic refresh period
+    #[inline]
+    pub fn refresh(mut self, refresh: Refresh) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Set the interpolation filter oversampling ratio
+    #[inline]
+    pub fn oversampling(mut self, oversampling: Oversampling) -> Self {
+        self.oversampling = oversampling;
+        self
+    }
+
+    /// Keep the output running in standby sleep mode
+    #[inline]
+    pub fn run_in_standby(mut self, run_in_standby: bool) -> Self {
+        self.run_in_standby = run_in_standby;
+        self
+    }
+}
+
+//==============================================================================
+// Dac
+//==============================================================================
+
+/// Digital-to-Analog Converter
+///
+/// Controls the settings shared by both channels.
+pub struct Dac {
+    dac: pac::Dac,
+    apbclk: ApbClk<DacId>,
+}
+
+/// Handles to the individual DAC channels
+pub struct Channels(pub Channel<Ch0>, pub Channel<Ch1>);
+
+impl Dac {
+    /// Create a new [`Dac`], and return handles to its two channels.
+    ///
+    /// The [`new`](Self::new) function currently takes an `&` reference to a
+    /// [`Pclk`]. In the meantime, you must ensure that the PCLK is enabled for
+    /// the `Dac` struct's lifetime.
+    ///
+    /// Returns [`Error::ClockTooFast`] if the PCLK frequency exceeds 12 MHz.
+    pub fn new<PS: PclkSourceId>(
+        dac: pac::Dac,
+        apbclk: ApbClk<DacId>,
+        pclk: &Pclk<DacId, PS>,
+        reference: Reference,
+    ) -> Result<(Self, Channels), Error> {
+        let freq = pclk.freq().to_Hz();
+        if freq > MAX_CLOCK_FREQ {
+            return Err(Error::ClockTooFast);
+        }
+
+        dac.ctrla().write(|w| w.swrst().set_bit());
+        while dac.syncbusy().read().swrst().bit_is_set() {
+            core::hint::spin_loop();
+        }
+
+        dac.ctrlb().write(|w| w.refsel().variant(reference));
+
+        // The current control must match the GCLK_DAC frequency
+        let cctrl = if freq > 6_000_000 {
+            Cctrlselect::Cc12m
+        } else if freq > 1_200_000 {
+            Cctrlselect::Cc1m
+        } else {
+            Cctrlselect::Cc100k
+        };
+        for dacctrl in dac.dacctrl_iter() {
+            dacctrl.write(|w| w.cctrl().variant(cctrl));
+        }
+
+        let channels = Channels(Channel::new(), Channel::new());
+        Ok((Self { dac, apbclk }, channels))
+    }
+
+    /// Change the voltage reference of both channels
+    ///
+    /// This briefly disables the DAC.
+    pub fn set_reference(&mut self, reference: Reference) {
+        with_disable(&self.dac, |dac| {
+            dac.ctrlb().modify(|_, w| w.refsel().variant(reference))
+        });
+    }
+
+    /// Output `VOUT0 - VOUT1` as a differential signal on both outputs.
+    ///
+    /// In differential mode, only `DATA0` is used, as a signed 12-bit value.
+    /// This briefly disables the DAC.
+    pub fn set_differential(&mut self, differential: bool) {
+        with_disable(&self.dac, |dac| {
+            dac.ctrlb().modify(|_, w| w.diff().bit(differential))
+        });
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.dac.intflag().read().bits())
+    }
+
+    /// Clear the specified interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.dac
+            .intflag()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable the specified interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.dac
+            .intenset()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the specified interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.dac
+            .intenclr()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Reset the DAC and return the underlying resources.
+    ///
+    /// **Note**: The [`Channels`] struct is consumed by this method. Any
+    /// [`Output`] must first be turned back into a [`Channel`] by calling
+    /// [`Output::free`].
+    pub fn free(self, _channels: Channels) -> (pac::Dac, ApbClk<DacId>) {
+        self.dac.ctrla().write(|w| w.swrst().set_bit());
+        while self.dac.syncbusy().read().swrst().bit_is_set() {
+            core::hint::spin_loop();
+        }
+        (self.dac, self.apbclk)
+    }
+}
+
+/// Run the provided closure with the DAC disabled, then re-enable it if it was
+/// previously enabled.
+fn with_disable<R>(
+    dac: &pac::dac::RegisterBlock,
+    fun: impl FnOnce(&pac::dac::RegisterBlock) -> R,
+) -> R {
+    critical_section::with(|_| {
+        let enabled = dac.ctrla().read().enable().bit_is_set();
+        dac.ctrla().modify(|_, w| w.enable().clear_bit());
+        while dac.syncbusy().read().enable().bit_is_set() {
+            core::hint::spin_loop();
+        }
+
+        let ret = fun(dac);
+
+        if enabled {
+            dac.ctrla().modify(|_, w| w.enable().set_bit());
+            while dac.syncbusy().read().enable().bit_is_set() {
+                core::hint::spin_loop();
+            }
+        }
+        ret
+    })
+}
+
+//==============================================================================
+// Channel
+//==============================================================================
+
+/// DAC channel
+///
+/// Use this struct to create an [`Output`] by calling
+/// [`with_pin`](Self::with_pin).
+pub struct Channel<Id: ChId> {
+    _id: PhantomData<Id>,
+}
+
+impl<Id: ChId> Channel<Id> {
+    fn new() -> Self {
+        Self { _id: PhantomData }
+    }
+
+    /// Borrow the DAC registers.
+    ///
+    /// Each channel only ever accesses the registers (or bits) belonging to
+    /// it, except for enable-protected registers, which are accessed through
+    /// [`with_disable`] in a critical section.
+    #[inline]
+    fn dac(&self) -> &pac::dac::RegisterBlock {
+        unsafe { &*pac::Dac::ptr() }
+    }
+
+    /// Assign a pin to this [`Channel`], and turn it into an enabled
+    /// [`Output`].
+    pub fn with_pin(self, pin: impl AnyPin<Id = Id::PinId>, config: OutputConfig) -> Output<Id> {
+        let mut output = Output {
+            chan: self,
+            pin: pin.into().into_alternate(),
+            start_event: false,
+        };
+        output.configure(config);
+        output
+    }
+}
+
+//==============================================================================
+// Output
+//==============================================================================
+
+/// A DAC channel driving its output pin
+pub struct Output<Id: ChId> {
+    chan: Channel<Id>,
+    pin: Pin<Id::PinId, AlternateB>,
+    start_event: bool,
+}
+
+impl<Id: ChId> Output<Id> {
+    /// Reconfigure the output.
+    ///
+    /// This briefly disables the DAC.
+    pub fn configure(&mut self, config: OutputConfig) {
+        let dac = self.chan.dac();
+        with_disable(dac, |dac| {
+            dac.dacctrl(Id::ID).modify(|_, w| {
+                w.leftadj().bit(config.left_adjust || config.dither);
+                w.dither().bit(config.dither);
+                w.refresh().variant(config.refresh);
+                w.osr().variant(config.oversampling);
+                w.runstdby().bit(config.run_in_standby);
+                w.fext().bit(config.external_filter);
+                w.enable().set_bit()
+            })
+        });
+
+        // The DAC is left disabled by `Dac::new` until a first channel is
+        // configured
+        dac.ctrla().modify(|_, w| w.enable().set_bit());
+        while dac.syncbusy().read().enable().bit_is_set() {
+            core::hint::spin_loop();
+        }
+
+        // Wait for the channel to complete its startup time
+        while !self.is_ready() {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Check whether the channel has completed its startup
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.chan.dac().status().read().bits() & (1 << Id::ID) != 0
+    }
+
+    /// Check whether the previous conversion has completed
+    #[inline]
+    pub fn is_conversion_complete(&self) -> bool {
+        self.chan.dac().status().read().bits() & (1 << (Id::ID + 2)) != 0
+    }
+
+    #[inline]
+    fn data_sync(&self) {
+        // SYNCBUSY.DATAx and SYNCBUSY.DATABUFx
+        let mask = (1 << (Id::ID + 2)) | (1 << (Id::ID + 4));
+        while self.chan.dac().syncbusy().read().bits() & mask != 0 {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Write a sample to the output.
+    ///
+    /// The sample is 12 bits wide (right-adjusted) by default. If dithering
+    /// or left adjustment is enabled, the sample is 16 bits wide.
+    #[inline]
+    pub fn write(&mut self, value: u16) {
+        self.data_sync();
+        self.chan
+            .dac()
+            .data(Id::ID)
+            .write(|w| unsafe { w.data().bits(value) });
+    }
+
+    /// Write a sample to the data buffer. It will be output on the next START
+    /// event. See [`enable_start_event`](Self::enable_start_event).
+    #[inline]
+    pub fn write_buffered(&mut self, value: u16) {
+        self.data_sync();
+        self.chan
+            .dac()
+            .databuf(Id::ID)
+            .write(|w| unsafe { w.databuf().bits(value) });
+    }
+
+    /// Start a conversion of `DATABUFx` on each incoming EVSYS event.
+    ///
+    /// When enabled, samples should be written to `DATABUFx` (which
+    /// [`write_buffered`](Self::write_buffered) and DMA transfers will do),
+    /// and are only output once the START event is received. The channel will
+    /// also emit an EMPTY event once `DATABUFx` has been consumed.
+    ///
+    /// This briefly disables the DAC.
+    pub fn enable_start_event(&mut self, enable: bool) {
+        let mask = (1 << Id::ID) | (1 << (Id::ID + 2));
+        with_disable(self.chan.dac(), |dac| {
+            dac.evctrl().modify(|r, w| {
+                let bits = if enable {
+                    r.bits() | mask
+                } else {
+                    r.bits() & !mask
+                };
+                unsafe { w.bits(bits) }
+            })
+        });
+        self.start_event = enable;
+    }
+
+    /// Read the output of the interpolation filter
+    #[inline]
+    pub fn filter_result(&self) -> u16 {
+        self.chan.dac().result(Id::ID).read().result().bits()
+    }
+
+    /// Disable the output and return the underlying [`Channel`] and pin
+    pub fn free(self) -> (Channel<Id>, Pin<Id::PinId, AlternateB>) {
+        with_disable(self.chan.dac(), |dac| {
+            dac.dacctrl(Id::ID).modify(|_, w| w.enable().clear_bit())
+        });
+        (self.chan, self.pin)
+    }
+
+    /// Pointer to the register written by DMA transfers
+    #[cfg(feature = "dma")]
+    #[inline]
+    fn dma_target(&self) -> *mut u16 {
+        if self.start_event {
+            self.chan.dac().databuf(Id::ID).as_ptr()
+        } else {
+            self.chan.dac().data(Id::ID).as_ptr()
+        }
+    }
+}
+
+#[cfg(feature = "dma")]
+mod dma {
+    use super::*;
+    use crate::dmac::{
+        self, AnyChannel, Buffer, Busy, Channel as DmaChannel, InterruptFlags, Ready, Transfer,
+        TriggerAction, TriggerSource, transfer::BufferPair,
+    };
+
+    /// DMA trigger sources of a DAC channel
+    pub trait DmaTriggers: ChId {
+        /// Trigger issued when `DATABUFx` is empty
+        const EMPTY_TRIGGER: TriggerSource;
+        /// Trigger issued when the interpolation filter result is ready
+        const RESRDY_TRIGGER: TriggerSource;
+    }
+
+    impl DmaTriggers for Ch0 {
+        const EMPTY_TRIGGER: TriggerSource = TriggerSource::DacEmpty0;
+        const RESRDY_TRIGGER: TriggerSource = TriggerSource::DacResrdy0;
+    }
+
+    impl DmaTriggers for Ch1 {
+        const EMPTY_TRIGGER: TriggerSource = TriggerSource::DacEmpty1;
+        const RESRDY_TRIGGER: TriggerSource = TriggerSource::DacResrdy1;
+    }
+
+    unsafe impl<Id: ChId> Buffer for Output<Id> {
+        type Beat = u16;
+
+        #[inline]
+        fn dma_ptr(&mut self) -> *mut Self::Beat {
+            self.dma_target()
+        }
+
+        #[inline]
+        fn incrementing(&self) -> bool {
+            false
+        }
+
+        #[inline]
+        fn buffer_len(&self) -> usize {
+            1
+        }
+    }
+
+    impl<Id: DmaTriggers> Output<Id> {
+        /// Transform an [`Output`] into a DMA [`Transfer`] and start streaming
+        /// the provided buffer.
+        ///
+        /// One sample is written each time `trigger` fires. Use a timer
+        /// overflow trigger to write directly to `DATAx`, or the channel's
+        /// [`EMPTY_TRIGGER`](DmaTriggers::EMPTY_TRIGGER) together with
+        /// [`enable_start_event`](Self::enable_start_event) to fill
+        /// `DATABUFx` and let EVSYS pace the conversions.
+        ///
+        /// Set `circular` to loop over the buffer indefinitely, eg for a
+        /// periodic waveform.
+        #[inline]
+        pub fn send_with_dma<Ch, B>(
+            self,
+            buf: B,
+            mut channel: Ch,
+            trigger: TriggerSource,
+            circular: bool,
+        ) -> Transfer<DmaChannel<Ch::Id, Busy>, BufferPair<B, Self>>
+        where
+            Ch: AnyChannel<Status = Ready>,
+            B: Buffer<Beat = u16> + 'static,
+        {
+            channel
+                .as_mut()
+                .enable_interrupts(InterruptFlags::new().with_tcmpl(true));
+
+            // SAFETY: This is safe because of the `'static` bound check for `B`,
+            // and the fact that the buffer length of an `Output` is always 1.
+            let xfer = unsafe { dmac::Transfer::new_unchecked(channel, buf, self, circular) };
+            xfer.begin(trigger, TriggerAction::Burst)
+        }
+    }
+}
+
+#[cfg(feature = "dma")]
+pub use dma::DmaTriggers;
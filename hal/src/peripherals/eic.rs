@@ -61,6 +61,12 @@
 //!   async channels.
 //! * Use the provided [`wait`](ExtInt::wait) method. async-enabled [`ExtInt`]s
 //!   also implement [`embedded_hal_async::digital::Wait`].
+//!
+//! ## Non-maskable interrupt
+//!
+//! The NMI pin is not an EXTINT channel. It is turned into an [`Nmi`] by
+//! calling [`Eic::nmi`], and triggers the `NonMaskableInt` exception. See the
+//! [`Nmi`] documentation for details.
 
 use core::marker::PhantomData;
 
@@ -82,6 +88,9 @@ mod impls {}
 #[cfg(feature = "async")]
 pub use impls::async_api::*;
 
+mod nmi;
+pub use nmi::*;
+
 #[hal_cfg("eic-d5x")]
 use super::clock::v2::{self, gclk::GclkId, osculp32k::OscUlp32kId, pclk::Pclk, rtcosc::RtcOsc};
 
@@ -54,12 +54,14 @@ where
 {
     /// Enables the event output of the channel for the event system.
     ///
+    /// The event can then be routed to event users with [`crate::evsys`].
+    ///
     /// Note that whilst this function is executed, the EIC peripheral is disabled
     /// in order to write to the evctrl register
     pub fn enable_event(&mut self) {
         self.chan.with_disable(|e| {
             e.evctrl()
-                .modify(|_, w| unsafe { w.bits(1 << P::ChId::ID) });
+                .modify(|r, w| unsafe { w.bits(r.bits() | 1 << P::ChId::ID) });
         });
     }
 
@@ -142,6 +144,40 @@ where
     }
 }
 
+impl<P, Id, F> crate::pm::WakeupSource for ExtInt<P, Id, F>
+where
+    P: EicPin,
+    Id: ChId,
+{
+    #[inline]
+    fn interrupt(&self) -> u16 {
+        use cortex_m::interrupt::InterruptNumber;
+        crate::pac::Interrupt::EIC.number()
+    }
+
+    /// The channel is armed when both its interrupt and its wakeup are
+    /// enabled.
+    #[inline]
+    fn is_armed(&self) -> bool {
+        let mask = 1 << P::ChId::ID;
+        self.chan.eic.intenset().read().bits() & mask != 0
+            && self.chan.eic.wakeup().read().bits() & mask != 0
+    }
+
+    /// Level detection without filter is asynchronous and works in standby.
+    /// Otherwise, the generic clock generator of the EIC must run in standby.
+    #[inline]
+    fn runs_in_standby(&self) -> bool {
+        // Each channel has 4 bits in the CONFIG registers: SENSE, then FILTEN
+        let config = self.chan.eic.config((P::ChId::ID >> 3) & 0b1).read().bits();
+        let config = (config >> ((P::ChId::ID & 0b111) * 4)) & 0b1111;
+        let sense = (config & 0b111) as u8;
+        let level = sense == Sense::High as u8 || sense == Sense::Low as u8;
+        let filter = config & 0b1000 != 0;
+        (level && !filter) || crate::clock::runs_in_standby(crate::clock::ClockId::Eic)
+    }
+}
+
 impl<P, C, Id, F> InputPin_02 for ExtInt<P, Id, F>
 where
     P: EicPin + AnyPin<Mode = Interrupt<C>>,
@@ -202,16 +238,8 @@ mod async_impls {
             self.disable_interrupt();
 
             match sense {
-                Sense::High => {
-                    if self.is_high().unwrap() {
-                        return;
-                    }
-                }
-                Sense::Low => {
-                    if self.is_low().unwrap() {
-                        return;
-                    }
-                }
+                Sense::High if self.is_high().unwrap() => return,
+                Sense::Low if self.is_low().unwrap() => return,
                 _ => (),
             }
 
@@ -328,6 +356,9 @@ mod impls {
 mod impls {
     use super::*;
 
+    #[hal_cfg("pa08")]
+    impl NmiPin for gpio::PA08 {}
+
     ei!(ExtInt[0] {
         #[hal_cfg("pa00")]
         PA00,
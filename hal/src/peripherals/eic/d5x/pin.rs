@@ -60,12 +60,14 @@ where
 {
     /// Enables the event output of the channel for the event system.
     ///
+    /// The event can then be routed to event users with [`crate::evsys`].
+    ///
     /// Note that whilst this function is executed, the EIC peripheral is disabled
     /// in order to write to the evctrl register
     pub fn enable_event(&mut self) {
         self.chan.with_disable(|e| {
             e.evctrl()
-                .modify(|_, w| unsafe { w.bits(1 << P::ChId::ID) });
+                .modify(|r, w| unsafe { w.bits(r.bits() | 1 << P::ChId::ID) });
         });
     }
 
@@ -162,6 +164,35 @@ where
     }
 }
 
+impl<P, Id, F> crate::pm::WakeupSource for ExtInt<P, Id, F>
+where
+    P: EicPin,
+    Id: ChId,
+{
+    #[inline]
+    fn interrupt(&self) -> u16 {
+        use cortex_m::interrupt::InterruptNumber;
+        // The EXTINT interrupts are contiguous
+        crate::pac::Interrupt::EIC_EXTINT_0.number() + P::ChId::ID as u16
+    }
+
+    #[inline]
+    fn is_armed(&self) -> bool {
+        self.chan.eic.intenset().read().bits() & (1 << P::ChId::ID) != 0
+    }
+
+    /// The EIC runs in standby when clocked by the ULP32K oscillator, or when
+    /// the channel uses asynchronous detection. Otherwise, the generic clock
+    /// generator of the EIC must run in standby.
+    #[inline]
+    fn runs_in_standby(&self) -> bool {
+        let eic = &self.chan.eic;
+        eic.ctrla().read().cksel().is_clk_ulp32k()
+            || eic.asynch().read().bits() & (1 << P::ChId::ID) != 0
+            || crate::clock::runs_in_standby(crate::clock::ClockId::EIC)
+    }
+}
+
 impl<P, C, Id, F> InputPin_02 for ExtInt<P, Id, F>
 where
     P: EicPin + AnyPin<Mode = Interrupt<C>>,
@@ -309,6 +340,9 @@ mod async_impls {
     }
 }
 
+#[hal_cfg("pa08")]
+impl NmiPin for gpio::PA08 {}
+
 ei!(ExtInt[0] {
     #[hal_cfg("pa00")]
     PA00,
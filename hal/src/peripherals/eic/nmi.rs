@@ -0,0 +1,130 @@
+//! Non-maskable interrupt
+//!
+//! The EIC has a dedicated NMI input, on top of the EXTINT channels. Unlike
+//! an EXTINT, it triggers the `NonMaskableInt` exception, which preempts any
+//! interrupt handler or critical section. This makes it suitable for
+//! emergency-stop inputs.
+//!
+//! The exception handler is defined with cortex-m-rt. Since it may run inside
+//! a critical section, it must not share the [`Nmi`] through a
+//! `critical_section::Mutex`. Use the free functions [`nmi_is_interrupt`] and
+//! [`nmi_clear_interrupt`] instead:
+//!
+//! ```no_run
+//! let mut nmi = eic.nmi(pins.pa08.into_pull_up_interrupt());
+//! nmi.filter(true);
+//! nmi.sense(Sense::Fall);
+//!
+//! #[exception]
+//! unsafe fn NonMaskableInt() {
+//!     if nmi_is_interrupt() {
+//!         nmi_clear_interrupt();
+//!         emergency_stop();
+//!     }
+//! }
+//! ```
+
+use atsamd_hal_macros::hal_cfg;
+
+use super::{Eic, Sense};
+use crate::gpio::{Interrupt, InterruptConfig, Pin, PinId};
+use crate::pac;
+
+/// Trait representing the GPIO pin of the NMI
+///
+/// It is implemented for `PA08` on the SAMD21 and SAMx5x chips.
+pub trait NmiPin: PinId {}
+
+/// The non-maskable interrupt, tied to its GPIO [`Pin`]
+///
+/// The NMI is disabled until a [`sense`](Self::sense) other than
+/// [`Sense::None`] is configured.
+pub struct Nmi<I: NmiPin, C: InterruptConfig> {
+    eic: core::mem::ManuallyDrop<pac::Eic>,
+    pin: Pin<I, Interrupt<C>>,
+}
+
+impl<F> Eic<F> {
+    /// Turn the NMI pin into an [`Nmi`].
+    ///
+    /// The [`Nmi`] must be [freed](Nmi::free) before the [`Eic`] itself is
+    /// [freed](Eic::free), since resetting the EIC also disables the NMI.
+    #[inline]
+    pub fn nmi<I: NmiPin, C: InterruptConfig>(&mut self, pin: Pin<I, Interrupt<C>>) -> Nmi<I, C> {
+        Nmi {
+            // SAFETY: The NMI registers are only accessed through the `Nmi`,
+            // which is unique since it owns the NMI pin.
+            eic: core::mem::ManuallyDrop::new(unsafe { core::ptr::read(&self.eic as *const _) }),
+            pin,
+        }
+    }
+}
+
+impl<I: NmiPin, C: InterruptConfig> Nmi<I, C> {
+    /// Set the condition triggering the NMI. [`Sense::None`] disables it.
+    #[inline]
+    pub fn sense(&mut self, sense: Sense) {
+        self.eic
+            .nmictrl()
+            .modify(|_, w| unsafe { w.nmisense().bits(sense as u8) });
+    }
+
+    /// Enable the filter on the NMI pin, which takes the majority of three
+    /// samples
+    #[inline]
+    pub fn filter(&mut self, filter: bool) {
+        self.eic.nmictrl().modify(|_, w| w.nmifilten().bit(filter));
+    }
+
+    /// Detect the edges asynchronously, without the EIC clock. This is
+    /// required to wake the device with an edge when the EIC clock is
+    /// stopped in sleep.
+    #[hal_cfg("eic-d5x")]
+    #[inline]
+    pub fn async_edge_detection(&mut self, asynch: bool) {
+        self.eic.nmictrl().modify(|_, w| w.nmiasynch().bit(asynch));
+    }
+
+    /// Returns `true` if the NMI has been triggered
+    #[inline]
+    pub fn is_interrupt(&self) -> bool {
+        nmi_is_interrupt()
+    }
+
+    /// Clear the NMI flag
+    #[inline]
+    pub fn clear_interrupt(&mut self) {
+        nmi_clear_interrupt();
+    }
+
+    /// Disable the NMI, and return the GPIO [`Pin`]
+    pub fn free(mut self) -> Pin<I, Interrupt<C>> {
+        self.sense(Sense::None);
+        self.eic.nmictrl().modify(|_, w| w.nmifilten().clear_bit());
+        nmi_clear_interrupt();
+        self.pin
+    }
+}
+
+/// Returns `true` if the NMI has been triggered.
+///
+/// Unlike [`Nmi::is_interrupt`], this can be called from the `NonMaskableInt`
+/// exception handler.
+#[inline]
+pub fn nmi_is_interrupt() -> bool {
+    // SAFETY: NMIFLAG is only read
+    let eic = unsafe { pac::Peripherals::steal().eic };
+    eic.nmiflag().read().nmi().bit_is_set()
+}
+
+/// Clear the NMI flag.
+///
+/// Unlike [`Nmi::clear_interrupt`], this can be called from the
+/// `NonMaskableInt` exception handler.
+#[inline]
+pub fn nmi_clear_interrupt() {
+    // SAFETY: Writing a zero to NMIFLAG has no effect, so clearing the flag
+    // doesn't race with other accesses to the EIC
+    let eic = unsafe { pac::Peripherals::steal().eic };
+    eic.nmiflag().write(|w| w.nmi().set_bit());
+}
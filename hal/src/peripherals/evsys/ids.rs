@@ -0,0 +1,368 @@
+//! Event generator and user IDs
+//!
+//! The generator and user tables differ for each chip family. Only the most
+//! commonly used generators and users are listed here.
+
+use atsamd_hal_macros::{hal_cfg, hal_macro_helper};
+
+use super::{Generator, User, UserId};
+use crate::typelevel::Sealed;
+
+macro_rules! generators {
+    (
+        $(
+            $( #[hal_cfg( $($cfg:tt)* )] )?
+            $Name:ident = $id:literal
+        ),+
+        $(,)?
+    ) => {
+        /// Type-level markers for event generators
+        pub mod generators {
+            use super::*;
+
+            $(
+                $( #[hal_cfg( $($cfg)* )] )?
+                #[doc = concat!("`", stringify!($Name), "` event generator")]
+                pub enum $Name {}
+
+                $( #[hal_cfg( $($cfg)* )] )?
+                impl Sealed for $Name {}
+
+                $( #[hal_cfg( $($cfg)* )] )?
+                impl Generator for $Name {
+                    const ID: u8 = $id;
+                }
+            )+
+        }
+    };
+}
+
+macro_rules! users {
+    (
+        $(
+            $( #[hal_cfg( $($cfg:tt)* )] )?
+            $field:ident: $Name:ident = $id:literal
+        ),+
+        $(,)?
+    ) => {
+        /// Type-level markers for event users
+        pub mod users {
+            use super::*;
+
+            $(
+                $( #[hal_cfg( $($cfg)* )] )?
+                #[doc = concat!("`", stringify!($Name), "` event user")]
+                pub enum $Name {}
+
+                $( #[hal_cfg( $($cfg)* )] )?
+                impl Sealed for $Name {}
+
+                $( #[hal_cfg( $($cfg)* )] )?
+                impl UserId for $Name {
+                    const ID: u8 = $id;
+                }
+            )+
+        }
+
+        /// Tokens for each event user.
+        ///
+        /// Each [`User`] can be attached to a single channel at a time.
+        #[hal_macro_helper]
+        pub struct Users {
+            $(
+                $( #[hal_cfg( $($cfg)* )] )?
+                pub $field: User<users::$Name>,
+            )+
+        }
+
+        impl Users {
+            #[hal_macro_helper]
+            pub(super) fn new() -> Self {
+                Self {
+                    $(
+                        $( #[hal_cfg( $($cfg)* )] )?
+                        $field: User::new(),
+                    )+
+                }
+            }
+        }
+    };
+}
+
+//==============================================================================
+// SAMD11
+//==============================================================================
+
+#[hal_cfg("evsys-d11")]
+generators! {
+    RtcCmp0 = 0x01,
+    RtcCmp1 = 0x02,
+    RtcOvf = 0x03,
+    RtcPer0 = 0x04,
+    RtcPer1 = 0x05,
+    RtcPer2 = 0x06,
+    RtcPer3 = 0x07,
+    RtcPer4 = 0x08,
+    RtcPer5 = 0x09,
+    RtcPer6 = 0x0A,
+    RtcPer7 = 0x0B,
+    EicExtint0 = 0x0C,
+    EicExtint1 = 0x0D,
+    EicExtint2 = 0x0E,
+    EicExtint3 = 0x0F,
+    EicExtint4 = 0x10,
+    EicExtint5 = 0x11,
+    EicExtint6 = 0x12,
+    EicExtint7 = 0x13,
+    DmacCh0 = 0x14,
+    DmacCh1 = 0x15,
+    DmacCh2 = 0x16,
+    DmacCh3 = 0x17,
+    Tcc0Ovf = 0x18,
+    Tc1Ovf = 0x1F,
+    Tc2Ovf = 0x22,
+    AdcResrdy = 0x25,
+    AdcWinmon = 0x26,
+    AcComp0 = 0x27,
+    AcComp1 = 0x28,
+    AcWin0 = 0x29,
+    DacEmpty = 0x2A,
+}
+
+#[hal_cfg("evsys-d11")]
+users! {
+    dmac_ch0: DmacCh0 = 0x00,
+    dmac_ch1: DmacCh1 = 0x01,
+    dmac_ch2: DmacCh2 = 0x02,
+    dmac_ch3: DmacCh3 = 0x03,
+    tcc0_ev0: Tcc0Ev0 = 0x04,
+    tcc0_ev1: Tcc0Ev1 = 0x05,
+    tc1: Tc1 = 0x0A,
+    tc2: Tc2 = 0x0B,
+    adc_start: AdcStart = 0x0C,
+    adc_sync: AdcSync = 0x0D,
+    ac_comp0: AcComp0 = 0x0E,
+    ac_comp1: AcComp1 = 0x0F,
+    dac_start: DacStart = 0x10,
+}
+
+//==============================================================================
+// SAMD21
+//==============================================================================
+
+#[hal_cfg("evsys-d21")]
+generators! {
+    RtcCmp0 = 0x01,
+    RtcCmp1 = 0x02,
+    RtcOvf = 0x03,
+    RtcPer0 = 0x04,
+    RtcPer1 = 0x05,
+    RtcPer2 = 0x06,
+    RtcPer3 = 0x07,
+    RtcPer4 = 0x08,
+    RtcPer5 = 0x09,
+    RtcPer6 = 0x0A,
+    RtcPer7 = 0x0B,
+    EicExtint0 = 0x0C,
+    EicExtint1 = 0x0D,
+    EicExtint2 = 0x0E,
+    EicExtint3 = 0x0F,
+    EicExtint4 = 0x10,
+    EicExtint5 = 0x11,
+    EicExtint6 = 0x12,
+    EicExtint7 = 0x13,
+    EicExtint8 = 0x14,
+    EicExtint9 = 0x15,
+    EicExtint10 = 0x16,
+    EicExtint11 = 0x17,
+    EicExtint12 = 0x18,
+    EicExtint13 = 0x19,
+    EicExtint14 = 0x1A,
+    EicExtint15 = 0x1B,
+    DmacCh0 = 0x1C,
+    DmacCh1 = 0x1D,
+    DmacCh2 = 0x1E,
+    DmacCh3 = 0x1F,
+    Tcc0Ovf = 0x20,
+    Tcc1Ovf = 0x27,
+    Tcc2Ovf = 0x2C,
+    Tc3Ovf = 0x31,
+    Tc4Ovf = 0x34,
+    Tc5Ovf = 0x37,
+    #[hal_cfg("tc6")]
+    Tc6Ovf = 0x3A,
+    #[hal_cfg("tc7")]
+    Tc7Ovf = 0x3D,
+    AdcResrdy = 0x40,
+    AdcWinmon = 0x41,
+    AcComp0 = 0x42,
+    AcComp1 = 0x43,
+    AcWin0 = 0x44,
+    DacEmpty = 0x45,
+}
+
+#[hal_cfg("evsys-d21")]
+users! {
+    dmac_ch0: DmacCh0 = 0x00,
+    dmac_ch1: DmacCh1 = 0x01,
+    dmac_ch2: DmacCh2 = 0x02,
+    dmac_ch3: DmacCh3 = 0x03,
+    tcc0_ev0: Tcc0Ev0 = 0x04,
+    tcc0_ev1: Tcc0Ev1 = 0x05,
+    tcc1_ev0: Tcc1Ev0 = 0x0A,
+    tcc1_ev1: Tcc1Ev1 = 0x0B,
+    tcc2_ev0: Tcc2Ev0 = 0x0E,
+    tcc2_ev1: Tcc2Ev1 = 0x0F,
+    tc3: Tc3 = 0x12,
+    tc4: Tc4 = 0x13,
+    tc5: Tc5 = 0x14,
+    #[hal_cfg("tc6")]
+    tc6: Tc6 = 0x15,
+    #[hal_cfg("tc7")]
+    tc7: Tc7 = 0x16,
+    adc_start: AdcStart = 0x17,
+    adc_sync: AdcSync = 0x18,
+    ac_comp0: AcComp0 = 0x19,
+    ac_comp1: AcComp1 = 0x1A,
+    dac_start: DacStart = 0x1B,
+}
+
+//==============================================================================
+// SAMx5x
+//==============================================================================
+
+#[hal_cfg("evsys-d5x")]
+generators! {
+    RtcPer0 = 0x04,
+    RtcPer1 = 0x05,
+    RtcPer2 = 0x06,
+    RtcPer3 = 0x07,
+    RtcPer4 = 0x08,
+    RtcPer5 = 0x09,
+    RtcPer6 = 0x0A,
+    RtcPer7 = 0x0B,
+    RtcCmp0 = 0x0C,
+    RtcCmp1 = 0x0D,
+    RtcCmp2 = 0x0E,
+    RtcCmp3 = 0x0F,
+    RtcTamper = 0x10,
+    RtcOvf = 0x11,
+    EicExtint0 = 0x12,
+    EicExtint1 = 0x13,
+    EicExtint2 = 0x14,
+    EicExtint3 = 0x15,
+    EicExtint4 = 0x16,
+    EicExtint5 = 0x17,
+    EicExtint6 = 0x18,
+    EicExtint7 = 0x19,
+    EicExtint8 = 0x1A,
+    EicExtint9 = 0x1B,
+    EicExtint10 = 0x1C,
+    EicExtint11 = 0x1D,
+    EicExtint12 = 0x1E,
+    EicExtint13 = 0x1F,
+    EicExtint14 = 0x20,
+    EicExtint15 = 0x21,
+    DmacCh0 = 0x22,
+    DmacCh1 = 0x23,
+    DmacCh2 = 0x24,
+    DmacCh3 = 0x25,
+    Tcc0Ovf = 0x29,
+    Tcc1Ovf = 0x32,
+    Tcc2Ovf = 0x39,
+    #[hal_cfg("tcc3")]
+    Tcc3Ovf = 0x3F,
+    #[hal_cfg("tcc4")]
+    Tcc4Ovf = 0x44,
+    Tc0Ovf = 0x49,
+    Tc1Ovf = 0x4C,
+    Tc2Ovf = 0x4F,
+    Tc3Ovf = 0x52,
+    #[hal_cfg("tc4")]
+    Tc4Ovf = 0x55,
+    #[hal_cfg("tc5")]
+    Tc5Ovf = 0x58,
+    #[hal_cfg("tc6")]
+    Tc6Ovf = 0x5B,
+    #[hal_cfg("tc7")]
+    Tc7Ovf = 0x5E,
+    PdecOvf = 0x61,
+    PdecErr = 0x62,
+    PdecDir = 0x63,
+    PdecVlc = 0x64,
+    PdecMc0 = 0x65,
+    PdecMc1 = 0x66,
+    Adc0Resrdy = 0x67,
+    Adc0Winmon = 0x68,
+    Adc1Resrdy = 0x69,
+    Adc1Winmon = 0x6A,
+    AcComp0 = 0x6B,
+    AcComp1 = 0x6C,
+    AcWin0 = 0x6D,
+    DacEmpty0 = 0x6E,
+    DacEmpty1 = 0x6F,
+    DacResrdy0 = 0x70,
+    DacResrdy1 = 0x71,
+    CclLutout0 = 0x74,
+    CclLutout1 = 0x75,
+    CclLutout2 = 0x76,
+    CclLutout3 = 0x77,
+}
+
+#[hal_cfg("evsys-d5x")]
+users! {
+    rtc_tamper: RtcTamper = 0,
+    port_ev0: PortEv0 = 1,
+    port_ev1: PortEv1 = 2,
+    port_ev2: PortEv2 = 3,
+    port_ev3: PortEv3 = 4,
+    dmac_ch0: DmacCh0 = 5,
+    dmac_ch1: DmacCh1 = 6,
+    dmac_ch2: DmacCh2 = 7,
+    dmac_ch3: DmacCh3 = 8,
+    dmac_ch4: DmacCh4 = 9,
+    dmac_ch5: DmacCh5 = 10,
+    dmac_ch6: DmacCh6 = 11,
+    dmac_ch7: DmacCh7 = 12,
+    tcc0_ev0: Tcc0Ev0 = 16,
+    tcc0_ev1: Tcc0Ev1 = 17,
+    tcc1_ev0: Tcc1Ev0 = 24,
+    tcc1_ev1: Tcc1Ev1 = 25,
+    tcc2_ev0: Tcc2Ev0 = 30,
+    tcc2_ev1: Tcc2Ev1 = 31,
+    #[hal_cfg("tcc3")]
+    tcc3_ev0: Tcc3Ev0 = 35,
+    #[hal_cfg("tcc3")]
+    tcc3_ev1: Tcc3Ev1 = 36,
+    #[hal_cfg("tcc4")]
+    tcc4_ev0: Tcc4Ev0 = 39,
+    #[hal_cfg("tcc4")]
+    tcc4_ev1: Tcc4Ev1 = 40,
+    tc0: Tc0 = 43,
+    tc1: Tc1 = 44,
+    tc2: Tc2 = 45,
+    tc3: Tc3 = 46,
+    #[hal_cfg("tc4")]
+    tc4: Tc4 = 47,
+    #[hal_cfg("tc5")]
+    tc5: Tc5 = 48,
+    #[hal_cfg("tc6")]
+    tc6: Tc6 = 49,
+    #[hal_cfg("tc7")]
+    tc7: Tc7 = 50,
+    pdec_evu0: PdecEvu0 = 51,
+    pdec_evu1: PdecEvu1 = 52,
+    pdec_evu2: PdecEvu2 = 53,
+    adc0_start: Adc0Start = 54,
+    adc0_sync: Adc0Sync = 55,
+    adc1_start: Adc1Start = 56,
+    adc1_sync: Adc1Sync = 57,
+    ac_comp0: AcComp0 = 58,
+    ac_comp1: AcComp1 = 59,
+    dac_start0: DacStart0 = 60,
+    dac_start1: DacStart1 = 61,
+    ccl_lutin0: CclLutin0 = 62,
+    ccl_lutin1: CclLutin1 = 63,
+    ccl_lutin2: CclLutin2 = 64,
+    ccl_lutin3: CclLutin3 = 65,
+}
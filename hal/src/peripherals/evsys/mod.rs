@@ -0,0 +1,683 @@
+//! # Event System
+//!
+//! The Event System (EVSYS) routes signals, called events, from peripherals
+//! generating them (generators) to peripherals reacting to them (users),
+//! without CPU intervention.
+//!
+//! This module provides a type-safe API to configure event routes:
+//!
+//! * Each EVSYS channel is represented by a [`Channel`], obtained when
+//!   creating the [`Evsys`].
+//! * A [`Channel`] is connected to a single event generator by calling
+//!   [`Channel::with_generator`]. Generators are type-level markers found in
+//!   the [`generators`] module.
+//! * Event users are represented by [`User`] tokens, provided by the
+//!   [`Users`] struct. Since each user can only listen to a single channel,
+//!   attaching a [`User`] to a [`Channel`] consumes the token, returning an
+//!   [`Attached`] token which can later be detached.
+//!
+//! ```no_run
+//! let (evsys, channels, users) = Evsys::new(&mut peripherals.pm, peripherals.evsys);
+//!
+//! // Route EXTINT[3] to the ADC START input
+//! let mut ch0 = channels.0.with_generator::<generators::EicExtint3>(Path::Asynchronous);
+//! let adc_start = ch0.attach(users.adc_start);
+//! ```
+//!
+//! Remember to also enable event output in the generating peripheral (eg with
+//! [`ExtInt::enable_event`](crate::eic::ExtInt::enable_event)), and event
+//! input in the user peripheral.
+//!
+//! ## Paths
+//!
+//! The [`Path`] of a channel selects how events are propagated:
+//!
+//! * [`Path::Asynchronous`] events are routed directly to users, and do not
+//!   require a clock. Edge detection is not available.
+//! * [`Path::Synchronous`] and [`Path::Resynchronized`] events are
+//!   synchronized to the channel clock, which allows for edge detection and
+//!   channel interrupts. The GCLK of the channel (eg `GCLK_EVSYS_CHANNEL_0`)
+//!   must be enabled.
+//!
+//! On SAMx5x chips, only channels 0 to 11 support the synchronous and
+//! resynchronized paths, and channel interrupts. These channels implement
+//! [`SyncChId`]. Other channels can only be configured with
+//! [`Channel::with_async_generator`].
+
+use core::marker::PhantomData;
+
+use atsamd_hal_macros::{hal_cfg, hal_macro_helper};
+use seq_macro::seq;
+
+use crate::pac;
+use crate::typelevel::{NoneT, Sealed};
+
+mod ids;
+pub use ids::{Users, generators, users};
+
+#[hal_cfg(any("evsys-d11", "evsys-d21"))]
+use crate::pac::evsys::channel::{Edgselselect, Pathselect};
+#[hal_cfg("evsys-d5x")]
+use crate::pac::evsys::channels::channel::{Edgselselect, Pathselect};
+
+#[hal_cfg("evsys-d5x")]
+use crate::clock::v2::{apb::ApbClk, types::EvSys as EvSysId};
+
+bitflags::bitflags! {
+    /// Channel interrupt flags
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Flags: u8 {
+        /// Overrun: a new event was received before the previous one was
+        /// handled by all users
+        const OVR = 0x01;
+        /// Event detected
+        const EVD = 0x02;
+    }
+}
+
+/// Edge detection for synchronous and resynchronized paths
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Edge {
+    /// Generate an event on the rising edge of the generator signal
+    Rising,
+    /// Generate an event on the falling edge of the generator signal
+    Falling,
+    /// Generate an event on both edges of the generator signal
+    Both,
+}
+
+/// Event path of a channel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Path {
+    /// The event is synchronized to the channel clock. Use when the
+    /// generator and users are clocked by the same clock as the channel.
+    Synchronous(Edge),
+    /// The event is resynchronized to the channel clock. Use when the
+    /// generator is clocked by a different clock than the channel.
+    Resynchronized(Edge),
+    /// The event is routed directly to users. No clock is required.
+    Asynchronous,
+}
+
+impl Path {
+    #[inline]
+    fn path(self) -> Pathselect {
+        match self {
+            Path::Synchronous(_) => Pathselect::Synchronous,
+            Path::Resynchronized(_) => Pathselect::Resynchronized,
+            Path::Asynchronous => Pathselect::Asynchronous,
+        }
+    }
+
+    #[inline]
+    fn edge(self) -> Edgselselect {
+        match self {
+            Path::Synchronous(edge) | Path::Resynchronized(edge) => match edge {
+                Edge::Rising => Edgselselect::RisingEdge,
+                Edge::Falling => Edgselselect::FallingEdge,
+                Edge::Both => Edgselselect::BothEdges,
+            },
+            Path::Asynchronous => Edgselselect::NoEvtOutput,
+        }
+    }
+}
+
+//==============================================================================
+// Generators and users
+//==============================================================================
+
+/// Type-level marker for an event generator
+pub trait Generator: Sealed {
+    /// `EVGEN` value of the generator
+    const ID: u8;
+}
+
+/// Type-level marker for an event user
+pub trait UserId: Sealed {
+    /// Index of the user's `USER` register
+    const ID: u8;
+}
+
+/// Token representing an event user which is not attached to any channel.
+///
+/// Each user can only listen to a single channel. A [`User`] is consumed when
+/// it is [attached](Channel::attach) to a channel.
+pub struct User<U: UserId> {
+    _user: PhantomData<U>,
+}
+
+impl<U: UserId> User<U> {
+    fn new() -> Self {
+        Self { _user: PhantomData }
+    }
+}
+
+/// Token representing an event user attached to channel `Id`.
+///
+/// Call [`Channel::detach`] to recover the [`User`].
+pub struct Attached<U: UserId, Id: ChId> {
+    user: User<U>,
+    _chan: PhantomData<Id>,
+}
+
+//==============================================================================
+// Channel IDs
+//==============================================================================
+
+/// Trait representing an EVSYS channel ID.
+pub trait ChId: Sealed {
+    /// Index of the channel
+    const ID: usize;
+}
+
+/// Trait representing an EVSYS channel which supports the synchronous and
+/// resynchronized paths, as well as channel interrupts.
+pub trait SyncChId: ChId {}
+
+#[hal_cfg("evsys-d11")]
+macro_rules! with_num_channels {
+    ($some_macro:ident) => {
+        $some_macro! {6}
+    };
+}
+
+#[hal_cfg("evsys-d21")]
+macro_rules! with_num_channels {
+    ($some_macro:ident) => {
+        $some_macro! {12}
+    };
+}
+
+#[hal_cfg("evsys-d5x")]
+macro_rules! with_num_channels {
+    ($some_macro:ident) => {
+        $some_macro! {32}
+    };
+}
+
+macro_rules! get {
+    ($literal:literal) => {
+        $literal
+    };
+}
+
+/// The number of EVSYS channels on this chip.
+pub const NUM_CHANNELS: usize = with_num_channels!(get);
+
+/// The number of EVSYS channels supporting the synchronous and resynchronized
+/// paths on this chip.
+#[hal_cfg(any("evsys-d11", "evsys-d21"))]
+pub const NUM_SYNC_CHANNELS: usize = NUM_CHANNELS;
+
+/// The number of EVSYS channels supporting the synchronous and resynchronized
+/// paths on this chip.
+#[hal_cfg("evsys-d5x")]
+pub const NUM_SYNC_CHANNELS: usize = 12;
+
+macro_rules! define_channels_struct {
+    ($num_channels:literal) => {
+        seq!(N in 0..$num_channels {
+            #(
+                /// Type alias for a channel number
+                pub enum Ch~N {}
+
+                impl Sealed for Ch~N {}
+
+                impl ChId for Ch~N {
+                    const ID: usize = N;
+                }
+            )*
+
+            /// Struct generating individual handles to each EVSYS channel
+            pub struct Channels(
+                #(
+                    pub Channel<Ch~N>,
+                )*
+            );
+
+            impl Channels {
+                fn new() -> Self {
+                    Channels(
+                        #(
+                            Channel::new(),
+                        )*
+                    )
+                }
+            }
+        });
+    };
+}
+
+with_num_channels!(define_channels_struct);
+
+#[hal_cfg(any("evsys-d11", "evsys-d21"))]
+macro_rules! impl_sync_channels {
+    ($num_channels:literal) => {
+        seq!(N in 0..$num_channels {
+            #(
+                impl SyncChId for Ch~N {}
+            )*
+        });
+    };
+}
+
+#[hal_cfg(any("evsys-d11", "evsys-d21"))]
+with_num_channels!(impl_sync_channels);
+
+#[hal_cfg("evsys-d5x")]
+seq!(N in 0..12 {
+    #(
+        impl SyncChId for Ch~N {}
+    )*
+});
+
+//==============================================================================
+// Evsys
+//==============================================================================
+
+/// Event System controller
+#[hal_macro_helper]
+pub struct Evsys {
+    evsys: pac::Evsys,
+    #[hal_cfg("evsys-d5x")]
+    apbclk: ApbClk<EvSysId>,
+}
+
+impl Evsys {
+    /// Create a new [`Evsys`], and return the individual [`Channels`] and
+    /// [`Users`].
+    #[hal_cfg(any("evsys-d11", "evsys-d21"))]
+    pub fn new(pm: &mut pac::Pm, evsys: pac::Evsys) -> (Self, Channels, Users) {
+        pm.apbcmask().modify(|_, w| w.evsys_().set_bit());
+        let evsys = Self { evsys };
+        evsys.swreset();
+        (evsys, Channels::new(), Users::new())
+    }
+
+    /// Create a new [`Evsys`], and return the individual [`Channels`] and
+    /// [`Users`].
+    #[hal_cfg("evsys-d5x")]
+    pub fn new(evsys: pac::Evsys, apbclk: ApbClk<EvSysId>) -> (Self, Channels, Users) {
+        let evsys = Self { evsys, apbclk };
+        evsys.swreset();
+        (evsys, Channels::new(), Users::new())
+    }
+
+    #[hal_macro_helper]
+    fn swreset(&self) {
+        #[hal_cfg(any("evsys-d11", "evsys-d21"))]
+        self.evsys.ctrl().write(|w| w.swrst().set_bit());
+        #[hal_cfg("evsys-d5x")]
+        {
+            self.evsys.ctrla().write(|w| w.swrst().set_bit());
+            while self.evsys.ctrla().read().swrst().bit_is_set() {
+                core::hint::spin_loop();
+            }
+        }
+    }
+
+    /// Reset the EVSYS and return the underlying resources.
+    ///
+    /// **Note**: The [`Channels`] and [`Users`] structs are consumed by this
+    /// method. Any [`Channel`] and [`User`] must be moved back into their
+    /// respective structs before calling this method.
+    #[hal_cfg(any("evsys-d11", "evsys-d21"))]
+    pub fn free(self, pm: &mut pac::Pm, _channels: Channels, _users: Users) -> pac::Evsys {
+        self.swreset();
+        pm.apbcmask().modify(|_, w| w.evsys_().clear_bit());
+        self.evsys
+    }
+
+    /// Reset the EVSYS and return the underlying resources.
+    ///
+    /// **Note**: The [`Channels`] and [`Users`] structs are consumed by this
+    /// method. Any [`Channel`] and [`User`] must be moved back into their
+    /// respective structs before calling this method.
+    #[hal_cfg("evsys-d5x")]
+    pub fn free(self, _channels: Channels, _users: Users) -> (pac::Evsys, ApbClk<EvSysId>) {
+        self.swreset();
+        (self.evsys, self.apbclk)
+    }
+}
+
+//==============================================================================
+// Channel
+//==============================================================================
+
+/// EVSYS channel
+///
+/// `G` is the [`Generator`] connected to the channel, or [`NoneT`] if the
+/// channel is not in use.
+pub struct Channel<Id: ChId, G = NoneT> {
+    path: Path,
+    _id: PhantomData<Id>,
+    _gen: PhantomData<G>,
+}
+
+impl<Id: ChId, G> Channel<Id, G> {
+    /// Borrow the EVSYS registers.
+    ///
+    /// Each channel only ever accesses the registers (or bits) belonging to
+    /// it, and the `USER` registers of the users it owns.
+    #[inline]
+    fn evsys(&self) -> &pac::evsys::RegisterBlock {
+        unsafe { &*pac::Evsys::ptr() }
+    }
+
+    fn change_gen<N>(self) -> Channel<Id, N> {
+        Channel {
+            path: self.path,
+            _id: PhantomData,
+            _gen: PhantomData,
+        }
+    }
+
+    /// Write the channel configuration, optionally triggering a software
+    /// event.
+    #[hal_macro_helper]
+    fn write_channel(&mut self, evgen: u8, software_event: bool) {
+        let path = self.path;
+        #[hal_cfg(any("evsys-d11", "evsys-d21"))]
+        {
+            // On SAMD11/SAMD21, the CHANNEL register selects the channel being
+            // configured; a software event is generated by rewriting the
+            // whole configuration with SWEVT set.
+            self.evsys().channel().write(|w| unsafe {
+                w.channel().bits(Id::ID as u8);
+                w.swevt().bit(software_event);
+                w.evgen().bits(evgen);
+                w.path().variant(path.path());
+                w.edgsel().variant(path.edge())
+            });
+        }
+        #[hal_cfg("evsys-d5x")]
+        {
+            let chan = self.evsys().channels(Id::ID);
+            chan.channel().modify(|_, w| unsafe {
+                w.evgen().bits(evgen);
+                w.path().variant(path.path());
+                w.edgsel().variant(path.edge())
+            });
+            if software_event {
+                self.evsys()
+                    .swevt()
+                    .write(|w| unsafe { w.bits(1 << Id::ID) });
+            }
+        }
+    }
+}
+
+impl<Id: ChId> Channel<Id> {
+    fn new() -> Self {
+        Self {
+            path: Path::Asynchronous,
+            _id: PhantomData,
+            _gen: PhantomData,
+        }
+    }
+
+    /// Connect generator `G` to this channel, using the provided event
+    /// [`Path`].
+    pub fn with_generator<G: Generator>(self, path: Path) -> Channel<Id, G>
+    where
+        Id: SyncChId,
+    {
+        self.configure(path)
+    }
+
+    /// Connect generator `G` to this channel, using the asynchronous path.
+    pub fn with_async_generator<G: Generator>(self) -> Channel<Id, G> {
+        self.configure(Path::Asynchronous)
+    }
+
+    fn configure<G: Generator>(self, path: Path) -> Channel<Id, G> {
+        let mut chan: Channel<Id, G> = self.change_gen();
+        chan.path = path;
+        chan.write_channel(G::ID, false);
+        chan
+    }
+}
+
+impl<Id: ChId, G: Generator> Channel<Id, G> {
+    /// Change the event [`Path`] of this channel.
+    pub fn set_path(&mut self, path: Path)
+    where
+        Id: SyncChId,
+    {
+        self.path = path;
+        self.write_channel(G::ID, false);
+    }
+
+    /// Attach a [`User`] to this channel.
+    ///
+    /// The user will react to events routed by this channel, until it is
+    /// detached with [`detach`](Self::detach).
+    #[hal_macro_helper]
+    pub fn attach<U: UserId>(&mut self, user: User<U>) -> Attached<U, Id> {
+        // USER.CHANNEL holds the channel number plus one; zero means that the
+        // user is not connected.
+        let channel = Id::ID as u8 + 1;
+        #[hal_cfg(any("evsys-d11", "evsys-d21"))]
+        self.evsys().user().write(|w| unsafe {
+            w.user().bits(U::ID);
+            w.channel().bits(channel)
+        });
+        #[hal_cfg("evsys-d5x")]
+        self.evsys()
+            .user(U::ID as usize)
+            .write(|w| unsafe { w.channel().bits(channel) });
+
+        Attached {
+            user,
+            _chan: PhantomData,
+        }
+    }
+
+    /// Detach a user from this channel, and return its [`User`] token.
+    #[hal_macro_helper]
+    pub fn detach<U: UserId>(&mut self, attached: Attached<U, Id>) -> User<U> {
+        #[hal_cfg(any("evsys-d11", "evsys-d21"))]
+        self.evsys().user().write(|w| unsafe {
+            w.user().bits(U::ID);
+            w.channel().bits(0)
+        });
+        #[hal_cfg("evsys-d5x")]
+        self.evsys()
+            .user(U::ID as usize)
+            .write(|w| unsafe { w.channel().bits(0) });
+
+        attached.user
+    }
+
+    /// Generate a software event on this channel.
+    #[inline]
+    pub fn trigger(&mut self) {
+        self.write_channel(G::ID, true);
+    }
+
+    /// Disconnect the generator from this channel.
+    ///
+    /// Attached users should be [detached](Self::detach) first, since they
+    /// would otherwise remain connected to an unused channel.
+    pub fn free(mut self) -> Channel<Id> {
+        self.path = Path::Asynchronous;
+        self.write_channel(0, false);
+        self.change_gen()
+    }
+}
+
+#[hal_cfg(any("evsys-d11", "evsys-d21"))]
+impl<Id: SyncChId, G: Generator> Channel<Id, G> {
+    /// Convert [`Flags`] to an INTFLAG mask for this channel
+    #[inline]
+    fn mask(flags: Flags) -> u32 {
+        sync_channel_mask(Id::ID, flags)
+    }
+
+    /// Enable the specified channel interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.evsys()
+            .intenset()
+            .write(|w| unsafe { w.bits(Self::mask(flags)) });
+    }
+
+    /// Disable the specified channel interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.evsys()
+            .intenclr()
+            .write(|w| unsafe { w.bits(Self::mask(flags)) });
+    }
+
+    /// Read the channel interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        let bits = self.evsys().intflag().read().bits();
+        let mut flags = Flags::empty();
+        flags.set(Flags::OVR, bits & Self::mask(Flags::OVR) != 0);
+        flags.set(Flags::EVD, bits & Self::mask(Flags::EVD) != 0);
+        flags
+    }
+
+    /// Clear the specified channel interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.evsys()
+            .intflag()
+            .write(|w| unsafe { w.bits(Self::mask(flags)) });
+    }
+
+    /// Check whether all users of this channel are ready to handle a new event
+    #[inline]
+    pub fn is_user_ready(&self) -> bool {
+        // USRRDYn bits share their layout with OVRn
+        self.evsys().chstatus().read().bits() & Self::mask(Flags::OVR) != 0
+    }
+
+    /// Check whether an event is being propagated through this channel
+    #[inline]
+    pub fn is_busy(&self) -> bool {
+        // CHBUSYn bits share their layout with EVDn
+        self.evsys().chstatus().read().bits() & Self::mask(Flags::EVD) != 0
+    }
+}
+
+#[hal_cfg("evsys-d5x")]
+impl<Id: SyncChId, G: Generator> Channel<Id, G> {
+    /// Enable the specified channel interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.evsys()
+            .channels(Id::ID)
+            .chintenset()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the specified channel interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.evsys()
+            .channels(Id::ID)
+            .chintenclr()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Read the channel interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.evsys().channels(Id::ID).chintflag().read().bits())
+    }
+
+    /// Clear the specified channel interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.evsys()
+            .channels(Id::ID)
+            .chintflag()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Check whether all users of this channel are ready to handle a new event
+    #[inline]
+    pub fn is_user_ready(&self) -> bool {
+        self.evsys()
+            .channels(Id::ID)
+            .chstatus()
+            .read()
+            .rdyusr()
+            .bit_is_set()
+    }
+
+    /// Check whether an event is being propagated through this channel
+    #[inline]
+    pub fn is_busy(&self) -> bool {
+        self.evsys()
+            .channels(Id::ID)
+            .chstatus()
+            .read()
+            .busych()
+            .bit_is_set()
+    }
+}
+
+/// Convert [`Flags`] to an INTFLAG mask for synchronous channel `id`
+///
+/// The OVRn bits of channels 0 to 7 are bits 0 to 7, and those of channels 8
+/// to 11 are bits 16 to 19. In both cases, the EVDn bits are 8 bits higher.
+#[hal_cfg(any("evsys-d11", "evsys-d21"))]
+#[inline]
+fn sync_channel_mask(id: usize, flags: Flags) -> u32 {
+    let shift = if id < 8 { id } else { 16 + (id - 8) };
+    let mut mask = 0;
+    if flags.contains(Flags::OVR) {
+        mask |= 1 << shift;
+    }
+    if flags.contains(Flags::EVD) {
+        mask |= 1 << (shift + 8);
+    }
+    mask
+}
+
+#[hal_cfg("evsys-d5x")]
+impl<Id: ChId, G: Generator> Channel<Id, G> {
+    /// Keep the channel running in standby sleep mode
+    pub fn run_in_standby(&mut self, enable: bool) {
+        self.evsys()
+            .channels(Id::ID)
+            .channel()
+            .modify(|_, w| w.runstdby().bit(enable));
+    }
+
+    /// Only request the channel clock when an event is being propagated.
+    ///
+    /// When disabled, the clock is always requested while the channel is in
+    /// use.
+    pub fn on_demand(&mut self, enable: bool) {
+        self.evsys()
+            .channels(Id::ID)
+            .channel()
+            .modify(|_, w| w.ondemand().bit(enable));
+    }
+}
+
+#[hal_cfg(any("evsys-d11", "evsys-d21"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sync_channel_masks() {
+        assert_eq!(sync_channel_mask(0, Flags::OVR), 1 << 0);
+        assert_eq!(sync_channel_mask(0, Flags::EVD), 1 << 8);
+        assert_eq!(sync_channel_mask(7, Flags::OVR), 1 << 7);
+        assert_eq!(sync_channel_mask(7, Flags::EVD), 1 << 15);
+        assert_eq!(sync_channel_mask(8, Flags::OVR), 1 << 16);
+        assert_eq!(sync_channel_mask(8, Flags::EVD), 1 << 24);
+        assert_eq!(sync_channel_mask(11, Flags::OVR), 1 << 19);
+        assert_eq!(sync_channel_mask(11, Flags::EVD), 1 << 27);
+        assert_eq!(sync_channel_mask(11, Flags::all()), (1 << 19) | (1 << 27));
+    }
+}
@@ -0,0 +1,112 @@
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::*;
+use crate::async_hal::interrupts::{Binding, FREQM, Handler, InterruptSource};
+use crate::typelevel::Sealed;
+
+static WAKER: AtomicWaker = AtomicWaker::new();
+
+/// Interrupt handler for the FREQM peripheral.
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Sealed for InterruptHandler {}
+
+impl Handler<FREQM> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        let freqm = unsafe { pac::Peripherals::steal().freqm };
+
+        // Disable the interrupt but don't clear the flag; it will be cleared
+        // when the next measurement is started.
+        if freqm.intenset().read().done().bit_is_set() && freqm.intflag().read().done().bit_is_set()
+        {
+            freqm.intenclr().write(|w| w.done().set_bit());
+            WAKER.wake();
+        }
+    }
+}
+
+impl<M: PclkSourceId, R: PclkSourceId> Freqm<M, R> {
+    /// Turn a [`Freqm`] into a [`FutureFreqm`], allowing for the use of async
+    /// methods. You are required to provide the struct created by the
+    /// [`bind_interrupts`](crate::bind_interrupts) macro to prove that the
+    /// interrupt source has been correctly configured. This function will
+    /// automatically enable the `FREQM` NVIC interrupt source.
+    pub fn into_future<F>(self, irqs: F) -> FutureFreqm<M, R, F>
+    where
+        F: Binding<FREQM, InterruptHandler>,
+    {
+        FREQM::unpend();
+        unsafe { FREQM::enable() };
+        FutureFreqm { inner: self, irqs }
+    }
+}
+
+/// A [`Freqm`] capable of `async` operations
+pub struct FutureFreqm<M: PclkSourceId, R: PclkSourceId, F> {
+    inner: Freqm<M, R>,
+    irqs: F,
+}
+
+impl<M, R, F> FutureFreqm<M, R, F>
+where
+    M: PclkSourceId,
+    R: PclkSourceId,
+    F: Binding<FREQM, InterruptHandler>,
+{
+    /// Convert the [`FutureFreqm`] back into a blocking [`Freqm`], and return
+    /// the IRQs
+    pub fn into_blocking(self) -> (Freqm<M, R>, F) {
+        FREQM::disable();
+        (self.inner, self.irqs)
+    }
+
+    /// Set the duration of a measurement, see [`Freqm::set_refnum`]
+    #[inline]
+    pub fn set_refnum(&mut self, refnum: u8) {
+        self.inner.set_refnum(refnum);
+    }
+
+    /// Return the frequency resolution of a measurement, see
+    /// [`Freqm::resolution`]
+    #[inline]
+    pub fn resolution(&self) -> Hertz {
+        self.inner.resolution()
+    }
+
+    /// Measure the frequency of the measured clock
+    pub async fn measure(&mut self) -> Result<Hertz, Error> {
+        self.inner.start();
+        core::future::poll_fn(|cx| {
+            if self.inner.is_done() {
+                return Poll::Ready(());
+            }
+
+            WAKER.register(cx.waker());
+            self.inner.regs().intenset().write(|w| w.done().set_bit());
+
+            if self.inner.is_done() {
+                return Poll::Ready(());
+            }
+
+            Poll::Pending
+        })
+        .await;
+        self.inner.result()
+    }
+
+    /// Measure the frequency of `clock` and check it against its expected
+    /// frequency, see [`Freqm::check`]
+    pub async fn check<S: Source<Id = M>>(
+        &mut self,
+        clock: &S,
+        tolerance_ppm: u32,
+    ) -> Result<Hertz, Error> {
+        let measured = self.measure().await?;
+        self.inner
+            .check_result(measured, clock.freq(), tolerance_ppm)
+    }
+}
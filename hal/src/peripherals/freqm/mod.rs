@@ -0,0 +1,323 @@
+//! # Frequency Meter
+//!
+//! This module provides a driver for the Frequency Meter (FREQM) found on
+//! SAMD5x/SAME5x chips. The FREQM counts the cycles of a measured clock,
+//! `GCLK_FREQM_MSR`, during a number of cycles of a reference clock,
+//! `GCLK_FREQM_REF`. Both are [`Pclk`]s, so any [`Gclk`] can be measured,
+//! including one driven by an external clock on a `GCLK_IO` pin.
+//!
+//! Taking the [`Pclk`]s makes measurements type-checked: [`Freqm::check`]
+//! only accepts the clock feeding the measured [`Pclk`], and compares the
+//! measured frequency to the frequency `clock::v2` computed for it. This can
+//! be used to self-test clock configurations at runtime.
+//!
+//! ```no_run
+//! let apb_freqm = buses.apb.enable(tokens.apbs.freq_m);
+//! // Measure the 120 MHz GCLK0 against the 32.768 kHz GCLK3
+//! let (pclk_msr, gclk0) = Pclk::enable(tokens.pclks.freq_m_measure, clocks.gclk0);
+//! let (pclk_ref, gclk3) = Pclk::enable(tokens.pclks.freq_m_reference, gclk3);
+//! let mut freqm = Freqm::new(peripherals.freqm, apb_freqm, pclk_msr, pclk_ref, 32);
+//!
+//! let freq = freqm.measure().unwrap();
+//! // Allow a 1% error
+//! freqm.check(&gclk0, 10_000).unwrap();
+//! ```
+//!
+//! ## Accuracy
+//!
+//! A measurement lasts `refnum` cycles of the reference clock, and its
+//! resolution is the reference frequency divided by `refnum`, see
+//! [`Freqm::resolution`]. A larger `refnum` improves the resolution, but the
+//! count overflows if the measured clock is more than `2^24 / refnum` times
+//! faster than the reference.
+//!
+//! ## Async
+//!
+//! With the `async` feature enabled, [`Freqm::into_future`] returns a
+//! [`FutureFreqm`] which waits for measurements to complete without
+//! blocking.
+//!
+//! [`Gclk`]: crate::clock::v2::gclk::Gclk
+
+use crate::clock::v2::{
+    Source,
+    apb::ApbClk,
+    pclk::{Pclk, PclkSourceId},
+    types::{FreqM, FreqMMeasure, FreqMReference},
+};
+use crate::pac;
+use crate::pac::freqm::RegisterBlock;
+use crate::time::Hertz;
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
+/// Errors that may occur when measuring a frequency
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The cycle count overflowed. Decrease the number of reference cycles.
+    Overflow,
+    /// The measured frequency is out of the requested tolerance
+    OutOfTolerance,
+}
+
+/// Frequency Meter
+pub struct Freqm<M: PclkSourceId, R: PclkSourceId> {
+    freqm: pac::Freqm,
+    apbclk: ApbClk<FreqM>,
+    msr: Pclk<FreqMMeasure, M>,
+    reference: Pclk<FreqMReference, R>,
+    refnum: u8,
+}
+
+impl<M: PclkSourceId, R: PclkSourceId> Freqm<M, R> {
+    /// Reset and enable the FREQM, to measure `msr` during `refnum` cycles of
+    /// `reference`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `refnum` is zero.
+    pub fn new(
+        freqm: pac::Freqm,
+        apbclk: ApbClk<FreqM>,
+        msr: Pclk<FreqMMeasure, M>,
+        reference: Pclk<FreqMReference, R>,
+        refnum: u8,
+    ) -> Self {
+        let mut freqm = Self {
+            freqm,
+            apbclk,
+            msr,
+            reference,
+            refnum,
+        };
+
+        let regs = freqm.regs();
+        regs.ctrla().write(|w| w.swrst().set_bit());
+        while regs.syncbusy().read().swrst().bit_is_set() {}
+
+        freqm.set_refnum(refnum);
+
+        let regs = freqm.regs();
+        regs.ctrla().write(|w| w.enable().set_bit());
+        while regs.syncbusy().read().enable().bit_is_set() {}
+
+        freqm
+    }
+
+    #[inline]
+    fn regs(&self) -> &RegisterBlock {
+        &self.freqm
+    }
+
+    /// Set the duration of a measurement, in cycles of the reference clock
+    ///
+    /// CFGA is enable-protected, so the FREQM is briefly disabled while the
+    /// new value is written, if it was enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `refnum` is zero.
+    pub fn set_refnum(&mut self, refnum: u8) {
+        assert!(refnum != 0, "The measurement must last at least one cycle");
+        self.refnum = refnum;
+        write_refnum(self.regs(), refnum);
+    }
+
+    /// Return the duration of a measurement, in cycles of the reference clock
+    #[inline]
+    pub fn refnum(&self) -> u8 {
+        self.refnum
+    }
+
+    /// Return the frequency resolution of a measurement, which is the
+    /// reference frequency divided by [`refnum`](Self::refnum)
+    #[inline]
+    pub fn resolution(&self) -> Hertz {
+        Hertz::from_raw(self.reference.freq().to_Hz().div_ceil(self.refnum as u32))
+    }
+
+    /// Start a measurement, whose result is read with
+    /// [`read`](Self::read)
+    #[inline]
+    pub fn start(&mut self) {
+        let regs = self.regs();
+        regs.intflag().write(|w| w.done().set_bit());
+        regs.status().write(|w| w.ovf().set_bit());
+        regs.ctrlb().write(|w| w.start().set_bit());
+    }
+
+    /// Check whether the current measurement is complete
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.regs().intflag().read().done().bit_is_set()
+    }
+
+    /// Read the result of the measurement started with
+    /// [`start`](Self::start)
+    #[inline]
+    pub fn read(&mut self) -> nb::Result<Hertz, Error> {
+        if self.is_done() {
+            self.result().map_err(nb::Error::Other)
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    /// Measure the frequency of the measured clock, blocking until the
+    /// measurement is complete
+    pub fn measure(&mut self) -> Result<Hertz, Error> {
+        self.start();
+        nb::block!(self.read())
+    }
+
+    /// Measure the frequency of `clock`, the source of the measured [`Pclk`],
+    /// and check that it is within `tolerance_ppm` parts per million of its
+    /// expected frequency. Deviations up to the
+    /// [`resolution`](Self::resolution) of the measurement are always
+    /// accepted.
+    ///
+    /// Returns the measured frequency.
+    pub fn check<S: Source<Id = M>>(
+        &mut self,
+        clock: &S,
+        tolerance_ppm: u32,
+    ) -> Result<Hertz, Error> {
+        let measured = self.measure()?;
+        self.check_result(measured, clock.freq(), tolerance_ppm)
+    }
+
+    /// Compute the measured frequency once a measurement is complete
+    fn result(&self) -> Result<Hertz, Error> {
+        let regs = self.regs();
+        if regs.status().read().ovf().bit_is_set() {
+            return Err(Error::Overflow);
+        }
+        let count = regs.value().read().value().bits() as u64;
+        let freq = count * self.reference.freq().to_Hz() as u64 / self.refnum as u64;
+        Ok(Hertz::from_raw(freq as u32))
+    }
+
+    fn check_result(
+        &self,
+        measured: Hertz,
+        expected: Hertz,
+        tolerance_ppm: u32,
+    ) -> Result<Hertz, Error> {
+        let close = measured.to_Hz().abs_diff(expected.to_Hz()) <= self.resolution().to_Hz();
+        if close || within_tolerance(measured, expected, tolerance_ppm) {
+            Ok(measured)
+        } else {
+            Err(Error::OutOfTolerance)
+        }
+    }
+
+    /// Return the frequency of the measured clock, as computed by `clock::v2`
+    #[inline]
+    pub fn expected_freq(&self) -> Hertz {
+        self.msr.freq()
+    }
+
+    /// Return the frequency of the reference clock
+    #[inline]
+    pub fn reference_freq(&self) -> Hertz {
+        self.reference.freq()
+    }
+
+    /// Disable the FREQM, and return the underlying resources
+    #[allow(clippy::type_complexity)]
+    pub fn free(
+        self,
+    ) -> (
+        pac::Freqm,
+        ApbClk<FreqM>,
+        Pclk<FreqMMeasure, M>,
+        Pclk<FreqMReference, R>,
+    ) {
+        let regs = self.regs();
+        regs.ctrla().write(|w| w.enable().clear_bit());
+        while regs.syncbusy().read().enable().bit_is_set() {}
+        (self.freqm, self.apbclk, self.msr, self.reference)
+    }
+}
+
+/// Write CFGA.REFNUM, which is enable-protected. An enabled FREQM is disabled
+/// for the duration of the write, and enabled again afterwards.
+fn write_refnum(regs: &RegisterBlock, refnum: u8) {
+    let enabled = regs.ctrla().read().enable().bit_is_set();
+    if enabled {
+        regs.ctrla().modify(|_, w| w.enable().clear_bit());
+        while regs.syncbusy().read().enable().bit_is_set() {}
+    }
+    // SAFETY: Every 8-bit value is valid
+    regs.cfga().write(|w| unsafe { w.refnum().bits(refnum) });
+    if enabled {
+        regs.ctrla().modify(|_, w| w.enable().set_bit());
+        while regs.syncbusy().read().enable().bit_is_set() {}
+    }
+}
+
+/// Check that `measured` is within `tolerance_ppm` parts per million of
+/// `expected`
+pub fn within_tolerance(measured: Hertz, expected: Hertz, tolerance_ppm: u32) -> bool {
+    let deviation = measured.to_Hz().abs_diff(expected.to_Hz()) as u64;
+    deviation * 1_000_000 <= expected.to_Hz() as u64 * tolerance_ppm as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tolerance() {
+        let expected = Hertz::MHz(48);
+        assert!(within_tolerance(expected, expected, 0));
+        assert!(within_tolerance(Hertz::Hz(48_048_000), expected, 1_000));
+        assert!(within_tolerance(Hertz::Hz(47_952_000), expected, 1_000));
+        assert!(!within_tolerance(Hertz::Hz(48_048_001), expected, 1_000));
+        assert!(!within_tolerance(Hertz::Hz(47_951_999), expected, 1_000));
+    }
+
+    /// Backing memory standing in for the FREQM registers
+    #[repr(C, align(4))]
+    struct Regs([u8; core::mem::size_of::<RegisterBlock>()]);
+
+    impl Regs {
+        fn block(&self) -> &RegisterBlock {
+            // SAFETY: The memory is large enough and aligned for the register
+            // block, and every bit pattern is a valid register value
+            unsafe { &*(self.0.as_ptr() as *const RegisterBlock) }
+        }
+    }
+
+    #[test]
+    fn refnum_is_written_while_enabled() {
+        let mem = Regs([0; core::mem::size_of::<RegisterBlock>()]);
+        let regs = mem.block();
+        write_refnum(regs, 32);
+        regs.ctrla().write(|w| w.enable().set_bit());
+
+        write_refnum(regs, 128);
+        assert_eq!(regs.cfga().read().refnum().bits(), 128);
+        assert!(regs.ctrla().read().enable().bit_is_set());
+    }
+
+    #[test]
+    fn refnum_is_written_while_disabled() {
+        let mem = Regs([0; core::mem::size_of::<RegisterBlock>()]);
+        let regs = mem.block();
+        write_refnum(regs, 32);
+        assert_eq!(regs.cfga().read().refnum().bits(), 32);
+        assert!(regs.ctrla().read().enable().bit_is_clear());
+    }
+
+    #[test]
+    fn tolerance_does_not_overflow() {
+        let expected = Hertz::Hz(u32::MAX);
+        assert!(within_tolerance(Hertz::Hz(0), expected, 1_000_000));
+        assert!(!within_tolerance(Hertz::Hz(0), expected, 999_999));
+    }
+}
@@ -0,0 +1,89 @@
+//! [`smoltcp::phy::Device`] implementation
+
+use smoltcp::phy::{self, DeviceCapabilities, Medium};
+use smoltcp::time::Instant;
+
+use super::*;
+
+/// Largest frame passed to the GMAC: a 1500 byte payload and the Ethernet
+/// header, without the FCS
+const MTU: usize = 1514;
+
+/// [`phy::RxToken`] handing out a frame received by a [`Gmac`]
+pub struct RxToken<'a> {
+    rx: &'a mut RxRing<'static>,
+}
+
+impl phy::RxToken for RxToken<'_> {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        self.rx
+            .receive(f)
+            .expect("A frame was ready when the token was created")
+    }
+}
+
+/// [`phy::TxToken`] queueing a frame for transmission by a [`Gmac`]
+pub struct TxToken<'a> {
+    tx: &'a mut TxRing<'static>,
+    regs: &'a RegisterBlock,
+}
+
+impl phy::TxToken for TxToken<'_> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let result = self
+            .tx
+            .send(len, f)
+            .expect("A descriptor was free when the token was created");
+        start_transmit(self.regs);
+        result
+    }
+}
+
+impl<P: GmacPins> phy::Device for Gmac<P> {
+    type RxToken<'a>
+        = RxToken<'a>
+    where
+        Self: 'a;
+    type TxToken<'a>
+        = TxToken<'a>
+    where
+        Self: 'a;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(RxToken<'_>, TxToken<'_>)> {
+        // smoltcp may reply to the frame right away, so only hand it out if
+        // the reply can be sent too
+        if !self.rx.is_ready() || !self.tx.is_ready() {
+            return None;
+        }
+        Some((
+            RxToken { rx: &mut self.rx },
+            TxToken {
+                tx: &mut self.tx,
+                regs: &self.gmac,
+            },
+        ))
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<TxToken<'_>> {
+        if !self.tx.is_ready() {
+            return None;
+        }
+        Some(TxToken {
+            tx: &mut self.tx,
+            regs: &self.gmac,
+        })
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut caps = DeviceCapabilities::default();
+        caps.medium = Medium::Ethernet;
+        caps.max_transmission_unit = MTU;
+        caps
+    }
+}
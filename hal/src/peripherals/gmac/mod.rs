@@ -0,0 +1,622 @@
+//! # Ethernet MAC
+//!
+//! This module provides a driver for the Ethernet MAC (GMAC) found on SAME53
+//! and SAME54 chips, talking to an external PHY at 10 or 100 Mbit/s through
+//! the Reduced Media Independent Interface (RMII). The MII is not supported.
+//!
+//! Frames are moved by the GMAC's own DMA, through rings of descriptors and
+//! buffers in RAM. These are provided by the user as a [`Rings`] with a
+//! `'static` lifetime; `RX` and `TX` set how many frames can be queued in each
+//! direction. Every buffer holds a whole frame, so sending and receiving is a
+//! single copy, or none at all through [`Gmac::receive_with`] and
+//! [`Gmac::send_with`].
+//!
+//! ```no_run
+//! let rings = cortex_m::singleton!(: Rings<8, 4> = Rings::new()).unwrap();
+//! let pins = RmiiPins::new(
+//!     pins.pa14, pins.pa17, pins.pa18, pins.pa19, pins.pa13, pins.pa12, pins.pa15, pins.pc20,
+//!     pins.pc11, pins.pc12,
+//! );
+//! let config = Config::new(mac_address_from_serial_number(), 120.MHz());
+//! let mut gmac = Gmac::new(
+//!     peripherals.gmac,
+//!     clocks.ahbs.gmac,
+//!     clocks.apbs.gmac,
+//!     pins,
+//!     rings,
+//!     config,
+//! );
+//!
+//! gmac.phy_reset(0).unwrap();
+//! gmac.phy_autonegotiate(0);
+//! // Later, once the link is up
+//! if let Some(link) = gmac.phy_link(0) {
+//!     gmac.set_link(link);
+//! }
+//! nb::block!(gmac.send(&frame)).unwrap();
+//! ```
+//!
+//! ## PHY management
+//!
+//! The PHY registers are reached through the MDIO interface with
+//! [`Gmac::mdio_read`] and [`Gmac::mdio_write`]. The `phy_*` methods only use
+//! the basic registers defined by IEEE 802.3 clause 22, so they should work
+//! with any PHY; vendor-specific setup is left to the user. The GMAC does not
+//! follow the link state by itself: [`Gmac::set_link`] must be called with the
+//! speed and duplex negotiated by the PHY.
+//!
+//! ## Address filtering
+//!
+//! Unicast frames are accepted if their destination matches one of the four
+//! specific address filters, the first of which holds the station address
+//! given in the [`Config`]. Multicast frames are accepted through a 64-bit
+//! hash filter, see [`Gmac::add_multicast`]. Broadcast frames are accepted
+//! unless disabled, and [`Gmac::set_copy_all_frames`] turns filtering off
+//! altogether.
+//!
+//! ## `smoltcp`
+//!
+//! With the `smoltcp` feature enabled, [`Gmac`] implements
+//! [`smoltcp::phy::Device`], so that it can be used with a smoltcp
+//! `Interface`. The HAL enables the `proto-ipv4`, `socket-tcp` and
+//! `socket-udp` features of smoltcp. Other features can be enabled by
+//! depending on smoltcp directly.
+
+use crate::clock::v2::{ahb::AhbClk, apb::ApbClk, types::Gmac as GmacId};
+use crate::pac;
+use crate::pac::gmac::RegisterBlock;
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+mod pin;
+pub use pin::*;
+
+mod ring;
+pub use ring::{BUFFER_LEN, Rings};
+use ring::{RxRing, TxRing};
+
+#[cfg(feature = "smoltcp")]
+mod device;
+#[cfg(feature = "smoltcp")]
+pub use device::*;
+
+/// Number of specific address filters
+pub const NUM_ADDRESS_FILTERS: usize = 4;
+
+/// Number of MDIO reads polling for the end of a PHY reset before giving up
+const PHY_RESET_RETRIES: usize = 50_000;
+
+/// Errors that may occur when using the GMAC
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The frame is longer than [`BUFFER_LEN`]
+    FrameTooLong,
+    /// The buffer is too small for the received frame, which has been dropped
+    BufferTooSmall,
+    /// The PHY did not come out of reset
+    PhyTimeout,
+}
+
+/// Link speed
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Speed {
+    /// 10 Mbit/s
+    Mbps10,
+    /// 100 Mbit/s
+    Mbps100,
+}
+
+/// Link duplex mode
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Duplex {
+    /// Half duplex
+    Half,
+    /// Full duplex
+    Full,
+}
+
+/// Speed and duplex mode of the link between the PHY and its partner
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Link {
+    /// Link speed
+    pub speed: Speed,
+    /// Link duplex mode
+    pub duplex: Duplex,
+}
+
+/// Type-level trait implemented by the pin sets connecting the GMAC to a PHY
+///
+/// Only [`RmiiPins`] is available.
+pub trait GmacPins: Sealed {}
+
+impl<RefClk, TxEn, Tx0, Tx1, Rx0, Rx1, RxEr, CrsDv, Mdc, Mdio> Sealed
+    for RmiiPins<RefClk, TxEn, Tx0, Tx1, Rx0, Rx1, RxEr, CrsDv, Mdc, Mdio>
+where
+    RefClk: RefClkPinId,
+    TxEn: TxEnPinId,
+    Tx0: TxDataPinId<0>,
+    Tx1: TxDataPinId<1>,
+    Rx0: RxDataPinId<0>,
+    Rx1: RxDataPinId<1>,
+    RxEr: RxErPinId,
+    CrsDv: CrsDvPinId,
+    Mdc: MdcPinId,
+    Mdio: MdioPinId,
+{
+}
+
+impl<RefClk, TxEn, Tx0, Tx1, Rx0, Rx1, RxEr, CrsDv, Mdc, Mdio> GmacPins
+    for RmiiPins<RefClk, TxEn, Tx0, Tx1, Rx0, Rx1, RxEr, CrsDv, Mdc, Mdio>
+where
+    RefClk: RefClkPinId,
+    TxEn: TxEnPinId,
+    Tx0: TxDataPinId<0>,
+    Tx1: TxDataPinId<1>,
+    Rx0: RxDataPinId<0>,
+    Rx1: RxDataPinId<1>,
+    RxEr: RxErPinId,
+    CrsDv: CrsDvPinId,
+    Mdc: MdcPinId,
+    Mdio: MdioPinId,
+{
+}
+
+//==============================================================================
+// Configuration
+//==============================================================================
+
+/// GMAC configuration
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    mac_address: [u8; 6],
+    mck_freq: Hertz,
+    link: Link,
+    broadcast: bool,
+    copy_all_frames: bool,
+}
+
+impl Config {
+    /// Create a configuration for the station address `mac_address`.
+    ///
+    /// `mck_freq` is the frequency of the main clock, which clocks the GMAC;
+    /// the MDIO clock is derived from it. The link starts at 100 Mbit/s, full
+    /// duplex, and broadcast frames are accepted.
+    pub fn new(mac_address: [u8; 6], mck_freq: Hertz) -> Self {
+        Self {
+            mac_address,
+            mck_freq,
+            link: Link {
+                speed: Speed::Mbps100,
+                duplex: Duplex::Full,
+            },
+            broadcast: true,
+            copy_all_frames: false,
+        }
+    }
+
+    /// Set the initial link speed and duplex mode
+    pub fn link(mut self, link: Link) -> Self {
+        self.link = link;
+        self
+    }
+
+    /// Accept broadcast frames
+    pub fn broadcast(mut self, broadcast: bool) -> Self {
+        self.broadcast = broadcast;
+        self
+    }
+
+    /// Accept all valid frames, regardless of their destination address
+    pub fn copy_all_frames(mut self, copy_all_frames: bool) -> Self {
+        self.copy_all_frames = copy_all_frames;
+        self
+    }
+}
+
+/// Derive a MAC address from the serial number of the chip.
+///
+/// The address is a locally administered unicast address, so it won't clash
+/// with vendor-assigned addresses. It is stable for a given chip, but as the
+/// 128-bit serial number is folded into 46 bits, two chips are not strictly
+/// guaranteed to get different addresses.
+pub fn mac_address_from_serial_number() -> [u8; 6] {
+    let serial = crate::serial_number();
+    let mut addr = [0; 6];
+    for (i, byte) in serial.iter().enumerate() {
+        addr[i % 6] ^= byte;
+    }
+    // Locally administered, unicast
+    addr[0] = (addr[0] & !0b01) | 0b10;
+    addr
+}
+
+/// Index of `addr` in the multicast hash filter
+fn hash_index(addr: &[u8; 6]) -> u32 {
+    let bits = addr
+        .iter()
+        .rev()
+        .fold(0u64, |bits, &byte| (bits << 8) | byte as u64);
+    (0..8).fold(0, |index, i| index ^ ((bits >> (6 * i)) & 0x3F)) as u32
+}
+
+//==============================================================================
+// Gmac
+//==============================================================================
+
+/// Ethernet MAC
+pub struct Gmac<P: GmacPins> {
+    gmac: pac::Gmac,
+    ahbclk: AhbClk<GmacId>,
+    apbclk: ApbClk<GmacId>,
+    pins: P,
+    rx: RxRing<'static>,
+    tx: TxRing<'static>,
+}
+
+impl<P: GmacPins> Gmac<P> {
+    /// Create and enable the GMAC.
+    ///
+    /// All of the `rings`' receive buffers are handed to the GMAC, and
+    /// reception starts right away.
+    pub fn new<const RX: usize, const TX: usize>(
+        gmac: pac::Gmac,
+        ahbclk: AhbClk<GmacId>,
+        apbclk: ApbClk<GmacId>,
+        pins: P,
+        rings: &'static mut Rings<RX, TX>,
+        config: Config,
+    ) -> Self {
+        let (rx, tx) = rings.split();
+        let regs: &RegisterBlock = &gmac;
+
+        regs.ncr().write(|w| w.clrstat().set_bit());
+        regs.idr().write(|w| unsafe { w.bits(0xFFFF_FFFF) });
+        regs.isr().read();
+        regs.tsr().write(|w| unsafe { w.bits(0xFFFF_FFFF) });
+        regs.rsr().write(|w| unsafe { w.bits(0xFFFF_FFFF) });
+
+        // The MDIO clock must not exceed 2.5 MHz
+        let mck = config.mck_freq.to_Hz();
+        regs.ncfgr().write(|w| {
+            match mck {
+                ..=20_000_000 => w.clk().mck8(),
+                20_000_001..=40_000_000 => w.clk().mck16(),
+                40_000_001..=80_000_000 => w.clk().mck32(),
+                80_000_001..=120_000_000 => w.clk().mck48(),
+                120_000_001..=160_000_000 => w.clk().mck64(),
+                _ => w.clk().mck96(),
+            };
+            w.spd().bit(config.link.speed == Speed::Mbps100);
+            w.fd().bit(config.link.duplex == Duplex::Full);
+            w.maxfs().set_bit();
+            w.rfcs().set_bit();
+            w.nbc().bit(!config.broadcast);
+            w.caf().bit(config.copy_all_frames)
+        });
+        regs.ur().write(|w| w.mii().clear_bit());
+
+        regs.dcfgr().write(|w| {
+            w.fbldo().incr4();
+            w.rxbms().full();
+            w.txpbms().set_bit();
+            // In units of 64 bytes
+            unsafe { w.drbs().bits((BUFFER_LEN / 64) as u8) }
+        });
+        regs.rbqb()
+            .write(|w| unsafe { w.addr().bits(rx.base() >> 2) });
+        regs.tbqb()
+            .write(|w| unsafe { w.addr().bits(tx.base() >> 2) });
+
+        let mut gmac = Self {
+            gmac,
+            ahbclk,
+            apbclk,
+            pins,
+            rx,
+            tx,
+        };
+        gmac.set_address_filter(0, Some(config.mac_address));
+        for i in 1..NUM_ADDRESS_FILTERS {
+            gmac.set_address_filter(i, None);
+        }
+        gmac.clear_multicast();
+
+        gmac.regs()
+            .ncr()
+            .write(|w| w.mpe().set_bit().rxen().set_bit().txen().set_bit());
+        gmac
+    }
+
+    #[inline]
+    fn regs(&self) -> &RegisterBlock {
+        &self.gmac
+    }
+
+    /// Set the link speed and duplex mode.
+    ///
+    /// This must match the link negotiated by the PHY, see
+    /// [`Gmac::phy_link`].
+    pub fn set_link(&mut self, link: Link) {
+        self.regs().ncfgr().modify(|_, w| {
+            w.spd().bit(link.speed == Speed::Mbps100);
+            w.fd().bit(link.duplex == Duplex::Full)
+        });
+    }
+
+    /// Loop transmitted frames back to the receiver inside the GMAC, without
+    /// involving the PHY.
+    pub fn set_local_loopback(&mut self, enabled: bool) {
+        self.regs().ncr().modify(|_, w| w.lbl().bit(enabled));
+    }
+
+    //--------------------------------------------------------------------------
+    // Address filtering
+    //--------------------------------------------------------------------------
+
+    /// Set or disable specific address filter `index`.
+    ///
+    /// Filter 0 holds the station address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than [`NUM_ADDRESS_FILTERS`].
+    pub fn set_address_filter(&mut self, index: usize, addr: Option<[u8; 6]>) {
+        assert!(index < NUM_ADDRESS_FILTERS);
+        let sa = self.regs().sa(index);
+        // Writing the bottom register disables the filter, writing the top
+        // register enables it again
+        match addr {
+            Some(addr) => {
+                let bottom = u32::from_le_bytes([addr[0], addr[1], addr[2], addr[3]]);
+                let top = u16::from_le_bytes([addr[4], addr[5]]);
+                sa.sab().write(|w| unsafe { w.addr().bits(bottom) });
+                sa.sat().write(|w| unsafe { w.addr().bits(top) });
+            }
+            None => sa.sab().write(|w| unsafe { w.addr().bits(0) }),
+        }
+    }
+
+    /// Return the station address
+    pub fn mac_address(&self) -> [u8; 6] {
+        let sa = self.regs().sa(0);
+        let bottom = sa.sab().read().addr().bits().to_le_bytes();
+        let top = sa.sat().read().addr().bits().to_le_bytes();
+        [bottom[0], bottom[1], bottom[2], bottom[3], top[0], top[1]]
+    }
+
+    /// Accept multicast frames sent to `addr`.
+    ///
+    /// The hash filter is not exact: frames sent to other multicast addresses
+    /// with the same hash are accepted too.
+    pub fn add_multicast(&mut self, addr: &[u8; 6]) {
+        let index = hash_index(addr);
+        let regs = self.regs();
+        if index < 32 {
+            regs.hrb()
+                .modify(|r, w| unsafe { w.addr().bits(r.addr().bits() | 1 << index) });
+        } else {
+            regs.hrt()
+                .modify(|r, w| unsafe { w.addr().bits(r.addr().bits() | 1 << (index - 32)) });
+        }
+        regs.ncfgr().modify(|_, w| w.mtihen().set_bit());
+    }
+
+    /// Stop accepting multicast frames
+    pub fn clear_multicast(&mut self) {
+        let regs = self.regs();
+        regs.ncfgr().modify(|_, w| w.mtihen().clear_bit());
+        regs.hrb().write(|w| unsafe { w.addr().bits(0) });
+        regs.hrt().write(|w| unsafe { w.addr().bits(0) });
+    }
+
+    /// Accept broadcast frames
+    pub fn set_broadcast(&mut self, enabled: bool) {
+        self.regs().ncfgr().modify(|_, w| w.nbc().bit(!enabled));
+    }
+
+    /// Accept all valid frames, regardless of their destination address
+    pub fn set_copy_all_frames(&mut self, enabled: bool) {
+        self.regs().ncfgr().modify(|_, w| w.caf().bit(enabled));
+    }
+
+    //--------------------------------------------------------------------------
+    // Frames
+    //--------------------------------------------------------------------------
+
+    /// Return `true` if a received frame is waiting
+    #[inline]
+    pub fn is_rx_ready(&mut self) -> bool {
+        self.rx.is_ready()
+    }
+
+    /// Return `true` if a frame can be queued for transmission
+    #[inline]
+    pub fn is_tx_ready(&self) -> bool {
+        self.tx.is_ready()
+    }
+
+    /// Copy the next received frame into `buf`, returning its length.
+    ///
+    /// The frame doesn't include its FCS.
+    pub fn receive(&mut self, buf: &mut [u8]) -> nb::Result<usize, Error> {
+        let result = self.receive_with(|frame| {
+            let len = frame.len();
+            let dest = buf.get_mut(..len).ok_or(Error::BufferTooSmall)?;
+            dest.copy_from_slice(frame);
+            Ok(len)
+        });
+        result
+            .ok_or(nb::Error::WouldBlock)?
+            .map_err(nb::Error::Other)
+    }
+
+    /// Pass the next received frame to `f`, in place.
+    ///
+    /// Returns `None` if no frame has been received.
+    #[inline]
+    pub fn receive_with<R>(&mut self, f: impl FnOnce(&[u8]) -> R) -> Option<R> {
+        self.rx.receive(f)
+    }
+
+    /// Queue `frame` for transmission.
+    ///
+    /// `frame` starts with the destination address, and doesn't include the
+    /// FCS, which is appended by the GMAC.
+    pub fn send(&mut self, frame: &[u8]) -> nb::Result<(), Error> {
+        if frame.len() > BUFFER_LEN {
+            return Err(nb::Error::Other(Error::FrameTooLong));
+        }
+        self.send_with(frame.len(), |buf| buf.copy_from_slice(frame))
+            .ok_or(nb::Error::WouldBlock)
+    }
+
+    /// Let `f` fill in a `len` byte frame in place, then queue it for
+    /// transmission.
+    ///
+    /// Returns `None` if the transmit ring is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is larger than [`BUFFER_LEN`].
+    pub fn send_with<R>(&mut self, len: usize, f: impl FnOnce(&mut [u8]) -> R) -> Option<R> {
+        let result = self.tx.send(len, f)?;
+        start_transmit(self.regs());
+        Some(result)
+    }
+
+    //--------------------------------------------------------------------------
+    // PHY management
+    //--------------------------------------------------------------------------
+
+    fn mdio(&mut self, op: u8, phy: u8, reg: u8, data: u16) -> u16 {
+        let regs = self.regs();
+        regs.man().write(|w| unsafe {
+            w.wzo().clear_bit();
+            // Clause 22 frame
+            w.cltto().set_bit();
+            w.op().bits(op);
+            w.phya().bits(phy);
+            w.rega().bits(reg);
+            w.wtn().bits(0b10);
+            w.data().bits(data)
+        });
+        while regs.nsr().read().idle().bit_is_clear() {}
+        regs.man().read().data().bits()
+    }
+
+    /// Read register `reg` of the PHY at address `phy`
+    pub fn mdio_read(&mut self, phy: u8, reg: u8) -> u16 {
+        self.mdio(0b10, phy, reg, 0)
+    }
+
+    /// Write register `reg` of the PHY at address `phy`
+    pub fn mdio_write(&mut self, phy: u8, reg: u8, data: u16) {
+        self.mdio(0b01, phy, reg, data);
+    }
+
+    /// Reset the PHY at address `phy`, and wait for the reset to complete
+    pub fn phy_reset(&mut self, phy: u8) -> Result<(), Error> {
+        self.mdio_write(phy, phy::BMCR, phy::BMCR_RESET);
+        for _ in 0..PHY_RESET_RETRIES {
+            if self.mdio_read(phy, phy::BMCR) & phy::BMCR_RESET == 0 {
+                return Ok(());
+            }
+        }
+        Err(Error::PhyTimeout)
+    }
+
+    /// Advertise all 10 and 100 Mbit/s modes and restart auto-negotiation on
+    /// the PHY at address `phy`
+    pub fn phy_autonegotiate(&mut self, phy: u8) {
+        let anar = self.mdio_read(phy, phy::ANAR);
+        self.mdio_write(phy, phy::ANAR, anar | phy::ANAR_ALL);
+        let bmcr = self.mdio_read(phy, phy::BMCR);
+        self.mdio_write(
+            phy,
+            phy::BMCR,
+            bmcr | phy::BMCR_AUTONEG_ENABLE | phy::BMCR_AUTONEG_RESTART,
+        );
+    }
+
+    /// Return the link negotiated by the PHY at address `phy`, or `None` if
+    /// the link is down or auto-negotiation hasn't completed.
+    pub fn phy_link(&mut self, phy: u8) -> Option<Link> {
+        // The link status bit latches low, so read it twice
+        self.mdio_read(phy, phy::BMSR);
+        let bmsr = self.mdio_read(phy, phy::BMSR);
+        if bmsr & phy::BMSR_LINK == 0 || bmsr & phy::BMSR_AUTONEG_COMPLETE == 0 {
+            return None;
+        }
+
+        let common = self.mdio_read(phy, phy::ANAR) & self.mdio_read(phy, phy::ANLPAR);
+        let (speed, duplex) = if common & phy::ANAR_100_FULL != 0 {
+            (Speed::Mbps100, Duplex::Full)
+        } else if common & phy::ANAR_100_HALF != 0 {
+            (Speed::Mbps100, Duplex::Half)
+        } else if common & phy::ANAR_10_FULL != 0 {
+            (Speed::Mbps10, Duplex::Full)
+        } else {
+            (Speed::Mbps10, Duplex::Half)
+        };
+        Some(Link { speed, duplex })
+    }
+
+    /// Disable the GMAC and return the underlying resources.
+    ///
+    /// The [`Rings`] stay borrowed by the GMAC.
+    pub fn free(self) -> (pac::Gmac, AhbClk<GmacId>, ApbClk<GmacId>, P) {
+        self.regs().ncr().write(|w| w.clrstat().set_bit());
+        (self.gmac, self.ahbclk, self.apbclk, self.pins)
+    }
+}
+
+/// Start transmitting the frames queued in the transmit ring, clearing the
+/// status left by the previous transmission
+#[inline]
+fn start_transmit(regs: &RegisterBlock) {
+    regs.tsr().write(|w| unsafe { w.bits(0xFFFF_FFFF) });
+    regs.ncr().modify(|_, w| w.tstart().set_bit());
+}
+
+/// IEEE 802.3 clause 22 PHY registers
+pub mod phy {
+    /// Basic mode control register
+    pub const BMCR: u8 = 0;
+    /// Basic mode status register
+    pub const BMSR: u8 = 1;
+    /// PHY identifier, high word
+    pub const PHYID1: u8 = 2;
+    /// PHY identifier, low word
+    pub const PHYID2: u8 = 3;
+    /// Auto-negotiation advertisement register
+    pub const ANAR: u8 = 4;
+    /// Auto-negotiation link partner ability register
+    pub const ANLPAR: u8 = 5;
+
+    /// [`BMCR`]: software reset
+    pub const BMCR_RESET: u16 = 1 << 15;
+    /// [`BMCR`]: enable auto-negotiation
+    pub const BMCR_AUTONEG_ENABLE: u16 = 1 << 12;
+    /// [`BMCR`]: restart auto-negotiation
+    pub const BMCR_AUTONEG_RESTART: u16 = 1 << 9;
+
+    /// [`BMSR`]: auto-negotiation complete
+    pub const BMSR_AUTONEG_COMPLETE: u16 = 1 << 5;
+    /// [`BMSR`]: link up
+    pub const BMSR_LINK: u16 = 1 << 2;
+
+    /// [`ANAR`]/[`ANLPAR`]: 100BASE-TX full duplex
+    pub const ANAR_100_FULL: u16 = 1 << 8;
+    /// [`ANAR`]/[`ANLPAR`]: 100BASE-TX half duplex
+    pub const ANAR_100_HALF: u16 = 1 << 7;
+    /// [`ANAR`]/[`ANLPAR`]: 10BASE-T full duplex
+    pub const ANAR_10_FULL: u16 = 1 << 6;
+    /// [`ANAR`]/[`ANLPAR`]: 10BASE-T half duplex
+    pub const ANAR_10_HALF: u16 = 1 << 5;
+    /// [`ANAR`]: all 10 and 100 Mbit/s modes
+    pub const ANAR_ALL: u16 = ANAR_100_FULL | ANAR_100_HALF | ANAR_10_FULL | ANAR_10_HALF;
+}
@@ -0,0 +1,179 @@
+//! GMAC pin definitions
+
+use atsamd_hal_macros::hal_cfg;
+
+use crate::gpio::{AlternateL, AnyPin, Pin, PinId};
+
+/// Type-level trait for [`PinId`]s which can be the RMII reference clock
+/// `GREFCK`
+pub trait RefClkPinId: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be the transmit enable `GTXEN`
+pub trait TxEnPinId: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be the transmit data `GTX[N]`
+pub trait TxDataPinId<const N: usize>: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be the receive data `GRX[N]`
+pub trait RxDataPinId<const N: usize>: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be the receive error `GRXER`
+pub trait RxErPinId: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be the RMII carrier sense and
+/// data valid `GCRSDV`
+pub trait CrsDvPinId: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be the management clock `GMDC`
+pub trait MdcPinId: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be the management data `GMDIO`
+pub trait MdioPinId: PinId {}
+
+/// Set of pins connecting the GMAC to a PHY through the Reduced Media
+/// Independent Interface (RMII), including the MDIO management interface
+///
+/// The PHY must provide the 50 MHz reference clock on `GREFCK`.
+pub struct RmiiPins<RefClk, TxEn, Tx0, Tx1, Rx0, Rx1, RxEr, CrsDv, Mdc, Mdio>
+where
+    RefClk: RefClkPinId,
+    TxEn: TxEnPinId,
+    Tx0: TxDataPinId<0>,
+    Tx1: TxDataPinId<1>,
+    Rx0: RxDataPinId<0>,
+    Rx1: RxDataPinId<1>,
+    RxEr: RxErPinId,
+    CrsDv: CrsDvPinId,
+    Mdc: MdcPinId,
+    Mdio: MdioPinId,
+{
+    ref_clk: Pin<RefClk, AlternateL>,
+    tx: TxPins<TxEn, Tx0, Tx1>,
+    rx: RxPins<Rx0, Rx1, RxEr, CrsDv>,
+    mdio: (Pin<Mdc, AlternateL>, Pin<Mdio, AlternateL>),
+}
+
+/// Transmit pins `GTXEN`, `GTX0` and `GTX1`
+type TxPins<TxEn, Tx0, Tx1> = (
+    Pin<TxEn, AlternateL>,
+    Pin<Tx0, AlternateL>,
+    Pin<Tx1, AlternateL>,
+);
+
+/// Receive pins `GRX0`, `GRX1`, `GRXER` and `GCRSDV`
+type RxPins<Rx0, Rx1, RxEr, CrsDv> = (
+    Pin<Rx0, AlternateL>,
+    Pin<Rx1, AlternateL>,
+    Pin<RxEr, AlternateL>,
+    Pin<CrsDv, AlternateL>,
+);
+
+impl<RefClk, TxEn, Tx0, Tx1, Rx0, Rx1, RxEr, CrsDv, Mdc, Mdio>
+    RmiiPins<RefClk, TxEn, Tx0, Tx1, Rx0, Rx1, RxEr, CrsDv, Mdc, Mdio>
+where
+    RefClk: RefClkPinId,
+    TxEn: TxEnPinId,
+    Tx0: TxDataPinId<0>,
+    Tx1: TxDataPinId<1>,
+    Rx0: RxDataPinId<0>,
+    Rx1: RxDataPinId<1>,
+    RxEr: RxErPinId,
+    CrsDv: CrsDvPinId,
+    Mdc: MdcPinId,
+    Mdio: MdioPinId,
+{
+    /// Put the pins in GMAC mode
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        ref_clk: impl AnyPin<Id = RefClk>,
+        tx_en: impl AnyPin<Id = TxEn>,
+        tx0: impl AnyPin<Id = Tx0>,
+        tx1: impl AnyPin<Id = Tx1>,
+        rx0: impl AnyPin<Id = Rx0>,
+        rx1: impl AnyPin<Id = Rx1>,
+        rx_er: impl AnyPin<Id = RxEr>,
+        crs_dv: impl AnyPin<Id = CrsDv>,
+        mdc: impl AnyPin<Id = Mdc>,
+        mdio: impl AnyPin<Id = Mdio>,
+    ) -> Self {
+        Self {
+            ref_clk: ref_clk.into().into_mode(),
+            tx: (
+                tx_en.into().into_mode(),
+                tx0.into().into_mode(),
+                tx1.into().into_mode(),
+            ),
+            rx: (
+                rx0.into().into_mode(),
+                rx1.into().into_mode(),
+                rx_er.into().into_mode(),
+                crs_dv.into().into_mode(),
+            ),
+            mdio: (mdc.into().into_mode(), mdio.into().into_mode()),
+        }
+    }
+
+    /// Release the pins
+    #[allow(clippy::type_complexity)]
+    pub fn free(
+        self,
+    ) -> (
+        Pin<RefClk, AlternateL>,
+        Pin<TxEn, AlternateL>,
+        Pin<Tx0, AlternateL>,
+        Pin<Tx1, AlternateL>,
+        Pin<Rx0, AlternateL>,
+        Pin<Rx1, AlternateL>,
+        Pin<RxEr, AlternateL>,
+        Pin<CrsDv, AlternateL>,
+        Pin<Mdc, AlternateL>,
+        Pin<Mdio, AlternateL>,
+    ) {
+        let (tx_en, tx0, tx1) = self.tx;
+        let (rx0, rx1, rx_er, crs_dv) = self.rx;
+        let (mdc, mdio) = self.mdio;
+        (
+            self.ref_clk,
+            tx_en,
+            tx0,
+            tx1,
+            rx0,
+            rx1,
+            rx_er,
+            crs_dv,
+            mdc,
+            mdio,
+        )
+    }
+}
+
+macro_rules! gmac_pins {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $PinId:ident: $Trait:ident $(<$n:literal>)?
+        ),+
+        $(,)?
+    ) => {
+        $(
+            $( #[$cfg] )?
+            impl $Trait $(<$n>)? for crate::gpio::$PinId {}
+        )+
+    };
+}
+
+gmac_pins! {
+    PA12: RxDataPinId<1>,
+    PA13: RxDataPinId<0>,
+    PA14: RefClkPinId,
+    PA15: RxErPinId,
+    PA17: TxEnPinId,
+    PA18: TxDataPinId<0>,
+    PA19: TxDataPinId<1>,
+    #[hal_cfg("pc11")]
+    PC11: MdcPinId,
+    #[hal_cfg("pc12")]
+    PC12: MdioPinId,
+    #[hal_cfg("pc20")]
+    PC20: CrsDvPinId,
+}
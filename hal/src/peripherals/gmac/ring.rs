@@ -0,0 +1,489 @@
+//! GMAC DMA descriptor rings
+//!
+//! The GMAC exchanges frames with software through two rings of buffer
+//! descriptors in RAM. Ownership of each descriptor is passed back and forth
+//! through a bit in the descriptor itself, so nothing here touches the GMAC
+//! registers, and the ring logic can be exercised on the host against a model
+//! of the DMA.
+
+use core::marker::PhantomData;
+use core::ptr::{addr_of, addr_of_mut, read_volatile, write_volatile};
+use core::sync::atomic::{Ordering, fence};
+
+/// Size of each receive and transmit buffer, in bytes
+///
+/// A buffer holds a whole frame, including a VLAN tag but excluding the FCS,
+/// so frames never span several descriptors.
+pub const BUFFER_LEN: usize = 1536;
+
+// Receive descriptor, word 0
+/// Set by the GMAC once it has written the buffer; software owns the
+/// descriptor while it is set
+const RX_OWNED: u32 = 1 << 0;
+const RX_WRAP: u32 = 1 << 1;
+
+// Receive descriptor, word 1
+const RX_LEN_MASK: u32 = 0x1FFF;
+const RX_SOF: u32 = 1 << 14;
+const RX_EOF: u32 = 1 << 15;
+
+// Transmit descriptor, word 1
+const TX_LEN_MASK: u32 = 0x3FFF;
+const TX_LAST: u32 = 1 << 15;
+const TX_WRAP: u32 = 1 << 30;
+/// Cleared by software to hand the descriptor to the GMAC, and set again by
+/// the GMAC once the frame has been sent
+const TX_USED: u32 = 1 << 31;
+
+/// GMAC receive buffer descriptor
+#[repr(C, align(8))]
+struct RxDescriptor {
+    addr: u32,
+    status: u32,
+}
+
+/// GMAC transmit buffer descriptor
+#[repr(C, align(8))]
+struct TxDescriptor {
+    addr: u32,
+    status: u32,
+}
+
+#[repr(C, align(32))]
+struct Buffer([u8; BUFFER_LEN]);
+
+/// Descriptor rings and frame buffers used by the GMAC DMA
+///
+/// `RX` and `TX` are the number of frames that can be queued for reception
+/// and transmission, each taking a [`BUFFER_LEN`] byte buffer. The rings must
+/// stay in place for as long as the GMAC uses them, which is why
+/// [`Gmac::new`](super::Gmac::new) takes a `&'static mut Rings`.
+#[repr(C)]
+pub struct Rings<const RX: usize, const TX: usize> {
+    rx_descriptors: [RxDescriptor; RX],
+    tx_descriptors: [TxDescriptor; TX],
+    rx_buffers: [Buffer; RX],
+    tx_buffers: [Buffer; TX],
+}
+
+impl<const RX: usize, const TX: usize> Rings<RX, TX> {
+    /// Create a new set of rings
+    pub const fn new() -> Self {
+        const { assert!(RX > 0 && TX > 0, "GMAC rings must not be empty") };
+        Self {
+            rx_descriptors: [const { RxDescriptor { addr: 0, status: 0 } }; RX],
+            tx_descriptors: [const { TxDescriptor { addr: 0, status: 0 } }; TX],
+            rx_buffers: [const { Buffer([0; BUFFER_LEN]) }; RX],
+            tx_buffers: [const { Buffer([0; BUFFER_LEN]) }; TX],
+        }
+    }
+
+    /// Initialize the descriptors and split the rings
+    ///
+    /// Every receive descriptor is handed to the GMAC, and every transmit
+    /// descriptor is kept by software.
+    pub(super) fn split(&mut self) -> (RxRing<'_>, TxRing<'_>) {
+        let rx = RxRing {
+            descriptors: self.rx_descriptors.as_mut_ptr(),
+            buffers: self.rx_buffers.as_mut_ptr(),
+            len: RX,
+            next: 0,
+            _rings: PhantomData,
+        };
+        let tx = TxRing {
+            descriptors: self.tx_descriptors.as_mut_ptr(),
+            buffers: self.tx_buffers.as_mut_ptr(),
+            len: TX,
+            next: 0,
+            _rings: PhantomData,
+        };
+
+        for i in 0..RX {
+            let wrap = if i == RX - 1 { RX_WRAP } else { 0 };
+            rx.write_addr(i, bus_addr(rx.buffer(i)) | wrap);
+            rx.write_status(i, 0);
+        }
+        for i in 0..TX {
+            let wrap = if i == TX - 1 { TX_WRAP } else { 0 };
+            tx.write_addr(i, bus_addr(tx.buffer(i)));
+            tx.write_status(i, TX_USED | wrap);
+        }
+        fence(Ordering::SeqCst);
+
+        (rx, tx)
+    }
+}
+
+impl<const RX: usize, const TX: usize> Default for Rings<RX, TX> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[inline]
+fn bus_addr<T>(ptr: *const T) -> u32 {
+    ptr as usize as u32
+}
+
+//==============================================================================
+// RxRing
+//==============================================================================
+
+/// Software side of the receive descriptor ring
+pub(super) struct RxRing<'a> {
+    descriptors: *mut RxDescriptor,
+    buffers: *mut Buffer,
+    len: usize,
+    next: usize,
+    _rings: PhantomData<&'a mut ()>,
+}
+
+// SAFETY: The ring has exclusive access to the descriptors and buffers it
+// points to, for its whole lifetime
+unsafe impl Send for RxRing<'_> {}
+
+impl RxRing<'_> {
+    /// Address of the first descriptor, to program into `RBQB`
+    #[inline]
+    pub(super) fn base(&self) -> u32 {
+        bus_addr(self.descriptors)
+    }
+
+    #[inline]
+    fn buffer(&self, i: usize) -> *mut Buffer {
+        unsafe { self.buffers.add(i) }
+    }
+
+    #[inline]
+    fn read_addr(&self, i: usize) -> u32 {
+        unsafe { read_volatile(addr_of!((*self.descriptors.add(i)).addr)) }
+    }
+
+    #[inline]
+    fn write_addr(&self, i: usize, addr: u32) {
+        unsafe { write_volatile(addr_of_mut!((*self.descriptors.add(i)).addr), addr) }
+    }
+
+    #[inline]
+    fn read_status(&self, i: usize) -> u32 {
+        unsafe { read_volatile(addr_of!((*self.descriptors.add(i)).status)) }
+    }
+
+    #[inline]
+    fn write_status(&self, i: usize, status: u32) {
+        unsafe { write_volatile(addr_of_mut!((*self.descriptors.add(i)).status), status) }
+    }
+
+    /// Return `true` if the next descriptor holds a complete frame
+    ///
+    /// Descriptors holding only part of a frame can't be produced with
+    /// [`BUFFER_LEN`] byte buffers, short of a DMA error; they are handed
+    /// straight back to the GMAC.
+    pub(super) fn is_ready(&mut self) -> bool {
+        loop {
+            if self.read_addr(self.next) & RX_OWNED == 0 {
+                return false;
+            }
+            fence(Ordering::SeqCst);
+            let status = self.read_status(self.next);
+            if status & (RX_SOF | RX_EOF) == RX_SOF | RX_EOF {
+                return true;
+            }
+            self.release();
+        }
+    }
+
+    /// Pass the next received frame to `f`, then give its buffer back to the
+    /// GMAC
+    ///
+    /// Returns `None` if no frame has been received.
+    pub(super) fn receive<R>(&mut self, f: impl FnOnce(&[u8]) -> R) -> Option<R> {
+        if !self.is_ready() {
+            return None;
+        }
+        let len = ((self.read_status(self.next) & RX_LEN_MASK) as usize).min(BUFFER_LEN);
+        let buffer = unsafe { &(*self.buffer(self.next)).0 };
+        let result = f(&buffer[..len]);
+        self.release();
+        Some(result)
+    }
+
+    fn release(&mut self) {
+        // Make sure the buffer has been read before the GMAC can overwrite it
+        fence(Ordering::SeqCst);
+        let addr = self.read_addr(self.next);
+        self.write_addr(self.next, addr & !RX_OWNED);
+        self.next = (self.next + 1) % self.len;
+    }
+}
+
+//==============================================================================
+// TxRing
+//==============================================================================
+
+/// Software side of the transmit descriptor ring
+pub(super) struct TxRing<'a> {
+    descriptors: *mut TxDescriptor,
+    buffers: *mut Buffer,
+    len: usize,
+    next: usize,
+    _rings: PhantomData<&'a mut ()>,
+}
+
+// SAFETY: The ring has exclusive access to the descriptors and buffers it
+// points to, for its whole lifetime
+unsafe impl Send for TxRing<'_> {}
+
+impl TxRing<'_> {
+    /// Address of the first descriptor, to program into `TBQB`
+    #[inline]
+    pub(super) fn base(&self) -> u32 {
+        bus_addr(self.descriptors)
+    }
+
+    #[inline]
+    fn buffer(&self, i: usize) -> *mut Buffer {
+        unsafe { self.buffers.add(i) }
+    }
+
+    #[inline]
+    fn write_addr(&self, i: usize, addr: u32) {
+        unsafe { write_volatile(addr_of_mut!((*self.descriptors.add(i)).addr), addr) }
+    }
+
+    #[inline]
+    fn read_status(&self, i: usize) -> u32 {
+        unsafe { read_volatile(addr_of!((*self.descriptors.add(i)).status)) }
+    }
+
+    #[inline]
+    fn write_status(&self, i: usize, status: u32) {
+        unsafe { write_volatile(addr_of_mut!((*self.descriptors.add(i)).status), status) }
+    }
+
+    /// Return `true` if a frame can be queued for transmission
+    #[inline]
+    pub(super) fn is_ready(&self) -> bool {
+        self.read_status(self.next) & TX_USED != 0
+    }
+
+    /// Let `f` fill in a `len` byte frame, then hand it to the GMAC
+    ///
+    /// Returns `None` if every descriptor is still in use by the GMAC. The
+    /// GMAC must be told to start transmitting afterwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is larger than [`BUFFER_LEN`].
+    pub(super) fn send<R>(&mut self, len: usize, f: impl FnOnce(&mut [u8]) -> R) -> Option<R> {
+        assert!(len <= BUFFER_LEN, "frame too long for the GMAC buffers");
+        if !self.is_ready() {
+            return None;
+        }
+        fence(Ordering::SeqCst);
+        let buffer = unsafe { &mut (*self.buffer(self.next)).0 };
+        let result = f(&mut buffer[..len]);
+
+        // Make sure the frame is in RAM before the GMAC can read it
+        fence(Ordering::SeqCst);
+        let wrap = if self.next == self.len - 1 {
+            TX_WRAP
+        } else {
+            0
+        };
+        self.write_status(self.next, (len as u32 & TX_LEN_MASK) | TX_LAST | wrap);
+        self.next = (self.next + 1) % self.len;
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Model of the GMAC DMA with its MAC in local loopback: every frame
+    /// queued for transmission is written to the next free receive
+    /// descriptor, or dropped if there is none.
+    #[derive(Default)]
+    struct Loopback {
+        tx_next: usize,
+        rx_next: usize,
+        dropped: usize,
+    }
+
+    impl Loopback {
+        /// Process all queued frames, returning how many were sent
+        fn run(&mut self, rx: &RxRing, tx: &TxRing) -> usize {
+            let mut sent = 0;
+            loop {
+                let status = tx.read_status(self.tx_next);
+                if status & TX_USED != 0 {
+                    return sent;
+                }
+                assert_ne!(status & TX_LAST, 0);
+                let len = (status & TX_LEN_MASK) as usize;
+
+                let addr = rx.read_addr(self.rx_next);
+                if addr & RX_OWNED == 0 {
+                    let frame = unsafe { &(*tx.buffer(self.tx_next)).0 };
+                    let buffer = unsafe { &mut (*rx.buffer(self.rx_next)).0 };
+                    buffer[..len].copy_from_slice(&frame[..len]);
+                    rx.write_status(self.rx_next, len as u32 | RX_SOF | RX_EOF);
+                    rx.write_addr(self.rx_next, addr | RX_OWNED);
+                    self.rx_next = if addr & RX_WRAP != 0 {
+                        0
+                    } else {
+                        self.rx_next + 1
+                    };
+                } else {
+                    self.dropped += 1;
+                }
+
+                tx.write_status(self.tx_next, status | TX_USED);
+                self.tx_next = if status & TX_WRAP != 0 {
+                    0
+                } else {
+                    self.tx_next + 1
+                };
+                sent += 1;
+            }
+        }
+    }
+
+    fn frame(seed: u8, len: usize) -> [u8; BUFFER_LEN] {
+        let mut frame = [0; BUFFER_LEN];
+        for (i, b) in frame[..len].iter_mut().enumerate() {
+            *b = seed.wrapping_add(i as u8);
+        }
+        frame
+    }
+
+    fn send(tx: &mut TxRing, seed: u8, len: usize) -> bool {
+        let frame = frame(seed, len);
+        tx.send(len, |buf| buf.copy_from_slice(&frame[..len]))
+            .is_some()
+    }
+
+    fn receive(rx: &mut RxRing) -> Option<(u8, usize)> {
+        rx.receive(|buf| {
+            assert_eq!(buf, &frame(buf[0], buf.len())[..buf.len()]);
+            (buf[0], buf.len())
+        })
+    }
+
+    #[test]
+    fn descriptors_are_initialized() {
+        let mut rings = Rings::<3, 2>::new();
+        let (rx, tx) = rings.split();
+
+        for i in 0..3 {
+            let addr = rx.read_addr(i);
+            assert_eq!(addr & !0b11, bus_addr(rx.buffer(i)));
+            assert_eq!(addr & RX_OWNED, 0);
+            assert_eq!(addr & RX_WRAP != 0, i == 2);
+        }
+        for i in 0..2 {
+            let status = tx.read_status(i);
+            assert_ne!(status & TX_USED, 0);
+            assert_eq!(status & TX_WRAP != 0, i == 1);
+        }
+        assert!(tx.is_ready());
+        assert_eq!(rx.base() % 8, 0);
+        assert_eq!(tx.base() % 8, 0);
+    }
+
+    #[test]
+    fn loopback_round_trip() {
+        let mut rings = Rings::<4, 2>::new();
+        let (mut rx, mut tx) = rings.split();
+        let mut model = Loopback::default();
+
+        assert_eq!(receive(&mut rx), None);
+        assert!(send(&mut tx, 7, 60));
+        assert_eq!(receive(&mut rx), None);
+        assert_eq!(model.run(&rx, &tx), 1);
+        assert_eq!(receive(&mut rx), Some((7, 60)));
+        assert_eq!(receive(&mut rx), None);
+    }
+
+    #[test]
+    fn rings_wrap() {
+        let mut rings = Rings::<3, 2>::new();
+        let (mut rx, mut tx) = rings.split();
+        let mut model = Loopback::default();
+
+        for seed in 0..20u8 {
+            let len = 60 + seed as usize * 70;
+            assert!(send(&mut tx, seed, len));
+            assert_eq!(model.run(&rx, &tx), 1);
+            assert_eq!(receive(&mut rx), Some((seed, len)));
+        }
+        assert_eq!(model.dropped, 0);
+    }
+
+    #[test]
+    fn transmit_ring_full() {
+        let mut rings = Rings::<4, 2>::new();
+        let (mut rx, mut tx) = rings.split();
+        let mut model = Loopback::default();
+
+        assert!(send(&mut tx, 1, 64));
+        assert!(send(&mut tx, 2, 64));
+        assert!(!tx.is_ready());
+        assert!(!send(&mut tx, 3, 64));
+
+        assert_eq!(model.run(&rx, &tx), 2);
+        assert!(tx.is_ready());
+        assert!(send(&mut tx, 3, BUFFER_LEN));
+        assert_eq!(model.run(&rx, &tx), 1);
+
+        assert_eq!(receive(&mut rx), Some((1, 64)));
+        assert_eq!(receive(&mut rx), Some((2, 64)));
+        assert_eq!(receive(&mut rx), Some((3, BUFFER_LEN)));
+        assert_eq!(receive(&mut rx), None);
+    }
+
+    #[test]
+    fn receive_ring_full() {
+        let mut rings = Rings::<2, 4>::new();
+        let (mut rx, mut tx) = rings.split();
+        let mut model = Loopback::default();
+
+        for seed in 0..4 {
+            assert!(send(&mut tx, seed, 100));
+        }
+        assert_eq!(model.run(&rx, &tx), 4);
+        assert_eq!(model.dropped, 2);
+
+        assert_eq!(receive(&mut rx), Some((0, 100)));
+        assert_eq!(receive(&mut rx), Some((1, 100)));
+        assert_eq!(receive(&mut rx), None);
+
+        // Buffers given back are reused
+        assert!(send(&mut tx, 9, 100));
+        assert_eq!(model.run(&rx, &tx), 1);
+        assert_eq!(receive(&mut rx), Some((9, 100)));
+    }
+
+    #[test]
+    fn fragments_are_dropped() {
+        let mut rings = Rings::<4, 2>::new();
+        let (mut rx, mut tx) = rings.split();
+        let mut model = Loopback::default();
+
+        // A frame split across two descriptors
+        rx.write_status(0, 64 | RX_SOF);
+        rx.write_addr(0, rx.read_addr(0) | RX_OWNED);
+        rx.write_status(1, 64 | RX_EOF);
+        rx.write_addr(1, rx.read_addr(1) | RX_OWNED);
+        model.rx_next = 2;
+
+        assert!(!rx.is_ready());
+        assert_eq!(rx.read_addr(0) & RX_OWNED, 0);
+        assert_eq!(rx.read_addr(1) & RX_OWNED, 0);
+
+        assert!(send(&mut tx, 5, 80));
+        assert_eq!(model.run(&rx, &tx), 1);
+        assert_eq!(receive(&mut rx), Some((5, 80)));
+    }
+}
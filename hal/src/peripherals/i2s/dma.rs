@@ -0,0 +1,491 @@
+//! I2S DMA support
+//!
+//! [`Transmitter`] and [`Receiver`] can be used as non-incrementing DMA
+//! [`Buffer`]s. Each serializer raises its own DMA trigger, so one DMA channel
+//! is needed per serializer.
+//!
+//! Audio usually requires an uninterrupted stream of samples. [`TxStream`] and
+//! [`RxStream`] implement a blocking ping-pong scheme with two buffers: while
+//! the DMAC transfers one of them, the other one is filled or processed by the
+//! application. With the `async` feature, [`Transmitter::stream_future`] and
+//! [`Receiver::stream_future`] provide the same scheme on top of
+//! [`dmac::async_api`](crate::dmac::async_api).
+
+use core::convert::Infallible;
+
+use atsamd_hal_macros::hal_cfg;
+
+use super::*;
+use crate::dmac::{
+    self, AnyChannel, Buffer, Busy, ChId as DmaChId, Channel as DmaChannel, InterruptFlags, Ready,
+    Transfer, TriggerAction, TriggerSource, transfer::BufferPair,
+};
+
+#[hal_cfg("dmac-d5x")]
+const TRIGGER_ACTION: TriggerAction = TriggerAction::Burst;
+
+#[hal_cfg(any("dmac-d11", "dmac-d21"))]
+const TRIGGER_ACTION: TriggerAction = TriggerAction::Beat;
+
+/// DMA trigger sources of a serializer
+pub trait DmaTriggers: SerId {
+    /// Trigger issued when the serializer is ready to transmit a new word
+    const TX_TRIGGER: TriggerSource;
+    /// Trigger issued when the serializer received a new word
+    const RX_TRIGGER: TriggerSource;
+}
+
+#[hal_cfg("i2s-d21")]
+impl DmaTriggers for Ser0 {
+    const TX_TRIGGER: TriggerSource = TriggerSource::I2sTx0;
+    const RX_TRIGGER: TriggerSource = TriggerSource::I2sRx0;
+}
+
+#[hal_cfg("i2s-d21")]
+impl DmaTriggers for Ser1 {
+    const TX_TRIGGER: TriggerSource = TriggerSource::I2sTx1;
+    const RX_TRIGGER: TriggerSource = TriggerSource::I2sRx1;
+}
+
+#[hal_cfg("i2s-d5x")]
+impl DmaTriggers for SerTx {
+    const TX_TRIGGER: TriggerSource = TriggerSource::I2sTx0;
+    const RX_TRIGGER: TriggerSource = TriggerSource::I2sRx0;
+}
+
+#[hal_cfg("i2s-d5x")]
+impl DmaTriggers for SerRx {
+    const TX_TRIGGER: TriggerSource = TriggerSource::I2sTx0;
+    const RX_TRIGGER: TriggerSource = TriggerSource::I2sRx0;
+}
+
+unsafe impl<Id: TxSerId, P> Buffer for Transmitter<Id, P> {
+    type Beat = u32;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        Id::data_ptr(regs())
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+unsafe impl<Id: RxSerId, P> Buffer for Receiver<Id, P> {
+    type Beat = u32;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        Id::data_ptr(regs())
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+/// Pointer to a serializer data register, used as a DMA [`Buffer`] by the
+/// async API without giving up ownership of the serializer.
+#[cfg(feature = "async")]
+struct DataPtr(*mut u32);
+
+#[cfg(feature = "async")]
+unsafe impl Buffer for DataPtr {
+    type Beat = u32;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.0
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+//==============================================================================
+// Transmitter
+//==============================================================================
+
+impl<Id: TxSerId + DmaTriggers, P> Transmitter<Id, P> {
+    /// Transform a [`Transmitter`] into a DMA [`Transfer`] and start sending
+    /// the provided buffer.
+    ///
+    /// Set `circular` to loop over the buffer indefinitely.
+    #[inline]
+    pub fn send_with_dma<Ch, B>(
+        self,
+        buf: B,
+        mut channel: Ch,
+        circular: bool,
+    ) -> Transfer<DmaChannel<Ch::Id, Busy>, BufferPair<B, Self>>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = u32> + 'static,
+    {
+        channel
+            .as_mut()
+            .enable_interrupts(InterruptFlags::new().with_tcmpl(true));
+
+        // SAFETY: This is safe because of the `'static` bound check for `B`,
+        // and the fact that the buffer length of a `Transmitter` is always 1.
+        let xfer = unsafe { dmac::Transfer::new_unchecked(channel, buf, self, circular) };
+        xfer.begin(Id::TX_TRIGGER, TRIGGER_ACTION)
+    }
+
+    /// Start a continuous, double-buffered DMA transmission.
+    ///
+    /// `first` is sent immediately, while `second` becomes the spare buffer.
+    /// Returns [`dmac::Error::LengthMismatch`] if both buffers don't have the
+    /// same length.
+    pub fn into_stream<Ch, B>(
+        self,
+        first: B,
+        second: B,
+        channel: Ch,
+    ) -> Result<TxStream<Id, P, Ch::Id, B>, dmac::Error>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = u32> + 'static,
+    {
+        if first.buffer_len() != second.buffer_len() {
+            return Err(dmac::Error::LengthMismatch);
+        }
+        Ok(TxStream {
+            xfer: self.send_with_dma(first, channel, false),
+            spare: Some(second),
+        })
+    }
+}
+
+/// Continuous, double-buffered I2S transmission
+///
+/// Fill the spare buffer through [`spare_mut`](Self::spare_mut), then call
+/// [`swap`](Self::swap) to queue it once the current buffer has been sent.
+/// The previously sent buffer then becomes the spare buffer.
+///
+/// The transmitter keeps repeating its last word if a buffer is not queued in
+/// time. This can be detected with [`Transmitter::check_underrun`] after
+/// stopping the stream.
+pub struct TxStream<Id: TxSerId, P, Ch: DmaChId, B: Buffer<Beat = u32>> {
+    xfer: Transfer<DmaChannel<Ch, Busy>, BufferPair<B, Transmitter<Id, P>>>,
+    // Always `Some`, except while being swapped
+    spare: Option<B>,
+}
+
+impl<Id, P, Ch, B> TxStream<Id, P, Ch, B>
+where
+    Id: TxSerId,
+    Ch: DmaChId,
+    B: Buffer<Beat = u32> + 'static,
+{
+    /// Borrow the spare buffer, which is not being sent
+    #[inline]
+    pub fn spare_mut(&mut self) -> &mut B {
+        self.spare.as_mut().unwrap()
+    }
+
+    /// Start sending the spare buffer if the current buffer has been sent.
+    ///
+    /// Returns [`nb::Error::WouldBlock`] while the current buffer is still
+    /// being sent.
+    pub fn swap(&mut self) -> nb::Result<(), Infallible> {
+        if !self.xfer.complete() {
+            return Err(nb::Error::WouldBlock);
+        }
+        // Recycling can't fail: the transfer is complete, and both buffers have
+        // the same length.
+        let spare = self.spare.take().unwrap();
+        let sent = self
+            .xfer
+            .recycle_destination(spare)
+            .unwrap_or_else(|_| unreachable!());
+        self.spare = Some(sent);
+        Ok(())
+    }
+
+    /// Stop the stream, and return the transmitter, DMA channel and buffers.
+    pub fn stop(self) -> (Transmitter<Id, P>, DmaChannel<Ch, Ready>, B, B) {
+        let (chan, buf, tx) = self.xfer.stop();
+        (tx, chan, buf, self.spare.unwrap())
+    }
+}
+
+//==============================================================================
+// Receiver
+//==============================================================================
+
+impl<Id: RxSerId + DmaTriggers, P> Receiver<Id, P> {
+    /// Transform a [`Receiver`] into a DMA [`Transfer`] and start receiving
+    /// into the provided buffer.
+    ///
+    /// Set `circular` to loop over the buffer indefinitely.
+    #[inline]
+    pub fn receive_with_dma<Ch, B>(
+        self,
+        buf: B,
+        mut channel: Ch,
+        circular: bool,
+    ) -> Transfer<DmaChannel<Ch::Id, Busy>, BufferPair<Self, B>>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = u32> + 'static,
+    {
+        channel
+            .as_mut()
+            .enable_interrupts(InterruptFlags::new().with_tcmpl(true));
+
+        // SAFETY: This is safe because of the `'static` bound check for `B`,
+        // and the fact that the buffer length of a `Receiver` is always 1.
+        let xfer = unsafe { dmac::Transfer::new_unchecked(channel, self, buf, circular) };
+        xfer.begin(Id::RX_TRIGGER, TRIGGER_ACTION)
+    }
+
+    /// Start a continuous, double-buffered DMA reception.
+    ///
+    /// `first` is filled immediately, while `second` becomes the spare buffer.
+    /// Returns [`dmac::Error::LengthMismatch`] if both buffers don't have the
+    /// same length.
+    pub fn into_stream<Ch, B>(
+        self,
+        first: B,
+        second: B,
+        channel: Ch,
+    ) -> Result<RxStream<Id, P, Ch::Id, B>, dmac::Error>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = u32> + 'static,
+    {
+        if first.buffer_len() != second.buffer_len() {
+            return Err(dmac::Error::LengthMismatch);
+        }
+        Ok(RxStream {
+            xfer: self.receive_with_dma(first, channel, false),
+            spare: Some(second),
+        })
+    }
+}
+
+/// Continuous, double-buffered I2S reception
+///
+/// Call [`swap`](Self::swap) once the current buffer is full to start filling
+/// the spare buffer. The received data is then available through
+/// [`spare_mut`](Self::spare_mut) until the next swap.
+///
+/// Samples are lost if the spare buffer is not swapped in time. This sets the
+/// overrun flag, which is reported by [`Receiver::read`] after stopping the
+/// stream.
+pub struct RxStream<Id: RxSerId, P, Ch: DmaChId, B: Buffer<Beat = u32>> {
+    xfer: Transfer<DmaChannel<Ch, Busy>, BufferPair<Receiver<Id, P>, B>>,
+    // Always `Some`, except while being swapped
+    spare: Option<B>,
+}
+
+impl<Id, P, Ch, B> RxStream<Id, P, Ch, B>
+where
+    Id: RxSerId,
+    Ch: DmaChId,
+    B: Buffer<Beat = u32> + 'static,
+{
+    /// Borrow the spare buffer, which holds the last received data
+    #[inline]
+    pub fn spare_mut(&mut self) -> &mut B {
+        self.spare.as_mut().unwrap()
+    }
+
+    /// Start filling the spare buffer if the current buffer is full.
+    ///
+    /// Returns [`nb::Error::WouldBlock`] while the current buffer is still
+    /// being filled.
+    pub fn swap(&mut self) -> nb::Result<(), Infallible> {
+        if !self.xfer.complete() {
+            return Err(nb::Error::WouldBlock);
+        }
+        // Recycling can't fail: the transfer is complete, and both buffers have
+        // the same length.
+        let spare = self.spare.take().unwrap();
+        let received = self
+            .xfer
+            .recycle_source(spare)
+            .unwrap_or_else(|_| unreachable!());
+        self.spare = Some(received);
+        Ok(())
+    }
+
+    /// Stop the stream, and return the receiver, DMA channel and buffers.
+    pub fn stop(self) -> (Receiver<Id, P>, DmaChannel<Ch, Ready>, B, B) {
+        let (chan, rx, buf) = self.xfer.stop();
+        (rx, chan, buf, self.spare.unwrap())
+    }
+}
+
+//==============================================================================
+// Async
+//==============================================================================
+
+#[cfg(feature = "async")]
+mod async_api {
+    use core::future::{Future, poll_fn};
+    use core::pin::pin;
+    use core::task::Poll;
+
+    use super::*;
+    use crate::dmac::ReadyFuture;
+
+    /// Run `xfer`, and call `work` once the transfer has started. Returns the
+    /// value returned by `work` once the transfer is complete.
+    async fn overlap(
+        xfer: impl Future<Output = Result<(), dmac::Error>>,
+        work: impl FnOnce() -> bool,
+    ) -> Result<bool, dmac::Error> {
+        let mut xfer = pin!(xfer);
+        // Poll once to start the transfer, then work while the DMAC is busy.
+        let started = poll_fn(|cx| Poll::Ready(xfer.as_mut().poll(cx))).await;
+        let more = work();
+        match started {
+            Poll::Ready(result) => result?,
+            Poll::Pending => xfer.await?,
+        }
+        Ok(more)
+    }
+
+    /// Transfer `buf` and `spare` in turn, calling `work` with the buffer
+    /// which is not being transferred. Returns once `work` returns `false`,
+    /// after sending the buffer `work` last filled when transmitting.
+    async fn ping_pong<Ch: DmaChId>(
+        channel: &mut DmaChannel<Ch, ReadyFuture>,
+        reg: *mut u32,
+        trigger: TriggerSource,
+        tx: bool,
+        mut buf: &mut [u32],
+        mut spare: &mut [u32],
+        mut work: impl FnMut(&mut [u32]) -> bool,
+    ) -> Result<(), dmac::Error> {
+        if buf.len() != spare.len() {
+            return Err(dmac::Error::LengthMismatch);
+        }
+
+        loop {
+            let more = if tx {
+                let xfer =
+                    channel.transfer_future(&mut *buf, DataPtr(reg), trigger, TRIGGER_ACTION);
+                overlap(xfer, || work(spare)).await?
+            } else {
+                let xfer =
+                    channel.transfer_future(DataPtr(reg), &mut *buf, trigger, TRIGGER_ACTION);
+                overlap(xfer, || work(spare)).await?
+            };
+
+            if !more {
+                if tx {
+                    channel
+                        .transfer_future(&mut *spare, DataPtr(reg), trigger, TRIGGER_ACTION)
+                        .await?;
+                }
+                return Ok(());
+            }
+            core::mem::swap(&mut buf, &mut spare);
+        }
+    }
+
+    impl<Id: TxSerId + DmaTriggers, P> Transmitter<Id, P> {
+        /// Send a buffer using DMA.
+        pub async fn send_dma<Ch: DmaChId>(
+            &mut self,
+            channel: &mut DmaChannel<Ch, ReadyFuture>,
+            buf: &[u32],
+        ) -> Result<(), dmac::Error> {
+            // SAFETY: The DMAC only reads from the source buffer, and the
+            // future returned by `transfer_future` stops the transfer when
+            // dropped.
+            let buf =
+                unsafe { core::slice::from_raw_parts_mut(buf.as_ptr() as *mut u32, buf.len()) };
+            let reg = DataPtr(Id::data_ptr(regs()));
+            channel
+                .transfer_future(buf, reg, Id::TX_TRIGGER, TRIGGER_ACTION)
+                .await
+        }
+
+        /// Continuously send two buffers in turn using DMA.
+        ///
+        /// `fill` is called with the buffer that is not being sent, and should
+        /// fill it with the next samples. The stream stops once `fill` returns
+        /// `false`, after the buffer it just filled has been sent. `first`
+        /// must be filled before calling this method.
+        ///
+        /// Both buffers must have the same length.
+        ///
+        /// **Note**: Each DMA transfer is restarted from the async executor, so
+        /// the transmitter's holding register must absorb the delay between
+        /// the end of a buffer and the start of the next one. At typical audio
+        /// rates this leaves one sample period of latency budget.
+        pub async fn stream_future<Ch: DmaChId>(
+            &mut self,
+            channel: &mut DmaChannel<Ch, ReadyFuture>,
+            first: &mut [u32],
+            second: &mut [u32],
+            fill: impl FnMut(&mut [u32]) -> bool,
+        ) -> Result<(), dmac::Error> {
+            let reg = Id::data_ptr(regs());
+            ping_pong(channel, reg, Id::TX_TRIGGER, true, first, second, fill).await
+        }
+    }
+
+    impl<Id: RxSerId + DmaTriggers, P> Receiver<Id, P> {
+        /// Receive into a buffer using DMA.
+        pub async fn receive_dma<Ch: DmaChId>(
+            &mut self,
+            channel: &mut DmaChannel<Ch, ReadyFuture>,
+            buf: &mut [u32],
+        ) -> Result<(), dmac::Error> {
+            let reg = DataPtr(Id::data_ptr(regs()));
+            channel
+                .transfer_future(reg, buf, Id::RX_TRIGGER, TRIGGER_ACTION)
+                .await
+        }
+
+        /// Continuously receive into two buffers in turn using DMA.
+        ///
+        /// `process` is called with the buffer that was last filled, while the
+        /// other one is being filled. The stream stops once `process` returns
+        /// `false`, after the current buffer has been filled. The first call to
+        /// `process` happens while `first` is being filled, and receives
+        /// `second`, which should be ignored.
+        ///
+        /// Both buffers must have the same length.
+        ///
+        /// **Note**: Each DMA transfer is restarted from the async executor, so
+        /// the receiver's holding register must absorb the delay between the
+        /// end of a buffer and the start of the next one. Words lost in
+        /// between are reported by the overrun flag.
+        pub async fn stream_future<Ch: DmaChId>(
+            &mut self,
+            channel: &mut DmaChannel<Ch, ReadyFuture>,
+            first: &mut [u32],
+            second: &mut [u32],
+            process: impl FnMut(&mut [u32]) -> bool,
+        ) -> Result<(), dmac::Error> {
+            let reg = Id::data_ptr(regs());
+            ping_pong(channel, reg, Id::RX_TRIGGER, false, first, second, process).await
+        }
+    }
+}
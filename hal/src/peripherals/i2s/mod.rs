@@ -0,0 +1,1058 @@
+//! # Inter-IC Sound
+//!
+//! This module provides an interface to the Inter-IC Sound (I2S) controller
+//! found on SAMD21E/G/J and SAMD5x/SAME5x chips (except SAMD51G).
+//!
+//! The I2S peripheral is made of two parts:
+//!
+//! * Two clock units, each generating (master mode) or receiving (slave
+//!   mode) a serial clock `SCKn` and a frame sync `FSn`. A clock unit is
+//!   represented by a [`ClockUnit`], and configured with a [`ClockConfig`].
+//! * Two serializers, which transmit or receive the serial data. A serializer
+//!   becomes a [`Transmitter`] or a [`Receiver`] once it is given ownership of
+//!   its data pin. On SAMD21, both serializers can either transmit or
+//!   receive. On SAMx5x, [`SerTx`] only transmits and [`SerRx`] only receives.
+//!
+//! All pins are typed: trying to use a pin which is not connected to the
+//! requested clock unit or serializer is a compile-time error.
+//!
+//! ```no_run
+//! let (i2s, mut clock_units, serializers) = I2s::new(&mut peripherals.pm, peripherals.i2s);
+//!
+//! let gclk = clocks.i2s0(&gclk0).unwrap();
+//! // 48 kHz stereo, 32-bit slots
+//! let config = ClockConfig::master(48.kHz().convert(), gclk.freq(), SlotSize::_32, 2).unwrap();
+//! let mut clk0 = clock_units.0.with_sck(pins.pa10).with_fs(pins.pa11);
+//! clk0.enable(config);
+//!
+//! let mut tx = serializers.0.into_transmitter(pins.pa07, SerializerConfig::default());
+//! nb::block!(tx.write(0x1234_5678)).unwrap();
+//! ```
+//!
+//! ## Slot formats
+//!
+//! Each frame is made of [`ClockConfig::num_slots`] slots of
+//! [`ClockConfig::slot_size`] bits. Standard I2S stereo uses two slots, while
+//! TDM uses up to eight. [`SerializerConfig::slots`] selects which slots are
+//! transferred, and [`SerializerConfig::mono`] duplicates (transmit) or drops
+//! (receive) the right channel.
+//!
+//! ## PDM microphones
+//!
+//! [`Serializer::into_pdm_receiver`] configures a receiver for one or two PDM
+//! microphones sharing the data line, sampled on each edge of the serial
+//! clock. The clock unit must be in master mode, and the microphone clock
+//! taken from the `SCKn` pin. The received words contain the raw PDM bit
+//! stream; decimation is left to the application.
+//!
+//! ## DMA
+//!
+//! With the `dma` feature enabled, a [`Transmitter`] or [`Receiver`] can
+//! stream samples through the DMAC. See [`TxStream`] and [`RxStream`] for
+//! continuous, double-buffered transfers. With the `async` feature,
+//! [`Transmitter::stream_future`] and [`Receiver::stream_future`] provide the
+//! same ping-pong scheme on top of [`dmac::async_api`](crate::dmac::async_api).
+
+use core::marker::PhantomData;
+use core::num::NonZeroU8;
+
+use atsamd_hal_macros::{hal_cfg, hal_macro_helper};
+
+use crate::gpio::{AnyPin, Pin};
+use crate::pac;
+use crate::time::Hertz;
+use crate::typelevel::{NoneT, Sealed};
+
+#[hal_cfg("i2s-d5x")]
+use crate::clock::v2::{apb::ApbClk, types::I2S as I2sId};
+
+pub use crate::pac::i2s::clkctrl::{
+    Bitdelayselect as Format, Fswidthselect as FrameSyncWidth, Slotsizeselect as SlotSize,
+};
+
+#[hal_cfg("i2s-d21")]
+pub use crate::pac::i2s::serctrl::{Datasizeselect as DataSize, Txdefaultselect as TxDefault};
+
+#[hal_cfg("i2s-d5x")]
+pub use crate::pac::i2s::txctrl::{Datasizeselect as DataSize, Txdefaultselect as TxDefault};
+
+mod pin;
+pub use pin::*;
+
+#[cfg(feature = "dma")]
+mod dma;
+#[cfg(feature = "dma")]
+pub use dma::*;
+
+/// Maximum value of the master clock divider
+#[hal_cfg("i2s-d21")]
+const MAX_DIVIDER: u32 = 32;
+
+/// Maximum value of the master clock divider
+#[hal_cfg("i2s-d5x")]
+const MAX_DIVIDER: u32 = 64;
+
+/// Errors that may occur when configuring or using the I2S peripheral
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The requested serial clock can't be derived from the GCLK frequency
+    InvalidClockDivider,
+    /// The number of slots must be between 1 and 8
+    InvalidSlotCount,
+    /// Received data was lost because it was not read in time
+    Overrun,
+    /// The transmitter ran out of data to send
+    Underrun,
+}
+
+bitflags::bitflags! {
+    /// I2S interrupt flags
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Flags: u16 {
+        /// Serializer 0 receive ready
+        const RXRDY0 = 1 << 0;
+        /// Serializer 1 receive ready
+        const RXRDY1 = 1 << 1;
+        /// Serializer 0 receive overrun
+        const RXOR0 = 1 << 4;
+        /// Serializer 1 receive overrun
+        const RXOR1 = 1 << 5;
+        /// Serializer 0 transmit ready
+        const TXRDY0 = 1 << 8;
+        /// Serializer 1 transmit ready
+        const TXRDY1 = 1 << 9;
+        /// Serializer 0 transmit underrun
+        const TXUR0 = 1 << 12;
+        /// Serializer 1 transmit underrun
+        const TXUR1 = 1 << 13;
+    }
+}
+
+//==============================================================================
+// ClockConfig
+//==============================================================================
+
+/// Clock source of a [`ClockUnit`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ClockSource {
+    /// The serial clock is generated by dividing `GCLK_I2S_n` by `divider`,
+    /// and the frame sync is generated from the serial clock.
+    Master {
+        /// Serial clock divider, up to 32 (SAMD21) or 64 (SAMx5x)
+        divider: NonZeroU8,
+    },
+    /// The serial clock and frame sync are received on the `SCKn` and `FSn`
+    /// pins.
+    Slave,
+}
+
+/// Configuration of a [`ClockUnit`]
+#[derive(Clone, Copy, Debug)]
+pub struct ClockConfig {
+    /// Clock source
+    pub source: ClockSource,
+    /// Size of each slot
+    pub slot_size: SlotSize,
+    /// Number of slots in a frame, between 1 and 8
+    pub num_slots: u8,
+    /// Width of the frame sync pulse
+    pub frame_sync_width: FrameSyncWidth,
+    /// Data delay relative to the frame sync: I2S (1 bit) or left-justified
+    pub format: Format,
+    /// Invert the frame sync signal
+    pub invert_frame_sync: bool,
+    /// Divider of the `MCKn` output, between 1 and 32 (SAMD21) or 64
+    /// (SAMx5x). Only used if an `MCKn` pin is assigned to the clock unit.
+    pub mck_output_divider: u8,
+}
+
+impl ClockConfig {
+    /// Create a master mode configuration.
+    ///
+    /// The serial clock divider is computed so that frames are generated at
+    /// `sample_rate`, from a `GCLK_I2S_n` running at `gclk_freq`. Returns
+    /// [`Error::InvalidClockDivider`] if `gclk_freq` is not an exact multiple
+    /// of the serial clock frequency within the divider range.
+    pub fn master(
+        sample_rate: Hertz,
+        gclk_freq: Hertz,
+        slot_size: SlotSize,
+        num_slots: u8,
+    ) -> Result<Self, Error> {
+        if !(1..=8).contains(&num_slots) {
+            return Err(Error::InvalidSlotCount);
+        }
+        let sck = sample_rate.to_Hz() * num_slots as u32 * slot_bits(slot_size);
+        if sck == 0 || gclk_freq.to_Hz() % sck != 0 {
+            return Err(Error::InvalidClockDivider);
+        }
+        let divider = gclk_freq.to_Hz() / sck;
+        if !(1..=MAX_DIVIDER).contains(&divider) {
+            return Err(Error::InvalidClockDivider);
+        }
+
+        Ok(Self {
+            source: ClockSource::Master {
+                // The range check above ensures the divider is non-zero
+                divider: NonZeroU8::new(divider as u8).unwrap(),
+            },
+            ..Self::slave(slot_size, num_slots)?
+        })
+    }
+
+    /// Create a slave mode configuration.
+    pub fn slave(slot_size: SlotSize, num_slots: u8) -> Result<Self, Error> {
+        if !(1..=8).contains(&num_slots) {
+            return Err(Error::InvalidSlotCount);
+        }
+        Ok(Self {
+            source: ClockSource::Slave,
+            slot_size,
+            num_slots,
+            frame_sync_width: FrameSyncWidth::Half,
+            format: Format::I2s,
+            invert_frame_sync: false,
+            mck_output_divider: 1,
+        })
+    }
+
+    /// Set the frame sync width
+    #[inline]
+    pub fn frame_sync_width(mut self, width: FrameSyncWidth) -> Self {
+        self.frame_sync_width = width;
+        self
+    }
+
+    /// Set the data format
+    #[inline]
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Invert the frame sync signal
+    #[inline]
+    pub fn invert_frame_sync(mut self, invert: bool) -> Self {
+        self.invert_frame_sync = invert;
+        self
+    }
+
+    /// Set the divider of the `MCKn` output
+    #[inline]
+    pub fn mck_output_divider(mut self, divider: u8) -> Self {
+        self.mck_output_divider = divider;
+        self
+    }
+}
+
+#[inline]
+fn slot_bits(slot_size: SlotSize) -> u32 {
+    match slot_size {
+        SlotSize::_8 => 8,
+        SlotSize::_16 => 16,
+        SlotSize::_24 => 24,
+        SlotSize::_32 => 32,
+    }
+}
+
+//==============================================================================
+// SerializerConfig
+//==============================================================================
+
+/// Configuration of a [`Transmitter`] or [`Receiver`]
+#[derive(Clone, Copy, Debug)]
+pub struct SerializerConfig {
+    /// Number of significant bits in each data word
+    pub data_size: DataSize,
+    /// Clock unit used by the serializer. Ignored for the SAMx5x transmitter,
+    /// which always uses clock unit 0.
+    pub clock_unit: usize,
+    /// Bitmask of the slots to transfer. Bit `n` enables slot `n`.
+    pub slots: u8,
+    /// Mono mode: the left channel is duplicated on transmit, and the right
+    /// channel is ignored on receive
+    pub mono: bool,
+    /// Left-adjust data within each slot
+    pub slot_adjust_left: bool,
+    /// Left-adjust data within each word
+    pub word_adjust_left: bool,
+    /// Transfer the LSB first
+    pub lsb_first: bool,
+    /// Line state when a slot is disabled, or on underrun (transmit only)
+    pub tx_default: TxDefault,
+}
+
+impl Default for SerializerConfig {
+    fn default() -> Self {
+        Self {
+            data_size: DataSize::_32,
+            clock_unit: 0,
+            slots: 0xFF,
+            mono: false,
+            slot_adjust_left: true,
+            word_adjust_left: false,
+            lsb_first: false,
+            tx_default: TxDefault::Zero,
+        }
+    }
+}
+
+impl SerializerConfig {
+    /// Set the data size
+    #[inline]
+    pub fn data_size(mut self, data_size: DataSize) -> Self {
+        self.data_size = data_size;
+        self
+    }
+
+    /// Set the clock unit used by the serializer
+    #[inline]
+    pub fn clock_unit(mut self, clock_unit: usize) -> Self {
+        self.clock_unit = clock_unit;
+        self
+    }
+
+    /// Set the bitmask of the slots to transfer
+    #[inline]
+    pub fn slots(mut self, slots: u8) -> Self {
+        self.slots = slots;
+        self
+    }
+
+    /// Enable or disable mono mode
+    #[inline]
+    pub fn mono(mut self, mono: bool) -> Self {
+        self.mono = mono;
+        self
+    }
+}
+
+//==============================================================================
+// IDs
+//==============================================================================
+
+/// Trait representing a clock unit ID.
+pub trait ClkId: Sealed {
+    /// Index of the clock unit
+    const ID: usize;
+}
+
+/// Type-level variant of [`ClkId`] for clock unit 0
+pub enum Clk0 {}
+impl Sealed for Clk0 {}
+impl ClkId for Clk0 {
+    const ID: usize = 0;
+}
+
+/// Type-level variant of [`ClkId`] for clock unit 1
+pub enum Clk1 {}
+impl Sealed for Clk1 {}
+impl ClkId for Clk1 {
+    const ID: usize = 1;
+}
+
+mod mode {
+    /// Serializer operating mode
+    ///
+    /// It is public in a private module, so that it can appear in the hidden
+    /// methods of [`SerId`](super::SerId) without being nameable outside of
+    /// the crate.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Mode {
+        Rx,
+        Tx,
+        Pdm2,
+    }
+}
+
+use mode::Mode;
+
+/// Trait representing a serializer ID.
+pub trait SerId: Sealed {
+    /// Index of the serializer's `RXRDY`/`TXRDY` flags and DMA triggers
+    const ID: usize;
+
+    #[doc(hidden)]
+    fn set_enabled(i2s: &pac::i2s::RegisterBlock, enabled: bool);
+
+    #[doc(hidden)]
+    fn write_ctrl(i2s: &pac::i2s::RegisterBlock, config: &SerializerConfig, mode: Mode);
+
+    #[doc(hidden)]
+    fn data_ptr(i2s: &pac::i2s::RegisterBlock) -> *mut u32;
+
+    #[doc(hidden)]
+    fn wait_data_sync(i2s: &pac::i2s::RegisterBlock);
+}
+
+/// Trait representing a serializer able to transmit data
+pub trait TxSerId: SerId {}
+
+/// Trait representing a serializer able to receive data
+pub trait RxSerId: SerId {}
+
+/// Type-level variant of [`SerId`] for serializer 0
+#[hal_cfg("i2s-d21")]
+pub enum Ser0 {}
+
+/// Type-level variant of [`SerId`] for serializer 1
+#[hal_cfg("i2s-d21")]
+pub enum Ser1 {}
+
+#[hal_cfg("i2s-d21")]
+macro_rules! d21_serializer {
+    ($Ser:ident, $id:literal, $seren:ident, $data:ident) => {
+        impl Sealed for $Ser {}
+
+        impl SerId for $Ser {
+            const ID: usize = $id;
+
+            #[inline]
+            fn set_enabled(i2s: &pac::i2s::RegisterBlock, enabled: bool) {
+                critical_section::with(|_| i2s.ctrla().modify(|_, w| w.$seren().bit(enabled)));
+                while i2s.syncbusy().read().$seren().bit_is_set() {
+                    core::hint::spin_loop();
+                }
+            }
+
+            fn write_ctrl(i2s: &pac::i2s::RegisterBlock, config: &SerializerConfig, mode: Mode) {
+                use pac::i2s::serctrl::Sermodeselect;
+                let mode = match mode {
+                    Mode::Rx => Sermodeselect::Rx,
+                    Mode::Tx => Sermodeselect::Tx,
+                    Mode::Pdm2 => Sermodeselect::Pdm2,
+                };
+                i2s.serctrl($id).write(|w| {
+                    w.sermode().variant(mode);
+                    w.clksel().bit(config.clock_unit == 1);
+                    w.txdefault().variant(config.tx_default);
+                    w.datasize().variant(config.data_size);
+                    w.slotadj().bit(config.slot_adjust_left);
+                    w.wordadj().bit(config.word_adjust_left);
+                    w.bitrev().bit(config.lsb_first);
+                    w.mono().bit(config.mono);
+                    w.slotdis0().bit(config.slots & 0x01 == 0);
+                    w.slotdis1().bit(config.slots & 0x02 == 0);
+                    w.slotdis2().bit(config.slots & 0x04 == 0);
+                    w.slotdis3().bit(config.slots & 0x08 == 0);
+                    w.slotdis4().bit(config.slots & 0x10 == 0);
+                    w.slotdis5().bit(config.slots & 0x20 == 0);
+                    w.slotdis6().bit(config.slots & 0x40 == 0);
+                    w.slotdis7().bit(config.slots & 0x80 == 0)
+                });
+            }
+
+            #[inline]
+            fn data_ptr(i2s: &pac::i2s::RegisterBlock) -> *mut u32 {
+                i2s.data($id).as_ptr()
+            }
+
+            #[inline]
+            fn wait_data_sync(i2s: &pac::i2s::RegisterBlock) {
+                while i2s.syncbusy().read().$data().bit_is_set() {
+                    core::hint::spin_loop();
+                }
+            }
+        }
+
+        impl TxSerId for $Ser {}
+        impl RxSerId for $Ser {}
+    };
+}
+
+#[hal_cfg("i2s-d21")]
+d21_serializer!(Ser0, 0, seren0, data0);
+#[hal_cfg("i2s-d21")]
+d21_serializer!(Ser1, 1, seren1, data1);
+
+/// Type-level variant of [`SerId`] for the transmit serializer
+#[hal_cfg("i2s-d5x")]
+pub enum SerTx {}
+
+#[hal_cfg("i2s-d5x")]
+impl Sealed for SerTx {}
+
+#[hal_cfg("i2s-d5x")]
+impl SerId for SerTx {
+    const ID: usize = 0;
+
+    #[inline]
+    fn set_enabled(i2s: &pac::i2s::RegisterBlock, enabled: bool) {
+        critical_section::with(|_| i2s.ctrla().modify(|_, w| w.txen().bit(enabled)));
+        while i2s.syncbusy().read().txen().bit_is_set() {
+            core::hint::spin_loop();
+        }
+    }
+
+    fn write_ctrl(i2s: &pac::i2s::RegisterBlock, config: &SerializerConfig, _mode: Mode) {
+        i2s.txctrl().write(|w| {
+            w.txdefault().variant(config.tx_default);
+            w.datasize().variant(config.data_size);
+            w.slotadj().bit(config.slot_adjust_left);
+            w.wordadj().bit(config.word_adjust_left);
+            w.bitrev().bit(config.lsb_first);
+            w.mono().bit(config.mono);
+            w.slotdis0().bit(config.slots & 0x01 == 0);
+            w.slotdis1().bit(config.slots & 0x02 == 0);
+            w.slotdis2().bit(config.slots & 0x04 == 0);
+            w.slotdis3().bit(config.slots & 0x08 == 0);
+            w.slotdis4().bit(config.slots & 0x10 == 0);
+            w.slotdis5().bit(config.slots & 0x20 == 0);
+            w.slotdis6().bit(config.slots & 0x40 == 0);
+            w.slotdis7().bit(config.slots & 0x80 == 0)
+        });
+    }
+
+    #[inline]
+    fn data_ptr(i2s: &pac::i2s::RegisterBlock) -> *mut u32 {
+        i2s.txdata().as_ptr()
+    }
+
+    #[inline]
+    fn wait_data_sync(i2s: &pac::i2s::RegisterBlock) {
+        while i2s.syncbusy().read().txdata().bit_is_set() {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+#[hal_cfg("i2s-d5x")]
+impl TxSerId for SerTx {}
+
+/// Type-level variant of [`SerId`] for the receive serializer
+#[hal_cfg("i2s-d5x")]
+pub enum SerRx {}
+
+#[hal_cfg("i2s-d5x")]
+impl Sealed for SerRx {}
+
+#[hal_cfg("i2s-d5x")]
+impl SerId for SerRx {
+    const ID: usize = 0;
+
+    #[inline]
+    fn set_enabled(i2s: &pac::i2s::RegisterBlock, enabled: bool) {
+        critical_section::with(|_| i2s.ctrla().modify(|_, w| w.rxen().bit(enabled)));
+        while i2s.syncbusy().read().rxen().bit_is_set() {
+            core::hint::spin_loop();
+        }
+    }
+
+    fn write_ctrl(i2s: &pac::i2s::RegisterBlock, config: &SerializerConfig, mode: Mode) {
+        use pac::i2s::rxctrl::Sermodeselect;
+        let mode = match mode {
+            Mode::Pdm2 => Sermodeselect::Pdm2,
+            _ => Sermodeselect::Rx,
+        };
+        i2s.rxctrl().write(|w| {
+            w.sermode().variant(mode);
+            w.clksel().bit(config.clock_unit == 1);
+            // SAFETY: RXCTRL.DATASIZE has the same layout as TXCTRL.DATASIZE
+            unsafe { w.datasize().bits(config.data_size as u8) };
+            w.slotadj().bit(config.slot_adjust_left);
+            w.wordadj().bit(config.word_adjust_left);
+            w.bitrev().bit(config.lsb_first);
+            w.mono().bit(config.mono);
+            w.slotdis0().bit(config.slots & 0x01 == 0);
+            w.slotdis1().bit(config.slots & 0x02 == 0);
+            w.slotdis2().bit(config.slots & 0x04 == 0);
+            w.slotdis3().bit(config.slots & 0x08 == 0);
+            w.slotdis4().bit(config.slots & 0x10 == 0);
+            w.slotdis5().bit(config.slots & 0x20 == 0);
+            w.slotdis6().bit(config.slots & 0x40 == 0);
+            w.slotdis7().bit(config.slots & 0x80 == 0)
+        });
+    }
+
+    #[inline]
+    fn data_ptr(i2s: &pac::i2s::RegisterBlock) -> *mut u32 {
+        i2s.rxdata().as_ptr()
+    }
+
+    #[inline]
+    fn wait_data_sync(i2s: &pac::i2s::RegisterBlock) {
+        while i2s.syncbusy().read().rxdata().bit_is_set() {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+#[hal_cfg("i2s-d5x")]
+impl RxSerId for SerRx {}
+
+//==============================================================================
+// I2s
+//==============================================================================
+
+/// Inter-IC Sound controller
+#[hal_macro_helper]
+pub struct I2s {
+    i2s: pac::I2s,
+    #[hal_cfg("i2s-d5x")]
+    apbclk: ApbClk<I2sId>,
+}
+
+/// Handles to the individual clock units
+pub struct ClockUnits(pub ClockUnit<Clk0>, pub ClockUnit<Clk1>);
+
+/// Handles to the individual serializers
+#[hal_cfg("i2s-d21")]
+pub struct Serializers(pub Serializer<Ser0>, pub Serializer<Ser1>);
+
+/// Handles to the individual serializers
+#[hal_cfg("i2s-d5x")]
+pub struct Serializers(pub Serializer<SerTx>, pub Serializer<SerRx>);
+
+impl I2s {
+    /// Create and enable a new [`I2s`], and return handles to its clock units
+    /// and serializers.
+    ///
+    /// The clock units are clocked by `GCLK_I2S_0` and `GCLK_I2S_1`, which
+    /// must be configured separately.
+    #[hal_cfg("i2s-d21")]
+    pub fn new(pm: &mut pac::Pm, i2s: pac::I2s) -> (Self, ClockUnits, Serializers) {
+        pm.apbcmask().modify(|_, w| w.i2s_().set_bit());
+        Self::init(&i2s);
+        let serializers = Serializers(Serializer::new(), Serializer::new());
+        (
+            Self { i2s },
+            ClockUnits(ClockUnit::new(), ClockUnit::new()),
+            serializers,
+        )
+    }
+
+    /// Create and enable a new [`I2s`], and return handles to its clock units
+    /// and serializers.
+    ///
+    /// The clock units are clocked by `GCLK_I2S_0` and `GCLK_I2S_1`, which
+    /// must be enabled through the corresponding
+    /// [`Pclk`](crate::clock::v2::pclk::Pclk).
+    #[hal_cfg("i2s-d5x")]
+    pub fn new(i2s: pac::I2s, apbclk: ApbClk<I2sId>) -> (Self, ClockUnits, Serializers) {
+        Self::init(&i2s);
+        let serializers = Serializers(Serializer::new(), Serializer::new());
+        let clock_units = ClockUnits(ClockUnit::new(), ClockUnit::new());
+        (Self { i2s, apbclk }, clock_units, serializers)
+    }
+
+    fn init(i2s: &pac::I2s) {
+        i2s.ctrla().write(|w| w.swrst().set_bit());
+        while i2s.syncbusy().read().swrst().bit_is_set() {
+            core::hint::spin_loop();
+        }
+        i2s.ctrla().write(|w| w.enable().set_bit());
+        while i2s.syncbusy().read().enable().bit_is_set() {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.i2s.intflag().read().bits())
+    }
+
+    /// Clear the specified interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.i2s
+            .intflag()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable the specified interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.i2s
+            .intenset()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the specified interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.i2s
+            .intenclr()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Reset the I2S peripheral and return the underlying resources.
+    ///
+    /// **Note**: The [`ClockUnits`] and [`Serializers`] structs are consumed by
+    /// this method. Any pins must first be released by calling
+    /// [`ClockUnit::free`], [`Transmitter::free`] or [`Receiver::free`].
+    #[hal_cfg("i2s-d21")]
+    pub fn free(self, pm: &mut pac::Pm, _clocks: ClockUnits, _sers: Serializers) -> pac::I2s {
+        self.reset();
+        pm.apbcmask().modify(|_, w| w.i2s_().clear_bit());
+        self.i2s
+    }
+
+    /// Reset the I2S peripheral and return the underlying resources.
+    ///
+    /// **Note**: The [`ClockUnits`] and [`Serializers`] structs are consumed by
+    /// this method. Any pins must first be released by calling
+    /// [`ClockUnit::free`], [`Transmitter::free`] or [`Receiver::free`].
+    #[hal_cfg("i2s-d5x")]
+    pub fn free(self, _clocks: ClockUnits, _sers: Serializers) -> (pac::I2s, ApbClk<I2sId>) {
+        self.reset();
+        (self.i2s, self.apbclk)
+    }
+
+    fn reset(&self) {
+        self.i2s.ctrla().write(|w| w.swrst().set_bit());
+        while self.i2s.syncbusy().read().swrst().bit_is_set() {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// Borrow the I2S registers.
+///
+/// Clock units and serializers only ever access their own registers, or bits
+/// of `CTRLA` in a critical section.
+#[inline]
+fn regs() -> &'static pac::i2s::RegisterBlock {
+    unsafe { &*pac::I2s::ptr() }
+}
+
+/// Run the provided closure with the I2S disabled, then re-enable it if it was
+/// previously enabled.
+///
+/// `CLKCTRLn` and the serializer control registers are enable-protected.
+fn with_disable<R>(fun: impl FnOnce(&pac::i2s::RegisterBlock) -> R) -> R {
+    let i2s = regs();
+    critical_section::with(|_| {
+        let enabled = i2s.ctrla().read().enable().bit_is_set();
+        i2s.ctrla().modify(|_, w| w.enable().clear_bit());
+        while i2s.syncbusy().read().enable().bit_is_set() {
+            core::hint::spin_loop();
+        }
+
+        let ret = fun(i2s);
+
+        if enabled {
+            i2s.ctrla().modify(|_, w| w.enable().set_bit());
+            while i2s.syncbusy().read().enable().bit_is_set() {
+                core::hint::spin_loop();
+            }
+        }
+        ret
+    })
+}
+
+//==============================================================================
+// ClockUnit
+//==============================================================================
+
+/// I2S clock unit
+///
+/// `Sck`, `Fs` and `Mck` are the `SCKn`, `FSn` and `MCKn` pins assigned to the
+/// clock unit, or [`NoneT`] if unused.
+pub struct ClockUnit<Id: ClkId, Sck = NoneT, Fs = NoneT, Mck = NoneT> {
+    sck: Sck,
+    fs: Fs,
+    mck: Mck,
+    _id: PhantomData<Id>,
+}
+
+impl<Id: ClkId> ClockUnit<Id> {
+    fn new() -> Self {
+        Self {
+            sck: NoneT,
+            fs: NoneT,
+            mck: NoneT,
+            _id: PhantomData,
+        }
+    }
+}
+
+impl<Id: ClkId, Sck, Fs, Mck> ClockUnit<Id, Sck, Fs, Mck> {
+    #[inline]
+    fn set_enabled(&mut self, enabled: bool) {
+        let i2s = regs();
+        critical_section::with(|_| {
+            i2s.ctrla().modify(|r, w| unsafe {
+                // CKENn bits are located at bit 2 + n
+                let mask = 1 << (2 + Id::ID);
+                w.bits(if enabled {
+                    r.bits() | mask
+                } else {
+                    r.bits() & !mask
+                })
+            });
+        });
+        while i2s.syncbusy().read().bits() & (1 << (2 + Id::ID)) != 0 {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Configure and enable the clock unit.
+    ///
+    /// This briefly disables the I2S peripheral.
+    pub fn enable(&mut self, config: ClockConfig)
+    where
+        Mck: OptionalPin,
+    {
+        self.set_enabled(false);
+        with_disable(|i2s| {
+            i2s.clkctrl(Id::ID).write(|w| unsafe {
+                w.slotsize().variant(config.slot_size);
+                w.nbslots().bits(config.num_slots - 1);
+                w.fswidth().variant(config.frame_sync_width);
+                w.bitdelay().variant(config.format);
+                w.fsinv().bit(config.invert_frame_sync);
+                match config.source {
+                    ClockSource::Master { divider } => {
+                        w.mcksel().clear_bit();
+                        w.scksel().clear_bit();
+                        w.fssel().clear_bit();
+                        w.mckdiv().bits(divider.get() - 1);
+                    }
+                    ClockSource::Slave => {
+                        w.scksel().set_bit();
+                        w.fssel().set_bit();
+                    }
+                }
+                w.mcken().bit(Mck::IS_PIN);
+                w.mckoutdiv()
+                    .bits(config.mck_output_divider.saturating_sub(1))
+            });
+        });
+        self.set_enabled(true);
+    }
+
+    /// Disable the clock unit
+    #[inline]
+    pub fn disable(&mut self) {
+        self.set_enabled(false);
+    }
+
+    /// Disable the clock unit, and return its pins.
+    pub fn free(mut self) -> (ClockUnit<Id>, Sck, Fs, Mck) {
+        self.disable();
+        (ClockUnit::new(), self.sck, self.fs, self.mck)
+    }
+}
+
+impl<Id: ClkId, Fs, Mck> ClockUnit<Id, NoneT, Fs, Mck> {
+    /// Assign an `SCKn` pin to the clock unit
+    pub fn with_sck<I: SckPinId<Id>>(
+        self,
+        pin: impl AnyPin<Id = I>,
+    ) -> ClockUnit<Id, Pin<I, I2sMode>, Fs, Mck> {
+        ClockUnit {
+            sck: pin.into().into_mode(),
+            fs: self.fs,
+            mck: self.mck,
+            _id: PhantomData,
+        }
+    }
+}
+
+impl<Id: ClkId, Sck, Mck> ClockUnit<Id, Sck, NoneT, Mck> {
+    /// Assign an `FSn` pin to the clock unit
+    pub fn with_fs<I: FsPinId<Id>>(
+        self,
+        pin: impl AnyPin<Id = I>,
+    ) -> ClockUnit<Id, Sck, Pin<I, I2sMode>, Mck> {
+        ClockUnit {
+            sck: self.sck,
+            fs: pin.into().into_mode(),
+            mck: self.mck,
+            _id: PhantomData,
+        }
+    }
+}
+
+impl<Id: ClkId, Sck, Fs> ClockUnit<Id, Sck, Fs, NoneT> {
+    /// Assign an `MCKn` pin to the clock unit.
+    ///
+    /// The master clock is output on this pin, divided by
+    /// [`ClockConfig::mck_output_divider`], once the clock unit is enabled.
+    pub fn with_mck<I: MckPinId<Id>>(
+        self,
+        pin: impl AnyPin<Id = I>,
+    ) -> ClockUnit<Id, Sck, Fs, Pin<I, I2sMode>> {
+        ClockUnit {
+            sck: self.sck,
+            fs: self.fs,
+            mck: pin.into().into_mode(),
+            _id: PhantomData,
+        }
+    }
+}
+
+//==============================================================================
+// Serializer
+//==============================================================================
+
+/// I2S serializer
+///
+/// Use this struct to create a [`Transmitter`] or [`Receiver`].
+pub struct Serializer<Id: SerId> {
+    _id: PhantomData<Id>,
+}
+
+impl<Id: SerId> Serializer<Id> {
+    fn new() -> Self {
+        Self { _id: PhantomData }
+    }
+
+    fn configure(config: &SerializerConfig, mode: Mode) {
+        Id::set_enabled(regs(), false);
+        with_disable(|i2s| Id::write_ctrl(i2s, config, mode));
+        Id::set_enabled(regs(), true);
+    }
+}
+
+impl<Id: TxSerId> Serializer<Id> {
+    /// Assign a data pin to the serializer, and turn it into an enabled
+    /// [`Transmitter`].
+    ///
+    /// This briefly disables the I2S peripheral.
+    pub fn into_transmitter<I: SdPinId<Id>>(
+        self,
+        pin: impl AnyPin<Id = I>,
+        config: SerializerConfig,
+    ) -> Transmitter<Id, Pin<I, I2sMode>> {
+        Self::configure(&config, Mode::Tx);
+        Transmitter {
+            pin: pin.into().into_mode(),
+            _id: PhantomData,
+        }
+    }
+}
+
+impl<Id: RxSerId> Serializer<Id> {
+    /// Assign a data pin to the serializer, and turn it into an enabled
+    /// [`Receiver`].
+    ///
+    /// This briefly disables the I2S peripheral.
+    pub fn into_receiver<I: SdPinId<Id>>(
+        self,
+        pin: impl AnyPin<Id = I>,
+        config: SerializerConfig,
+    ) -> Receiver<Id, Pin<I, I2sMode>> {
+        Self::configure(&config, Mode::Rx);
+        Receiver {
+            pin: pin.into().into_mode(),
+            _id: PhantomData,
+        }
+    }
+
+    /// Assign a data pin to the serializer, and turn it into an enabled
+    /// [`Receiver`] for PDM microphones.
+    ///
+    /// Data is sampled on both edges of the serial clock, so that two
+    /// microphones can share the data line. In mono mode, only the
+    /// microphone sampled on the rising edge is used.
+    ///
+    /// This briefly disables the I2S peripheral.
+    pub fn into_pdm_receiver<I: SdPinId<Id>>(
+        self,
+        pin: impl AnyPin<Id = I>,
+        config: SerializerConfig,
+    ) -> Receiver<Id, Pin<I, I2sMode>> {
+        Self::configure(&config, Mode::Pdm2);
+        Receiver {
+            pin: pin.into().into_mode(),
+            _id: PhantomData,
+        }
+    }
+}
+
+//==============================================================================
+// Transmitter
+//==============================================================================
+
+/// An I2S serializer transmitting data
+pub struct Transmitter<Id: TxSerId, P> {
+    pin: P,
+    _id: PhantomData<Id>,
+}
+
+impl<Id: TxSerId, P> Transmitter<Id, P> {
+    const TXRDY: u16 = Flags::TXRDY0.bits() << Id::ID;
+    const TXUR: u16 = Flags::TXUR0.bits() << Id::ID;
+
+    /// Write a data word.
+    ///
+    /// Returns [`nb::Error::WouldBlock`] while the previous word has not been
+    /// sent yet.
+    #[inline]
+    pub fn write(&mut self, word: u32) -> nb::Result<(), Error> {
+        let i2s = regs();
+        if i2s.intflag().read().bits() & Self::TXRDY == 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+        unsafe { Id::data_ptr(i2s).write_volatile(word) };
+        Id::wait_data_sync(i2s);
+        Ok(())
+    }
+
+    /// Check and clear the underrun flag.
+    ///
+    /// Returns [`Error::Underrun`] if the transmitter ran out of data since the
+    /// last call.
+    #[inline]
+    pub fn check_underrun(&mut self) -> Result<(), Error> {
+        let i2s = regs();
+        if i2s.intflag().read().bits() & Self::TXUR != 0 {
+            i2s.intflag().write(|w| unsafe { w.bits(Self::TXUR) });
+            Err(Error::Underrun)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Disable the transmitter, and return its pin.
+    pub fn free(self) -> (Serializer<Id>, P) {
+        Id::set_enabled(regs(), false);
+        (Serializer::new(), self.pin)
+    }
+}
+
+//==============================================================================
+// Receiver
+//==============================================================================
+
+/// An I2S serializer receiving data
+pub struct Receiver<Id: RxSerId, P> {
+    pin: P,
+    _id: PhantomData<Id>,
+}
+
+impl<Id: RxSerId, P> Receiver<Id, P> {
+    const RXRDY: u16 = Flags::RXRDY0.bits() << Id::ID;
+    const RXOR: u16 = Flags::RXOR0.bits() << Id::ID;
+
+    /// Read a data word.
+    ///
+    /// Returns [`nb::Error::WouldBlock`] while no new word was received, and
+    /// [`Error::Overrun`] if a word was lost since the last read. In the
+    /// latter case, the overrun flag is cleared and the next word can be read
+    /// normally.
+    #[inline]
+    pub fn read(&mut self) -> nb::Result<u32, Error> {
+        let i2s = regs();
+        let flags = i2s.intflag().read().bits();
+        if flags & Self::RXOR != 0 {
+            i2s.intflag().write(|w| unsafe { w.bits(Self::RXOR) });
+            return Err(nb::Error::Other(Error::Overrun));
+        }
+        if flags & Self::RXRDY == 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+        Id::wait_data_sync(i2s);
+        Ok(unsafe { Id::data_ptr(i2s).read_volatile() })
+    }
+
+    /// Disable the receiver, and return its pin.
+    pub fn free(self) -> (Serializer<Id>, P) {
+        Id::set_enabled(regs(), false);
+        (Serializer::new(), self.pin)
+    }
+}
@@ -0,0 +1,107 @@
+//! I2S pin definitions
+
+use atsamd_hal_macros::hal_cfg;
+
+use super::{ClkId, SerId};
+use crate::gpio::{Pin, PinId};
+use crate::typelevel::NoneT;
+
+/// Pin mode of the I2S pins
+#[hal_cfg("i2s-d21")]
+pub type I2sMode = crate::gpio::AlternateG;
+
+/// Pin mode of the I2S pins
+#[hal_cfg("i2s-d5x")]
+pub type I2sMode = crate::gpio::AlternateJ;
+
+/// Type-level trait for [`PinId`]s which can be the serial clock `SCKn` of
+/// clock unit `C`
+pub trait SckPinId<C: ClkId>: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be the frame sync `FSn` of clock
+/// unit `C`
+pub trait FsPinId<C: ClkId>: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be the master clock `MCKn` of
+/// clock unit `C`
+pub trait MckPinId<C: ClkId>: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be the data line of serializer
+/// `S`
+pub trait SdPinId<S: SerId>: PinId {}
+
+/// Type-level trait implemented by [`NoneT`] and I2S [`Pin`]s, used to
+/// determine which optional outputs a [`ClockUnit`](super::ClockUnit) drives
+pub trait OptionalPin {
+    #[doc(hidden)]
+    const IS_PIN: bool;
+}
+
+impl OptionalPin for NoneT {
+    const IS_PIN: bool = false;
+}
+
+impl<I: PinId> OptionalPin for Pin<I, I2sMode> {
+    const IS_PIN: bool = true;
+}
+
+macro_rules! i2s_pins {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $PinId:ident: $Trait:ident<$Unit:ident>
+        ),+
+        $(,)?
+    ) => {
+        $(
+            $( #[$cfg] )?
+            impl $Trait<super::$Unit> for crate::gpio::$PinId {}
+        )+
+    };
+}
+
+#[hal_cfg("i2s-d21")]
+i2s_pins! {
+    #[hal_cfg("pa07")]
+    PA07: SdPinId<Ser0>,
+    #[hal_cfg("pa08")]
+    PA08: SdPinId<Ser1>,
+    #[hal_cfg("pa09")]
+    PA09: MckPinId<Clk0>,
+    #[hal_cfg("pa10")]
+    PA10: SckPinId<Clk0>,
+    #[hal_cfg("pa11")]
+    PA11: FsPinId<Clk0>,
+    #[hal_cfg("pa19")]
+    PA19: SdPinId<Ser0>,
+    #[hal_cfg("pa20")]
+    PA20: SckPinId<Clk0>,
+    #[hal_cfg("pa21")]
+    PA21: FsPinId<Clk0>,
+    #[hal_cfg("pb10")]
+    PB10: MckPinId<Clk1>,
+    #[hal_cfg("pb11")]
+    PB11: SckPinId<Clk1>,
+    #[hal_cfg("pb12")]
+    PB12: FsPinId<Clk1>,
+    #[hal_cfg("pb16")]
+    PB16: SdPinId<Ser1>,
+    #[hal_cfg("pb17")]
+    PB17: MckPinId<Clk0>,
+}
+
+#[hal_cfg("i2s-d5x")]
+i2s_pins! {
+    #[hal_cfg("pa20")]
+    PA20: FsPinId<Clk0>,
+    #[hal_cfg("pa21")]
+    PA21: SdPinId<SerTx>,
+    #[hal_cfg("pa22")]
+    PA22: SdPinId<SerRx>,
+    #[hal_cfg("pa23")]
+    PA23: FsPinId<Clk1>,
+    #[hal_cfg("pb16")]
+    PB16: SckPinId<Clk0>,
+    #[hal_cfg("pb17")]
+    PB17: MckPinId<Clk0>,
+}
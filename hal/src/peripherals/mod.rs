@@ -3,6 +3,9 @@ use atsamd_hal_macros::{hal_cfg, hal_module};
 #[cfg(feature = "device")]
 pub mod adc;
 
+#[hal_module("ac")]
+pub mod ac {}
+
 #[hal_module(
     any("nvmctrl-d11", "nvmctrl-d21") => "calibration/d11.rs",
     "nvmctrl-d5x" => "calibration/d5x.rs",
@@ -37,6 +40,60 @@ pub mod pwm {}
 )]
 pub mod clock {}
 
+#[hal_module(
+    any("dac-d11", "dac-d21") => "dac/d11.rs",
+    "dac-d5x" => "dac/d5x.rs",
+)]
+pub mod dac {}
+
+#[hal_module("evsys")]
+pub mod evsys {}
+
+#[hal_module("ccl")]
+pub mod ccl {}
+
+#[hal_module("i2s")]
+pub mod i2s {}
+
+#[hal_module("sdhc0")]
+pub mod sdhc {}
+
+#[hal_module("gmac")]
+pub mod gmac {}
+
+#[hal_module("pdec")]
+pub mod pdec {}
+
+#[hal_module("pcc")]
+pub mod pcc {}
+
+#[hal_module("freqm")]
+pub mod freqm {}
+
+#[hal_module("supc")]
+pub mod supc {}
+
+#[hal_module(
+    any("pm-d11", "pm-d21") => "pm/d11.rs",
+    "pm-d5x" => "pm/d5x.rs",
+)]
+pub mod pm {}
+
+#[hal_module("ramecc")]
+pub mod ramecc {}
+
+#[hal_module("cmcc")]
+pub mod cmcc {}
+
+#[hal_module(
+    any("pac-d11", "pac-d21") => "write_protect/d11.rs",
+    "pac-d5x" => "write_protect/d5x/mod.rs",
+)]
+pub mod write_protect {}
+
+#[hal_module(any("tcc0-d21", "tcc0-d5x"))]
+pub mod tcc {}
+
 #[hal_module("aes")]
 pub mod aes {}
 
@@ -55,7 +112,10 @@ pub mod trng {}
 #[hal_module("icm")]
 pub mod icm {}
 
-#[hal_module("nvmctrl-d5x")]
+#[hal_module(
+    any("nvmctrl-d11", "nvmctrl-d21") => "nvm/d11.rs",
+    "nvmctrl-d5x" => "nvm/d5x.rs",
+)]
 pub mod nvm {}
 
 #[cfg(feature = "can")]
@@ -0,0 +1,624 @@
+//! # Non-volatile Memory Controller
+//!
+//! This module allows users to interact with non-volatile memory controller.
+//!
+//! The flash memory is organized in rows of four pages. It is erased one row
+//! at a time, and written one page at a time through the page buffer. It is
+//! also split into 16 lock regions, which can be locked to prevent erasing and
+//! writing their rows.
+//!
+//! Some SAMD21 variants (B, C and L) have a separate Read-While-Write EEPROM
+//! section (RWWEE). It is erased and written like the main flash, but without
+//! stalling the code executed from the main flash, which makes it a good fit
+//! to store configuration. Its size is given by [`retrieve_rww_eeprom_size`],
+//! which is 0 on the other devices.
+//!
+//! Module features:
+//! - Erase & write over non-volatile memory in a device.
+//! - Erase & write over the RWWEE section
+//! - Lock & unlock regions
+//! - Read & modify the user row
+#![warn(missing_docs)]
+
+use crate::pac::Nvmctrl;
+use crate::pac::nvmctrl::ctrla::Cmdselect;
+use core::ops::Range;
+
+use bitfield::bitfield;
+
+/// Retrieve a total NVM size using HW registers
+#[inline]
+pub fn retrieve_flash_size() -> u32 {
+    // Safety: PARAM is a read-only register
+    let nvm_params = unsafe { &*Nvmctrl::ptr() }.param().read();
+    if !nvm_params.psz().is_64() {
+        unreachable!("NVM page size is always expected to be 64 bytes");
+    }
+    nvm_params.nvmp().bits() as u32 * PAGESIZE
+}
+
+/// Retrieve the RWWEE section size using HW registers. It is 0 on devices
+/// without RWWEE section.
+#[inline]
+pub fn retrieve_rww_eeprom_size() -> u32 {
+    // Safety: PARAM is a read-only register
+    let nvm_params = unsafe { &*Nvmctrl::ptr() }.param().read();
+    // PARAM.RWWEEP is not described by the PAC. It reads as 0 on devices
+    // without RWWEE section.
+    ((nvm_params.bits() >> 20) & 0xfff) * PAGESIZE
+}
+
+/// Size of a page in bytes
+pub const PAGESIZE: u32 = 64;
+
+/// Size of a row in bytes. A row is the smallest area that can be erased.
+pub const ROWSIZE: u32 = PAGESIZE * 4;
+
+/// Number of lock regions
+pub const REGIONS_COUNT: u32 = 16;
+
+/// Address of the RWWEE section
+pub const RWW_EEPROM_ADDR: u32 = 0x0040_0000;
+
+/// RWWEE Erase Row command, not described by the PAC
+const CMD_RWWEE_ER: u8 = 0x1a;
+
+/// RWWEE Write Page command, not described by the PAC
+const CMD_RWWEE_WP: u8 = 0x1c;
+
+/// Non-volatile memory controller
+pub struct Nvm {
+    /// PAC peripheral
+    nvm: Nvmctrl,
+}
+
+/// Errors generated by the NVM peripheral
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PeripheralError {
+    /// NVM error
+    NvmError,
+    /// Locked error
+    LockError,
+    /// Programming error
+    ProgrammingError,
+}
+
+/// Driver errors
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// Address range outside of flash
+    NonFlash,
+    /// The device doesn't have a RWWEE section
+    NoRwwEeprom,
+    /// Errors generated by hardware
+    Peripheral(PeripheralError),
+    /// An alignment requirement was not fulfilled
+    Alignment,
+}
+
+/// NVM result type
+pub type Result<T> = core::result::Result<T, Error>;
+
+impl Nvm {
+    /// Pointer to the user row region of the flash memory
+    pub const USER_ROW_ADDR: *const [u8; ROWSIZE as usize] = 0x0080_4000 as _;
+
+    /// Create a new NVM controller
+    ///
+    /// The pages are only written by explicit commands from then on.
+    #[inline]
+    pub fn new(nvm: Nvmctrl) -> Self {
+        nvm.ctrlb().modify(|_, w| w.manw().set_bit());
+        Self { nvm }
+    }
+
+    /// Raw access to the registers.
+    ///
+    /// # Safety
+    ///
+    /// The abstraction assumes that it has exclusive ownership of the
+    /// registers. Direct access can break such assumptions.
+    pub unsafe fn registers(&self) -> &Nvmctrl {
+        &self.nvm
+    }
+
+    /// Check if the security bit is set
+    #[inline]
+    pub fn is_security_bit_set(&self) -> bool {
+        self.nvm.status().read().sb().bit()
+    }
+
+    /// Enable security bit
+    ///
+    /// It locks the chip from external access for code security. Consult the
+    /// datasheet for more details.
+    ///
+    /// In order to disable it, chip erase command must be issued through the
+    /// debugger.
+    #[inline]
+    pub fn enable_security_bit(&mut self) -> Result<()> {
+        self.command_sync(Cmdselect::Ssb)
+    }
+
+    /// Get the lock state of the regions
+    ///
+    /// Each of the 16 bits is 0 if the corresponding region is locked, and 1
+    /// if it is unlocked. Less significant bits represent lower addresses.
+    #[inline]
+    pub fn region_lock_state(&self) -> u16 {
+        self.nvm.lock().read().lock().bits()
+    }
+
+    /// Enable/disable region lock
+    ///
+    /// Flash memory is split into 16 regions. The 16 bits of the `mask`
+    /// determine if each region should be locked (if its bit is 0) and prevent
+    /// writing and erasing rows, or unlocked (if its bit is 1) and allow
+    /// writing and erasing rows.
+    ///
+    /// Less significant bits represent lower addresses, more significant bits
+    /// represent higher addresses.
+    ///
+    /// The lock state is reset to the `nvm_locks` field of the user row on
+    /// each reset.
+    #[inline]
+    pub fn region_lock(&mut self, mask: u16) -> Result<()> {
+        let region_size = retrieve_flash_size() / REGIONS_COUNT;
+        for i in 0..REGIONS_COUNT {
+            self.set_address(i * region_size);
+            let protect = mask & (1 << i) == 0;
+            self.command_sync(if protect {
+                Cmdselect::Lr
+            } else {
+                Cmdselect::Ur
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Read the user row from the flash memory
+    #[inline]
+    pub fn read_user_row(&self) -> UserRow {
+        let mut user_row = RawUserRow([0_u8; ROWSIZE as usize]);
+        // Safety:
+        // - Nvm is a singleton because it is constructed using the PAC singleton
+        //   Nvmctrl.
+        // - You need Nvm or Nvmctrl to modify the memory, so the &self singleton is
+        //   enough to prevent concurrent modification.
+        // - Underlying [u8; ROWSIZE] has no reserved bit patterns.
+        // - The pointer is aligned.
+        user_row
+            .0
+            .iter_mut()
+            .zip((0..ROWSIZE as isize).map(|i| unsafe {
+                Self::USER_ROW_ADDR
+                    .cast::<u8>()
+                    .wrapping_offset(i)
+                    .read_volatile()
+            }))
+            .for_each(|(l, r)| *l = r);
+        user_row
+    }
+
+    /// Modify the NVM User Row
+    ///
+    /// User is expected to provide a closure that modifies the user row
+    /// according to the user's needs.
+    ///
+    /// This method will read the current user row, call the closure on it,
+    /// *erase the row in the flash memory* and *write it* back again.
+    ///
+    /// Erasure and flashing is skipped if the user row stays the same after
+    /// calling the closure on it.
+    ///
+    /// # Safety
+    ///
+    /// The reserved fields of the user row hold factory settings, which can be
+    /// mutated via raw access to the `user_row.0` field.
+    ///
+    /// Power loss between the erase and the write will result in *data loss*,
+    /// including the factory settings.
+    ///
+    /// If these settings are erased, device might stop behaving correctly!
+    #[inline]
+    pub unsafe fn modify_user_row(
+        &mut self,
+        f: impl FnOnce(&mut UserRow),
+    ) -> Result<UserRowStatus> {
+        let original = self.read_user_row();
+        let mut modified = original.clone();
+
+        f(&mut modified);
+
+        if original != modified {
+            // The page buffer is written one word at a time, from an aligned
+            // copy
+            let mut words = [0_u32; ROWSIZE as usize / 4];
+            for (word, bytes) in words.iter_mut().zip(modified.0.chunks_exact(4)) {
+                *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            }
+
+            let address = Self::USER_ROW_ADDR as u32;
+            unsafe {
+                self.erase(Section::UserRow, address, 1)?;
+                self.write(Section::UserRow, address, words.as_ptr(), ROWSIZE / 4)?;
+            }
+
+            Ok(UserRowStatus::Updated)
+        } else {
+            Ok(UserRowStatus::Skipped)
+        }
+    }
+
+    /// Write to the main address space flash memory from a slice
+    ///
+    /// This call will fail if area that is being written to is
+    /// - outside of the main address space flash area
+    /// - locked, or protected by the BOOTPROT or EEPROM fuses
+    ///
+    /// `destination` has to be 4 bytes aligned.
+    ///
+    /// # Safety
+    ///
+    /// Writes to the main address space flash area containing currently
+    /// executed application are unsound.
+    #[inline]
+    pub unsafe fn write_flash_from_slice(
+        &mut self,
+        destination: *mut u32,
+        source_slice: &[u32],
+    ) -> Result<()> {
+        // Safety: prerequisites bubbled up to the method signature
+        unsafe {
+            self.write(
+                Section::Flash,
+                destination as u32,
+                source_slice.as_ptr(),
+                source_slice.len() as u32,
+            )
+        }
+    }
+
+    /// Write to the main address space flash memory
+    ///
+    /// This call will fail if area that is being written to is
+    /// - outside of the main address space flash area
+    /// - locked, or protected by the BOOTPROT or EEPROM fuses
+    ///
+    /// `destination` has to be 4 bytes aligned.
+    /// `source` has to be 4 bytes aligned.
+    ///
+    /// The pages must have been erased beforehand, since a write can only
+    /// clear bits.
+    ///
+    /// # Safety
+    ///
+    /// Writes to the main address space flash area containing currently
+    /// executed application are unsound.
+    #[inline]
+    pub unsafe fn write_flash(
+        &mut self,
+        destination: *mut u32,
+        source: *const u32,
+        words: u32,
+    ) -> Result<()> {
+        // Safety: prerequisites bubbled up to the method signature
+        unsafe { self.write(Section::Flash, destination as u32, source, words) }
+    }
+
+    /// Erase the portion of the main address space flash memory
+    ///
+    /// Erase granularity is expressed in rows (4 pages == 256 bytes)
+    ///
+    /// This call will fail if area that is being erased is
+    /// - outside of the main address space flash area
+    /// - locked, or protected by the BOOTPROT or EEPROM fuses
+    ///
+    /// # Safety
+    ///
+    /// Erasure of the main address space flash area containing currently
+    /// executed application is unsound.
+    #[inline]
+    pub unsafe fn erase_flash(&mut self, address: *mut u32, rows: u32) -> Result<()> {
+        // Safety: prerequisites bubbled up to the method signature
+        unsafe { self.erase(Section::Flash, address as u32, rows) }
+    }
+
+    /// Read from the RWWEE section into a slice
+    ///
+    /// `offset` is relative to the start of the RWWEE section, and has to be 4
+    /// bytes aligned.
+    #[inline]
+    pub fn read_rww_eeprom(&self, offset: u32, destination: &mut [u32]) -> Result<()> {
+        let address = RWW_EEPROM_ADDR + offset;
+        Section::RwwEeprom.check(address, destination.len() as u32 * 4)?;
+
+        for (i, word) in destination.iter_mut().enumerate() {
+            // Safety: the range has been checked to be inside the RWWEE section
+            *word = unsafe { core::ptr::read_volatile((address as *const u32).add(i)) };
+        }
+        Ok(())
+    }
+
+    /// Write to the RWWEE section from a slice
+    ///
+    /// `offset` is relative to the start of the RWWEE section, and has to be 4
+    /// bytes aligned. The pages must have been erased beforehand, since a
+    /// write can only clear bits.
+    ///
+    /// This call will fail if the device has no RWWEE section, or if the area
+    /// that is being written to is outside of it.
+    #[inline]
+    pub fn write_rww_eeprom(&mut self, offset: u32, source: &[u32]) -> Result<()> {
+        // Safety: no code is executed from the RWWEE section
+        unsafe {
+            self.write(
+                Section::RwwEeprom,
+                RWW_EEPROM_ADDR + offset,
+                source.as_ptr(),
+                source.len() as u32,
+            )
+        }
+    }
+
+    /// Erase a portion of the RWWEE section
+    ///
+    /// `offset` is relative to the start of the RWWEE section. Erase
+    /// granularity is expressed in rows (4 pages == 256 bytes).
+    ///
+    /// This call will fail if the device has no RWWEE section, or if the area
+    /// that is being erased is outside of it.
+    #[inline]
+    pub fn erase_rww_eeprom(&mut self, offset: u32, rows: u32) -> Result<()> {
+        // Safety: no code is executed from the RWWEE section
+        unsafe { self.erase(Section::RwwEeprom, RWW_EEPROM_ADDR + offset, rows) }
+    }
+
+    /// Write to a flash memory section through the page buffer
+    ///
+    /// # Safety
+    ///
+    /// Safety requirements regarding writes to the main address space flash
+    /// area are mentioned in [`Self::write_flash`] documentation
+    ///
+    /// Safety requirements regarding user row modifications are mentioned in
+    /// [`Self::modify_user_row`] documentation
+    unsafe fn write(
+        &mut self,
+        section: Section,
+        destination: u32,
+        source: *const u32,
+        words: u32,
+    ) -> Result<()> {
+        if source as u32 % 4 != 0 {
+            return Err(Error::Alignment);
+        }
+        section.check(destination, words * 4)?;
+
+        let end = destination + words * 4;
+        let mut address = destination;
+        let mut source = source;
+        while address < end {
+            let page = address - address % PAGESIZE;
+            let page_end = end.min(page + PAGESIZE);
+
+            // The cleared bytes of the page buffer are left untouched by the
+            // write
+            self.command_sync(Cmdselect::Pbc)?;
+            while address < page_end {
+                // Write to the page buffer, 32 bits, 1 word. Memory is not
+                // written until the write page command is issued.
+                unsafe {
+                    core::ptr::write_volatile(
+                        address as *mut u32,
+                        core::ptr::read_volatile(source),
+                    );
+                    source = source.add(1);
+                }
+                address += 4;
+            }
+
+            self.set_address(page);
+            self.execute(section.write_command())?;
+        }
+
+        Ok(())
+    }
+
+    /// Erase rows of a flash memory section
+    ///
+    /// # Safety
+    ///
+    /// Safety requirements regarding erasure of the main address space flash
+    /// area are mentioned in [`Self::erase_flash`] documentation
+    ///
+    /// Safety requirements regarding user row modifications are mentioned in
+    /// [`Self::modify_user_row`] documentation
+    unsafe fn erase(&mut self, section: Section, address: u32, rows: u32) -> Result<()> {
+        // Align to row boundary
+        let row = address - address % ROWSIZE;
+        section.check(row, rows * ROWSIZE)?;
+
+        for address in (row..row + rows * ROWSIZE).step_by(ROWSIZE as usize) {
+            // Set target address to current row
+            self.set_address(address);
+
+            // Erase row, wait for completion
+            self.execute(section.erase_command())?;
+        }
+
+        Ok(())
+    }
+
+    /// Set address for reading/writing
+    #[inline]
+    fn set_address(&mut self, address: u32) {
+        // ADDR holds the address of a 16-bit word
+        unsafe {
+            self.nvm
+                .addr()
+                .write(|w| w.addr().bits((address >> 1) & 0x003f_ffff));
+        }
+    }
+
+    /// Execute a command, wait until it is done and check error states
+    #[inline]
+    fn command_sync(&mut self, command: Cmdselect) -> Result<()> {
+        self.execute(command as u8)
+    }
+
+    /// Execute a raw command, wait until it is done and check error states
+    fn execute(&mut self, command: u8) -> Result<()> {
+        // Wait until INTFLAG.READY
+        while !self.nvm.intflag().read().ready().bit() {}
+
+        // Disable the cache while the command executes, so that it doesn't
+        // hold stale contents of the modified flash
+        let ctrlb = self.nvm.ctrlb().read().bits();
+        self.nvm.ctrlb().modify(|_, w| w.cachedis().set_bit());
+
+        self.nvm
+            .ctrla()
+            .write(|w| unsafe { w.cmdex().key().cmd().bits(command) });
+
+        // Wait until INTFLAG.READY
+        while !self.nvm.intflag().read().ready().bit() {}
+
+        self.nvm.ctrlb().write(|w| unsafe { w.bits(ctrlb) });
+
+        self.manage_error_states()
+    }
+
+    /// Read the peripheral state to check error flags and clear the up
+    /// afterwards
+    #[inline]
+    fn manage_error_states(&mut self) -> Result<()> {
+        let read_status = self.nvm.status().read();
+        // Check LOCKE first as it is more specific than PROGE
+        let state = if read_status.locke().bit_is_set() {
+            Err(Error::Peripheral(PeripheralError::LockError))
+        } else if read_status.proge().bit_is_set() {
+            Err(Error::Peripheral(PeripheralError::ProgrammingError))
+        } else if read_status.nvme().bit_is_set() {
+            Err(Error::Peripheral(PeripheralError::NvmError))
+        } else {
+            Ok(())
+        };
+
+        // Clear error flags
+        self.nvm
+            .status()
+            .write(|w| w.locke().set_bit().proge().set_bit().nvme().set_bit());
+        self.nvm.intflag().write(|w| w.error().set_bit());
+
+        state
+    }
+}
+
+/// The outcome of [`Nvm::modify_user_row`]
+#[derive(Copy, Clone, Debug)]
+pub enum UserRowStatus {
+    /// User row has been updated
+    Updated,
+    /// Update has been skipped; expected value is already present.
+    Skipped,
+}
+
+/// Flash memory sections, which are erased and written with their own
+/// commands
+#[derive(Copy, Clone, Debug)]
+enum Section {
+    /// Main address space
+    Flash,
+    /// Read-While-Write EEPROM section
+    RwwEeprom,
+    /// User row, in the auxiliary space
+    UserRow,
+}
+
+impl Section {
+    #[inline]
+    fn erase_command(&self) -> u8 {
+        match self {
+            Self::Flash => Cmdselect::Er as u8,
+            Self::RwwEeprom => CMD_RWWEE_ER,
+            Self::UserRow => Cmdselect::Ear as u8,
+        }
+    }
+
+    #[inline]
+    fn write_command(&self) -> u8 {
+        match self {
+            Self::Flash => Cmdselect::Wp as u8,
+            Self::RwwEeprom => CMD_RWWEE_WP,
+            Self::UserRow => Cmdselect::Wap as u8,
+        }
+    }
+
+    #[inline]
+    fn range(&self) -> Range<u32> {
+        match self {
+            Self::Flash => 0..retrieve_flash_size(),
+            Self::RwwEeprom => RWW_EEPROM_ADDR..(RWW_EEPROM_ADDR + retrieve_rww_eeprom_size()),
+            Self::UserRow => {
+                let start = Nvm::USER_ROW_ADDR as u32;
+                start..(start + ROWSIZE)
+            }
+        }
+    }
+
+    /// Check that `length` bytes starting at `address` are inside the section
+    #[inline]
+    fn check(&self, address: u32, length: u32) -> Result<()> {
+        let range = self.range();
+        if range.is_empty() && matches!(self, Self::RwwEeprom) {
+            Err(Error::NoRwwEeprom)
+        } else if address % 4 != 0 {
+            Err(Error::Alignment)
+        } else if address < range.start || address + length > range.end {
+            Err(Error::NonFlash)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Type alias to the user row with a concrete underlying storage type
+pub type UserRow = RawUserRow<[u8; ROWSIZE as usize]>;
+
+bitfield! {
+    /// Raw user row POD struct that exposes bitfields via methods
+    #[derive(Clone, PartialEq, Eq)]
+    pub struct RawUserRow([u8]);
+    impl Debug;
+    u8;
+    /// Access the `bootprot` field
+    pub bootprot, set_bootprot: 2, 0;
+    /// Access the `eeprom` field
+    pub eeprom, set_eeprom: 6, 4;
+    /// Access the `bod33_level` field
+    pub bod33_level, set_bod33_level: 13, 8;
+    /// Access the `bod33_enable` field
+    pub bod33_enable, set_bod33_enable: 14;
+    /// Access the `bod33_action` field
+    pub bod33_action, set_bod33_action: 16, 15;
+    /// Access the `wdt_enable` field
+    pub wdt_enable, set_wdt_enable: 25;
+    /// Access the `wdt_always_on` field
+    pub wdt_always_on, set_wdt_always_on: 26;
+    /// Access the `wdt_period` field
+    pub wdt_period, set_wdt_period: 30, 27;
+    /// Access the `wdt_window` field
+    pub wdt_window, set_wdt_window: 34, 31;
+    /// Access the `wdt_ewoffset` field
+    pub wdt_ewoffset, set_wdt_ewoffset: 38, 35;
+    /// Access the `wdt_wen` field
+    pub wdt_wen, set_wdt_wen: 39;
+    /// Access the `bod33_hysteresis` field
+    pub bod33_hysteresis, set_bod33_hysteresis: 40;
+    /// Access the `nvm_locks` field
+    pub u16, nvm_locks, set_nvm_locks: 63, 48;
+}
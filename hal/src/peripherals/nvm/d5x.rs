@@ -18,9 +18,14 @@
 //! Module features:
 //! - Erase & write over non-volatile memory in a device.
 //! - Swap banks
+//! - `embedded-storage` NOR flash traits, over a region of the flash memory
+//!   (More in [`storage`] module) and over the SmartEEPROM
+//! - A/B firmware updates with rollback (More in [`dual_bank`] module)
 #![warn(missing_docs)]
 
+pub mod dual_bank;
 pub mod smart_eeprom;
+pub mod storage;
 
 use crate::pac::Nvmctrl;
 pub use crate::pac::nvmctrl::ctrla::Prmselect;
@@ -115,7 +120,7 @@ pub enum Error {
 }
 
 /// Physical flash banks
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Copy, Clone)]
 pub enum PhysicalBank {
     /// Flash bank A
     A,
@@ -248,6 +253,14 @@ impl Nvm {
         // Clear INTFLAG.DONE
         self.nvm.intflag().write(|w| w.done().set_bit());
 
+        // The CMCC may hold stale contents of the modified flash
+        if matches!(
+            command,
+            Cmdselect::Ep | Cmdselect::Eb | Cmdselect::Wp | Cmdselect::Wqw
+        ) {
+            crate::cmcc::invalidate_all();
+        }
+
         self.manage_error_states()
     }
 
@@ -0,0 +1,534 @@
+//! # Dual-bank firmware updates
+//!
+//! [`DualBank`] builds A/B firmware updates with automatic rollback on top of
+//! the flash banks:
+//!
+//! 1. The new image is streamed into the inactive bank with an
+//!    [`ImageWriter`], which reads back everything it writes.
+//! 1. [`ImageWriter::finish`] checks the digest of the whole image with an
+//!    [`ImageVerifier`], such as the [`Dsu`] CRC32.
+//! 1. [`DualBank::activate`] marks the new image as pending, then swaps the
+//!    banks and resets the device. It fails unless the last update was
+//!    successfully verified.
+//! 1. On each boot, the application calls [`DualBank::boot`] as early as
+//!    possible. While the new image is on trial, each boot counts as an
+//!    attempt. Once the new image has used all its attempts without calling
+//!    [`DualBank::confirm`], the banks are swapped back to the previous image.
+//!
+//! A trial image that hangs is only rebooted by the watchdog, which should
+//! therefore be running until the image confirms itself.
+//!
+//! The update state is a small record, stored either in the SmartEEPROM or in
+//! the user page. The SmartEEPROM is preferred, since the user page is erased
+//! and rewritten on each boot of a trial image.
+//!
+//! ```no_run
+//! let mut nvm = Nvm::new(peripherals.nvmctrl);
+//! let mut dual_bank = DualBank::with_smart_eeprom(&mut nvm, 0);
+//!
+//! match dual_bank.boot()? {
+//!     BootStatus::Trial { .. } => {
+//!         if self_test() {
+//!             dual_bank.confirm()?;
+//!         }
+//!     }
+//!     BootStatus::RolledBack => report_failed_update(),
+//!     BootStatus::Confirmed => (),
+//! }
+//!
+//! // Later, when an update is received
+//! let mut writer = dual_bank.update(image_size)?;
+//! while let Some(chunk) = receive_chunk() {
+//!     writer.write(chunk)?;
+//! }
+//! writer.finish(&mut dsu, &expected_crc)?;
+//! dual_bank.activate(NonZeroU8::new(3).unwrap())?;
+//! ```
+
+use core::convert::Infallible;
+use core::num::NonZeroU8;
+
+use embedded_storage::nor_flash::{NorFlash, ReadNorFlash};
+
+use super::smart_eeprom::{SmartEeprom, SmartEepromMode, SmartEepromState, Unlocked};
+use super::storage::FlashStorage;
+use super::{BLOCKSIZE, Bank, Nvm, PhysicalBank, QUADWORDSIZE};
+use crate::dsu::Dsu;
+
+/// Errors of the dual-bank updates
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// NVM or verifier error
+    Nvm(super::Error),
+    /// The SmartEEPROM is disabled or misconfigured, see the
+    /// [`smart_eeprom`](super::smart_eeprom) module
+    SmartEepromUnavailable,
+    /// The update state record doesn't fit in the SmartEEPROM
+    StateOutOfBounds,
+    /// The running image must be confirmed before it can be updated
+    Unconfirmed,
+    /// The image doesn't fit in the inactive bank
+    ImageTooLarge,
+    /// The data read back from the flash doesn't match the written data
+    WriteVerification,
+    /// The digest of the image doesn't match the expected digest
+    DigestMismatch,
+    /// No image has been verified since the last call to
+    /// [`DualBank::update`]
+    Unverified,
+}
+
+impl From<super::Error> for Error {
+    fn from(error: super::Error) -> Self {
+        Error::Nvm(error)
+    }
+}
+
+/// Dual-bank result type
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Digest computation over an image stored in the flash memory
+///
+/// It is implemented for the [`Dsu`], which computes a CRC32.
+pub trait ImageVerifier {
+    /// Digest of an image
+    type Digest: PartialEq;
+
+    /// Compute the digest of the `length` bytes at `address`
+    fn digest(&mut self, address: u32, length: u32) -> super::Result<Self::Digest>;
+}
+
+impl ImageVerifier for Dsu {
+    type Digest = u32;
+
+    /// Compute the CRC32 of the image, as computed by [`Dsu::crc32`]. The
+    /// length of the image must be a multiple of 4 bytes.
+    #[inline]
+    fn digest(&mut self, address: u32, length: u32) -> super::Result<u32> {
+        self.crc32(address, length).map_err(super::Error::Dsu)
+    }
+}
+
+/// Outcome of [`DualBank::boot`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BootStatus {
+    /// The running image is confirmed
+    Confirmed,
+    /// The running image is on trial, and must call [`DualBank::confirm`]
+    /// before it has used all its attempts
+    Trial {
+        /// Number of boots of the image, including this one
+        attempt: u8,
+        /// Number of boots allowed before rolling back
+        max_attempts: u8,
+    },
+    /// The new image didn't confirm itself, and the running image has been
+    /// restored. It is confirmed again.
+    RolledBack,
+}
+
+/// Where the update state record is stored
+#[derive(Debug, Clone, Copy)]
+enum StateLocation {
+    /// Byte offset in the SmartEEPROM
+    SmartEeprom(usize),
+    /// Byte offset in the user-writable section of the user page
+    Userpage(usize),
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Status {
+    Confirmed = 0,
+    Pending = 1,
+    RolledBack = 2,
+}
+
+/// Update state record
+#[derive(Debug, Clone, Copy)]
+struct State {
+    status: Status,
+    /// Physical bank of the image on trial
+    trial_bank: PhysicalBank,
+    attempts: u8,
+    max_attempts: u8,
+}
+
+impl State {
+    /// Size of the record in bytes
+    const SIZE: usize = 8;
+
+    const MAGIC: [u8; 4] = *b"DBNK";
+
+    const CONFIRMED: Self = Self {
+        status: Status::Confirmed,
+        trial_bank: PhysicalBank::A,
+        attempts: 0,
+        max_attempts: 0,
+    };
+
+    /// Decode a record. A missing or invalid record, e.g. on a new device,
+    /// means that the running image is confirmed.
+    fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        let status = match bytes[4] {
+            0 => Status::Confirmed,
+            1 => Status::Pending,
+            2 => Status::RolledBack,
+            _ => return Self::CONFIRMED,
+        };
+        let trial_bank = match bytes[5] {
+            0 => PhysicalBank::A,
+            1 => PhysicalBank::B,
+            _ => return Self::CONFIRMED,
+        };
+        if bytes[..4] != Self::MAGIC {
+            return Self::CONFIRMED;
+        }
+
+        Self {
+            status,
+            trial_bank,
+            attempts: bytes[6],
+            max_attempts: bytes[7],
+        }
+    }
+
+    fn to_bytes(self) -> [u8; Self::SIZE] {
+        let [m0, m1, m2, m3] = Self::MAGIC;
+        let trial_bank = match self.trial_bank {
+            PhysicalBank::A => 0,
+            PhysicalBank::B => 1,
+        };
+        [
+            m0,
+            m1,
+            m2,
+            m3,
+            self.status as u8,
+            trial_bank,
+            self.attempts,
+            self.max_attempts,
+        ]
+    }
+}
+
+/// Dual-bank firmware update manager
+///
+/// See the [module-level documentation](self) for the update sequence.
+pub struct DualBank<'a> {
+    nvm: &'a mut Nvm,
+    location: StateLocation,
+    /// Whether the inactive bank holds an image verified by
+    /// [`ImageWriter::finish`] since the last call to [`DualBank::update`]
+    verified: bool,
+}
+
+impl<'a> DualBank<'a> {
+    /// Create a [`DualBank`] storing its state at `offset` bytes in the
+    /// SmartEEPROM
+    ///
+    /// The state takes 8 bytes, which the application must not use for
+    /// anything else.
+    #[inline]
+    pub fn with_smart_eeprom(nvm: &'a mut Nvm, offset: usize) -> Self {
+        Self {
+            nvm,
+            location: StateLocation::SmartEeprom(offset),
+            verified: false,
+        }
+    }
+
+    /// Create a [`DualBank`] storing its state at `offset` bytes in the
+    /// user-writable section of the user page
+    ///
+    /// The state takes 8 bytes, which the application must not use for
+    /// anything else.
+    ///
+    /// # Safety
+    ///
+    /// The user page is erased and rewritten on each state change. See
+    /// [`Nvm::modify_userpage`] for the consequences of a power loss.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state doesn't fit in the user-writable section.
+    #[inline]
+    pub unsafe fn with_userpage(nvm: &'a mut Nvm, offset: usize) -> Self {
+        let userpage = nvm.read_userpage();
+        assert!(
+            offset + State::SIZE <= userpage.userpage1_as_slice().len(),
+            "the state must fit in the user page"
+        );
+        Self {
+            nvm,
+            location: StateLocation::Userpage(offset),
+            verified: false,
+        }
+    }
+
+    /// Handle a pending update, rolling back if the new image has used all
+    /// its attempts. Must be called on each boot, as early as possible.
+    ///
+    /// On rollback, the banks are swapped and the device is reset, so this
+    /// method doesn't return.
+    pub fn boot(&mut self) -> Result<BootStatus> {
+        let mut state = self.load()?;
+        let on_trial_bank = self.nvm.first_bank() == state.trial_bank;
+
+        match state.status {
+            Status::Confirmed => Ok(BootStatus::Confirmed),
+            // The banks were not swapped, the update is abandoned
+            Status::Pending if !on_trial_bank => {
+                self.store(State::CONFIRMED)?;
+                Ok(BootStatus::Confirmed)
+            }
+            Status::Pending if state.attempts >= state.max_attempts => self.roll_back(state),
+            Status::Pending => {
+                state.attempts += 1;
+                self.store(state)?;
+                Ok(BootStatus::Trial {
+                    attempt: state.attempts,
+                    max_attempts: state.max_attempts,
+                })
+            }
+            // The rollback was interrupted before swapping the banks
+            Status::RolledBack if on_trial_bank => self.roll_back(state),
+            Status::RolledBack => {
+                self.store(State::CONFIRMED)?;
+                Ok(BootStatus::RolledBack)
+            }
+        }
+    }
+
+    /// Confirm the running image, which will not be rolled back anymore
+    pub fn confirm(&mut self) -> Result<()> {
+        if self.load()?.status == Status::Confirmed {
+            Ok(())
+        } else {
+            self.store(State::CONFIRMED)
+        }
+    }
+
+    /// Start an update, by erasing the part of the inactive bank which will
+    /// hold an image of `image_size` bytes.
+    ///
+    /// Fails if the running image is not confirmed, since the inactive bank
+    /// then holds the image to roll back to.
+    ///
+    /// Any image previously verified is discarded, so [`DualBank::activate`]
+    /// fails until the new image is verified.
+    pub fn update(&mut self, image_size: u32) -> Result<ImageWriter<'_>> {
+        if self.load()?.status != Status::Confirmed {
+            return Err(Error::Unconfirmed);
+        }
+
+        let start = Bank::Inactive.address();
+        let size = image_size.div_ceil(BLOCKSIZE) * BLOCKSIZE;
+        if size > Bank::Inactive.length() {
+            return Err(Error::ImageTooLarge);
+        }
+
+        self.verified = false;
+        // Safety: the code is executed from the active bank
+        let mut flash = unsafe { FlashStorage::new(self.nvm, start..start + size)? };
+        flash.erase(0, size)?;
+
+        Ok(ImageWriter {
+            flash,
+            verified: &mut self.verified,
+            written: 0,
+            buffer: [0xff; QUADWORDSIZE as usize],
+            buffered: 0,
+        })
+    }
+
+    /// Mark the image verified by [`ImageWriter::finish`] as pending, then swap
+    /// the banks and reset the device.
+    ///
+    /// The new image is allowed `max_attempts` boots to confirm itself. The
+    /// first boot after the swap is one of them, so the image can always
+    /// confirm itself at least once.
+    ///
+    /// Only returns on error. Returns [`Error::Unverified`] if no image has
+    /// been verified since the last call to [`DualBank::update`].
+    pub fn activate(&mut self, max_attempts: NonZeroU8) -> Result<Infallible> {
+        if !self.verified {
+            return Err(Error::Unverified);
+        }
+
+        let trial_bank = match self.nvm.first_bank() {
+            PhysicalBank::A => PhysicalBank::B,
+            PhysicalBank::B => PhysicalBank::A,
+        };
+        self.store(State {
+            status: Status::Pending,
+            trial_bank,
+            attempts: 0,
+            max_attempts: max_attempts.get(),
+        })?;
+
+        // Safety: the image in the inactive bank has been verified
+        unsafe { self.nvm.bank_swap() }
+    }
+
+    /// Swap back to the previous image
+    fn roll_back(&mut self, state: State) -> Result<BootStatus> {
+        self.store(State {
+            status: Status::RolledBack,
+            ..state
+        })?;
+
+        // Safety: the previous image was confirmed before the update
+        unsafe { self.nvm.bank_swap() }
+    }
+
+    fn load(&mut self) -> Result<State> {
+        let mut bytes = [0; State::SIZE];
+        match self.location {
+            StateLocation::SmartEeprom(offset) => match self.nvm.smart_eeprom() {
+                Ok(SmartEepromMode::Locked(see)) => read_smart_eeprom(&see, offset, &mut bytes)?,
+                Ok(SmartEepromMode::Unlocked(see)) => read_smart_eeprom(&see, offset, &mut bytes)?,
+                Err(_) => return Err(Error::SmartEepromUnavailable),
+            },
+            StateLocation::Userpage(offset) => {
+                let userpage = self.nvm.read_userpage();
+                bytes.copy_from_slice(&userpage.userpage1_as_slice()[offset..offset + State::SIZE]);
+            }
+        }
+        Ok(State::from_bytes(bytes))
+    }
+
+    fn store(&mut self, state: State) -> Result<()> {
+        let bytes = state.to_bytes();
+        match self.location {
+            StateLocation::SmartEeprom(offset) => {
+                match self.nvm.smart_eeprom() {
+                    // The SmartEEPROM is locked again after the write
+                    Ok(SmartEepromMode::Locked(see)) => {
+                        let mut see = see.unlock();
+                        let result = write_smart_eeprom(&mut see, offset, &bytes);
+                        see.lock();
+                        result?
+                    }
+                    Ok(SmartEepromMode::Unlocked(mut see)) => {
+                        write_smart_eeprom(&mut see, offset, &bytes)?
+                    }
+                    Err(_) => return Err(Error::SmartEepromUnavailable),
+                }
+            }
+            StateLocation::Userpage(offset) => {
+                // Safety: the risk of a power loss is accepted in `with_userpage`
+                unsafe {
+                    self.nvm.modify_userpage(|userpage| {
+                        userpage.userpage1_as_slice_mut()[offset..offset + State::SIZE]
+                            .copy_from_slice(&bytes)
+                    })?
+                };
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read the state record at `offset` bytes in the SmartEEPROM
+fn read_smart_eeprom<T: SmartEepromState>(
+    see: &SmartEeprom<'_, T>,
+    offset: usize,
+    bytes: &mut [u8; State::SIZE],
+) -> Result<()> {
+    if offset + State::SIZE > see.capacity() {
+        return Err(Error::StateOutOfBounds);
+    }
+    see.get(offset, bytes);
+    Ok(())
+}
+
+/// Write the state record at `offset` bytes in the SmartEEPROM
+fn write_smart_eeprom(
+    see: &mut SmartEeprom<'_, Unlocked>,
+    offset: usize,
+    bytes: &[u8; State::SIZE],
+) -> Result<()> {
+    if offset + State::SIZE > see.capacity() {
+        return Err(Error::StateOutOfBounds);
+    }
+    see.set(offset, bytes);
+    Ok(())
+}
+
+/// Writer streaming a new image into the inactive bank, returned by
+/// [`DualBank::update`]
+///
+/// The image can be written in chunks of any size. Each quad word is read back
+/// after being written.
+pub struct ImageWriter<'a> {
+    flash: FlashStorage<'a>,
+    /// Verification state of the [`DualBank`], set once the image is verified
+    verified: &'a mut bool,
+    /// Number of bytes written to the flash
+    written: u32,
+    buffer: [u8; QUADWORDSIZE as usize],
+    /// Number of bytes in `buffer`
+    buffered: usize,
+}
+
+impl ImageWriter<'_> {
+    /// Append `data` to the image
+    pub fn write(&mut self, mut data: &[u8]) -> Result<()> {
+        while !data.is_empty() {
+            let len = data.len().min(self.buffer.len() - self.buffered);
+            let (chunk, rest) = data.split_at(len);
+            self.buffer[self.buffered..self.buffered + len].copy_from_slice(chunk);
+            self.buffered += len;
+            data = rest;
+
+            if self.buffered == self.buffer.len() {
+                self.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the end of the image, and check that its digest matches
+    /// `expected`.
+    pub fn finish<V: ImageVerifier>(
+        mut self,
+        verifier: &mut V,
+        expected: &V::Digest,
+    ) -> Result<()> {
+        let length = self.written + self.buffered as u32;
+        if self.buffered > 0 {
+            self.flush()?;
+        }
+
+        let digest = verifier.digest(self.flash.region().start, length)?;
+        if digest == *expected {
+            *self.verified = true;
+            Ok(())
+        } else {
+            Err(Error::DigestMismatch)
+        }
+    }
+
+    /// Write the buffered quad word, padded with erased bytes, and read it
+    /// back
+    fn flush(&mut self) -> Result<()> {
+        if (self.written + QUADWORDSIZE) as usize > self.flash.capacity() {
+            return Err(Error::ImageTooLarge);
+        }
+
+        self.buffer[self.buffered..].fill(0xff);
+        self.flash.write(self.written, &self.buffer)?;
+
+        let mut read_back = [0; QUADWORDSIZE as usize];
+        self.flash.read(self.written, &mut read_back)?;
+        if read_back != self.buffer {
+            return Err(Error::WriteVerification);
+        }
+
+        self.written += QUADWORDSIZE;
+        self.buffered = 0;
+        Ok(())
+    }
+}
@@ -28,9 +28,18 @@
 //!
 //! To access [`SmartEeprom`] struct, call [`Nvm::smart_eeprom`] method to
 //! retrieve its instance.
+//!
+//! [`SmartEeprom`] implements the `embedded-storage` NOR flash traits. Unlike
+//! a real NOR flash, any byte can be rewritten at any time, and erasing only
+//! fills the erased area with `0xFF`.
 
 use core::marker::PhantomData;
 
+use embedded_storage::nor_flash::{
+    ErrorType, MultiwriteNorFlash, NorFlash, NorFlashErrorKind, ReadNorFlash, check_erase,
+    check_read, check_write,
+};
+
 use super::Nvm;
 use crate::pac::{Nvmctrl, nvmctrl::ctrlb::Cmdselect};
 use crate::typelevel::Sealed;
@@ -285,6 +294,50 @@ impl<'a> SmartEeprom<'a, Locked> {
     }
 }
 
+impl<T: SmartEepromState> ErrorType for SmartEeprom<'_, T> {
+    type Error = NorFlashErrorKind;
+}
+
+impl<T: SmartEepromState> ReadNorFlash for SmartEeprom<'_, T> {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> core::result::Result<(), Self::Error> {
+        check_read(self, offset, bytes.len())?;
+        self.get(offset as usize, bytes);
+        Ok(())
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.virtual_size
+    }
+}
+
+impl NorFlash for SmartEeprom<'_, Unlocked> {
+    const WRITE_SIZE: usize = 1;
+
+    /// The smallest possible SmartEEPROM size, so that the erase size always
+    /// divides the capacity
+    const ERASE_SIZE: usize = 512;
+
+    fn erase(&mut self, from: u32, to: u32) -> core::result::Result<(), Self::Error> {
+        check_erase(self, from, to)?;
+        let erased = [u32::MAX; 32];
+        for offset in (from..to).step_by(core::mem::size_of_val(&erased)) {
+            self.set(offset as usize / 4, &erased);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> core::result::Result<(), Self::Error> {
+        check_write(self, offset, bytes.len())?;
+        self.set(offset as usize, bytes);
+        Ok(())
+    }
+}
+
+impl MultiwriteNorFlash for SmartEeprom<'_, Unlocked> {}
+
 /// A type representing an immutable iterator over SmartEEPROM address space
 pub struct SmartEepromIter<'a, TP: SmartEepromPointableSize> {
     iter: core::slice::Iter<'a, TP>,
@@ -0,0 +1,168 @@
+//! # `embedded-storage` over the flash memory
+//!
+//! [`FlashStorage`] implements the [`ReadNorFlash`] and [`NorFlash`] traits
+//! over a region of the main address space flash memory, so that it can be
+//! used by crates such as `sequential-storage`, `littlefs2` or `ekv`.
+//!
+//! The region is erased one block ([`BLOCKSIZE`]) at a time, and written one
+//! quad word ([`QUADWORDSIZE`]) at a time. Both sizes are the same on all
+//! SAMD5x/SAME5x chips, whatever their flash size. A quad word can't be written twice
+//! without erasing its block, so
+//! [`MultiwriteNorFlash`](embedded_storage::nor_flash::MultiwriteNorFlash) is
+//! not implemented.
+//!
+//! ```no_run
+//! let mut nvm = Nvm::new(peripherals.nvmctrl);
+//! // Use the last 64 KiB of the inactive bank
+//! let end = Bank::Inactive.address() + Bank::Inactive.length();
+//! let mut storage = unsafe { FlashStorage::new(&mut nvm, end - 8 * BLOCKSIZE..end)? };
+//!
+//! storage.erase(0, BLOCKSIZE)?;
+//! storage.write(0, &[0x55; 16])?;
+//! ```
+
+use core::ops::Range;
+
+use embedded_storage::nor_flash::{
+    ErrorType, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash, check_erase, check_read,
+    check_write,
+};
+
+use super::{BLOCKSIZE, Error, Nvm, QUADWORDSIZE, Result, WriteGranularity};
+
+/// A region of the main address space flash memory, implementing the
+/// `embedded-storage` NOR flash traits
+///
+/// The offsets passed to the trait methods are relative to the start of the
+/// region.
+pub struct FlashStorage<'a> {
+    nvm: &'a mut Nvm,
+    region: Range<u32>,
+}
+
+impl<'a> FlashStorage<'a> {
+    /// Create a [`FlashStorage`] over the `region` of the main address space
+    /// flash memory
+    ///
+    /// This call will fail if `region`
+    /// - is not aligned on blocks
+    /// - is outside of the main address space flash area
+    /// - contains the first block, which holds the vector table
+    /// - overlaps with SmartEEPROM flash region
+    ///
+    /// Writes and erasures will also fail on the blocks protected by BOOTPROT.
+    ///
+    /// # Safety
+    ///
+    /// `region` must not contain the currently executed application.
+    pub unsafe fn new(nvm: &'a mut Nvm, region: Range<u32>) -> Result<Self> {
+        if region.start % BLOCKSIZE != 0 || region.end % BLOCKSIZE != 0 {
+            Err(Error::Alignment)
+        } else if nvm.contains_non_flash_memory_area(&region) {
+            Err(Error::NonFlash)
+        } else if region.start < BLOCKSIZE {
+            Err(Error::Protected)
+        } else if nvm.contains_smart_eeprom(&region) {
+            Err(Error::SmartEepromArea)
+        } else {
+            Ok(Self { nvm, region })
+        }
+    }
+
+    /// Returns the region of the flash memory
+    #[inline]
+    pub fn region(&self) -> Range<u32> {
+        self.region.clone()
+    }
+
+    /// Release the [`Nvm`]
+    #[inline]
+    pub fn free(self) -> &'a mut Nvm {
+        self.nvm
+    }
+}
+
+impl NorFlashError for Error {
+    fn kind(&self) -> NorFlashErrorKind {
+        match self {
+            Error::Alignment => NorFlashErrorKind::NotAligned,
+            Error::NonFlash => NorFlashErrorKind::OutOfBounds,
+            _ => NorFlashErrorKind::Other,
+        }
+    }
+}
+
+impl From<NorFlashErrorKind> for Error {
+    fn from(kind: NorFlashErrorKind) -> Self {
+        match kind {
+            NorFlashErrorKind::NotAligned => Error::Alignment,
+            _ => Error::NonFlash,
+        }
+    }
+}
+
+impl ErrorType for FlashStorage<'_> {
+    type Error = Error;
+}
+
+impl ReadNorFlash for FlashStorage<'_> {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<()> {
+        check_read(self, offset, bytes.len())?;
+
+        let address = (self.region.start + offset) as *const u8;
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            // Safety: the read is inside the region, which is inside the flash
+            *byte = unsafe { address.add(i).read_volatile() };
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.region.len()
+    }
+}
+
+impl NorFlash for FlashStorage<'_> {
+    const WRITE_SIZE: usize = QUADWORDSIZE as usize;
+
+    // `NorFlash` requires a constant erase size. The flash of all SAMD5x/SAME5x
+    // chips is made of 8 KiB blocks of 16 pages of 512 bytes, only the number
+    // of blocks differs, so unlike the bank size, the block size doesn't have
+    // to be read from the NVMCTRL.
+    const ERASE_SIZE: usize = BLOCKSIZE as usize;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<()> {
+        check_erase(self, from, to)?;
+
+        let address = (self.region.start + from) as *mut u32;
+        // Safety: the region doesn't contain the executed application
+        unsafe { self.nvm.erase_flash(address, (to - from) / BLOCKSIZE) }
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<()> {
+        check_write(self, offset, bytes.len())?;
+
+        let mut address = self.region.start + offset;
+        for quad_word in bytes.chunks_exact(QUADWORDSIZE as usize) {
+            // The NVM is written one word at a time, from an aligned copy
+            let mut words = [0_u32; QUADWORDSIZE as usize / 4];
+            for (word, bytes) in words.iter_mut().zip(quad_word.chunks_exact(4)) {
+                *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            }
+
+            // Safety: the region doesn't contain the executed application
+            unsafe {
+                self.nvm.write_flash_from_slice(
+                    address as *mut u32,
+                    &words,
+                    WriteGranularity::QuadWord,
+                )?;
+            }
+            address += QUADWORDSIZE;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+//! PCC DMA support
+//!
+//! A [`Pcc`] can be used as a non-incrementing DMA [`Buffer`], which yields
+//! one word of packed samples per beat. The DMAC is triggered each time a word
+//! is ready, so a frame buffer of the right size is filled line after line as
+//! the camera sends them.
+
+use super::*;
+use crate::dmac::{
+    self, AnyChannel, Buffer, Busy, Channel as DmaChannel, InterruptFlags, Ready, Transfer,
+    TriggerAction, TriggerSource, transfer::BufferPair,
+};
+
+unsafe impl<D: DataPins> Buffer for Pcc<D> {
+    type Beat = u32;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.regs().rhr().as_ptr()
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+impl<D: DataPins> Pcc<D> {
+    /// Enable the PCC, transform it into a DMA [`Transfer`] and start
+    /// capturing into the provided buffer.
+    ///
+    /// Set `circular` to loop over the buffer indefinitely. The PCC is still
+    /// enabled once the transfer is stopped, see [`Pcc::disable`].
+    #[inline]
+    pub fn capture_with_dma<Ch, B>(
+        mut self,
+        buf: B,
+        mut channel: Ch,
+        circular: bool,
+    ) -> Transfer<DmaChannel<Ch::Id, Busy>, BufferPair<Self, B>>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = u32> + 'static,
+    {
+        channel
+            .as_mut()
+            .enable_interrupts(InterruptFlags::new().with_tcmpl(true));
+        self.enable();
+
+        // SAFETY: This is safe because of the `'static` bound check for `B`,
+        // and the fact that the buffer length of a `Pcc` is always 1.
+        let xfer = unsafe { dmac::Transfer::new_unchecked(channel, self, buf, circular) };
+        xfer.begin(TriggerSource::PccRx, TriggerAction::Burst)
+    }
+}
+
+#[cfg(feature = "async")]
+mod async_api {
+    use super::*;
+    use crate::dmac::{ChId as DmaChId, ReadyFuture};
+
+    /// Pointer to the PCC data register, used as a DMA [`Buffer`] by the async
+    /// API without giving up ownership of the [`Pcc`].
+    struct DataPtr(*mut u32);
+
+    unsafe impl Buffer for DataPtr {
+        type Beat = u32;
+
+        #[inline]
+        fn dma_ptr(&mut self) -> *mut Self::Beat {
+            self.0
+        }
+
+        #[inline]
+        fn incrementing(&self) -> bool {
+            false
+        }
+
+        #[inline]
+        fn buffer_len(&self) -> usize {
+            1
+        }
+    }
+
+    impl<D: DataPins> Pcc<D> {
+        /// Capture one frame into `frame` using DMA.
+        ///
+        /// The PCC is enabled for the duration of the capture, which ends once
+        /// `frame` is full. Capture starts with the first qualified sample, so
+        /// this method must be called while the camera is between two frames
+        /// (VSYNC inactive) for the frame to be aligned with the buffer.
+        ///
+        /// Returns [`Error::Overrun`] if the DMAC could not keep up with the
+        /// camera.
+        pub async fn capture_frame<Ch: DmaChId>(
+            &mut self,
+            channel: &mut DmaChannel<Ch, ReadyFuture>,
+            frame: &mut [u32],
+        ) -> Result<(), Error> {
+            let reg = DataPtr(self.regs().rhr().as_ptr());
+            self.enable();
+            let result = channel
+                .transfer_future(reg, frame, TriggerSource::PccRx, TriggerAction::Burst)
+                .await;
+            self.disable();
+            result?;
+
+            if self.read_flags().contains(Flags::OVRE) {
+                Err(Error::Overrun)
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! # Parallel Capture Controller
+//!
+//! This module provides a driver for the Parallel Capture Controller (PCC)
+//! found on SAMD5x/SAME5x chips. The PCC samples an 8 to 14-bit parallel bus
+//! on the rising edges of an external clock, which makes it suitable for
+//! reading frames from camera sensors such as the OV7670.
+//!
+//! ```no_run
+//! let apb_pcc = buses.apb.enable(tokens.apbs.pcc);
+//! let data = Data8::new(
+//!     pins.pa16, pins.pa17, pins.pa18, pins.pa19, pins.pa20, pins.pa21, pins.pa22, pins.pa23,
+//! );
+//! let pins = Pins::new(pins.pa14, pins.pa12, pins.pa13, data);
+//! let mut pcc = Pcc::new(peripherals.pcc, apb_pcc, pins, Config::new());
+//!
+//! pcc.enable();
+//! let word = nb::block!(pcc.read()).unwrap();
+//! ```
+//!
+//! ## Data size
+//!
+//! The width of the data bus is set by the [`DataPins`] given to [`Pins`]:
+//! [`Data8`], [`Data10`], [`Data12`] or [`Data14`]. Samples are packed into
+//! 32-bit words, [`DataPins::SAMPLES_PER_WORD`] at a time, the first sample
+//! in the least significant bits.
+//!
+//! ## Synchronization
+//!
+//! The PCC has two data enable inputs, `DEN1` and `DEN2`, which are usually
+//! connected to the VSYNC and HSYNC (or HREF) outputs of a camera. By default,
+//! data is only sampled while both inputs are high, so the camera must be
+//! configured with active-high sync signals. [`Config::qualification`] can
+//! also ignore the enable inputs, and [`Config::clear_on`] discards partially
+//! packed words at the end of a line or frame, so that every line or frame
+//! starts on a word boundary.
+//!
+//! ## DMA
+//!
+//! With the `dma` feature enabled, a [`Pcc`] can be used as a DMA source
+//! through [`Pcc::capture_with_dma`]. With the `async` feature,
+//! [`Pcc::capture_frame`] captures a whole frame with
+//! [`dmac::async_api`](crate::dmac::async_api).
+
+use crate::clock::v2::{apb::ApbClk, types::Pcc as PccId};
+use crate::pac;
+use crate::pac::pcc::RegisterBlock;
+
+mod pin;
+pub use pin::*;
+
+#[cfg(feature = "dma")]
+mod dma;
+
+/// Errors that may occur when capturing data with the PCC
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// Captured data was lost because it was not read in time
+    Overrun,
+    /// The DMA transfer failed
+    #[cfg(feature = "dma")]
+    Dma(crate::dmac::Error),
+}
+
+#[cfg(feature = "dma")]
+impl From<crate::dmac::Error> for Error {
+    fn from(value: crate::dmac::Error) -> Self {
+        Error::Dma(value)
+    }
+}
+
+bitflags::bitflags! {
+    /// PCC interrupt flags
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Flags: u32 {
+        /// A word of captured data is ready to be read
+        const DRDY = 1 << 0;
+        /// Captured data was overwritten before being read
+        const OVRE = 1 << 1;
+    }
+}
+
+/// Use of the data enable inputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Qualification {
+    /// Only sample data while both `DEN1` and `DEN2` are high
+    DataEnables,
+    /// Sample data on every clock edge, ignoring `DEN1` and `DEN2`
+    Always,
+}
+
+/// Data enable inputs which discard a partially packed word when they go low
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum ClearOn {
+    /// Keep partially packed words
+    Never = 0,
+    /// Clear when `DEN1` goes low, usually at the end of a frame
+    Den1 = 1,
+    /// Clear when `DEN2` goes low, usually at the end of a line
+    Den2 = 2,
+    /// Clear when either enable input goes low
+    Both = 3,
+}
+
+/// Subset of the qualified samples which are captured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Sampling {
+    /// Capture every sample
+    All,
+    /// Capture every other sample, starting with the first one. With a YUV422
+    /// camera, this keeps the luminance only.
+    Even,
+    /// Capture every other sample, starting with the second one
+    Odd,
+}
+
+/// PCC configuration
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    qualification: Qualification,
+    clear_on: ClearOn,
+    sampling: Sampling,
+}
+
+impl Config {
+    /// Create the default configuration: sample all data while both enable
+    /// inputs are high, and never clear partially packed words
+    pub fn new() -> Self {
+        Self {
+            qualification: Qualification::DataEnables,
+            clear_on: ClearOn::Never,
+            sampling: Sampling::All,
+        }
+    }
+
+    /// Set how the data enable inputs qualify samples
+    pub fn qualification(mut self, qualification: Qualification) -> Self {
+        self.qualification = qualification;
+        self
+    }
+
+    /// Discard partially packed words when one of the enable inputs goes low
+    pub fn clear_on(mut self, clear_on: ClearOn) -> Self {
+        self.clear_on = clear_on;
+        self
+    }
+
+    /// Only capture one sample out of two
+    pub fn sampling(mut self, sampling: Sampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parallel Capture Controller
+pub struct Pcc<D: DataPins> {
+    pcc: pac::Pcc,
+    apbclk: ApbClk<PccId>,
+    pins: Pins<D>,
+}
+
+impl<D: DataPins> Pcc<D> {
+    /// Configure the PCC. It stays disabled until [`Pcc::enable`] is called.
+    pub fn new(pcc: pac::Pcc, apbclk: ApbClk<PccId>, pins: Pins<D>, config: Config) -> Self {
+        let pcc = Self { pcc, apbclk, pins };
+        let regs = pcc.regs();
+        regs.wpmr().write(|w| w.wpkey().passwd().wpen().clear_bit());
+        regs.idr().write(|w| w.drdy().set_bit().ovre().set_bit());
+        regs.mr().write(|w| {
+            w.isize().variant(D::ISIZE);
+            w.dsize().variant(D::DSIZE);
+            w.alwys().bit(config.qualification == Qualification::Always);
+            w.halfs().bit(config.sampling != Sampling::All);
+            w.frsts().bit(config.sampling == Sampling::Odd);
+            // SAFETY: Every 2-bit value is valid
+            unsafe { w.cid().bits(config.clear_on as u8) }
+        });
+        pcc
+    }
+
+    #[inline]
+    fn regs(&self) -> &RegisterBlock {
+        &self.pcc
+    }
+
+    /// Start capturing data
+    ///
+    /// Stale data and a pending overrun are discarded first.
+    #[inline]
+    pub fn enable(&mut self) {
+        let regs = self.regs();
+        regs.rhr().read();
+        regs.isr().read();
+        regs.mr().modify(|_, w| w.pcen().set_bit());
+    }
+
+    /// Stop capturing data
+    #[inline]
+    pub fn disable(&mut self) {
+        self.regs().mr().modify(|_, w| w.pcen().clear_bit());
+    }
+
+    /// Check whether the PCC is capturing data
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.regs().mr().read().pcen().bit_is_set()
+    }
+
+    /// Read a word of captured data
+    ///
+    /// Returns [`Error::Overrun`] if data was lost since the last read. The
+    /// next word can still be read afterwards.
+    #[inline]
+    pub fn read(&mut self) -> nb::Result<u32, Error> {
+        let flags = self.read_flags();
+        if flags.contains(Flags::OVRE) {
+            Err(nb::Error::Other(Error::Overrun))
+        } else if flags.contains(Flags::DRDY) {
+            Ok(self.regs().rhr().read().rdata().bits())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    /// Read the interrupt flags
+    ///
+    /// Reading the flags clears [`Flags::OVRE`]. [`Flags::DRDY`] is cleared by
+    /// reading the data.
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.regs().isr().read().bits())
+    }
+
+    /// Enable the interrupts set in `flags`
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.regs().ier().write(|w| {
+            w.drdy()
+                .bit(flags.contains(Flags::DRDY))
+                .ovre()
+                .bit(flags.contains(Flags::OVRE))
+        });
+    }
+
+    /// Disable the interrupts set in `flags`
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.regs().idr().write(|w| {
+            w.drdy()
+                .bit(flags.contains(Flags::DRDY))
+                .ovre()
+                .bit(flags.contains(Flags::OVRE))
+        });
+    }
+
+    /// Disable the PCC, and return the underlying resources
+    pub fn free(mut self) -> (pac::Pcc, ApbClk<PccId>, Pins<D>) {
+        self.disable();
+        (self.pcc, self.apbclk, self.pins)
+    }
+}
@@ -0,0 +1,235 @@
+//! PCC pin definitions
+//!
+//! Each PCC signal is only available on a single pin, so the pin sets below
+//! are fully determined by the data width.
+
+use atsamd_hal_macros::hal_cfg;
+
+use crate::gpio::{
+    AlternateK, AnyPin, PA12, PA13, PA14, PA16, PA17, PA18, PA19, PA20, PA21, PA22, PA23, Pin,
+};
+use crate::pac::pcc::mr::{Dsizeselect, Isizeselect};
+use crate::typelevel::Sealed;
+
+#[hal_cfg("pb14")]
+use crate::gpio::{PB14, PB15};
+
+#[hal_cfg("pc12")]
+use crate::gpio::{PC12, PC13, PC14, PC15};
+
+/// Set of PCC data pins, which determines the size of a captured sample
+pub trait DataPins: Sealed {
+    /// Number of data bits sampled on each clock edge
+    const BITS: u8;
+    /// Number of samples packed in each 32-bit word read from the PCC. 8-bit
+    /// samples are packed four at a time, wider samples two at a time in the
+    /// low bits of each half-word.
+    const SAMPLES_PER_WORD: usize = if Self::BITS == 8 { 4 } else { 2 };
+    #[doc(hidden)]
+    const ISIZE: Isizeselect;
+    #[doc(hidden)]
+    const DSIZE: Dsizeselect = if Self::BITS == 8 {
+        Dsizeselect::_4data
+    } else {
+        Dsizeselect::_2data
+    };
+}
+
+/// Pins `DATA[7:0]`, as released by [`Data8::free`]
+pub type Data8Pins = (
+    Pin<PA16, AlternateK>,
+    Pin<PA17, AlternateK>,
+    Pin<PA18, AlternateK>,
+    Pin<PA19, AlternateK>,
+    Pin<PA20, AlternateK>,
+    Pin<PA21, AlternateK>,
+    Pin<PA22, AlternateK>,
+    Pin<PA23, AlternateK>,
+);
+
+/// Data pins `DATA[7:0]`, capturing 8-bit samples
+pub struct Data8 {
+    pins: Data8Pins,
+}
+
+impl Data8 {
+    /// Put the pins in PCC mode
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        d0: impl AnyPin<Id = PA16>,
+        d1: impl AnyPin<Id = PA17>,
+        d2: impl AnyPin<Id = PA18>,
+        d3: impl AnyPin<Id = PA19>,
+        d4: impl AnyPin<Id = PA20>,
+        d5: impl AnyPin<Id = PA21>,
+        d6: impl AnyPin<Id = PA22>,
+        d7: impl AnyPin<Id = PA23>,
+    ) -> Self {
+        Self {
+            pins: (
+                d0.into().into_mode(),
+                d1.into().into_mode(),
+                d2.into().into_mode(),
+                d3.into().into_mode(),
+                d4.into().into_mode(),
+                d5.into().into_mode(),
+                d6.into().into_mode(),
+                d7.into().into_mode(),
+            ),
+        }
+    }
+
+    /// Release the pins
+    pub fn free(self) -> Data8Pins {
+        self.pins
+    }
+}
+
+impl Sealed for Data8 {}
+
+impl DataPins for Data8 {
+    const BITS: u8 = 8;
+    const ISIZE: Isizeselect = Isizeselect::_8bits;
+}
+
+/// Data pins `DATA[9:0]`, capturing 10-bit samples
+#[hal_cfg("pb14")]
+pub struct Data10 {
+    low: Data8,
+    high: (Pin<PB14, AlternateK>, Pin<PB15, AlternateK>),
+}
+
+#[hal_cfg("pb14")]
+impl Data10 {
+    /// Extend a set of 8 data pins with `DATA8` and `DATA9`
+    pub fn new(low: Data8, d8: impl AnyPin<Id = PB14>, d9: impl AnyPin<Id = PB15>) -> Self {
+        Self {
+            low,
+            high: (d8.into().into_mode(), d9.into().into_mode()),
+        }
+    }
+
+    /// Release the pins
+    pub fn free(self) -> (Data8, Pin<PB14, AlternateK>, Pin<PB15, AlternateK>) {
+        let (d8, d9) = self.high;
+        (self.low, d8, d9)
+    }
+}
+
+#[hal_cfg("pb14")]
+impl Sealed for Data10 {}
+
+#[hal_cfg("pb14")]
+impl DataPins for Data10 {
+    const BITS: u8 = 10;
+    const ISIZE: Isizeselect = Isizeselect::_10bits;
+}
+
+/// Data pins `DATA[11:0]`, capturing 12-bit samples
+#[hal_cfg("pc12")]
+pub struct Data12 {
+    low: Data10,
+    high: (Pin<PC12, AlternateK>, Pin<PC13, AlternateK>),
+}
+
+#[hal_cfg("pc12")]
+impl Data12 {
+    /// Extend a set of 10 data pins with `DATA10` and `DATA11`
+    pub fn new(low: Data10, d10: impl AnyPin<Id = PC12>, d11: impl AnyPin<Id = PC13>) -> Self {
+        Self {
+            low,
+            high: (d10.into().into_mode(), d11.into().into_mode()),
+        }
+    }
+
+    /// Release the pins
+    pub fn free(self) -> (Data10, Pin<PC12, AlternateK>, Pin<PC13, AlternateK>) {
+        let (d10, d11) = self.high;
+        (self.low, d10, d11)
+    }
+}
+
+#[hal_cfg("pc12")]
+impl Sealed for Data12 {}
+
+#[hal_cfg("pc12")]
+impl DataPins for Data12 {
+    const BITS: u8 = 12;
+    const ISIZE: Isizeselect = Isizeselect::_12bits;
+}
+
+/// Data pins `DATA[13:0]`, capturing 14-bit samples
+#[hal_cfg("pc12")]
+pub struct Data14 {
+    low: Data12,
+    high: (Pin<PC14, AlternateK>, Pin<PC15, AlternateK>),
+}
+
+#[hal_cfg("pc12")]
+impl Data14 {
+    /// Extend a set of 12 data pins with `DATA12` and `DATA13`
+    pub fn new(low: Data12, d12: impl AnyPin<Id = PC14>, d13: impl AnyPin<Id = PC15>) -> Self {
+        Self {
+            low,
+            high: (d12.into().into_mode(), d13.into().into_mode()),
+        }
+    }
+
+    /// Release the pins
+    pub fn free(self) -> (Data12, Pin<PC14, AlternateK>, Pin<PC15, AlternateK>) {
+        let (d12, d13) = self.high;
+        (self.low, d12, d13)
+    }
+}
+
+#[hal_cfg("pc12")]
+impl Sealed for Data14 {}
+
+#[hal_cfg("pc12")]
+impl DataPins for Data14 {
+    const BITS: u8 = 14;
+    const ISIZE: Isizeselect = Isizeselect::_14bits;
+}
+
+/// Pins `CLK`, `DEN1` and `DEN2` and the data pins, as released by
+/// [`Pins::free`]
+pub type ReleasedPins<D> = (
+    Pin<PA14, AlternateK>,
+    Pin<PA12, AlternateK>,
+    Pin<PA13, AlternateK>,
+    D,
+);
+
+/// Complete set of PCC pins: the sampling clock, the two data enable pins
+/// and the data pins
+///
+/// `DEN1` and `DEN2` are usually connected to the VSYNC and HSYNC (or HREF)
+/// outputs of a camera sensor.
+pub struct Pins<D: DataPins> {
+    clk: Pin<PA14, AlternateK>,
+    den1: Pin<PA12, AlternateK>,
+    den2: Pin<PA13, AlternateK>,
+    data: D,
+}
+
+impl<D: DataPins> Pins<D> {
+    /// Put the clock and data enable pins in PCC mode
+    pub fn new(
+        clk: impl AnyPin<Id = PA14>,
+        den1: impl AnyPin<Id = PA12>,
+        den2: impl AnyPin<Id = PA13>,
+        data: D,
+    ) -> Self {
+        Self {
+            clk: clk.into().into_mode(),
+            den1: den1.into().into_mode(),
+            den2: den2.into().into_mode(),
+            data,
+        }
+    }
+
+    /// Release the pins
+    pub fn free(self) -> ReleasedPins<D> {
+        (self.clk, self.den1, self.den2, self.data)
+    }
+}
@@ -0,0 +1,167 @@
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::*;
+use crate::async_hal::interrupts::{Binding, Handler, InterruptSource, PDEC};
+
+static WAKER: AtomicWaker = AtomicWaker::new();
+
+/// Interrupt handler for the PDEC peripheral.
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Sealed for InterruptHandler {}
+
+impl Handler<PDEC> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        let pdec = unsafe { pac::Peripherals::steal().pdec };
+
+        let flags_pending = Flags::from_bits_truncate(pdec.intflag().read().bits());
+        let enabled_flags = Flags::from_bits_truncate(pdec.intenset().read().bits());
+        let flags = flags_pending & enabled_flags;
+
+        // Disable the interrupts but don't clear the flags; they will be cleared
+        // when the future is next polled.
+        pdec.intenclr().write(|w| unsafe { w.bits(flags.bits()) });
+        if !flags.is_empty() {
+            WAKER.wake();
+        }
+    }
+}
+
+impl<M, I0, I1, I2> Pdec<M, I0, I1, I2>
+where
+    M: Mode,
+    I0: OptionalInput<0>,
+    I1: OptionalInput<1>,
+    I2: OptionalInput<2>,
+{
+    /// Turn a [`Pdec`] into a [`FuturePdec`], allowing for the use of async
+    /// methods. You are required to provide the struct created by the
+    /// [`bind_multiple_interrupts`](crate::bind_multiple_interrupts) macro to
+    /// prove that the interrupt sources have been correctly configured. This
+    /// function will automatically enable the `PDEC_*` NVIC interrupt sources.
+    pub fn into_future<F>(self, irqs: F) -> FuturePdec<M, I0, I1, I2, F>
+    where
+        F: Binding<PDEC, InterruptHandler>,
+    {
+        PDEC::unpend();
+        unsafe { PDEC::enable() };
+        FuturePdec { inner: self, irqs }
+    }
+}
+
+/// A [`Pdec`] capable of `async` operations
+pub struct FuturePdec<M, I0, I1, I2, F>
+where
+    M: Mode,
+    I0: OptionalInput<0>,
+    I1: OptionalInput<1>,
+    I2: OptionalInput<2>,
+{
+    inner: Pdec<M, I0, I1, I2>,
+    irqs: F,
+}
+
+impl<M, I0, I1, I2, F> FuturePdec<M, I0, I1, I2, F>
+where
+    M: Mode,
+    I0: OptionalInput<0>,
+    I1: OptionalInput<1>,
+    I2: OptionalInput<2>,
+    F: Binding<PDEC, InterruptHandler>,
+{
+    /// Convert the [`FuturePdec`] back into a blocking [`Pdec`], and return
+    /// the IRQs
+    pub fn into_blocking(self) -> (Pdec<M, I0, I1, I2>, F) {
+        PDEC::disable();
+        (self.inner, self.irqs)
+    }
+
+    /// Read the counter
+    #[inline]
+    pub fn count(&mut self) -> u16 {
+        self.inner.count()
+    }
+
+    /// Set the compare value of `channel`, see [`Pdec::set_compare`]
+    #[inline]
+    pub fn set_compare(&mut self, channel: usize, value: u16) {
+        self.inner.set_compare(channel, value);
+    }
+
+    /// Read the error status
+    #[inline]
+    pub fn read_errors(&self) -> Errors {
+        self.inner.read_errors()
+    }
+
+    /// Clear errors
+    #[inline]
+    pub fn clear_errors(&mut self, errors: Errors) {
+        self.inner.clear_errors(errors);
+    }
+
+    /// Wait until one of `flags` is raised, and return the raised flags.
+    ///
+    /// Flags raised before this method is called are ignored.
+    pub async fn wait(&mut self, flags: Flags) -> Flags {
+        self.inner.clear_flags(flags);
+        core::future::poll_fn(|cx| {
+            let raised = self.inner.read_flags() & flags;
+            if !raised.is_empty() {
+                self.inner.clear_flags(raised);
+                return Poll::Ready(raised);
+            }
+
+            WAKER.register(cx.waker());
+            self.inner.enable_interrupts(flags);
+
+            let raised = self.inner.read_flags() & flags;
+            if !raised.is_empty() {
+                self.inner.clear_flags(raised);
+                return Poll::Ready(raised);
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Wait for a compare match on `channel`, 0 or 1
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is greater than 1.
+    pub async fn wait_compare(&mut self, channel: usize) {
+        let flag = match channel {
+            0 => Flags::MC0,
+            1 => Flags::MC1,
+            _ => panic!("PDEC only has two compare channels"),
+        };
+        self.wait(flag).await;
+    }
+}
+
+impl<I0, I1, I2, F> FuturePdec<Quadrature, I0, I1, I2, F>
+where
+    I0: OptionalInput<0>,
+    I1: OptionalInput<1>,
+    I2: OptionalInput<2>,
+    F: Binding<PDEC, InterruptHandler>,
+{
+    /// Read the position, split into angle and revolution count
+    #[inline]
+    pub fn position(&mut self) -> Position {
+        self.inner.position()
+    }
+
+    /// Wait for the position counter to wrap, on an index pulse or at the
+    /// position period, and return the new position
+    pub async fn wait_index(&mut self) -> Position {
+        self.wait(Flags::OVF).await;
+        self.inner.position()
+    }
+}
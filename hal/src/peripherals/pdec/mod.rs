@@ -0,0 +1,742 @@
+//! # Position Decoder
+//!
+//! This module provides a driver for the Position Decoder (PDEC) found on
+//! SAMD5x/SAME5x chips. The PDEC counts pulses in hardware, so no count is
+//! lost at speed, and supports three modes of operation:
+//!
+//! * [`Quadrature`]: decodes the phase A and B signals of an incremental
+//!   encoder, with an optional index input. The 16-bit counter can be split
+//!   into a position (angular) counter and a revolution counter.
+//! * [`Hall`]: tracks the three signals of Hall effect sensors, flagging
+//!   invalid codes and sequences.
+//! * [`Counter`]: a plain counter clocked by the prescaled `GCLK_PDEC`, or by
+//!   events.
+//!
+//! ```no_run
+//! let apb_pdec = buses.apb.enable(tokens.apbs.p_dec);
+//! let (pclk_pdec, gclk0) = Pclk::enable(tokens.pclks.pdec, clocks.gclk0);
+//! let pins = Pins::new().with_all(pins.pc16, pins.pc17, pins.pc18);
+//! let config = Config::new().filter(8);
+//! // 1024-line encoder: 4096 positions per revolution
+//! let quadrature = QuadratureConfig::new().angular_bits(12);
+//! let mut pdec = Pdec::new_quadrature(peripherals.pdec, apb_pdec, &pclk_pdec, pins, config, quadrature);
+//!
+//! let position = pdec.position();
+//! ```
+//!
+//! ## Inputs
+//!
+//! Each of the three inputs can be driven by a pin, set in [`Pins`], or by an
+//! EVSYS event, set with [`Config::event_inputs`]. The inputs go through a
+//! digital filter which rejects pulses shorter than [`Config::filter`]
+//! prescaled clock cycles.
+//!
+//! ## Events
+//!
+//! The PDEC can generate events on compare matches, overflows, direction
+//! changes, errors and velocity events, see [`Pdec::set_event_outputs`] and
+//! the [`evsys`](crate::evsys) module for routing them.
+//!
+//! ## Velocity
+//!
+//! [`VelocityMeter`] turns counter samples taken at a fixed interval, for
+//! example from a timer interrupt, into a signed count rate.
+//!
+//! ## Async
+//!
+//! With the `async` feature enabled, [`Pdec::into_future`] returns a
+//! [`FuturePdec`] which can wait for compare matches and index pulses.
+
+use core::marker::PhantomData;
+
+use bitflags::bitflags;
+
+use crate::clock::v2::{
+    apb::ApbClk,
+    pclk::{Pclk, PclkSourceId},
+    types::PDec,
+};
+use crate::pac;
+use crate::pac::pdec::RegisterBlock;
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+mod pin;
+pub use pin::*;
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
+pub use pac::pdec::presc::Prescselect as Prescaler;
+
+bitflags! {
+    /// PDEC interrupt flags, which are also the PDEC event outputs
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct Flags: u8 {
+        /// Overflow or underflow of the counter. In quadrature mode, this
+        /// includes the position counter wrapping on an index pulse.
+        const OVF = 0x01;
+        /// One of the [`Errors`] has occurred
+        const ERR = 0x02;
+        /// The counting direction changed
+        const DIR = 0x04;
+        /// Velocity event
+        const VLC = 0x08;
+        /// Compare match on channel 0
+        const MC0 = 0x10;
+        /// Compare match on channel 1
+        const MC1 = 0x20;
+    }
+}
+
+bitflags! {
+    /// PDEC error status
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct Errors: u16 {
+        /// Invalid quadrature transition
+        const QUADRATURE = 0x01;
+        /// Index pulse at an unexpected position
+        const INDEX = 0x02;
+        /// More than the maximum number of consecutive index pulses missing
+        const MISSING_PULSE = 0x04;
+        /// Hall code changed outside of the window
+        const WINDOW = 0x08;
+        /// Invalid Hall code or sequence
+        const HALL = 0x10;
+    }
+}
+
+/// Counting direction
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    /// The counter counts up
+    Up,
+    /// The counter counts down
+    Down,
+}
+
+/// Action taken on the event input 0
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EventAction {
+    /// Events don't trigger any action
+    Off,
+    /// Events restart the counter
+    Retrigger,
+    /// Events are counted, in counter mode
+    Count,
+}
+
+/// Quadrature decoding configuration
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Encoding {
+    /// Count every edge of both phases
+    X4,
+    /// Count every edge of both phases, and check the transitions
+    X4Secure,
+    /// Count every edge of phase A
+    X2,
+    /// Count every edge of phase A, and check the transitions
+    X2Secure,
+    /// Count every edge of both phases, and correct the position on each
+    /// index pulse
+    AutoCorrection,
+}
+
+//==============================================================================
+// Modes
+//==============================================================================
+
+/// Type-level trait for the PDEC operating modes
+pub trait Mode: Sealed {
+    #[doc(hidden)]
+    const MODE: pac::pdec::ctrla::Modeselect;
+}
+
+/// Quadrature decoder mode
+pub enum Quadrature {}
+
+/// Hall sensor decoder mode
+pub enum Hall {}
+
+/// Counter mode
+pub enum Counter {}
+
+impl Sealed for Quadrature {}
+impl Sealed for Hall {}
+impl Sealed for Counter {}
+
+impl Mode for Quadrature {
+    const MODE: pac::pdec::ctrla::Modeselect = pac::pdec::ctrla::Modeselect::Qdec;
+}
+
+impl Mode for Hall {
+    const MODE: pac::pdec::ctrla::Modeselect = pac::pdec::ctrla::Modeselect::Hall;
+}
+
+impl Mode for Counter {
+    const MODE: pac::pdec::ctrla::Modeselect = pac::pdec::ctrla::Modeselect::Counter;
+}
+
+//==============================================================================
+// Configuration
+//==============================================================================
+
+/// Configuration common to all PDEC modes
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    prescaler: Prescaler,
+    filter: u8,
+    invert: [bool; 3],
+    swap: bool,
+    event_inputs: [bool; 3],
+    invert_events: [bool; 3],
+    event_action: EventAction,
+    period: Option<u16>,
+    run_in_standby: bool,
+}
+
+impl Config {
+    /// Create the default configuration: no prescaler, no filter, and inputs
+    /// driven by the pins only
+    pub fn new() -> Self {
+        Self {
+            prescaler: Prescaler::Div1,
+            filter: 0,
+            invert: [false; 3],
+            swap: false,
+            event_inputs: [false; 3],
+            invert_events: [false; 3],
+            event_action: EventAction::Off,
+            period: None,
+            run_in_standby: false,
+        }
+    }
+
+    /// Divide `GCLK_PDEC` by `prescaler`. This clocks the input filter, and
+    /// the counter in [`Counter`] mode.
+    pub fn prescaler(mut self, prescaler: Prescaler) -> Self {
+        self.prescaler = prescaler;
+        self
+    }
+
+    /// Reject input pulses shorter than `filter` prescaled clock cycles
+    pub fn filter(mut self, filter: u8) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Invert input `n`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than 2.
+    pub fn invert_input(mut self, n: usize, invert: bool) -> Self {
+        self.invert[n] = invert;
+        self
+    }
+
+    /// Swap inputs 0 and 1, reversing the counting direction in quadrature
+    /// mode
+    pub fn swap(mut self, swap: bool) -> Self {
+        self.swap = swap;
+        self
+    }
+
+    /// Drive the inputs set in `inputs` by EVSYS events instead of pins, and
+    /// invert those set in `invert`.
+    ///
+    /// Bit `n` of each mask stands for input `n`.
+    pub fn event_inputs(mut self, inputs: u8, invert: u8) -> Self {
+        for n in 0..3 {
+            self.event_inputs[n] = inputs & (1 << n) != 0;
+            self.invert_events[n] = invert & (1 << n) != 0;
+        }
+        self
+    }
+
+    /// Set the action taken on event input 0
+    pub fn event_action(mut self, action: EventAction) -> Self {
+        self.event_action = action;
+        self
+    }
+
+    /// Wrap the counter at `period`. In quadrature mode, this is the position
+    /// period, and the revolution counter wraps at its full width.
+    pub fn period(mut self, period: u16) -> Self {
+        self.period = Some(period);
+        self
+    }
+
+    /// Keep the PDEC running in standby sleep mode
+    pub fn run_in_standby(mut self, run_in_standby: bool) -> Self {
+        self.run_in_standby = run_in_standby;
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configuration specific to [`Quadrature`] mode
+#[derive(Debug, Clone, Copy)]
+pub struct QuadratureConfig {
+    encoding: Encoding,
+    angular_bits: u8,
+    max_missing_pulses: u8,
+}
+
+impl QuadratureConfig {
+    /// Create the default configuration: [`Encoding::X4`], with the whole
+    /// counter used as position counter
+    pub fn new() -> Self {
+        Self {
+            encoding: Encoding::X4,
+            angular_bits: 16,
+            max_missing_pulses: 0,
+        }
+    }
+
+    /// Set the decoding configuration
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Use the lower `bits` bits of the counter as the position counter, and
+    /// the upper bits as the revolution counter. The position counter is
+    /// reset by each index pulse.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is not between 9 and 16.
+    pub fn angular_bits(mut self, bits: u8) -> Self {
+        assert!(
+            (9..=16).contains(&bits),
+            "angular counter must have 9 to 16 bits"
+        );
+        self.angular_bits = bits;
+        self
+    }
+
+    /// Flag an [`Errors::MISSING_PULSE`] error after `pulses` consecutive
+    /// missing index pulses, between 0 (disabled) and 15
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pulses` is greater than 15.
+    pub fn max_missing_pulses(mut self, pulses: u8) -> Self {
+        assert!(pulses <= 15);
+        self.max_missing_pulses = pulses;
+        self
+    }
+}
+
+impl Default for QuadratureConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Position read in [`Quadrature`] mode
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Position {
+    /// Position within the revolution, from the angular counter
+    pub angle: u16,
+    /// Revolution counter, wrapping at its width
+    pub revolution: u16,
+}
+
+//==============================================================================
+// Pdec
+//==============================================================================
+
+/// Position decoder, operating in mode `M`
+pub struct Pdec<M: Mode, I0, I1, I2>
+where
+    I0: OptionalInput<0>,
+    I1: OptionalInput<1>,
+    I2: OptionalInput<2>,
+{
+    pdec: pac::Pdec,
+    apbclk: ApbClk<PDec>,
+    pins: Pins<I0, I1, I2>,
+    freq: Hertz,
+    angular_bits: u8,
+    _mode: PhantomData<M>,
+}
+
+impl<I0, I1, I2> Pdec<Quadrature, I0, I1, I2>
+where
+    I0: OptionalInput<0>,
+    I1: OptionalInput<1>,
+    I2: OptionalInput<2>,
+{
+    /// Create and enable a PDEC in quadrature decoder mode
+    pub fn new_quadrature<PS: PclkSourceId>(
+        pdec: pac::Pdec,
+        apbclk: ApbClk<PDec>,
+        pclk: &Pclk<PDec, PS>,
+        pins: Pins<I0, I1, I2>,
+        config: Config,
+        quadrature: QuadratureConfig,
+    ) -> Self {
+        let conf = match quadrature.encoding {
+            Encoding::X4 => pac::pdec::ctrla::Confselect::X4,
+            Encoding::X4Secure => pac::pdec::ctrla::Confselect::X4s,
+            Encoding::X2 => pac::pdec::ctrla::Confselect::X2,
+            Encoding::X2Secure => pac::pdec::ctrla::Confselect::X2s,
+            Encoding::AutoCorrection => pac::pdec::ctrla::Confselect::Autoc,
+        };
+        let mut pdec = Self::create(pdec, apbclk, pclk, pins, quadrature.angular_bits);
+        pdec.init(&config, |w| unsafe {
+            w.conf().variant(conf);
+            w.angular().bits(quadrature.angular_bits - 9);
+            w.maxcmp().bits(quadrature.max_missing_pulses)
+        });
+        pdec
+    }
+
+    /// Read the position, split into angle and revolution count
+    pub fn position(&mut self) -> Position {
+        let count = self.count();
+        let bits = self.angular_bits;
+        if bits >= 16 {
+            Position {
+                angle: count,
+                revolution: 0,
+            }
+        } else {
+            Position {
+                angle: count & ((1 << bits) - 1),
+                revolution: count >> bits,
+            }
+        }
+    }
+
+    /// Number of bits of the angular counter
+    #[inline]
+    pub fn angular_bits(&self) -> u8 {
+        self.angular_bits
+    }
+}
+
+impl<I0, I1, I2> Pdec<Hall, I0, I1, I2>
+where
+    I0: OptionalInput<0>,
+    I1: OptionalInput<1>,
+    I2: OptionalInput<2>,
+{
+    /// Create and enable a PDEC in Hall sensor decoder mode
+    pub fn new_hall<PS: PclkSourceId>(
+        pdec: pac::Pdec,
+        apbclk: ApbClk<PDec>,
+        pclk: &Pclk<PDec, PS>,
+        pins: Pins<I0, I1, I2>,
+        config: Config,
+    ) -> Self {
+        let mut pdec = Self::create(pdec, apbclk, pclk, pins, 16);
+        pdec.init(&config, |w| w);
+        pdec
+    }
+
+    /// Read the current Hall code, from the three inputs
+    pub fn hall_code(&mut self) -> u8 {
+        (self.count() & 0b111) as u8
+    }
+}
+
+impl<I0, I1, I2> Pdec<Counter, I0, I1, I2>
+where
+    I0: OptionalInput<0>,
+    I1: OptionalInput<1>,
+    I2: OptionalInput<2>,
+{
+    /// Create and enable a PDEC in counter mode
+    ///
+    /// The counter counts prescaled `GCLK_PDEC` cycles, or events on event
+    /// input 0 with [`EventAction::Count`].
+    pub fn new_counter<PS: PclkSourceId>(
+        pdec: pac::Pdec,
+        apbclk: ApbClk<PDec>,
+        pclk: &Pclk<PDec, PS>,
+        pins: Pins<I0, I1, I2>,
+        config: Config,
+    ) -> Self {
+        let mut pdec = Self::create(pdec, apbclk, pclk, pins, 16);
+        pdec.init(&config, |w| w);
+        pdec
+    }
+}
+
+impl<M: Mode, I0, I1, I2> Pdec<M, I0, I1, I2>
+where
+    I0: OptionalInput<0>,
+    I1: OptionalInput<1>,
+    I2: OptionalInput<2>,
+{
+    fn create<PS: PclkSourceId>(
+        pdec: pac::Pdec,
+        apbclk: ApbClk<PDec>,
+        pclk: &Pclk<PDec, PS>,
+        pins: Pins<I0, I1, I2>,
+        angular_bits: u8,
+    ) -> Self {
+        Self {
+            pdec,
+            apbclk,
+            pins,
+            freq: pclk.freq(),
+            angular_bits,
+            _mode: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn regs(&self) -> &RegisterBlock {
+        &self.pdec
+    }
+
+    /// Reset and configure the PDEC, then start it. `mode` sets the
+    /// mode-specific fields of `CTRLA`.
+    fn init(
+        &mut self,
+        config: &Config,
+        mode: impl FnOnce(&mut pac::pdec::ctrla::W) -> &mut pac::pdec::ctrla::W,
+    ) {
+        let regs = self.regs();
+        regs.ctrla().write(|w| w.swrst().set_bit());
+        while regs.syncbusy().read().swrst().bit_is_set() {}
+
+        let pins = Pins::<I0, I1, I2>::enabled();
+        regs.ctrla().write(|w| {
+            w.mode().variant(M::MODE);
+            w.runstdby().bit(config.run_in_standby);
+            w.swap().bit(config.swap);
+            w.peren().bit(config.period.is_some());
+            w.pinen0().bit(pins[0] && !config.event_inputs[0]);
+            w.pinen1().bit(pins[1] && !config.event_inputs[1]);
+            w.pinen2().bit(pins[2] && !config.event_inputs[2]);
+            w.pinven0().bit(config.invert[0]);
+            w.pinven1().bit(config.invert[1]);
+            w.pinven2().bit(config.invert[2]);
+            mode(w)
+        });
+
+        let mask = |bits: [bool; 3]| bits.iter().rev().fold(0, |m, &b| (m << 1) | b as u8);
+        regs.evctrl().write(|w| unsafe {
+            match config.event_action {
+                EventAction::Off => w.evact().off(),
+                EventAction::Retrigger => w.evact().retrigger(),
+                EventAction::Count => w.evact().count(),
+            };
+            w.evei().bits(mask(config.event_inputs));
+            w.evinv().bits(mask(config.invert_events))
+        });
+
+        regs.presc().write(|w| w.presc().variant(config.prescaler));
+        regs.filter()
+            .write(|w| unsafe { w.filter().bits(config.filter) });
+        if let Some(period) = config.period {
+            regs.cc(0).write(|w| unsafe { w.cc().bits(period) });
+        }
+        self.wait_sync();
+
+        regs.ctrla().modify(|_, w| w.enable().set_bit());
+        self.wait_sync();
+        self.command(|w| w.cmd().start());
+    }
+
+    #[inline]
+    fn wait_sync(&self) {
+        while self.regs().syncbusy().read().bits() != 0 {}
+    }
+
+    fn command(
+        &mut self,
+        cmd: impl FnOnce(&mut pac::pdec::ctrlbset::W) -> &mut pac::pdec::ctrlbset::W,
+    ) {
+        let regs = self.regs();
+        regs.ctrlbset().write(cmd);
+        while regs.syncbusy().read().ctrlb().bit_is_set() {}
+        while !regs.ctrlbset().read().cmd().is_none() {}
+    }
+
+    /// Frequency of the prescaled clock, which clocks the input filter, and
+    /// the counter in [`Counter`] mode
+    pub fn clock_freq(&self) -> Hertz {
+        let div = 1 << self.regs().presc().read().presc().bits();
+        self.freq / div
+    }
+
+    /// Read the counter
+    pub fn count(&mut self) -> u16 {
+        self.command(|w| w.cmd().readsync());
+        self.wait_sync();
+        self.regs().count().read().count().bits()
+    }
+
+    /// Set the counter
+    pub fn set_count(&mut self, count: u16) {
+        self.regs()
+            .count()
+            .write(|w| unsafe { w.count().bits(count) });
+        self.wait_sync();
+    }
+
+    /// Restart the counter from zero
+    pub fn retrigger(&mut self) {
+        self.command(|w| w.cmd().retrigger());
+    }
+
+    /// Stop counting
+    pub fn stop(&mut self) {
+        self.command(|w| w.cmd().stop());
+    }
+
+    /// Resume counting after [`Pdec::stop`]
+    pub fn start(&mut self) {
+        self.command(|w| w.cmd().start());
+    }
+
+    /// Return `true` if the PDEC is stopped
+    pub fn is_stopped(&self) -> bool {
+        self.regs().status().read().stop().bit_is_set()
+    }
+
+    /// Return the current counting direction
+    pub fn direction(&self) -> Direction {
+        if self.regs().status().read().dir().bit_is_set() {
+            Direction::Down
+        } else {
+            Direction::Up
+        }
+    }
+
+    /// Set the compare value of `channel`, 0 or 1.
+    ///
+    /// Channel 0 is the period when [`Config::period`] is used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is greater than 1.
+    pub fn set_compare(&mut self, channel: usize, value: u16) {
+        assert!(channel < 2);
+        self.regs()
+            .cc(channel)
+            .write(|w| unsafe { w.cc().bits(value) });
+        self.wait_sync();
+    }
+
+    /// Read the error status
+    #[inline]
+    pub fn read_errors(&self) -> Errors {
+        Errors::from_bits_truncate(self.regs().status().read().bits())
+    }
+
+    /// Clear errors
+    #[inline]
+    pub fn clear_errors(&mut self, errors: Errors) {
+        self.regs()
+            .status()
+            .write(|w| unsafe { w.bits(errors.bits()) });
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.regs().intflag().read().bits())
+    }
+
+    /// Clear interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.regs()
+            .intflag()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable interrupts for the specified flags
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.regs()
+            .intenset()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable interrupts for the specified flags
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.regs()
+            .intenclr()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Generate EVSYS events for the specified flags only
+    pub fn set_event_outputs(&mut self, flags: Flags) {
+        self.regs().evctrl().modify(|_, w| {
+            w.ovfeo().bit(flags.contains(Flags::OVF));
+            w.erreo().bit(flags.contains(Flags::ERR));
+            w.direo().bit(flags.contains(Flags::DIR));
+            w.vlceo().bit(flags.contains(Flags::VLC));
+            w.mceo0().bit(flags.contains(Flags::MC0));
+            w.mceo1().bit(flags.contains(Flags::MC1))
+        });
+    }
+
+    /// Disable the PDEC and return the underlying resources
+    pub fn free(self) -> (pac::Pdec, ApbClk<PDec>, Pins<I0, I1, I2>) {
+        let regs = self.regs();
+        regs.ctrla().modify(|_, w| w.enable().clear_bit());
+        while regs.syncbusy().read().enable().bit_is_set() {}
+        (self.pdec, self.apbclk, self.pins)
+    }
+}
+
+//==============================================================================
+// VelocityMeter
+//==============================================================================
+
+/// Velocity measurement from counter samples
+///
+/// Feed it with [`Pdec::count`] at a fixed interval; the counter must not move
+/// by more than half its range between two samples. Counts wrapping at a
+/// [`Config::period`] are not supported.
+#[derive(Debug, Clone, Copy)]
+pub struct VelocityMeter {
+    last: u16,
+    interval: fugit::MicrosDurationU32,
+}
+
+impl VelocityMeter {
+    /// Create a meter for samples taken every `interval`, starting from
+    /// `count`
+    pub fn new(count: u16, interval: fugit::MicrosDurationU32) -> Self {
+        Self {
+            last: count,
+            interval,
+        }
+    }
+
+    /// Take a sample, and return the counts since the previous one
+    pub fn sample(&mut self, count: u16) -> i16 {
+        let delta = count.wrapping_sub(self.last) as i16;
+        self.last = count;
+        delta
+    }
+
+    /// Take a sample, and return the velocity in counts per second
+    pub fn velocity(&mut self, count: u16) -> i32 {
+        let delta = self.sample(count) as i64;
+        let micros = self.interval.to_micros().max(1) as i64;
+        (delta * 1_000_000 / micros) as i32
+    }
+}
@@ -0,0 +1,166 @@
+//! PDEC pin definitions
+
+use atsamd_hal_macros::hal_cfg;
+
+use crate::gpio::{AlternateG, AnyPin, Pin, PinId};
+use crate::typelevel::NoneT;
+
+/// Type-level trait for [`PinId`]s which can be the PDEC input `QDI[N]`
+///
+/// In quadrature mode, `QDI0` and `QDI1` are the phase A and B inputs and
+/// `QDI2` is the index input. In Hall mode, they are the three Hall sensor
+/// inputs. In counter mode, `QDI0` is counted.
+pub trait QdiPinId<const N: usize>: PinId {}
+
+/// Type-level trait implemented by [`NoneT`] and PDEC [`Pin`]s, used to
+/// determine which inputs of the PDEC are driven by pins
+pub trait OptionalInput<const N: usize> {
+    #[doc(hidden)]
+    const IS_PIN: bool;
+}
+
+impl<const N: usize> OptionalInput<N> for NoneT {
+    const IS_PIN: bool = false;
+}
+
+impl<const N: usize, I: QdiPinId<N>> OptionalInput<N> for Pin<I, AlternateG> {
+    const IS_PIN: bool = true;
+}
+
+/// Set of pins used as PDEC inputs
+///
+/// Inputs which are not connected to a pin can still be driven by events,
+/// see [`Config::event_inputs`](super::Config::event_inputs).
+pub struct Pins<I0 = NoneT, I1 = NoneT, I2 = NoneT>
+where
+    I0: OptionalInput<0>,
+    I1: OptionalInput<1>,
+    I2: OptionalInput<2>,
+{
+    qdi0: I0,
+    qdi1: I1,
+    qdi2: I2,
+}
+
+impl Pins {
+    /// Create an empty set of pins
+    pub fn new() -> Self {
+        Self {
+            qdi0: NoneT,
+            qdi1: NoneT,
+            qdi2: NoneT,
+        }
+    }
+}
+
+impl Default for Pins {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I0, I1, I2> Pins<I0, I1, I2>
+where
+    I0: OptionalInput<0>,
+    I1: OptionalInput<1>,
+    I2: OptionalInput<2>,
+{
+    /// Set the pins of all three inputs
+    #[inline]
+    pub fn with_all<P0, P1, P2>(
+        self,
+        qdi0: impl AnyPin<Id = P0>,
+        qdi1: impl AnyPin<Id = P1>,
+        qdi2: impl AnyPin<Id = P2>,
+    ) -> Pins<Pin<P0, AlternateG>, Pin<P1, AlternateG>, Pin<P2, AlternateG>>
+    where
+        P0: QdiPinId<0>,
+        P1: QdiPinId<1>,
+        P2: QdiPinId<2>,
+    {
+        self.with_qdi0(qdi0).with_qdi1(qdi1).with_qdi2(qdi2)
+    }
+
+    /// Set the pin of input 0: quadrature phase A, or Hall sensor 0
+    #[inline]
+    pub fn with_qdi0<P: QdiPinId<0>>(
+        self,
+        pin: impl AnyPin<Id = P>,
+    ) -> Pins<Pin<P, AlternateG>, I1, I2> {
+        Pins {
+            qdi0: pin.into().into_mode(),
+            qdi1: self.qdi1,
+            qdi2: self.qdi2,
+        }
+    }
+
+    /// Set the pin of input 1: quadrature phase B, or Hall sensor 1
+    #[inline]
+    pub fn with_qdi1<P: QdiPinId<1>>(
+        self,
+        pin: impl AnyPin<Id = P>,
+    ) -> Pins<I0, Pin<P, AlternateG>, I2> {
+        Pins {
+            qdi0: self.qdi0,
+            qdi1: pin.into().into_mode(),
+            qdi2: self.qdi2,
+        }
+    }
+
+    /// Set the pin of input 2: quadrature index, or Hall sensor 2
+    #[inline]
+    pub fn with_qdi2<P: QdiPinId<2>>(
+        self,
+        pin: impl AnyPin<Id = P>,
+    ) -> Pins<I0, I1, Pin<P, AlternateG>> {
+        Pins {
+            qdi0: self.qdi0,
+            qdi1: self.qdi1,
+            qdi2: pin.into().into_mode(),
+        }
+    }
+
+    /// Which of the inputs are driven by pins
+    #[inline]
+    pub(super) const fn enabled() -> [bool; 3] {
+        [I0::IS_PIN, I1::IS_PIN, I2::IS_PIN]
+    }
+
+    /// Release the pins
+    #[inline]
+    pub fn free(self) -> (I0, I1, I2) {
+        (self.qdi0, self.qdi1, self.qdi2)
+    }
+}
+
+macro_rules! pdec_pins {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $PinId:ident: $n:literal
+        ),+
+        $(,)?
+    ) => {
+        $(
+            $( #[$cfg] )?
+            impl QdiPinId<$n> for crate::gpio::$PinId {}
+        )+
+    };
+}
+
+pdec_pins! {
+    PA24: 0,
+    PA25: 1,
+    #[hal_cfg("pb18")]
+    PB18: 0,
+    #[hal_cfg("pb19")]
+    PB19: 1,
+    #[hal_cfg("pb20")]
+    PB20: 2,
+    #[hal_cfg("pc16")]
+    PC16: 0,
+    #[hal_cfg("pc17")]
+    PC17: 1,
+    #[hal_cfg("pc18")]
+    PC18: 2,
+}
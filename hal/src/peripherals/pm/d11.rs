@@ -0,0 +1,92 @@
+//! # Power Manager sleep modes
+//!
+//! The SAMD11/SAMD21 chips have three IDLE modes, which stop increasingly
+//! many clock domains, and the STANDBY mode, which stops all clocks except
+//! those configured to run in standby. In all modes, RAM is retained and the
+//! execution resumes after the sleep function once the device wakes up.
+//!
+//! Sleeping requires a [`WakeupSource`], an armed peripheral whose interrupt
+//! wakes the device:
+//!
+//! ```no_run
+//! let mut extint = eic_channels.2.with_pin(button);
+//! // Level detection runs in standby without the EIC clock
+//! extint.sense(Sense::Low);
+//! extint.enable_interrupt();
+//! extint.enable_interrupt_wake();
+//! unsafe { NVIC::unmask(interrupt::EIC) };
+//!
+//! let wakeup = pm::sleep(&mut peripherals.pm, &mut core.SCB, SleepMode::Standby, &extint);
+//! assert_eq!(wakeup, Wakeup::Source);
+//! ```
+//!
+//! The PM registers also hold the clock masks used by the other drivers,
+//! so the functions of this module borrow the PAC [`Pm`](crate::pac::Pm)
+//! instead of owning it.
+//!
+//! Each peripheral and clock generator has its own run-in-standby setting,
+//! see for example
+//! [`GenericClockController::configure_standby`](crate::clock::GenericClockController::configure_standby).
+//! A peripheral which doesn't run in standby can't wake the device from
+//! [`SleepMode::Standby`].
+//!
+//! This module doesn't configure the run-in-standby setting of the
+//! peripherals. It is set with the drivers of the peripherals, for example
+//! with the `run_in_standby` method of the SERCOM configurations. Before
+//! entering standby, the sleep functions only check that the
+//! [`WakeupSource`] runs in standby, see [`WakeupSource::runs_in_standby`].
+
+use cortex_m::peripheral::SCB;
+
+use crate::pac::Pm;
+
+mod wakeup;
+pub use wakeup::*;
+
+/// Sleep mode entered by [`sleep`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SleepMode {
+    /// The CPU clock domain is stopped
+    Idle0,
+    /// The CPU and AHB clock domains are stopped
+    Idle1,
+    /// The CPU, AHB and APB clock domains are stopped
+    Idle2,
+    /// All clocks are stopped, except those running in standby
+    Standby,
+}
+
+/// Enter the sleep `mode`, until an interrupt wakes the device.
+///
+/// Returns the reason of the wakeup. The interrupt handler of the source
+/// which woke the device runs before this function returns.
+///
+/// # Panics
+///
+/// Panics if `source` is not armed, if its interrupt is masked in the NVIC,
+/// or if `mode` is [`SleepMode::Standby`] and `source` doesn't run in
+/// standby.
+pub fn sleep<S: WakeupSource>(pm: &mut Pm, scb: &mut SCB, mode: SleepMode, source: &S) -> Wakeup {
+    if mode == SleepMode::Standby {
+        assert!(
+            source.runs_in_standby(),
+            "the wakeup source doesn't run in standby"
+        );
+    }
+
+    match mode {
+        SleepMode::Idle0 => pm.sleep().write(|w| w.idle().cpu()),
+        SleepMode::Idle1 => pm.sleep().write(|w| w.idle().ahb()),
+        SleepMode::Idle2 => pm.sleep().write(|w| w.idle().apb()),
+        SleepMode::Standby => (),
+    }
+
+    if mode == SleepMode::Standby {
+        scb.set_sleepdeep();
+    } else {
+        scb.clear_sleepdeep();
+    }
+
+    wait_for_wakeup(source)
+}
@@ -0,0 +1,251 @@
+//! # Power Manager sleep modes
+//!
+//! The SAMD5x/SAME5x chips have the following sleep modes:
+//!
+//! * IDLE, where the CPU clock is stopped. Unlike the SAMD11/SAMD21 chips,
+//!   there is a single IDLE mode.
+//! * STANDBY, where all clocks are stopped except those configured to run in
+//!   standby. The RAM retention is configured with [`Pm::configure_standby`].
+//! * HIBERNATE, where the core domain is powered off, and only the backup
+//!   domain and the retained RAM are kept. See [`Pm::configure_hibernate`].
+//! * BACKUP, where only the backup domain is powered. See
+//!   [`Pm::configure_backup`].
+//! * OFF, where the whole chip is powered off.
+//!
+//! The execution resumes after [`Pm::sleep`] when waking up from IDLE or
+//! STANDBY. Waking up from HIBERNATE or BACKUP resets the device, and
+//! [`backup_exit`] tells what woke it. The device only leaves OFF through an
+//! external reset.
+//!
+//! Sleeping requires a [`WakeupSource`], an armed peripheral whose interrupt
+//! wakes the device:
+//!
+//! ```no_run
+//! let mut pm = Pm::new(peripherals.pm);
+//! pm.configure_standby(RamRetention::Full, FastWakeup::No);
+//!
+//! extint.sense(Sense::Fall);
+//! extint.enable_interrupt();
+//! unsafe { NVIC::unmask(interrupt::EIC_EXTINT_2) };
+//!
+//! let wakeup = pm.sleep(SleepMode::Standby, &extint);
+//! assert_eq!(wakeup, Wakeup::Source);
+//! ```
+//!
+//! Each peripheral and clock generator has its own run-in-standby setting. A
+//! peripheral which doesn't run in standby can't wake the device from
+//! [`SleepMode::Standby`].
+//!
+//! This module doesn't configure the run-in-standby setting of the
+//! peripherals. It is set with the drivers of the peripherals, for example
+//! with the `run_in_standby` method of the SERCOM configurations. Before
+//! entering standby, the sleep functions only check that the
+//! [`WakeupSource`] runs in standby, see [`WakeupSource::runs_in_standby`].
+
+use crate::pac;
+use crate::pac::pm::sleepcfg::Sleepmodeselect;
+use crate::pac::pm::stdbycfg::Fastwkupselect;
+
+mod wakeup;
+pub use wakeup::*;
+
+/// Fast wakeup from standby, which keeps the NVM or the main voltage
+/// regulator powered at the expense of a higher consumption
+pub type FastWakeup = Fastwkupselect;
+
+/// Sleep mode entered by [`Pm::sleep`], from which the execution resumes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SleepMode {
+    /// The CPU clock is stopped
+    Idle,
+    /// All clocks are stopped, except those running in standby
+    Standby,
+}
+
+/// RAM retention in a sleep mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RamRetention {
+    /// The whole RAM is retained
+    Full,
+    /// Only part of the RAM is retained, see the datasheet
+    Partial,
+    /// The RAM is not retained
+    Off,
+}
+
+/// A [`WakeupSource`] in the backup domain, which can wake the device from
+/// hibernate and backup modes
+///
+/// Only the [`Rtc`](crate::rtc::Rtc) is in the backup domain.
+pub trait BackupWakeupSource: WakeupSource {}
+
+/// Reason why the device left the hibernate or backup mode, see
+/// [`backup_exit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BackupExit {
+    /// The device left the hibernate mode, not the backup mode
+    pub hibernate: bool,
+    /// The RTC woke the device
+    pub rtc: bool,
+    /// The battery backup power switch woke the device, when `VDD` was
+    /// restored
+    pub battery_switch: bool,
+}
+
+/// Returns the reason why the device left the hibernate or backup mode, or
+/// `None` if the last reset has another cause
+pub fn backup_exit(rstc: &pac::Rstc) -> Option<BackupExit> {
+    if rstc.rcause().read().backup().bit_is_clear() {
+        return None;
+    }
+
+    let bkupexit = rstc.bkupexit().read();
+    Some(BackupExit {
+        hibernate: bkupexit.hib().bit_is_set(),
+        rtc: bkupexit.rtc().bit_is_set(),
+        battery_switch: bkupexit.bbps().bit_is_set(),
+    })
+}
+
+/// Power Manager
+pub struct Pm {
+    pm: pac::Pm,
+}
+
+impl Pm {
+    /// Create a new [`Pm`]
+    #[inline]
+    pub fn new(pm: pac::Pm) -> Self {
+        Self { pm }
+    }
+
+    /// Configure the RAM retention in standby, and whether to keep the NVM or
+    /// the main voltage regulator powered for a fast wakeup
+    pub fn configure_standby(&mut self, ram: RamRetention, fast_wakeup: FastWakeup) {
+        self.pm.stdbycfg().write(|w| {
+            match ram {
+                RamRetention::Full => w.ramcfg().ret(),
+                RamRetention::Partial => w.ramcfg().partial(),
+                RamRetention::Off => w.ramcfg().off(),
+            };
+            w.fastwkup().variant(fast_wakeup)
+        });
+    }
+
+    /// Configure the retention of the RAM and of the backup RAM in hibernate
+    pub fn configure_hibernate(&mut self, ram: RamRetention, backup_ram: RamRetention) {
+        self.pm.hibcfg().write(|w| {
+            match ram {
+                RamRetention::Full => w.ramcfg().ret(),
+                RamRetention::Partial => w.ramcfg().partial(),
+                RamRetention::Off => w.ramcfg().off(),
+            };
+            match backup_ram {
+                RamRetention::Full => w.bramcfg().ret(),
+                RamRetention::Partial => w.bramcfg().partial(),
+                RamRetention::Off => w.bramcfg().off(),
+            }
+        });
+    }
+
+    /// Configure the retention of the backup RAM in backup mode
+    pub fn configure_backup(&mut self, backup_ram: RamRetention) {
+        self.pm.bkupcfg().write(|w| match backup_ram {
+            RamRetention::Full => w.bramcfg().ret(),
+            RamRetention::Partial => w.bramcfg().partial(),
+            RamRetention::Off => w.bramcfg().off(),
+        });
+    }
+
+    /// Keep the I/O lines in their state when entering hibernate or backup
+    /// mode.
+    ///
+    /// The lines stay frozen after the wakeup, until the I/O retention is
+    /// disabled. This must be done after reconfiguring the pins.
+    #[inline]
+    pub fn set_io_retention(&mut self, retain: bool) {
+        self.pm.ctrla().write(|w| w.ioret().bit(retain));
+    }
+
+    /// Enter the sleep `mode`, until an interrupt wakes the device.
+    ///
+    /// Returns the reason of the wakeup. The interrupt handler of the source
+    /// which woke the device runs before this function returns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` is not armed, if its interrupt is masked in the
+    /// NVIC, or if `mode` is [`SleepMode::Standby`] and `source` doesn't run
+    /// in standby.
+    pub fn sleep<S: WakeupSource>(&mut self, mode: SleepMode, source: &S) -> Wakeup {
+        if mode == SleepMode::Standby {
+            assert!(
+                source.runs_in_standby(),
+                "the wakeup source doesn't run in standby"
+            );
+        }
+
+        self.set_sleep_mode(match mode {
+            SleepMode::Idle => Sleepmodeselect::Idle,
+            SleepMode::Standby => Sleepmodeselect::Standby,
+        });
+        wait_for_wakeup(source)
+    }
+
+    /// Enter the hibernate mode. The device is reset when `source` wakes it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` is not armed.
+    pub fn hibernate<S: BackupWakeupSource>(&mut self, source: &S) -> ! {
+        self.enter_backup_domain_mode(Sleepmodeselect::Hibernate, source)
+    }
+
+    /// Enter the backup mode. The device is reset when `source` wakes it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` is not armed.
+    pub fn backup<S: BackupWakeupSource>(&mut self, source: &S) -> ! {
+        self.enter_backup_domain_mode(Sleepmodeselect::Backup, source)
+    }
+
+    /// Power the device off. Only an external reset wakes it.
+    pub fn off(&mut self) -> ! {
+        self.set_sleep_mode(Sleepmodeselect::Off);
+        cortex_m::asm::dsb();
+        loop {
+            cortex_m::asm::wfi();
+        }
+    }
+
+    /// Return the PAC [`Pm`](pac::Pm)
+    #[inline]
+    pub fn free(self) -> pac::Pm {
+        self.pm
+    }
+
+    /// Enter a mode left through a reset. The wakeup doesn't go through the
+    /// NVIC, so only the source itself must be armed.
+    fn enter_backup_domain_mode<S: BackupWakeupSource>(
+        &mut self,
+        mode: Sleepmodeselect,
+        source: &S,
+    ) -> ! {
+        assert!(source.is_armed(), "the wakeup source is not armed");
+        self.set_sleep_mode(mode);
+        cortex_m::asm::dsb();
+        loop {
+            cortex_m::asm::wfi();
+        }
+    }
+
+    fn set_sleep_mode(&mut self, mode: Sleepmodeselect) {
+        self.pm.sleepcfg().write(|w| w.sleepmode().variant(mode));
+        // The mode must be written before entering sleep
+        while self.pm.sleepcfg().read().sleepmode().variant() != Some(mode) {}
+    }
+}
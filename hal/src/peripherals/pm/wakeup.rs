@@ -0,0 +1,108 @@
+//! Wakeup sources shared by all chips
+
+use cortex_m::interrupt::InterruptNumber;
+use cortex_m::peripheral::{NVIC, SCB};
+use cortex_m::{asm, interrupt, register::primask};
+
+/// A peripheral configured to wake the device from sleep
+///
+/// The sleep functions of this module take a [`WakeupSource`], which
+/// guarantees at the type level that a wakeup source exists. It is
+/// implemented by:
+///
+/// * [`Rtc`](crate::rtc::Rtc), with at least one interrupt enabled.
+/// * [`ExtInt`](crate::eic::ExtInt), with its interrupt enabled. On SAMD11/21
+///   chips, wakeup must also be enabled with `ExtInt::enable_interrupt_wake`.
+///
+/// The interrupt of the source must be unmasked in the NVIC.
+///
+/// # Limitations
+///
+/// The type system only guarantees that a source is passed, and, on SAMx5x
+/// chips, that the source of the hibernate and backup modes is in the backup
+/// domain. Whether the source is armed, its interrupt unmasked, and, before
+/// entering [standby](super::SleepMode::Standby), whether it runs in standby
+/// is only checked at runtime, and the sleep functions panic otherwise.
+///
+/// The clock generator feeding the source must be configured to run in
+/// standby with the clock drivers, for example with
+/// `GenericClockController::configure_standby`. Only the generator is
+/// checked, not the oscillator it is sourced from.
+pub trait WakeupSource {
+    /// Number of the interrupt raised by the source
+    fn interrupt(&self) -> u16;
+
+    /// Returns `true` if the source is configured to raise its interrupt
+    fn is_armed(&self) -> bool;
+
+    /// Returns `true` if the source keeps running in standby, and can thus
+    /// wake the device from it
+    fn runs_in_standby(&self) -> bool;
+}
+
+/// Reason why the CPU woke up from sleep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Wakeup {
+    /// The [`WakeupSource`] passed to the sleep function raised its interrupt
+    Source,
+    /// Another interrupt, with the given number
+    Interrupt(u16),
+    /// No interrupt is pending, the CPU was woken by an event or a debugger
+    Unknown,
+}
+
+/// Interrupt number used to query the NVIC
+#[derive(Clone, Copy)]
+struct Irq(u16);
+
+// SAFETY: the number comes from a peripheral interrupt of the PAC
+unsafe impl InterruptNumber for Irq {
+    #[inline]
+    fn number(self) -> u16 {
+        self.0
+    }
+}
+
+/// Sleep until an interrupt is pending, and return the wakeup reason.
+///
+/// Interrupts are masked while the CPU sleeps, so that the wakeup reason can
+/// be read before the interrupt handler runs. The handler runs when this
+/// function returns, unless interrupts were already masked by the caller.
+///
+/// # Panics
+///
+/// Panics if the source is not armed, or if its interrupt is masked in the
+/// NVIC.
+pub(super) fn wait_for_wakeup<S: WakeupSource>(source: &S) -> Wakeup {
+    let irq = Irq(source.interrupt());
+    assert!(source.is_armed(), "the wakeup source is not armed");
+    assert!(
+        NVIC::is_enabled(irq),
+        "the interrupt of the wakeup source is masked"
+    );
+
+    let was_active = primask::read().is_active();
+    interrupt::disable();
+
+    asm::dsb();
+    asm::wfi();
+
+    let wakeup = if NVIC::is_pending(irq) {
+        Wakeup::Source
+    } else {
+        // SAFETY: reading ICSR has no side effect
+        let icsr = unsafe { (*SCB::PTR).icsr.read() };
+        // VECTPENDING holds the exception number, interrupts start at 16
+        match (icsr >> 12) & 0x1ff {
+            vector @ 16.. => Wakeup::Interrupt(vector as u16 - 16),
+            _ => Wakeup::Unknown,
+        }
+    };
+
+    if was_active {
+        // SAFETY: interrupts were enabled when we entered the function
+        unsafe { interrupt::enable() };
+    }
+    wakeup
+}
@@ -4,6 +4,12 @@ use crate::{
     pac::{self, Mclk},
 };
 use core::marker::PhantomData;
+use core::ops::Range;
+
+use embedded_storage::nor_flash::{
+    ErrorType, MultiwriteNorFlash, NorFlash, NorFlashErrorKind, ReadNorFlash, check_erase,
+    check_read, check_write,
+};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -215,6 +221,9 @@ impl Qspi<OneShot> {
     /// Latches the peripheral in a read/execute state, so it can be used to
     /// read or execute directly from flash.
     ///
+    /// The CMCC cache is invalidated, as the flash may have been modified
+    /// since it was last memory-mapped.
+    ///
     /// Note: Hardcodes 8 dummy cycles.
     pub fn into_xip(self) -> Qspi<XIP> {
         let tfm = TransferMode {
@@ -228,6 +237,7 @@ impl Qspi<OneShot> {
         unsafe {
             self.run_read_instruction(Command::QuadRead, tfm, 0, &mut [], false);
         }
+        crate::cmcc::invalidate_all();
 
         Qspi::<XIP> {
             qspi: self.qspi,
@@ -456,3 +466,144 @@ impl Command {
 }
 
 const QSPI_AHB: u32 = 0x04000000;
+
+/// Typical size of a page programmed by [`Command::QuadPageProgram`], the
+/// default page size of [`QspiFlash`]
+pub const PAGE_SIZE: u32 = 256;
+
+/// Typical size of a sector erased by [`Command::EraseSector`], the default
+/// sector size of [`QspiFlash`]
+pub const SECTOR_SIZE: u32 = 4096;
+
+/// Size of the address space, with 24-bit addresses
+const ADDRESS_SPACE: u32 = 1 << 24;
+
+/// A region of a QSPI flash device, implementing the `embedded-storage` NOR
+/// flash traits
+///
+/// The geometry of the device is given by `SECTOR`, the size erased by
+/// [`Command::EraseSector`], and `PAGE`, the size programmed at once by
+/// [`Command::QuadPageProgram`]. They default to the common 4 KiB sectors
+/// and 256-byte pages.
+///
+/// The region is erased one sector at a time. The device must have its quad
+/// mode enabled, as [`Qspi::read_memory`] and [`Qspi::write_memory`] use quad
+/// transfers.
+///
+/// The offsets passed to the trait methods are relative to the start of the
+/// region.
+pub struct QspiFlash<'a, const SECTOR: u32 = SECTOR_SIZE, const PAGE: u32 = PAGE_SIZE> {
+    qspi: &'a mut Qspi<OneShot>,
+    region: Range<u32>,
+}
+
+impl<'a, const SECTOR: u32, const PAGE: u32> QspiFlash<'a, SECTOR, PAGE> {
+    /// Create a [`QspiFlash`] over the `region` of the flash device
+    ///
+    /// This call will fail if
+    /// - `SECTOR` and `PAGE` are not powers of two, with `PAGE` no larger than
+    ///   `SECTOR`
+    /// - `region` is not aligned on sectors
+    /// - `region` goes past the 16 MiB reachable with 24-bit addresses
+    pub fn new(qspi: &'a mut Qspi<OneShot>, region: Range<u32>) -> Result<Self, NorFlashErrorKind> {
+        if !SECTOR.is_power_of_two() || !PAGE.is_power_of_two() || PAGE > SECTOR {
+            Err(NorFlashErrorKind::Other)
+        } else if region.start % SECTOR != 0 || region.end % SECTOR != 0 {
+            Err(NorFlashErrorKind::NotAligned)
+        } else if region.start > region.end || region.end > ADDRESS_SPACE {
+            Err(NorFlashErrorKind::OutOfBounds)
+        } else {
+            Ok(Self { qspi, region })
+        }
+    }
+
+    /// Returns the region of the flash device
+    #[inline]
+    pub fn region(&self) -> Range<u32> {
+        self.region.clone()
+    }
+
+    /// Release the [`Qspi`]
+    #[inline]
+    pub fn free(self) -> &'a mut Qspi<OneShot> {
+        self.qspi
+    }
+
+    /// Set the write enable latch, required before each program or erase
+    fn write_enable(&mut self) {
+        // Never fails for this command
+        let _ = self.qspi.run_command(Command::WriteEnable);
+    }
+
+    /// Wait until the device has completed the program or erase
+    fn wait_ready(&mut self) {
+        let mut status = [0_u8; 1];
+        loop {
+            // Never fails for this command
+            let _ = self.qspi.read_command(Command::ReadStatus, &mut status);
+            // Write In Progress
+            if status[0] & 0x01 == 0 {
+                break;
+            }
+        }
+        // The CMCC may hold stale contents of the modified flash
+        crate::cmcc::invalidate_all();
+    }
+}
+
+impl<const SECTOR: u32, const PAGE: u32> ErrorType for QspiFlash<'_, SECTOR, PAGE> {
+    type Error = NorFlashErrorKind;
+}
+
+impl<const SECTOR: u32, const PAGE: u32> ReadNorFlash for QspiFlash<'_, SECTOR, PAGE> {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        check_read(self, offset, bytes.len())?;
+        self.qspi.read_memory(self.region.start + offset, bytes);
+        Ok(())
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.region.len()
+    }
+}
+
+impl<const SECTOR: u32, const PAGE: u32> NorFlash for QspiFlash<'_, SECTOR, PAGE> {
+    const WRITE_SIZE: usize = 1;
+
+    const ERASE_SIZE: usize = SECTOR as usize;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+        check_erase(self, from, to)?;
+        let start = self.region.start;
+        for address in (start + from..start + to).step_by(SECTOR as usize) {
+            self.write_enable();
+            // Never fails for this command
+            let _ = self.qspi.erase_command(Command::EraseSector, address);
+            self.wait_ready();
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u32, mut bytes: &[u8]) -> Result<(), Self::Error> {
+        check_write(self, offset, bytes.len())?;
+        let mut address = self.region.start + offset;
+        while !bytes.is_empty() {
+            // A page program wraps around at the end of the page
+            let len = bytes.len().min((PAGE - address % PAGE) as usize);
+            let (page, rest) = bytes.split_at(len);
+
+            self.write_enable();
+            self.qspi.write_memory(address, page);
+            self.wait_ready();
+
+            address += len as u32;
+            bytes = rest;
+        }
+        Ok(())
+    }
+}
+
+impl<const SECTOR: u32, const PAGE: u32> MultiwriteNorFlash for QspiFlash<'_, SECTOR, PAGE> {}
@@ -0,0 +1,113 @@
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::*;
+use crate::async_hal::interrupts::{Binding, Handler, InterruptSource, RAMECC};
+use crate::typelevel::Sealed;
+
+static WAKER: AtomicWaker = AtomicWaker::new();
+
+/// Interrupt handler for the RAMECC peripheral.
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Sealed for InterruptHandler {}
+
+impl Handler<RAMECC> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        let ramecc = unsafe { pac::Peripherals::steal().ramecc };
+
+        let flags_pending = Flags::from_bits_truncate(ramecc.intflag().read().bits());
+        let enabled_flags = Flags::from_bits_truncate(ramecc.intenset().read().bits());
+        let flags = flags_pending & enabled_flags;
+
+        // Disable the interrupts but don't clear the flags; they will be cleared
+        // when the event is read.
+        ramecc.intenclr().write(|w| unsafe { w.bits(flags.bits()) });
+        if !flags.is_empty() {
+            WAKER.wake();
+        }
+    }
+}
+
+impl RamEcc {
+    /// Turn a [`RamEcc`] into a [`FutureRamEcc`], allowing for the use of
+    /// async methods. You are required to provide the struct created by the
+    /// [`bind_interrupts`](crate::bind_interrupts) macro to prove that the
+    /// interrupt source has been correctly configured. This function will
+    /// automatically enable the `RAMECC` NVIC interrupt source.
+    pub fn into_future<F>(self, irqs: F) -> FutureRamEcc<F>
+    where
+        F: Binding<RAMECC, InterruptHandler>,
+    {
+        RAMECC::unpend();
+        unsafe { RAMECC::enable() };
+        FutureRamEcc { inner: self, irqs }
+    }
+}
+
+/// A [`RamEcc`] capable of `async` operations
+pub struct FutureRamEcc<F> {
+    inner: RamEcc,
+    irqs: F,
+}
+
+impl<F> FutureRamEcc<F>
+where
+    F: Binding<RAMECC, InterruptHandler>,
+{
+    /// Convert the [`FutureRamEcc`] back into a blocking [`RamEcc`], and
+    /// return the IRQs
+    pub fn into_blocking(mut self) -> (RamEcc, F) {
+        RAMECC::disable();
+        self.inner.disable_interrupts(Flags::all());
+        (self.inner, self.irqs)
+    }
+
+    /// Wait for the next ECC error, and update the [`Counters`]
+    ///
+    /// An error which occurred since the last event was read is returned
+    /// immediately. The RAMECC only remembers the last error of each type, so
+    /// errors occurring between two calls are reported and counted once.
+    pub async fn next_event(&mut self) -> EccEvent {
+        core::future::poll_fn(|cx| {
+            if let Some(event) = self.inner.read_event() {
+                return Poll::Ready(event);
+            }
+
+            WAKER.register(cx.waker());
+            self.inner.enable_interrupts(Flags::all());
+
+            if let Some(event) = self.inner.read_event() {
+                return Poll::Ready(event);
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Return the error counters
+    #[inline]
+    pub fn counters(&self) -> Counters {
+        self.inner.counters()
+    }
+
+    /// Reset the error counters, and return their previous value
+    #[inline]
+    pub fn take_counters(&mut self) -> Counters {
+        self.inner.take_counters()
+    }
+
+    /// Rewrite the word holding a single-bit error, see [`RamEcc::scrub`]
+    ///
+    /// # Safety
+    ///
+    /// No DMA transfer may write to the word while it is scrubbed.
+    #[inline]
+    pub unsafe fn scrub(&mut self, event: &EccEvent) -> bool {
+        unsafe { self.inner.scrub(event) }
+    }
+}
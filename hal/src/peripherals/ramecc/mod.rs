@@ -0,0 +1,225 @@
+//! # RAM ECC
+//!
+//! This module provides a driver for the RAM Error Correction Code (RAMECC)
+//! controller found on SAMD5x/SAME5x chips. When ECC is enabled in the NVM
+//! user row, the SRAM is protected by a code which corrects single-bit errors
+//! and detects double-bit errors in each 64-bit word. The RAMECC reports the
+//! address of the last faulty word read by the CPU or a DMA.
+//!
+//! ```no_run
+//! let mut ramecc = RamEcc::new(peripherals.ramecc, clocks.apbs.ram_ecc);
+//!
+//! if let Some(event) = ramecc.read_event() {
+//!     if event.kind == ErrorKind::Single {
+//!         // SAFETY: No DMA transfer targets the faulty word
+//!         unsafe { ramecc.scrub(&event) };
+//!     }
+//! }
+//! let counters = ramecc.counters();
+//! ```
+//!
+//! ## Scrubbing
+//!
+//! Single-bit errors are corrected on the fly when the word is read, but the
+//! SRAM still holds the wrong bit, and a second bit error in the same word
+//! would be uncorrectable. [`RamEcc::scrub`] rewrites the corrected word to
+//! clear the error.
+//!
+//! ## Async
+//!
+//! With the `async` feature enabled, [`RamEcc::into_future`] returns a
+//! [`FutureRamEcc`], whose [`next_event`](FutureRamEcc::next_event) method
+//! waits for the next ECC error.
+
+use crate::clock::v2::{apb::ApbClk, types::RamEcc as RamEccId};
+use crate::pac;
+use crate::pac::ramecc::RegisterBlock;
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
+/// Start address of the SRAM
+const SRAM_BASE: u32 = 0x2000_0000;
+
+/// Size of a word protected by ECC, in bytes
+pub const WORD_SIZE: u32 = 8;
+
+bitflags::bitflags! {
+    /// RAMECC interrupt flags
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Flags: u8 {
+        /// A single-bit error was corrected
+        const SINGLEE = 1 << 0;
+        /// A double-bit error was detected
+        const DUALE = 1 << 1;
+    }
+}
+
+/// Type of ECC error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ErrorKind {
+    /// Single-bit error, which was corrected
+    Single,
+    /// Double-bit error, which can't be corrected
+    Dual,
+}
+
+/// ECC error reported by the RAMECC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EccEvent {
+    /// Type of error
+    pub kind: ErrorKind,
+    /// Address of the faulty word, aligned to [`WORD_SIZE`]
+    pub address: u32,
+}
+
+/// Number of ECC errors seen since the counters were last reset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Counters {
+    /// Number of single-bit errors
+    pub single: u32,
+    /// Number of double-bit errors
+    pub dual: u32,
+}
+
+/// RAM ECC controller
+pub struct RamEcc {
+    ramecc: pac::Ramecc,
+    apbclk: ApbClk<RamEccId>,
+    counters: Counters,
+}
+
+impl RamEcc {
+    /// Create the RAMECC driver, with all interrupts disabled
+    pub fn new(ramecc: pac::Ramecc, apbclk: ApbClk<RamEccId>) -> Self {
+        let mut ramecc = Self {
+            ramecc,
+            apbclk,
+            counters: Counters::default(),
+        };
+        ramecc.disable_interrupts(Flags::all());
+        ramecc
+    }
+
+    #[inline]
+    fn regs(&self) -> &RegisterBlock {
+        &self.ramecc
+    }
+
+    /// Check whether ECC is enabled in the NVM user row
+    #[inline]
+    pub fn is_ecc_enabled(&self) -> bool {
+        self.regs().status().read().eccdis().bit_is_clear()
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.regs().intflag().read().bits())
+    }
+
+    /// Read the last ECC error, if any, and update the [`Counters`]
+    ///
+    /// The RAMECC only remembers the last error of each type, so errors
+    /// occurring between two calls are counted once. If both a single and a
+    /// double-bit error occurred, both are counted, but only the double-bit
+    /// error is returned.
+    pub fn read_event(&mut self) -> Option<EccEvent> {
+        let flags = self.read_flags();
+        if flags.is_empty() {
+            return None;
+        }
+
+        let offset = self.regs().erraddr().read().erraddr().bits();
+        // SAFETY: Writing 1 to a flag clears it
+        self.regs()
+            .intflag()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+
+        if flags.contains(Flags::SINGLEE) {
+            self.counters.single = self.counters.single.saturating_add(1);
+        }
+        let kind = if flags.contains(Flags::DUALE) {
+            self.counters.dual = self.counters.dual.saturating_add(1);
+            ErrorKind::Dual
+        } else {
+            ErrorKind::Single
+        };
+
+        Some(EccEvent {
+            kind,
+            address: SRAM_BASE + offset * WORD_SIZE,
+        })
+    }
+
+    /// Return the error counters
+    #[inline]
+    pub fn counters(&self) -> Counters {
+        self.counters
+    }
+
+    /// Reset the error counters, and return their previous value
+    #[inline]
+    pub fn take_counters(&mut self) -> Counters {
+        core::mem::take(&mut self.counters)
+    }
+
+    /// Rewrite the word holding a single-bit error, so that the SRAM holds
+    /// the corrected value again
+    ///
+    /// Returns `false` without touching the SRAM for a double-bit error,
+    /// which can't be corrected. The word is read and written back in a
+    /// critical section, so no interrupt handler can modify it in between.
+    ///
+    /// # Safety
+    ///
+    /// No DMA transfer may write to the word while it is scrubbed.
+    pub unsafe fn scrub(&mut self, event: &EccEvent) -> bool {
+        if event.kind == ErrorKind::Dual {
+            return false;
+        }
+
+        let word = event.address as *mut u64;
+        critical_section::with(|_| {
+            // SAFETY: The address was reported by the RAMECC, so it is an
+            // aligned SRAM word. Reading it returns the corrected value.
+            unsafe { word.write_volatile(word.read_volatile()) };
+        });
+
+        // Reading the word raised the error again
+        // SAFETY: Writing 1 to a flag clears it
+        self.regs()
+            .intflag()
+            .write(|w| unsafe { w.bits(Flags::SINGLEE.bits()) });
+        true
+    }
+
+    /// Enable the interrupts set in `flags`
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        // SAFETY: Writing 1 to a bit enables the interrupt, and 0 has no effect
+        self.regs()
+            .intenset()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the interrupts set in `flags`
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        // SAFETY: Writing 1 to a bit disables the interrupt, and 0 has no effect
+        self.regs()
+            .intenclr()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the interrupts, and return the underlying resources
+    pub fn free(mut self) -> (pac::Ramecc, ApbClk<RamEccId>) {
+        self.disable_interrupts(Flags::all());
+        (self.ramecc, self.apbclk)
+    }
+}
@@ -0,0 +1,177 @@
+use core::marker::PhantomData;
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::*;
+use crate::async_hal::interrupts::{Binding, Handler, InterruptSource};
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_WAKER: AtomicWaker = AtomicWaker::new();
+pub(super) static WAKERS: [AtomicWaker; 2] = [NEW_WAKER; 2];
+
+/// Interrupt handler for an SDHC peripheral.
+pub struct InterruptHandler<Id: SdhcId> {
+    _private: (),
+    _id: PhantomData<Id>,
+}
+
+impl<Id: SdhcId> Sealed for InterruptHandler<Id> {}
+
+impl<Id: SdhcId> Handler<Id::Interrupt> for InterruptHandler<Id> {
+    unsafe fn on_interrupt() {
+        let regs = Id::regs();
+
+        // Stop signalling, but don't clear the flags; they will be checked and
+        // cleared when the future is next polled.
+        regs.nisier().write(|w| unsafe { w.bits(0) });
+        regs.eisier().write(|w| unsafe { w.bits(0) });
+        Id::waker().wake();
+    }
+}
+
+/// Abort an ongoing transfer when dropped, so that the DMA doesn't outlive
+/// the buffers of a cancelled future.
+struct AbortOnDrop(&'static RegisterBlock);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.nisier().write(|w| unsafe { w.bits(0) });
+        self.0.eisier().write(|w| unsafe { w.bits(0) });
+        reset_lines(self.0);
+    }
+}
+
+impl<Id: SdhcId, P> Sdhc<Id, P> {
+    /// Turn an [`Sdhc`] into a [`FutureSdhc`], allowing for the use of async
+    /// block transfers. You are required to provide the struct created by the
+    /// [`bind_interrupts`](crate::bind_interrupts) macro to prove that the
+    /// interrupt sources have been correctly configured. This function will
+    /// automatically enable the `SDHCn` NVIC interrupt source.
+    pub fn into_future<I>(self, irqs: I) -> FutureSdhc<Id, P, I>
+    where
+        I: Binding<Id::Interrupt, InterruptHandler<Id>>,
+    {
+        Id::Interrupt::unpend();
+        unsafe { Id::Interrupt::enable() };
+        FutureSdhc { inner: self, irqs }
+    }
+}
+
+/// An [`Sdhc`] capable of `async` block transfers
+pub struct FutureSdhc<Id: SdhcId, P, I> {
+    inner: Sdhc<Id, P>,
+    irqs: I,
+}
+
+impl<Id, P, I> FutureSdhc<Id, P, I>
+where
+    Id: SdhcId,
+    I: Binding<Id::Interrupt, InterruptHandler<Id>>,
+{
+    /// Convert the [`FutureSdhc`] back into a blocking [`Sdhc`], and return
+    /// the IRQs
+    pub fn into_blocking(self) -> (Sdhc<Id, P>, I) {
+        Id::Interrupt::disable();
+        (self.inner, self.irqs)
+    }
+
+    /// Reset the controller, then identify and initialize the card.
+    ///
+    /// See [`Sdhc::init_card`]. This method busy waits.
+    #[inline]
+    pub fn init_card(&mut self) -> Result<CardInfo, Error> {
+        self.inner.init_card()
+    }
+
+    /// Return the information about the initialized card, if any
+    #[inline]
+    pub fn card(&self) -> Option<&CardInfo> {
+        self.inner.card()
+    }
+
+    /// Return the capacity of the initialized card, in blocks
+    #[inline]
+    pub fn num_blocks(&self) -> Result<u32, Error> {
+        self.inner.num_blocks()
+    }
+
+    /// Wait for the end of a data transfer
+    async fn wait_transfer(&mut self) -> Result<(), Error> {
+        let regs = Id::regs();
+        core::future::poll_fn(|cx| {
+            if let Some(result) = self.inner.poll_transfer() {
+                return Poll::Ready(result);
+            }
+
+            Id::waker().register(cx.waker());
+            regs.nisier().write(|w| w.trfc().set_bit());
+            regs.eisier().write(|w| unsafe { w.bits(0xFFFF) });
+
+            if let Some(result) = self.inner.poll_transfer() {
+                return Poll::Ready(result);
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+
+    async fn transfer(
+        &mut self,
+        dir: Direction,
+        start: u32,
+        count: usize,
+        block: impl Fn(usize) -> *mut u8,
+    ) -> Result<(), Error> {
+        let card = self.inner.check_range(start, count)?;
+        let mut done = 0;
+        while done < count {
+            let block = |i: usize| block(done + i);
+            let (n, dma) =
+                self.inner
+                    .start_transfer(&card, dir, start + done as u32, count - done, block)?;
+            if !dma {
+                self.inner.pio(dir, BLOCK_LEN, n, block)?;
+            }
+
+            let guard = AbortOnDrop(Id::regs());
+            let result = self.wait_transfer().await;
+            core::mem::forget(guard);
+            result?;
+            done += n;
+        }
+        Ok(())
+    }
+
+    /// Read blocks from the card, starting at block `start`.
+    ///
+    /// Dropping the returned future before it completes aborts the transfer.
+    pub async fn read_blocks(
+        &mut self,
+        start: u32,
+        blocks: &mut [[u8; BLOCK_LEN]],
+    ) -> Result<(), Error> {
+        let base = blocks.as_mut_ptr();
+        self.transfer(Direction::Read, start, blocks.len(), |i| unsafe {
+            base.add(i) as *mut u8
+        })
+        .await
+    }
+
+    /// Write blocks to the card, starting at block `start`.
+    ///
+    /// Dropping the returned future before it completes aborts the transfer,
+    /// which may leave the card with partially written data.
+    pub async fn write_blocks(
+        &mut self,
+        start: u32,
+        blocks: &[[u8; BLOCK_LEN]],
+    ) -> Result<(), Error> {
+        let base = blocks.as_ptr();
+        self.transfer(Direction::Write, start, blocks.len(), |i| unsafe {
+            base.add(i) as *mut u8
+        })
+        .await
+    }
+}
@@ -0,0 +1,890 @@
+//! # SD/MMC Host Controller
+//!
+//! This module provides a driver for the SD/MMC Host Controller (SDHC) found
+//! on SAMD5x/SAME5x chips, talking to SD cards over a native 4-bit bus.
+//!
+//! Data is moved by the SDHC's own ADMA2 engine: each transfer is described by
+//! a small table of descriptors, so multi-block reads and writes run without
+//! CPU involvement. Buffers which are not word-aligned fall back to programmed
+//! I/O through the buffer data port.
+//!
+//! ```no_run
+//! let (pclk_sdhc, gclk0) = Pclk::enable(tokens.pclks.sdhc0, clocks.gclk0);
+//! let pins = Pins::new(pins.pa08, pins.pb11, pins.pa09, pins.pa10, pins.pa11, pins.pb10);
+//! let mut sdhc = Sdhc::new(peripherals.sdhc0, clocks.ahbs.sdhc0, &pclk_sdhc, pins);
+//!
+//! let card = sdhc.init_card().unwrap();
+//! let mut blocks = [[0; BLOCK_LEN]; 4];
+//! sdhc.read_blocks(0, &mut blocks).unwrap();
+//! ```
+//!
+//! ## Card initialization
+//!
+//! [`Sdhc::init_card`] runs the SD identification sequence at 400 kHz,
+//! selects the card, switches the bus to 4 bits, and then tries to enable
+//! high-speed mode (50 MHz). Cards which don't support high-speed mode are
+//! clocked at 25 MHz. SD version 1 cards and high-capacity (SDHC/SDXC) cards
+//! are supported; MMC cards are not.
+//!
+//! ## `embedded-sdmmc`
+//!
+//! With the `sdmmc` feature enabled, wrapping an initialized [`Sdhc`] in an
+//! [`SdCard`] provides an implementation of
+//! [`embedded_sdmmc::BlockDevice`], so that it can be used with a
+//! `VolumeManager`.
+//!
+//! ## Async
+//!
+//! With the `async` feature enabled, [`Sdhc::into_future`] returns a
+//! [`FutureSdhc`] whose block transfers wait for the `SDHCn` interrupt instead
+//! of polling. Commands which don't transfer data are short, and still busy
+//! wait.
+
+use atsamd_hal_macros::hal_cfg;
+
+use crate::clock::v2::{
+    ahb::{AhbClk, AhbId},
+    pclk::{Pclk, PclkId, PclkSourceId},
+    types::Sdhc0,
+};
+use crate::pac;
+use crate::pac::sdhc0::RegisterBlock;
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+#[hal_cfg("sdhc1")]
+use crate::clock::v2::types::Sdhc1;
+
+mod pin;
+pub use pin::*;
+
+#[cfg(feature = "sdmmc")]
+mod sdmmc;
+#[cfg(feature = "sdmmc")]
+pub use sdmmc::*;
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
+/// Size of a block, in bytes
+pub const BLOCK_LEN: usize = 512;
+
+/// Number of ADMA2 descriptors in the descriptor table
+const NUM_DESCRIPTORS: usize = 8;
+
+/// Maximum number of blocks covered by a single ADMA2 descriptor, whose length
+/// field is 16 bits wide
+const MAX_DESCRIPTOR_BLOCKS: usize = (u16::MAX as usize) / BLOCK_LEN;
+
+/// Number of `ACMD41` attempts before giving up on a card which stays busy
+const INIT_RETRIES: usize = 10_000;
+
+/// Errors that may occur when using the SDHC
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The card did not respond to a command
+    CommandTimeout,
+    /// The response to a command had a bad CRC
+    CommandCrc,
+    /// The response to a command had a bad end bit or index
+    Command,
+    /// The card did not send or acknowledge data in time
+    DataTimeout,
+    /// Received data or the write acknowledgement had a bad CRC
+    DataCrc,
+    /// Received data had a bad end bit
+    Data,
+    /// The automatic `CMD12` ending a multi-block transfer failed
+    AutoCmd12,
+    /// The ADMA2 engine hit an invalid descriptor or a bus error
+    Adma,
+    /// The card reported an error in its status
+    CardStatus(u32),
+    /// The card does not support the host voltage range, or is not an SD card
+    UnsupportedCard,
+    /// No card has been initialized with [`Sdhc::init_card`]
+    NotInitialized,
+    /// The requested blocks are beyond the end of the card
+    OutOfRange,
+}
+
+//==============================================================================
+// SdhcId
+//==============================================================================
+
+/// Type-level trait identifying an SDHC instance
+pub trait SdhcId: PclkId + AhbId + Sealed {
+    /// PAC peripheral struct
+    type Sdhc;
+
+    /// Interrupt source of the SDHC
+    #[cfg(feature = "async")]
+    type Interrupt: crate::async_hal::interrupts::InterruptSource;
+
+    #[doc(hidden)]
+    fn regs() -> &'static RegisterBlock;
+
+    #[cfg(feature = "async")]
+    #[doc(hidden)]
+    fn waker() -> &'static embassy_sync::waitqueue::AtomicWaker;
+}
+
+macro_rules! sdhc_id {
+    ($Id:ident, $Pac:ident, $Irq:ident, $n:literal) => {
+        impl SdhcId for $Id {
+            type Sdhc = pac::$Pac;
+
+            #[cfg(feature = "async")]
+            type Interrupt = crate::async_hal::interrupts::$Irq;
+
+            #[inline]
+            fn regs() -> &'static RegisterBlock {
+                unsafe { &*pac::$Pac::ptr() }
+            }
+
+            #[cfg(feature = "async")]
+            #[inline]
+            fn waker() -> &'static embassy_sync::waitqueue::AtomicWaker {
+                &async_api::WAKERS[$n]
+            }
+        }
+    };
+}
+
+sdhc_id!(Sdhc0, Sdhc0, SDHC0, 0);
+
+#[hal_cfg("sdhc1")]
+sdhc_id!(Sdhc1, Sdhc1, SDHC1, 1);
+
+//==============================================================================
+// Commands
+//==============================================================================
+
+/// Response type of a command
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Response {
+    None,
+    /// R1, R5, R6 and R7
+    R1,
+    /// R1 with busy signalling on `DAT0`
+    R1b,
+    /// 136-bit CID or CSD
+    R2,
+    /// OCR, without CRC
+    R3,
+}
+
+/// Direction of a data transfer
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Read,
+    Write,
+}
+
+const CMD0_GO_IDLE_STATE: u8 = 0;
+const CMD2_ALL_SEND_CID: u8 = 2;
+const CMD3_SEND_RELATIVE_ADDR: u8 = 3;
+const CMD6_SWITCH_FUNC: u8 = 6;
+const CMD7_SELECT_CARD: u8 = 7;
+const CMD8_SEND_IF_COND: u8 = 8;
+const CMD9_SEND_CSD: u8 = 9;
+const CMD16_SET_BLOCKLEN: u8 = 16;
+const CMD17_READ_SINGLE_BLOCK: u8 = 17;
+const CMD18_READ_MULTIPLE_BLOCK: u8 = 18;
+const CMD24_WRITE_BLOCK: u8 = 24;
+const CMD25_WRITE_MULTIPLE_BLOCK: u8 = 25;
+const CMD55_APP_CMD: u8 = 55;
+const ACMD6_SET_BUS_WIDTH: u8 = 6;
+const ACMD41_SD_SEND_OP_COND: u8 = 41;
+
+/// `CMD8` argument: 2.7-3.6V, check pattern `0xAA`
+const IF_COND_ARG: u32 = 0x1AA;
+/// `ACMD41` argument: 3.2-3.4V window
+const OCR_VOLTAGE_WINDOW: u32 = 0x0030_0000;
+/// OCR: host supports high capacity / card is high capacity
+const OCR_CCS: u32 = 1 << 30;
+/// OCR: card power up status (not busy)
+const OCR_READY: u32 = 1 << 31;
+/// R1 card status bits which indicate an error
+const R1_ERRORS: u32 = 0xFDF9_8008;
+
+//==============================================================================
+// ADMA2 descriptors
+//==============================================================================
+
+/// ADMA2 descriptor attribute: valid
+const ATTR_VALID: u16 = 1 << 0;
+/// ADMA2 descriptor attribute: last descriptor
+const ATTR_END: u16 = 1 << 1;
+/// ADMA2 descriptor attribute: transfer data
+const ATTR_TRAN: u16 = 0b10 << 4;
+
+/// 32-bit ADMA2 descriptor
+#[derive(Clone, Copy, Default)]
+#[repr(C, align(4))]
+struct Descriptor {
+    attr: u16,
+    len: u16,
+    addr: u32,
+}
+
+//==============================================================================
+// CardInfo
+//==============================================================================
+
+/// Information about an initialized card
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CardInfo {
+    /// Relative card address
+    pub rca: u16,
+    /// High capacity (SDHC/SDXC) cards are addressed in blocks rather than
+    /// bytes
+    pub high_capacity: bool,
+    /// The card runs in high-speed mode
+    pub high_speed: bool,
+    /// Capacity of the card, in blocks of [`BLOCK_LEN`] bytes
+    pub num_blocks: u32,
+    /// Card identification register, without the CRC
+    pub cid: [u32; 4],
+}
+
+/// Extract bits `msb..=lsb` of a CSD register, as numbered in the SD
+/// specification.
+///
+/// The controller strips the CRC byte from R2 responses, so bit `n` of the
+/// register is stored at bit `n - 8` of the response.
+fn csd_bits(csd: &[u32; 4], msb: usize, lsb: usize) -> u32 {
+    let mut value = 0;
+    for bit in (lsb..=msb).rev() {
+        let pos = bit - 8;
+        value = (value << 1) | ((csd[pos / 32] >> (pos % 32)) & 1);
+    }
+    value
+}
+
+/// Reset the command and data line state machines of an SDHC
+fn reset_lines(regs: &RegisterBlock) {
+    regs.srr()
+        .write(|w| w.swrstcmd().set_bit().swrstdat().set_bit());
+    while regs.srr().read().bits() != 0 {
+        core::hint::spin_loop();
+    }
+}
+
+/// Compute the capacity of a card in blocks from its CSD register
+fn csd_num_blocks(csd: &[u32; 4]) -> u32 {
+    match csd_bits(csd, 127, 126) {
+        // CSD version 2.0: capacity is (C_SIZE + 1) * 512 KiB
+        1 => (csd_bits(csd, 69, 48) + 1) * 1024,
+        // CSD version 1.0
+        _ => {
+            let c_size = csd_bits(csd, 73, 62);
+            let c_size_mult = csd_bits(csd, 49, 47);
+            let read_bl_len = csd_bits(csd, 83, 80);
+            let bytes = ((c_size + 1) as u64) << (c_size_mult + 2 + read_bl_len);
+            (bytes / BLOCK_LEN as u64) as u32
+        }
+    }
+}
+
+//==============================================================================
+// Sdhc
+//==============================================================================
+
+/// SD/MMC Host Controller
+pub struct Sdhc<Id: SdhcId, P> {
+    sdhc: Id::Sdhc,
+    ahbclk: AhbClk<Id>,
+    pins: P,
+    base_freq: Hertz,
+    card: Option<CardInfo>,
+    descriptors: [Descriptor; NUM_DESCRIPTORS],
+}
+
+impl<Id: SdhcId, P> Sdhc<Id, P> {
+    /// Create a new [`Sdhc`], reset the controller and power the card.
+    ///
+    /// `GCLK_SDHCn`, provided through `pclk`, is the base clock from which the
+    /// SD clock is divided. It should run at 50 MHz or more for the card to be
+    /// clocked at full speed. The data timeout is derived from the shared
+    /// `GCLK_SDHCn_SLOW` clock, which must also be enabled.
+    pub fn new<PS: PclkSourceId>(
+        sdhc: Id::Sdhc,
+        ahbclk: AhbClk<Id>,
+        pclk: &Pclk<Id, PS>,
+        pins: P,
+    ) -> Self {
+        let mut sdhc = Self {
+            sdhc,
+            ahbclk,
+            pins,
+            base_freq: pclk.freq(),
+            card: None,
+            descriptors: [Descriptor::default(); NUM_DESCRIPTORS],
+        };
+        sdhc.reset();
+        sdhc
+    }
+
+    #[inline]
+    fn regs(&self) -> &'static RegisterBlock {
+        Id::regs()
+    }
+
+    /// Reset the controller, power the card at 3.3V and start the SD clock at
+    /// 400 kHz.
+    fn reset(&mut self) {
+        let regs = self.regs();
+        regs.srr().write(|w| w.swrstall().set_bit());
+        while regs.srr().read().swrstall().bit_is_set() {
+            core::hint::spin_loop();
+        }
+
+        regs.pcr().write(|w| {
+            w.sdbvsel().variant(pac::sdhc0::pcr::Sdbvselselect::_3v3);
+            w.sdbpwr().set_bit()
+        });
+        // Report every status flag, without signalling interrupts
+        regs.nister().write(|w| unsafe { w.bits(0xFFFF) });
+        regs.eister().write(|w| unsafe { w.bits(0xFFFF) });
+        // Longest data timeout
+        regs.tcr().write(|w| unsafe { w.dtcval().bits(0xE) });
+
+        self.card = None;
+        self.set_clock(Hertz::kHz(400));
+    }
+
+    /// Set the SD clock to the highest frequency not exceeding `freq`, and
+    /// return the actual frequency.
+    fn set_clock(&mut self, freq: Hertz) -> Hertz {
+        let regs = self.regs();
+        let base = self.base_freq.to_Hz();
+        // SDCLK = base / (2 * div), or base if div is 0
+        let div = if base <= freq.to_Hz() {
+            0
+        } else {
+            base.div_ceil(2 * freq.to_Hz()).min(0x3FF)
+        };
+
+        regs.ccr().modify(|_, w| w.sdclken().clear_bit());
+        regs.ccr().write(|w| unsafe {
+            w.sdclkfsel().bits(div as u8);
+            w.usdclkfsel().bits((div >> 8) as u8);
+            w.intclken().set_bit()
+        });
+        while regs.ccr().read().intclks().bit_is_clear() {
+            core::hint::spin_loop();
+        }
+        regs.ccr().modify(|_, w| w.sdclken().set_bit());
+
+        if div == 0 {
+            self.base_freq
+        } else {
+            Hertz::from_raw(base / (2 * div))
+        }
+    }
+
+    /// Return the information about the initialized card, if any
+    #[inline]
+    pub fn card(&self) -> Option<&CardInfo> {
+        self.card.as_ref()
+    }
+
+    /// Return the capacity of the initialized card, in blocks
+    #[inline]
+    pub fn num_blocks(&self) -> Result<u32, Error> {
+        self.card
+            .as_ref()
+            .map(|c| c.num_blocks)
+            .ok_or(Error::NotInitialized)
+    }
+
+    /// Check whether the card detect input reports a card.
+    ///
+    /// Only meaningful if the board routes the card detect switch to the
+    /// `SDCD` pin.
+    #[inline]
+    pub fn card_inserted(&self) -> bool {
+        self.regs().psr().read().cardins().bit_is_set()
+    }
+
+    /// Reset the controller, then identify and initialize the card.
+    ///
+    /// On success, the card is selected, the bus is 4 bits wide and the clock
+    /// runs at 50 MHz if the card supports high-speed mode, or 25 MHz
+    /// otherwise.
+    pub fn init_card(&mut self) -> Result<CardInfo, Error> {
+        self.reset();
+
+        self.command(CMD0_GO_IDLE_STATE, 0, Response::None)?;
+
+        // CMD8 is only answered by version 2.00 or later cards
+        let v2 = match self.command(CMD8_SEND_IF_COND, IF_COND_ARG, Response::R1) {
+            Ok(()) => {
+                if self.response() & 0xFFF != IF_COND_ARG {
+                    return Err(Error::UnsupportedCard);
+                }
+                true
+            }
+            Err(Error::CommandTimeout) => false,
+            Err(e) => return Err(e),
+        };
+
+        let hcs = if v2 { OCR_CCS } else { 0 };
+        let mut ocr = 0;
+        for _ in 0..INIT_RETRIES {
+            self.app_command(0)?;
+            self.command(
+                ACMD41_SD_SEND_OP_COND,
+                hcs | OCR_VOLTAGE_WINDOW,
+                Response::R3,
+            )?;
+            ocr = self.response();
+            if ocr & OCR_READY != 0 {
+                break;
+            }
+        }
+        if ocr & OCR_READY == 0 {
+            return Err(Error::UnsupportedCard);
+        }
+        let high_capacity = ocr & OCR_CCS != 0;
+
+        self.command(CMD2_ALL_SEND_CID, 0, Response::R2)?;
+        let cid = self.long_response();
+
+        self.command(CMD3_SEND_RELATIVE_ADDR, 0, Response::R1)?;
+        let rca = (self.response() >> 16) as u16;
+        let rca_arg = (rca as u32) << 16;
+
+        self.command(CMD9_SEND_CSD, rca_arg, Response::R2)?;
+        let num_blocks = csd_num_blocks(&self.long_response());
+
+        self.command(CMD7_SELECT_CARD, rca_arg, Response::R1b)?;
+        self.check_status()?;
+
+        // 4-bit bus
+        self.app_command(rca_arg)?;
+        self.command(ACMD6_SET_BUS_WIDTH, 0b10, Response::R1)?;
+        self.check_status()?;
+        self.regs().hc1r().modify(|_, w| w.dw().set_bit());
+
+        if !high_capacity {
+            self.command(CMD16_SET_BLOCKLEN, BLOCK_LEN as u32, Response::R1)?;
+            self.check_status()?;
+        }
+
+        let high_speed = self.switch_high_speed()?;
+        if high_speed {
+            self.regs().hc1r().modify(|_, w| w.hsen().set_bit());
+            self.set_clock(Hertz::MHz(50));
+        } else {
+            self.set_clock(Hertz::MHz(25));
+        }
+
+        let card = CardInfo {
+            rca,
+            high_capacity,
+            high_speed,
+            num_blocks,
+            cid,
+        };
+        self.card = Some(card);
+        Ok(card)
+    }
+
+    /// Try to switch the card to high-speed mode with `CMD6`. Returns whether
+    /// the switch succeeded.
+    fn switch_high_speed(&mut self) -> Result<bool, Error> {
+        let mut status = [0u32; 16];
+        // Mode 1 (switch), function group 1 = 1 (high speed), others unchanged
+        self.setup_data(Direction::Read, 64, 1, false);
+        match self.issue_command(CMD6_SWITCH_FUNC, 0x80FF_FFF1, Response::R1, true) {
+            Ok(()) => {}
+            // Version 1.0 cards don't support CMD6
+            Err(Error::CommandTimeout | Error::Command) => return Ok(false),
+            Err(e) => return Err(e),
+        }
+        let ptr = status.as_mut_ptr() as *mut u8;
+        self.pio(Direction::Read, 64, 1, |_| ptr)?;
+        self.wait_transfer()?;
+
+        // The status is sent MSB first: bits 379:376 hold the function
+        // selected in group 1, ie the low nibble of byte 16.
+        let byte16 = status[4].to_le_bytes()[0];
+        Ok(byte16 & 0xF == 1)
+    }
+
+    /// Send `CMD55` ahead of an application command
+    fn app_command(&mut self, rca_arg: u32) -> Result<(), Error> {
+        self.command(CMD55_APP_CMD, rca_arg, Response::R1)
+    }
+
+    /// Check the card status returned in the last R1 response
+    fn check_status(&self) -> Result<(), Error> {
+        let status = self.response();
+        if status & R1_ERRORS != 0 {
+            Err(Error::CardStatus(status))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn response(&self) -> u32 {
+        self.regs().rr(0).read().cmdresp().bits()
+    }
+
+    #[inline]
+    fn long_response(&self) -> [u32; 4] {
+        let regs = self.regs();
+        core::array::from_fn(|i| regs.rr(i).read().cmdresp().bits())
+    }
+
+    /// Issue a command and wait for its response.
+    ///
+    /// For [`Response::R1b`] commands without data, this also waits for the
+    /// card to release `DAT0`.
+    fn command(&mut self, index: u8, arg: u32, response: Response) -> Result<(), Error> {
+        self.issue_command(index, arg, response, false)?;
+        if response == Response::R1b {
+            self.wait_transfer()?;
+        }
+        Ok(())
+    }
+
+    /// Write the command registers and wait for the command to complete.
+    ///
+    /// The transfer mode must already be configured if `data` is `true`.
+    fn issue_command(
+        &mut self,
+        index: u8,
+        arg: u32,
+        response: Response,
+        data: bool,
+    ) -> Result<(), Error> {
+        use pac::sdhc0::cr::Resptypselect;
+
+        let regs = self.regs();
+        let uses_dat = data || response == Response::R1b;
+        loop {
+            let psr = regs.psr().read();
+            if psr.cmdinhc().bit_is_clear() && !(uses_dat && psr.cmdinhd().bit_is_set()) {
+                break;
+            }
+        }
+
+        if !data {
+            regs.tmr().write(|w| w.dmaen().clear_bit());
+        }
+
+        let (resptyp, crc, idx) = match response {
+            Response::None => (Resptypselect::None, false, false),
+            Response::R1 => (Resptypselect::_48Bit, true, true),
+            Response::R1b => (Resptypselect::_48BitBusy, true, true),
+            Response::R2 => (Resptypselect::_136Bit, true, false),
+            Response::R3 => (Resptypselect::_48Bit, false, false),
+        };
+
+        regs.arg1r().write(|w| unsafe { w.arg().bits(arg) });
+        regs.cr().write(|w| unsafe {
+            w.cmdidx().bits(index);
+            w.resptyp().variant(resptyp);
+            w.cmdccen().bit(crc);
+            w.cmdicen().bit(idx);
+            w.dpsel().bit(data)
+        });
+
+        loop {
+            let nistr = regs.nistr().read();
+            if nistr.errint().bit_is_set() {
+                return Err(self.take_error());
+            }
+            if nistr.cmdc().bit_is_set() {
+                regs.nistr().write(|w| w.cmdc().set_bit());
+                return Ok(());
+            }
+        }
+    }
+
+    /// Read and clear the error status, reset the command and data lines, and
+    /// return the corresponding error.
+    fn take_error(&mut self) -> Error {
+        let regs = self.regs();
+        let eistr = regs.eistr().read();
+        let error = if eistr.cmdteo().bit_is_set() {
+            Error::CommandTimeout
+        } else if eistr.cmdcrc().bit_is_set() {
+            Error::CommandCrc
+        } else if eistr.cmdend().bit_is_set() || eistr.cmdidx().bit_is_set() {
+            Error::Command
+        } else if eistr.datteo().bit_is_set() {
+            Error::DataTimeout
+        } else if eistr.datcrc().bit_is_set() {
+            Error::DataCrc
+        } else if eistr.acmd().bit_is_set() {
+            Error::AutoCmd12
+        } else if eistr.adma().bit_is_set() {
+            Error::Adma
+        } else {
+            Error::Data
+        };
+
+        regs.eistr().write(|w| unsafe { w.bits(0xFFFF) });
+        regs.nistr().write(|w| unsafe { w.bits(0xFFFF) });
+        self.reset_lines();
+        error
+    }
+
+    /// Reset the command and data line state machines, aborting any ongoing
+    /// transfer.
+    fn reset_lines(&mut self) {
+        reset_lines(self.regs());
+    }
+
+    /// Configure the block size, block count and transfer mode of the next
+    /// data command.
+    fn setup_data(&mut self, dir: Direction, block_len: u16, count: u16, dma: bool) {
+        use pac::sdhc0::tmr::Acmdenselect;
+
+        let regs = self.regs();
+        let multi = count > 1;
+        regs.bsr()
+            .write(|w| unsafe { w.blocksize().bits(block_len) });
+        regs.bcr().write(|w| unsafe { w.bcnt().bits(count) });
+        if dma {
+            regs.hc1r()
+                .modify(|_, w| w.dmasel().variant(pac::sdhc0::hc1r::Dmaselselect::_32bit));
+            regs.asar(0)
+                .write(|w| unsafe { w.admasa().bits(self.descriptors.as_ptr() as u32) });
+        }
+        regs.tmr().write(|w| {
+            w.dmaen().bit(dma);
+            w.bcen().bit(multi);
+            w.acmden().variant(if multi {
+                Acmdenselect::Cmd12
+            } else {
+                Acmdenselect::Disabled
+            });
+            w.dtdsel().bit(dir == Direction::Read);
+            w.msbsel().bit(multi)
+        });
+    }
+
+    /// Poll for the end of a data transfer or busy signal.
+    ///
+    /// Returns `None` while the transfer is ongoing.
+    fn poll_transfer(&mut self) -> Option<Result<(), Error>> {
+        let regs = self.regs();
+        let nistr = regs.nistr().read();
+        if nistr.errint().bit_is_set() {
+            Some(Err(self.take_error()))
+        } else if nistr.trfc().bit_is_set() {
+            regs.nistr()
+                .write(|w| w.trfc().set_bit().dmaint().set_bit().blkge().set_bit());
+            Some(Ok(()))
+        } else {
+            None
+        }
+    }
+
+    /// Wait for the end of a data transfer or busy signal
+    fn wait_transfer(&mut self) -> Result<(), Error> {
+        loop {
+            if let Some(result) = self.poll_transfer() {
+                return result;
+            }
+        }
+    }
+
+    /// Move `count` blocks of `block_len` bytes through the buffer data port.
+    /// `block` returns a pointer to the `i`th block, which may be unaligned.
+    fn pio(
+        &mut self,
+        dir: Direction,
+        block_len: usize,
+        count: usize,
+        block: impl Fn(usize) -> *mut u8,
+    ) -> Result<(), Error> {
+        let regs = self.regs();
+        for i in 0..count {
+            loop {
+                let nistr = regs.nistr().read();
+                if nistr.errint().bit_is_set() {
+                    return Err(self.take_error());
+                }
+                match dir {
+                    Direction::Read if nistr.brdrdy().bit_is_set() => {
+                        regs.nistr().write(|w| w.brdrdy().set_bit());
+                        break;
+                    }
+                    Direction::Write if nistr.bwrrdy().bit_is_set() => {
+                        regs.nistr().write(|w| w.bwrrdy().set_bit());
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+
+            let ptr = block(i);
+            for offset in (0..block_len).step_by(4) {
+                // SAFETY: The caller guarantees that the block is `block_len`
+                // bytes long and valid for the transfer direction.
+                unsafe {
+                    let word = ptr.add(offset) as *mut [u8; 4];
+                    match dir {
+                        Direction::Read => {
+                            word.write_unaligned(regs.bdpr().read().bits().to_le_bytes())
+                        }
+                        Direction::Write => {
+                            let bits = u32::from_le_bytes(word.read_unaligned());
+                            regs.bdpr().write(|w| w.bits(bits));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill the descriptor table with up to `remaining` blocks, merging blocks
+    /// which are contiguous in memory. Returns the number of blocks covered.
+    fn fill_descriptors(&mut self, remaining: usize, block: impl Fn(usize) -> *mut u8) -> usize {
+        let mut blocks = 0;
+        let mut n = 0;
+        while blocks < remaining && blocks < u16::MAX as usize {
+            let addr = block(blocks) as u32;
+            if n > 0 {
+                let last = &mut self.descriptors[n - 1];
+                if last.addr + last.len as u32 == addr
+                    && (last.len as usize) < MAX_DESCRIPTOR_BLOCKS * BLOCK_LEN
+                {
+                    last.len += BLOCK_LEN as u16;
+                    blocks += 1;
+                    continue;
+                }
+            }
+            if n == NUM_DESCRIPTORS {
+                break;
+            }
+            self.descriptors[n] = Descriptor {
+                attr: ATTR_VALID | ATTR_TRAN,
+                len: BLOCK_LEN as u16,
+                addr,
+            };
+            n += 1;
+            blocks += 1;
+        }
+        self.descriptors[n - 1].attr |= ATTR_END;
+        blocks
+    }
+
+    /// Check that a transfer of `count` blocks starting at `start` is valid,
+    /// and return the initialized card.
+    fn check_range(&self, start: u32, count: usize) -> Result<CardInfo, Error> {
+        let card = self.card.ok_or(Error::NotInitialized)?;
+        if start as u64 + count as u64 > card.num_blocks as u64 {
+            return Err(Error::OutOfRange);
+        }
+        Ok(card)
+    }
+
+    /// Start a transfer of up to `count` blocks, and return the number of
+    /// blocks being transferred along with whether ADMA2 is used.
+    ///
+    /// If ADMA2 is used, the caller must wait for the transfer to complete.
+    /// Otherwise, the caller must move the data with [`Self::pio`].
+    fn start_transfer(
+        &mut self,
+        card: &CardInfo,
+        dir: Direction,
+        start: u32,
+        count: usize,
+        block: impl Fn(usize) -> *mut u8,
+    ) -> Result<(usize, bool), Error> {
+        // ADMA2 requires word-aligned addresses
+        let dma = (0..count).all(|i| block(i) as usize % 4 == 0);
+        let n = if dma {
+            self.fill_descriptors(count, &block)
+        } else {
+            count.min(u16::MAX as usize)
+        };
+
+        let index = match (dir, n > 1) {
+            (Direction::Read, false) => CMD17_READ_SINGLE_BLOCK,
+            (Direction::Read, true) => CMD18_READ_MULTIPLE_BLOCK,
+            (Direction::Write, false) => CMD24_WRITE_BLOCK,
+            (Direction::Write, true) => CMD25_WRITE_MULTIPLE_BLOCK,
+        };
+        let arg = if card.high_capacity {
+            start
+        } else {
+            start * BLOCK_LEN as u32
+        };
+
+        self.setup_data(dir, BLOCK_LEN as u16, n as u16, dma);
+        self.issue_command(index, arg, Response::R1, true)?;
+        self.check_status()?;
+        Ok((n, dma))
+    }
+
+    /// Transfer `count` blocks, starting at block `start`
+    fn transfer(
+        &mut self,
+        dir: Direction,
+        start: u32,
+        count: usize,
+        block: impl Fn(usize) -> *mut u8,
+    ) -> Result<(), Error> {
+        let card = self.check_range(start, count)?;
+        let mut done = 0;
+        while done < count {
+            let block = |i: usize| block(done + i);
+            let (n, dma) =
+                self.start_transfer(&card, dir, start + done as u32, count - done, block)?;
+            if !dma {
+                self.pio(dir, BLOCK_LEN, n, block)?;
+            }
+            self.wait_transfer()?;
+            done += n;
+        }
+        Ok(())
+    }
+
+    /// Read blocks from the card, starting at block `start`.
+    ///
+    /// Consecutive blocks are read with a single multi-block command when
+    /// possible.
+    pub fn read_blocks(&mut self, start: u32, blocks: &mut [[u8; BLOCK_LEN]]) -> Result<(), Error> {
+        let base = blocks.as_mut_ptr();
+        self.transfer(Direction::Read, start, blocks.len(), |i| unsafe {
+            base.add(i) as *mut u8
+        })
+    }
+
+    /// Write blocks to the card, starting at block `start`.
+    ///
+    /// Consecutive blocks are written with a single multi-block command when
+    /// possible.
+    pub fn write_blocks(&mut self, start: u32, blocks: &[[u8; BLOCK_LEN]]) -> Result<(), Error> {
+        let base = blocks.as_ptr();
+        self.transfer(Direction::Write, start, blocks.len(), |i| unsafe {
+            base.add(i) as *mut u8
+        })
+    }
+
+    /// Power off the card, disable the controller and return the underlying
+    /// resources.
+    pub fn free(self) -> (Id::Sdhc, AhbClk<Id>, P) {
+        let regs = self.regs();
+        regs.srr().write(|w| w.swrstall().set_bit());
+        while regs.srr().read().swrstall().bit_is_set() {
+            core::hint::spin_loop();
+        }
+        regs.pcr().write(|w| w.sdbpwr().clear_bit());
+        (self.sdhc, self.ahbclk, self.pins)
+    }
+}
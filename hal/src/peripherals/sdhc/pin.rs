@@ -0,0 +1,133 @@
+//! SDHC pin definitions
+
+use atsamd_hal_macros::hal_cfg;
+
+use super::SdhcId;
+use crate::clock::v2::types::Sdhc0;
+use crate::gpio::{AlternateI, AnyPin, Pin, PinId};
+
+#[hal_cfg("sdhc1")]
+use crate::clock::v2::types::Sdhc1;
+
+/// Type-level trait for [`PinId`]s which can be the `SDCMD` pin of SDHC `S`
+pub trait CmdPinId<S: SdhcId>: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be the `SDCK` pin of SDHC `S`
+pub trait CkPinId<S: SdhcId>: PinId {}
+
+/// Type-level trait for [`PinId`]s which can be the `SDDAT[N]` pin of SDHC
+/// `S`
+pub trait DataPinId<S: SdhcId, const N: usize>: PinId {}
+
+/// Set of pins used by an SDHC in 4-bit mode
+///
+/// The card requires pull-ups on `SDCMD` and `SDDAT[3:0]`. Most boards provide
+/// external resistors; the internal pull-ups are not enabled by this driver.
+pub struct Pins<S, Cmd, Ck, D0, D1, D2, D3>
+where
+    S: SdhcId,
+    Cmd: CmdPinId<S>,
+    Ck: CkPinId<S>,
+    D0: DataPinId<S, 0>,
+    D1: DataPinId<S, 1>,
+    D2: DataPinId<S, 2>,
+    D3: DataPinId<S, 3>,
+{
+    cmd: Pin<Cmd, AlternateI>,
+    ck: Pin<Ck, AlternateI>,
+    data: DataPins<D0, D1, D2, D3>,
+    _sdhc: core::marker::PhantomData<S>,
+}
+
+/// Data pins `SDDAT[3:0]`
+type DataPins<D0, D1, D2, D3> = (
+    Pin<D0, AlternateI>,
+    Pin<D1, AlternateI>,
+    Pin<D2, AlternateI>,
+    Pin<D3, AlternateI>,
+);
+
+impl<S, Cmd, Ck, D0, D1, D2, D3> Pins<S, Cmd, Ck, D0, D1, D2, D3>
+where
+    S: SdhcId,
+    Cmd: CmdPinId<S>,
+    Ck: CkPinId<S>,
+    D0: DataPinId<S, 0>,
+    D1: DataPinId<S, 1>,
+    D2: DataPinId<S, 2>,
+    D3: DataPinId<S, 3>,
+{
+    /// Put the pins in SDHC mode
+    pub fn new(
+        cmd: impl AnyPin<Id = Cmd>,
+        ck: impl AnyPin<Id = Ck>,
+        d0: impl AnyPin<Id = D0>,
+        d1: impl AnyPin<Id = D1>,
+        d2: impl AnyPin<Id = D2>,
+        d3: impl AnyPin<Id = D3>,
+    ) -> Self {
+        Self {
+            cmd: cmd.into().into_mode(),
+            ck: ck.into().into_mode(),
+            data: (
+                d0.into().into_mode(),
+                d1.into().into_mode(),
+                d2.into().into_mode(),
+                d3.into().into_mode(),
+            ),
+            _sdhc: core::marker::PhantomData,
+        }
+    }
+
+    /// Release the pins
+    #[allow(clippy::type_complexity)]
+    pub fn free(
+        self,
+    ) -> (
+        Pin<Cmd, AlternateI>,
+        Pin<Ck, AlternateI>,
+        Pin<D0, AlternateI>,
+        Pin<D1, AlternateI>,
+        Pin<D2, AlternateI>,
+        Pin<D3, AlternateI>,
+    ) {
+        let (d0, d1, d2, d3) = self.data;
+        (self.cmd, self.ck, d0, d1, d2, d3)
+    }
+}
+
+macro_rules! sdhc_pins {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $PinId:ident: $Trait:ident<$Sdhc:ident $(, $n:literal)?>
+        ),+
+        $(,)?
+    ) => {
+        $(
+            $( #[$cfg] )?
+            impl $Trait<$Sdhc $(, $n)?> for crate::gpio::$PinId {}
+        )+
+    };
+}
+
+sdhc_pins! {
+    PA08: CmdPinId<Sdhc0>,
+    PA09: DataPinId<Sdhc0, 0>,
+    PA10: DataPinId<Sdhc0, 1>,
+    PA11: DataPinId<Sdhc0, 2>,
+    #[hal_cfg("pb10")]
+    PB10: DataPinId<Sdhc0, 3>,
+    #[hal_cfg("pb11")]
+    PB11: CkPinId<Sdhc0>,
+}
+
+#[hal_cfg("sdhc1")]
+sdhc_pins! {
+    PA20: CmdPinId<Sdhc1>,
+    PA21: CkPinId<Sdhc1>,
+    PB18: DataPinId<Sdhc1, 0>,
+    PB19: DataPinId<Sdhc1, 1>,
+    PB20: DataPinId<Sdhc1, 2>,
+    PB21: DataPinId<Sdhc1, 3>,
+}
@@ -0,0 +1,64 @@
+//! [`embedded_sdmmc`] support
+
+use core::cell::RefCell;
+
+use embedded_sdmmc::{Block, BlockCount, BlockDevice, BlockIdx};
+
+use super::*;
+
+/// An SD card driven by an [`Sdhc`], usable as an [`embedded_sdmmc`]
+/// [`BlockDevice`]
+///
+/// The card must be initialized with [`Sdhc::init_card`] before being wrapped.
+pub struct SdCard<Id: SdhcId, P> {
+    sdhc: RefCell<Sdhc<Id, P>>,
+}
+
+impl<Id: SdhcId, P> SdCard<Id, P> {
+    /// Wrap an [`Sdhc`] with an initialized card
+    #[inline]
+    pub fn new(sdhc: Sdhc<Id, P>) -> Self {
+        Self {
+            sdhc: RefCell::new(sdhc),
+        }
+    }
+
+    /// Return the underlying [`Sdhc`]
+    #[inline]
+    pub fn free(self) -> Sdhc<Id, P> {
+        self.sdhc.into_inner()
+    }
+}
+
+impl<Id: SdhcId, P> BlockDevice for SdCard<Id, P> {
+    type Error = Error;
+
+    fn read(
+        &self,
+        blocks: &mut [Block],
+        start_block_idx: BlockIdx,
+        _reason: &str,
+    ) -> Result<(), Self::Error> {
+        let base = blocks.as_mut_ptr();
+        self.sdhc.borrow_mut().transfer(
+            Direction::Read,
+            start_block_idx.0,
+            blocks.len(),
+            |i| unsafe { core::ptr::addr_of_mut!((*base.add(i)).contents) as *mut u8 },
+        )
+    }
+
+    fn write(&self, blocks: &[Block], start_block_idx: BlockIdx) -> Result<(), Self::Error> {
+        let base = blocks.as_ptr();
+        self.sdhc.borrow_mut().transfer(
+            Direction::Write,
+            start_block_idx.0,
+            blocks.len(),
+            |i| unsafe { core::ptr::addr_of!((*base.add(i)).contents) as *mut u8 },
+        )
+    }
+
+    fn num_blocks(&self) -> Result<BlockCount, Self::Error> {
+        self.sdhc.borrow().num_blocks().map(BlockCount)
+    }
+}
@@ -0,0 +1,123 @@
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::*;
+use crate::async_hal::interrupts::{Binding, Handler, InterruptSource, SUPC};
+use crate::typelevel::Sealed;
+
+static WAKER: AtomicWaker = AtomicWaker::new();
+
+/// Interrupt handler for the SUPC peripheral.
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Sealed for InterruptHandler {}
+
+impl Handler<SUPC> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        let supc = unsafe { pac::Peripherals::steal().supc };
+
+        let flags_pending = Flags::from_bits_truncate(supc.intflag().read().bits());
+        let enabled_flags = Flags::from_bits_truncate(supc.intenset().read().bits());
+        let flags = flags_pending & enabled_flags;
+
+        // Disable the interrupts but don't clear the flags; they will be cleared
+        // when the future is next polled.
+        supc.intenclr().write(|w| unsafe { w.bits(flags.bits()) });
+        if !flags.is_empty() {
+            WAKER.wake();
+        }
+    }
+}
+
+impl Supc {
+    /// Turn a [`Supc`] into a [`FutureSupc`], allowing for the use of async
+    /// methods. You are required to provide the struct created by the
+    /// [`bind_multiple_interrupts`](crate::bind_multiple_interrupts) macro to
+    /// prove that the interrupt sources have been correctly configured. This
+    /// function will automatically enable the `SUPC_*` NVIC interrupt sources.
+    pub fn into_future<F>(self, irqs: F) -> FutureSupc<F>
+    where
+        F: Binding<SUPC, InterruptHandler>,
+    {
+        SUPC::unpend();
+        unsafe { SUPC::enable() };
+        FutureSupc { inner: self, irqs }
+    }
+}
+
+/// A [`Supc`] capable of `async` operations
+pub struct FutureSupc<F> {
+    inner: Supc,
+    irqs: F,
+}
+
+impl<F> FutureSupc<F>
+where
+    F: Binding<SUPC, InterruptHandler>,
+{
+    /// Convert the [`FutureSupc`] back into a blocking [`Supc`], and return
+    /// the IRQs
+    pub fn into_blocking(self) -> (Supc, F) {
+        SUPC::disable();
+        (self.inner, self.irqs)
+    }
+
+    /// Borrow the underlying [`Supc`], to change its configuration
+    #[inline]
+    pub fn supc(&mut self) -> &mut Supc {
+        &mut self.inner
+    }
+
+    /// Wait until one of `flags` is raised, and return the raised flags.
+    ///
+    /// Flags raised before this method is called are ignored.
+    pub async fn wait(&mut self, flags: Flags) -> Flags {
+        self.inner.clear_flags(flags);
+        core::future::poll_fn(|cx| {
+            let raised = self.inner.read_flags() & flags;
+            if !raised.is_empty() {
+                self.inner.clear_flags(raised);
+                return Poll::Ready(raised);
+            }
+
+            WAKER.register(cx.waker());
+            self.inner.enable_interrupts(flags);
+
+            let raised = self.inner.read_flags() & flags;
+            if !raised.is_empty() {
+                self.inner.clear_flags(raised);
+                return Poll::Ready(raised);
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Wait until `VDD` drops below the BOD33 threshold
+    ///
+    /// Returns immediately if `VDD` is already below the threshold. The BOD33
+    /// must be enabled with [`Bod33Action::Interrupt`].
+    pub async fn wait_brown_out(&mut self) {
+        self.inner.clear_flags(Flags::BOD33DET);
+        core::future::poll_fn(|cx| {
+            if self.inner.is_brown_out() {
+                return Poll::Ready(());
+            }
+
+            WAKER.register(cx.waker());
+            self.inner.enable_interrupts(Flags::BOD33DET);
+
+            if self.inner.is_brown_out() || self.inner.read_flags().contains(Flags::BOD33DET) {
+                self.inner.clear_flags(Flags::BOD33DET);
+                return Poll::Ready(());
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+}
@@ -0,0 +1,474 @@
+//! # Supply Controller
+//!
+//! This module provides a driver for the Supply Controller (SUPC) found on
+//! SAMD5x/SAME5x chips. The SUPC manages:
+//!
+//! * The 3.3V brown-out detector, BOD33, which monitors `VDD` and can reset
+//!   the chip, raise an interrupt or enter backup sleep mode when it drops
+//!   below a threshold. See [`Bod33Config`].
+//! * The main voltage regulator, which can either be the internal LDO or a
+//!   buck converter, see [`Supc::set_regulator`].
+//! * The internal voltage reference used by the ADC and DAC, see
+//!   [`VrefConfig`].
+//! * The battery backup power switch, which powers the backup domain from
+//!   `VBAT` when `VDD` fails, see [`Supc::set_backup_power_switch`], and the
+//!   two backup output pins `OUT[1:0]`, see [`BackupOutput`].
+//!
+//! ```no_run
+//! let mut supc = Supc::new(peripherals.supc, clocks.apbs.sup_c);
+//!
+//! // Interrupt instead of resetting when VDD drops
+//! let config = Bod33Config::new(BOD33_LEVEL).action(Bod33Action::Interrupt);
+//! supc.configure_bod33(config);
+//! supc.enable_interrupts(Flags::BOD33DET);
+//! ```
+//!
+//! ## Early brown-out warning
+//!
+//! Setting the BOD33 threshold above the voltage at which the chip stops
+//! operating, with [`Bod33Action::Interrupt`], leaves some time to save state
+//! before power is lost. With the `async` feature enabled,
+//! [`FutureSupc::wait_brown_out`] waits for this interrupt.
+//!
+//! Note that the BOD33 is configured from the NVM user row at reset. Before
+//! reconfiguring it, the BOD33 is disabled, so `VDD` is not monitored for a
+//! few microseconds.
+//!
+//! ## Temperature sensor
+//!
+//! [`Adc::read_cpu_temperature`](crate::adc::Adc::read_cpu_temperature) needs
+//! the PAC `Supc`, which can be recovered with [`Supc::free`].
+
+use crate::clock::v2::{apb::ApbClk, types::SupC};
+use crate::pac;
+use crate::pac::supc::RegisterBlock;
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
+pub use pac::supc::bod33::Pselselect as Bod33Prescaler;
+pub use pac::supc::vref::Selselect as VrefLevel;
+
+bitflags::bitflags! {
+    /// SUPC interrupt flags
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Flags: u32 {
+        /// BOD33 is ready
+        const BOD33RDY = 1 << 0;
+        /// `VDD` is below the BOD33 threshold
+        const BOD33DET = 1 << 1;
+        /// BOD33 configuration can be written
+        const B33SRDY = 1 << 2;
+        /// The voltage regulator is ready after a switch
+        const VREGRDY = 1 << 8;
+        /// The core voltage is ready
+        const VCORERDY = 1 << 10;
+    }
+}
+
+//==============================================================================
+// BOD33
+//==============================================================================
+
+/// Action taken when `VDD` drops below the BOD33 threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Bod33Action {
+    /// Only set [`Flags::BOD33DET`]
+    None,
+    /// Reset the chip
+    Reset,
+    /// Raise the `SUPC_BODDET` interrupt
+    Interrupt,
+    /// Enter backup sleep mode
+    Backup,
+}
+
+/// Operating mode of the BOD33 in standby sleep mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bod33Mode {
+    /// Monitor `VDD` continuously
+    Continuous,
+    /// Sample `VDD` periodically, using the 1 kHz `OSCULP32K` output divided
+    /// by the prescaler. This reduces the power consumption.
+    Sampling(Bod33Prescaler),
+}
+
+// The PAC prescaler doesn't implement `defmt::Format`, so it is logged as its
+// raw value
+#[cfg(feature = "defmt")]
+impl defmt::Format for Bod33Mode {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Bod33Mode::Continuous => defmt::write!(f, "Continuous"),
+            Bod33Mode::Sampling(prescaler) => {
+                defmt::write!(f, "Sampling({=u8})", u8::from(*prescaler))
+            }
+        }
+    }
+}
+
+/// BOD33 configuration
+#[derive(Debug, Clone, Copy)]
+pub struct Bod33Config {
+    level: u8,
+    vbat_level: u8,
+    hysteresis: u8,
+    action: Bod33Action,
+    standby_mode: Bod33Mode,
+    run_in_standby: bool,
+    run_in_hibernate: bool,
+    run_in_backup: bool,
+}
+
+impl Bod33Config {
+    /// Create a configuration with the `VDD` threshold `level`, which resets
+    /// the chip when `VDD` drops below it
+    ///
+    /// The threshold voltage corresponding to `level` is given in the
+    /// electrical characteristics of the datasheet. The `VBAT` threshold used
+    /// in backup mode is set to the same level.
+    pub fn new(level: u8) -> Self {
+        Self {
+            level,
+            vbat_level: level,
+            hysteresis: 0,
+            action: Bod33Action::Reset,
+            standby_mode: Bod33Mode::Continuous,
+            run_in_standby: true,
+            run_in_hibernate: false,
+            run_in_backup: false,
+        }
+    }
+
+    /// Set the `VDD` threshold
+    pub fn level(mut self, level: u8) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Set the `VBAT` threshold, monitored in backup sleep mode
+    pub fn vbat_level(mut self, level: u8) -> Self {
+        self.vbat_level = level;
+        self
+    }
+
+    /// Set the hysteresis added to the threshold when `VDD` rises again, from
+    /// 0 (no hysteresis) to 15
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hysteresis` is greater than 15.
+    pub fn hysteresis(mut self, hysteresis: u8) -> Self {
+        assert!(hysteresis <= 15, "BOD33 hysteresis must fit in 4 bits");
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Set the action taken when `VDD` drops below the threshold
+    pub fn action(mut self, action: Bod33Action) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Set the operating mode in standby sleep mode. The BOD33 always
+    /// monitors `VDD` continuously in active mode.
+    pub fn standby_mode(mut self, mode: Bod33Mode) -> Self {
+        self.standby_mode = mode;
+        self
+    }
+
+    /// Keep the BOD33 running in standby sleep mode
+    pub fn run_in_standby(mut self, run: bool) -> Self {
+        self.run_in_standby = run;
+        self
+    }
+
+    /// Keep the BOD33 running in hibernate sleep mode
+    pub fn run_in_hibernate(mut self, run: bool) -> Self {
+        self.run_in_hibernate = run;
+        self
+    }
+
+    /// Keep the BOD33 running in backup sleep mode, monitoring `VBAT`
+    pub fn run_in_backup(mut self, run: bool) -> Self {
+        self.run_in_backup = run;
+        self
+    }
+}
+
+//==============================================================================
+// Regulator, VREF and backup domain
+//==============================================================================
+
+/// Main voltage regulator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Regulator {
+    /// Linear regulator, the default
+    Ldo,
+    /// Switching regulator, which needs an inductor on `VSW`
+    Buck,
+}
+
+/// Internal voltage reference configuration
+#[derive(Debug, Clone, Copy)]
+pub struct VrefConfig {
+    level: VrefLevel,
+    output: bool,
+    on_demand: bool,
+    run_in_standby: bool,
+}
+
+impl VrefConfig {
+    /// Create a configuration with the reference at `level`, only running
+    /// when requested by a peripheral
+    pub fn new(level: VrefLevel) -> Self {
+        Self {
+            level,
+            output: false,
+            on_demand: true,
+            run_in_standby: false,
+        }
+    }
+
+    /// Output the reference on the ADC's `VREFA` pin
+    pub fn output(mut self, output: bool) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Only run the reference when requested by a peripheral. Otherwise, it
+    /// is always running.
+    pub fn on_demand(mut self, on_demand: bool) -> Self {
+        self.on_demand = on_demand;
+        self
+    }
+
+    /// Keep the reference running in standby sleep mode
+    pub fn run_in_standby(mut self, run: bool) -> Self {
+        self.run_in_standby = run;
+        self
+    }
+}
+
+/// Condition under which the backup domain is powered from `VBAT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BackupPowerSwitch {
+    /// Switch to `VBAT` when the BOD33 detects that `VDD` dropped below its
+    /// threshold. The BOD33 must be enabled.
+    Bod33,
+    /// Always power the backup domain from `VBAT`
+    Forced,
+}
+
+/// Backup output pin `OUT[n]`, driven by the SUPC even in backup sleep mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BackupOutput {
+    /// `OUT[0]`
+    Out0,
+    /// `OUT[1]`
+    Out1,
+}
+
+//==============================================================================
+// Supc
+//==============================================================================
+
+/// Supply Controller
+pub struct Supc {
+    supc: pac::Supc,
+    apbclk: ApbClk<SupC>,
+}
+
+impl Supc {
+    /// Create the SUPC driver. The current configuration is not changed.
+    pub fn new(supc: pac::Supc, apbclk: ApbClk<SupC>) -> Self {
+        Self { supc, apbclk }
+    }
+
+    #[inline]
+    fn regs(&self) -> &RegisterBlock {
+        &self.supc
+    }
+
+    /// Disable the BOD33, and wait until it can be reconfigured
+    pub fn disable_bod33(&mut self) {
+        let regs = self.regs();
+        regs.bod33().modify(|_, w| w.enable().clear_bit());
+        while regs.status().read().b33srdy().bit_is_clear() {}
+    }
+
+    /// Configure and enable the BOD33, and wait until it is ready
+    pub fn configure_bod33(&mut self, config: Bod33Config) {
+        self.disable_bod33();
+
+        let regs = self.regs();
+        regs.bod33().write(|w| {
+            match config.action {
+                Bod33Action::None => w.action().none(),
+                Bod33Action::Reset => w.action().reset(),
+                Bod33Action::Interrupt => w.action().int(),
+                Bod33Action::Backup => w.action().bkup(),
+            };
+            match config.standby_mode {
+                Bod33Mode::Continuous => w.stdbycfg().clear_bit(),
+                Bod33Mode::Sampling(prescaler) => {
+                    w.psel().variant(prescaler);
+                    w.stdbycfg().set_bit()
+                }
+            };
+            w.runstdby().bit(config.run_in_standby);
+            w.runhib().bit(config.run_in_hibernate);
+            w.runbkup().bit(config.run_in_backup);
+            // SAFETY: Every value is valid, and the hysteresis was checked to
+            // fit in 4 bits.
+            unsafe {
+                w.hyst().bits(config.hysteresis);
+                w.level().bits(config.level);
+                w.vbatlevel().bits(config.vbat_level)
+            }
+        });
+        while regs.status().read().b33srdy().bit_is_clear() {}
+
+        regs.bod33().modify(|_, w| w.enable().set_bit());
+        while regs.status().read().bod33rdy().bit_is_clear() {}
+    }
+
+    /// Check whether `VDD` is currently below the BOD33 threshold
+    #[inline]
+    pub fn is_brown_out(&self) -> bool {
+        self.regs().status().read().bod33det().bit_is_set()
+    }
+
+    /// Switch the main voltage regulator, and wait until the new one is ready
+    ///
+    /// The buck converter must only be selected if the board has an inductor
+    /// on the `VSW` pin.
+    pub fn set_regulator(&mut self, regulator: Regulator) {
+        let regs = self.regs();
+        regs.intflag().write(|w| w.vregrdy().set_bit());
+        regs.vreg().modify(|_, w| match regulator {
+            Regulator::Ldo => w.sel().ldo(),
+            Regulator::Buck => w.sel().buck(),
+        });
+        // STATUS.VREGRDY may still report the previous regulator, while the
+        // flag is only raised once the new one is ready
+        while regs.intflag().read().vregrdy().bit_is_clear() {}
+    }
+
+    /// Return the main voltage regulator in use
+    #[inline]
+    pub fn regulator(&self) -> Regulator {
+        if self.regs().vreg().read().sel().is_buck() {
+            Regulator::Buck
+        } else {
+            Regulator::Ldo
+        }
+    }
+
+    /// Configure the internal voltage reference
+    ///
+    /// The temperature sensor settings, which share the register, are kept.
+    pub fn set_vref(&mut self, config: VrefConfig) {
+        self.regs().vref().modify(|_, w| {
+            w.sel().variant(config.level);
+            w.vrefoe().bit(config.output);
+            w.ondemand().bit(config.on_demand);
+            w.runstdby().bit(config.run_in_standby)
+        });
+    }
+
+    /// Set when the backup domain is powered from `VBAT`. If `wake` is set,
+    /// switching to `VBAT` wakes the chip up from backup sleep mode.
+    pub fn set_backup_power_switch(&mut self, switch: BackupPowerSwitch, wake: bool) {
+        self.regs().bbps().write(|w| {
+            match switch {
+                BackupPowerSwitch::Bod33 => w.conf().bod33(),
+                BackupPowerSwitch::Forced => w.conf().forced(),
+            };
+            w.wakeen().bit(wake)
+        });
+    }
+
+    /// Drive the backup output `out` with the SUPC. If `rtc_toggle` is set,
+    /// the output toggles on each RTC toggle event.
+    pub fn enable_backup_output(&mut self, out: BackupOutput, rtc_toggle: bool) {
+        self.regs().bkout().modify(|_, w| match out {
+            BackupOutput::Out0 => w.enout0().set_bit().rtctglout0().bit(rtc_toggle),
+            BackupOutput::Out1 => w.enout1().set_bit().rtctglout1().bit(rtc_toggle),
+        });
+    }
+
+    /// Stop driving the backup output `out`, which is then controlled by
+    /// the PORT again
+    pub fn disable_backup_output(&mut self, out: BackupOutput) {
+        self.regs().bkout().modify(|_, w| match out {
+            BackupOutput::Out0 => w.enout0().clear_bit().rtctglout0().clear_bit(),
+            BackupOutput::Out1 => w.enout1().clear_bit().rtctglout1().clear_bit(),
+        });
+    }
+
+    /// Set the level of the backup output `out`
+    pub fn set_backup_output(&mut self, out: BackupOutput, high: bool) {
+        self.regs().bkout().modify(|_, w| match (out, high) {
+            (BackupOutput::Out0, true) => w.setout0().set_bit(),
+            (BackupOutput::Out0, false) => w.clrout0().set_bit(),
+            (BackupOutput::Out1, true) => w.setout1().set_bit(),
+            (BackupOutput::Out1, false) => w.clrout1().set_bit(),
+        });
+    }
+
+    /// Read the level of the backup pin `OUT[n]`
+    #[inline]
+    pub fn read_backup_input(&self, out: BackupOutput) -> bool {
+        let bkin = self.regs().bkin().read();
+        match out {
+            BackupOutput::Out0 => bkin.bkin0().bit_is_set(),
+            BackupOutput::Out1 => bkin.bkin1().bit_is_set(),
+        }
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.regs().intflag().read().bits())
+    }
+
+    /// Clear interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        // SAFETY: Writing 1 to a flag clears it, and 0 has no effect
+        self.regs()
+            .intflag()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable the interrupts set in `flags`. [`Flags::BOD33DET`] is routed to
+    /// the `SUPC_BODDET` interrupt, and the other flags to `SUPC_OTHER`.
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        // SAFETY: Writing 1 to a bit enables the interrupt, and 0 has no effect
+        self.regs()
+            .intenset()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the interrupts set in `flags`
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        // SAFETY: Writing 1 to a bit disables the interrupt, and 0 has no effect
+        self.regs()
+            .intenclr()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Return the underlying resources. The SUPC configuration is kept.
+    pub fn free(self) -> (pac::Supc, ApbClk<SupC>) {
+        (self.supc, self.apbclk)
+    }
+}
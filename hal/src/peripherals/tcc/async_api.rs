@@ -0,0 +1,105 @@
+use core::marker::PhantomData;
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::*;
+use crate::async_hal::interrupts::{Binding, Handler, InterruptSource};
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_WAKER: AtomicWaker = AtomicWaker::new();
+pub(super) static WAKERS: [AtomicWaker; 5] = [NEW_WAKER; 5];
+
+/// Interrupt handler for a TCC peripheral.
+pub struct InterruptHandler<T: TccId> {
+    _private: (),
+    _tcc: PhantomData<T>,
+}
+
+impl<T: TccId> Sealed for InterruptHandler<T> {}
+
+impl<T: TccId> Handler<T::Interrupt> for InterruptHandler<T> {
+    unsafe fn on_interrupt() {
+        let regs = T::regs();
+        let pending = regs.intflag().read().bits() & regs.intenset().read().bits();
+
+        // Disable the interrupts but don't clear the flags; they will be checked
+        // and cleared when the future is next polled.
+        if pending != 0 {
+            regs.intenclr().write(|w| unsafe { w.bits(pending) });
+            T::waker().wake();
+        }
+    }
+}
+
+impl<T: TccId, P: OutputPins<T>> Tcc<T, P> {
+    /// Turn a [`Tcc`] into a [`FutureTcc`], allowing for the use of async
+    /// methods. You are required to provide the struct created by the
+    /// [`bind_interrupts`](crate::bind_interrupts) macro to prove that the
+    /// interrupt sources have been correctly configured. This function will
+    /// automatically enable the `TCCn` NVIC interrupt sources.
+    pub fn into_future<I>(self, irqs: I) -> FutureTcc<T, P, I>
+    where
+        I: Binding<T::Interrupt, InterruptHandler<T>>,
+    {
+        T::Interrupt::unpend();
+        unsafe { T::Interrupt::enable() };
+        FutureTcc { inner: self, irqs }
+    }
+}
+
+/// A [`Tcc`] capable of `async` operations
+pub struct FutureTcc<T: TccId, P: OutputPins<T>, I> {
+    inner: Tcc<T, P>,
+    irqs: I,
+}
+
+impl<T, P, I> FutureTcc<T, P, I>
+where
+    T: TccId,
+    P: OutputPins<T>,
+    I: Binding<T::Interrupt, InterruptHandler<T>>,
+{
+    /// Convert the [`FutureTcc`] back into a blocking [`Tcc`], and return the
+    /// IRQs
+    pub fn into_blocking(mut self) -> (Tcc<T, P>, I) {
+        T::Interrupt::disable();
+        self.inner.disable_interrupts(Flags::all());
+        (self.inner, self.irqs)
+    }
+
+    /// Borrow the underlying [`Tcc`], to change the period, duty cycles or
+    /// configuration
+    #[inline]
+    pub fn tcc(&mut self) -> &mut Tcc<T, P> {
+        &mut self.inner
+    }
+
+    /// Wait until any of the interrupt `flags` is raised, then clear and
+    /// return the raised flags among `flags`
+    ///
+    /// For example, waiting for [`Flags::OVF`] before calling
+    /// [`Tcc::set_duty`] updates the duty cycle once per period.
+    pub async fn wait(&mut self, flags: Flags) -> Flags {
+        core::future::poll_fn(|cx| {
+            let raised = self.inner.read_flags() & flags;
+            if !raised.is_empty() {
+                self.inner.clear_flags(raised);
+                return Poll::Ready(raised);
+            }
+
+            T::waker().register(cx.waker());
+            self.inner.enable_interrupts(flags);
+
+            let raised = self.inner.read_flags() & flags;
+            if !raised.is_empty() {
+                self.inner.disable_interrupts(flags);
+                self.inner.clear_flags(raised);
+                return Poll::Ready(raised);
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+}
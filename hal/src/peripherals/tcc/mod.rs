@@ -0,0 +1,1354 @@
+//! # Timer/Counter for Control Applications
+//!
+//! This module provides a driver for the TCC peripherals found on SAMD21 and
+//! SAMD5x/SAME5x chips. Where the [`pwm`](crate::pwm) module only offers a
+//! single-slope PWM at a given frequency, [`Tcc`] exposes the features needed
+//! for motor control and power conversion:
+//!
+//! * single-slope, dual-slope (centre-aligned) and frequency waveforms, see
+//!   [`Waveform`]
+//! * double-buffered period and duty cycle updates, applied at the end of a
+//!   period so that no glitch is produced, see [`Tcc::set_duty`] and
+//!   [`Tcc::update_atomically`]
+//! * circular buffering of the period and compare values
+//! * recoverable and non-recoverable fault inputs, driven by EVSYS events
+//!   from an AC, an EIC channel or any other event generator
+//! * dithering, to increase the PWM resolution
+//! * output matrix, dead-time insertion, output swapping and pattern
+//!   generation, on TCC0 and TCC1 (SAMx5x) or TCC0 only (SAMD21)
+//!
+//! On SAMx5x chips, a [`Tcc`] takes the APB clock and the `GCLK_TCCn`
+//! `Pclk` of its instance:
+//!
+//! ```no_run
+//! let apb_tcc0 = buses.apb.enable(tokens.apbs.tcc0);
+//! let (pclk_tcc0, gclk0) = Pclk::enable(tokens.pclks.tcc0_tcc1, clocks.gclk0);
+//!
+//! // Complementary outputs on WO[0] and WO[4]
+//! let pins = (WoPin::new(pins.pa20), WoPin::new(pins.pa16));
+//! // 20 kHz centre-aligned PWM from a 120 MHz clock
+//! let config = Config::new(3000).waveform(Waveform::DualSlopeBottom);
+//! let mut tcc = Tcc::new(peripherals.tcc0, apb_tcc0, &pclk_tcc0, pins, config);
+//! // 500 ns of dead time on the low and high sides of pair 0
+//! tcc.set_dead_time(0b0001, 60, 60);
+//! tcc.set_duty(0, 1500);
+//! tcc.enable();
+//! ```
+//!
+//! On SAMD21 chips, it takes the clock token returned by the
+//! `GenericClockController`, and enables its APB clock through the `PM`:
+//!
+//! ```no_run
+//! let gclk0 = clocks.gclk0();
+//! let clock = clocks.tcc0_tcc1(&gclk0).unwrap();
+//!
+//! let pins = (WoPin::new(pins.pa04), WoPin::new(pins.pa14));
+//! let config = Config::new(2400).waveform(Waveform::DualSlopeBottom);
+//! let mut tcc = Tcc::new(peripherals.tcc0, &clock, &mut peripherals.pm, pins, config);
+//! tcc.set_dead_time(0b0001, 24, 24);
+//! tcc.set_duty(0, 1200);
+//! tcc.enable();
+//! ```
+//!
+//! ## Waveform outputs
+//!
+//! Each TCC drives up to 8 waveform outputs `WO[n]`. A [`WoPin`] configures a
+//! pin as one of these outputs, and the [`Tcc`] takes ownership of a set of
+//! [`OutputPins`]. By default, `WO[n]` is driven by compare channel
+//! `n % CC_NUM`, which can be changed with the output matrix on TCC0 and
+//! TCC1.
+//!
+//! ## Dead-time insertion
+//!
+//! On TCC0 and TCC1, the outputs `WO[n]` and `WO[n + WO_NUM / 2]` form pair
+//! `n`. With dead-time insertion enabled, the low side `WO[n]` and the high
+//! side `WO[n + WO_NUM / 2]` are complementary, and are both held low for the
+//! dead time after each transition of compare channel `n`.
+//!
+//! ## Faults
+//!
+//! The recoverable faults A and B are driven by the match or capture event
+//! inputs 0 and 1. They can halt the outputs until the fault is gone or until
+//! it is cleared by software, see [`RecoverableFault`]. The non-recoverable
+//! faults 0 and 1 are driven by the event inputs 0 and 1, and force the
+//! outputs to the values set with [`Tcc::set_fault_outputs`] until the fault
+//! state is cleared with [`Tcc::clear_faults`].
+//!
+//! ## Async
+//!
+//! With the `async` feature enabled, [`Tcc::into_future`] returns a
+//! [`FutureTcc`] which can wait for interrupt flags, such as the end of a
+//! period or a fault.
+
+use atsamd_hal_macros::{hal_cfg, hal_macro_helper};
+use bitflags::bitflags;
+
+#[hal_cfg("tcc0-d21")]
+use crate::clock;
+#[hal_cfg("tcc0-d5x")]
+use crate::clock::v2::{
+    apb::{ApbClk, ApbId},
+    pclk::{Pclk, PclkId, PclkSourceId},
+    types::{Tcc0, Tcc0Tcc1, Tcc1, Tcc2, Tcc2Tcc3},
+};
+use crate::pac;
+#[hal_cfg("tcc0-d21")]
+use crate::pac::Pm;
+use crate::pac::tcc0::RegisterBlock;
+use crate::pac::tcc0::ctrlbset::Cmdselect;
+use crate::pac::tcc0::wave::Wavegenselect;
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+#[hal_cfg("tcc3")]
+use crate::clock::v2::types::Tcc3;
+#[hal_cfg("tcc4")]
+use crate::clock::v2::types::Tcc4;
+
+mod pin;
+pub use pin::*;
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
+pub use pac::tcc0::ctrla::{Prescalerselect as Prescaler, Resolutionselect as Resolution};
+pub use pac::tcc0::fctrla::Blankselect as Blanking;
+pub use pac::tcc0::wave::Rampselect as Ramp;
+
+bitflags! {
+    /// TCC interrupt flags
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct Flags: u32 {
+        /// Overflow or underflow, at the end of each period
+        const OVF = 1 << 0;
+        /// The counter was retriggered
+        const TRG = 1 << 1;
+        /// Counter event
+        const CNT = 1 << 2;
+        /// A capture overwrote an unread value
+        const ERR = 1 << 3;
+        /// Non-recoverable update fault (SAMx5x only)
+        const UFS = 1 << 10;
+        /// Non-recoverable debug fault
+        const DFS = 1 << 11;
+        /// Recoverable fault A
+        const FAULTA = 1 << 12;
+        /// Recoverable fault B
+        const FAULTB = 1 << 13;
+        /// Non-recoverable fault 0
+        const FAULT0 = 1 << 14;
+        /// Non-recoverable fault 1
+        const FAULT1 = 1 << 15;
+        /// Match or capture on channel 0
+        const MC0 = 1 << 16;
+        /// Match or capture on channel 1
+        const MC1 = 1 << 17;
+        /// Match or capture on channel 2
+        const MC2 = 1 << 18;
+        /// Match or capture on channel 3
+        const MC3 = 1 << 19;
+        /// Match or capture on channel 4 (SAMx5x only)
+        const MC4 = 1 << 20;
+        /// Match or capture on channel 5 (SAMx5x only)
+        const MC5 = 1 << 21;
+    }
+}
+
+bitflags! {
+    /// Fault states, from the `STATUS` register
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct Faults: u32 {
+        /// Recoverable fault A occurred
+        const A = 1 << 12;
+        /// Recoverable fault B occurred
+        const B = 1 << 13;
+        /// Non-recoverable fault 0 occurred
+        const NR0 = 1 << 14;
+        /// Non-recoverable fault 1 occurred
+        const NR1 = 1 << 15;
+    }
+}
+
+/// Waveform generation operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Waveform {
+    /// Normal frequency: each output toggles on a match of its channel
+    NormalFrequency,
+    /// Match frequency: the period is set by `CC0` instead of `PER`
+    MatchFrequency,
+    /// Single-slope PWM: the counter counts up to `PER` and restarts at 0
+    SingleSlope,
+    /// Dual-slope PWM, with an update at the bottom of each period only
+    DualSlopeCritical,
+    /// Centre-aligned dual-slope PWM: the counter counts up to `PER` then
+    /// down to 0. Buffers are updated, and the period ends, at the bottom.
+    DualSlopeBottom,
+    /// Dual-slope PWM, with the period ending at both the top and the bottom
+    DualSlopeBoth,
+    /// Dual-slope PWM, with the period ending at the top
+    DualSlopeTop,
+}
+
+impl Waveform {
+    #[inline]
+    fn wavegen(self) -> Wavegenselect {
+        match self {
+            Waveform::NormalFrequency => Wavegenselect::Nfrq,
+            Waveform::MatchFrequency => Wavegenselect::Mfrq,
+            Waveform::SingleSlope => Wavegenselect::Npwm,
+            Waveform::DualSlopeCritical => Wavegenselect::Dscritical,
+            Waveform::DualSlopeBottom => Wavegenselect::Dsbottom,
+            Waveform::DualSlopeBoth => Wavegenselect::Dsboth,
+            Waveform::DualSlopeTop => Wavegenselect::Dstop,
+        }
+    }
+
+    /// Check whether the counter counts both up and down
+    #[inline]
+    pub fn is_dual_slope(self) -> bool {
+        matches!(
+            self,
+            Waveform::DualSlopeCritical
+                | Waveform::DualSlopeBottom
+                | Waveform::DualSlopeBoth
+                | Waveform::DualSlopeTop
+        )
+    }
+}
+
+/// Output matrix, selecting which compare channel drives each output
+///
+/// The channels driving `WO[7]` to `WO[0]` are, for each variant:
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OutputMatrix {
+    /// `CC3 CC2 CC1 CC0 CC3 CC2 CC1 CC0`, the default
+    Default = 0,
+    /// `CC1 CC0 CC1 CC0 CC1 CC0 CC1 CC0`
+    Alternate = 1,
+    /// `CC0` drives all the outputs
+    Cc0 = 2,
+    /// `CC1` drives all the outputs but `WO[0]`, which is driven by `CC0`
+    Cc0Cc1 = 3,
+}
+
+/// Recoverable fault input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecoverableInput {
+    /// Fault A, driven by match or capture event input 0
+    A,
+    /// Fault B, driven by match or capture event input 1
+    B,
+}
+
+/// Non-recoverable fault input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NonRecoverableInput {
+    /// Fault 0, driven by event input 0
+    Nr0,
+    /// Fault 1, driven by event input 1
+    Nr1,
+}
+
+/// Source of a recoverable fault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FaultSource {
+    /// The fault is active when the event is high
+    Event,
+    /// The fault is active when the event is low
+    InvertedEvent,
+    /// The fault is active when the event of the other recoverable input is
+    /// high
+    AltEvent,
+}
+
+/// Action taken on a recoverable fault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FaultHalt {
+    /// The outputs are not halted
+    Disabled,
+    /// The outputs are halted while the fault is active
+    Hardware,
+    /// The outputs are halted until the fault is gone and cleared with
+    /// [`Tcc::clear_faults`]
+    Software,
+    /// The fault is handled as a non-recoverable fault
+    NonRecoverable,
+}
+
+/// Capture action taken on a recoverable fault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FaultCapture {
+    /// The counter value is not captured
+    Disabled,
+    /// Capture the counter value on each fault
+    Capture,
+    /// Capture the counter value on a fault if it is lower than the last
+    /// captured value
+    Minimum,
+    /// Capture the counter value on a fault if it is higher than the last
+    /// captured value
+    Maximum,
+    /// Capture the counter value on a fault if it is a local minimum
+    LocalMinimum,
+    /// Capture the counter value on a fault if it is a local maximum
+    LocalMaximum,
+    /// Capture the counter value on a fault if it is a local minimum or
+    /// maximum
+    LocalExtremum,
+}
+
+/// Recoverable fault configuration
+#[hal_macro_helper]
+#[derive(Debug, Clone, Copy)]
+pub struct RecoverableFault {
+    source: FaultSource,
+    halt: FaultHalt,
+    restart: bool,
+    keep: bool,
+    qualify: bool,
+    blanking: Blanking,
+    blanking_time: u8,
+    #[hal_cfg("tcc0-d5x")]
+    blanking_prescaler: bool,
+    capture: FaultCapture,
+    channel: u8,
+    filter: u8,
+}
+
+impl RecoverableFault {
+    /// Create a fault driven by its event input, with the action `halt`
+    #[hal_macro_helper]
+    pub fn new(halt: FaultHalt) -> Self {
+        Self {
+            source: FaultSource::Event,
+            halt,
+            restart: false,
+            keep: false,
+            qualify: false,
+            #[hal_cfg("tcc0-d5x")]
+            blanking: Blanking::Start,
+            #[hal_cfg("tcc0-d21")]
+            blanking: Blanking::None,
+            blanking_time: 0,
+            #[hal_cfg("tcc0-d5x")]
+            blanking_prescaler: false,
+            capture: FaultCapture::Disabled,
+            channel: 0,
+            filter: 0,
+        }
+    }
+
+    /// Set the source of the fault
+    pub fn source(mut self, source: FaultSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Restart the counter when the fault is gone
+    pub fn restart(mut self, restart: bool) -> Self {
+        self.restart = restart;
+        self
+    }
+
+    /// Keep the outputs halted until the end of the period in which the fault
+    /// is gone
+    pub fn keep(mut self, keep: bool) -> Self {
+        self.keep = keep;
+        self
+    }
+
+    /// Only take the fault into account while the output of the channel
+    /// with the same index is inactive
+    pub fn qualify(mut self, qualify: bool) -> Self {
+        self.qualify = qualify;
+        self
+    }
+
+    /// Ignore the fault for `cycles` cycles after the `edge` of the output,
+    /// to mask the switching noise
+    pub fn blanking(mut self, edge: Blanking, cycles: u8) -> Self {
+        self.blanking = edge;
+        self.blanking_time = cycles;
+        self
+    }
+
+    /// Count the [`blanking`](Self::blanking) cycles with a 64 times
+    /// prescaler
+    #[hal_cfg("tcc0-d5x")]
+    pub fn blanking_prescaler(mut self, prescaled: bool) -> Self {
+        self.blanking_prescaler = prescaled;
+        self
+    }
+
+    /// Capture the counter value on a fault into compare channel `channel`,
+    /// between 0 and 3, according to `capture`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is greater than 3.
+    pub fn capture(mut self, capture: FaultCapture, channel: u8) -> Self {
+        assert!(channel <= 3);
+        self.capture = capture;
+        self.channel = channel;
+        self
+    }
+
+    /// Reject fault pulses shorter than `filter` cycles, between 0 (no
+    /// filter) and 15
+    ///
+    /// # Panics
+    ///
+    /// Panics if `filter` is greater than 15.
+    pub fn filter(mut self, filter: u8) -> Self {
+        assert!(filter <= 15);
+        self.filter = filter;
+        self
+    }
+}
+
+/// TCC configuration
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    prescaler: Prescaler,
+    waveform: Waveform,
+    ramp: Ramp,
+    period: u32,
+    polarity: u8,
+    invert_outputs: u8,
+    circular_period: bool,
+    circular_cc: u8,
+    run_in_standby: bool,
+}
+
+impl Config {
+    /// Create a single-slope PWM configuration, without prescaler, with a
+    /// `period` of `period + 1` cycles
+    pub fn new(period: u32) -> Self {
+        Self {
+            prescaler: Prescaler::Div1,
+            waveform: Waveform::SingleSlope,
+            ramp: Ramp::Ramp1,
+            period,
+            polarity: 0,
+            invert_outputs: 0,
+            circular_period: false,
+            circular_cc: 0,
+            run_in_standby: false,
+        }
+    }
+
+    /// Divide `GCLK_TCCn` by `prescaler`
+    pub fn prescaler(mut self, prescaler: Prescaler) -> Self {
+        self.prescaler = prescaler;
+        self
+    }
+
+    /// Set the waveform generation operation
+    pub fn waveform(mut self, waveform: Waveform) -> Self {
+        self.waveform = waveform;
+        self
+    }
+
+    /// Set the ramp operation, see the datasheet
+    pub fn ramp(mut self, ramp: Ramp) -> Self {
+        self.ramp = ramp;
+        self
+    }
+
+    /// Set the period value `PER`
+    pub fn period(mut self, period: u32) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Reverse the polarity of the compare channels set in `channels`. In
+    /// single-slope PWM, the output of a channel is then low until the
+    /// compare value, instead of high.
+    ///
+    /// Bit `n` of the mask stands for channel `n`.
+    pub fn polarity(mut self, channels: u8) -> Self {
+        self.polarity = channels & 0x3F;
+        self
+    }
+
+    /// Invert the waveform outputs set in `outputs`, after the dead-time
+    /// insertion and the pattern generation
+    ///
+    /// Bit `n` of the mask stands for `WO[n]`.
+    pub fn invert_outputs(mut self, outputs: u8) -> Self {
+        self.invert_outputs = outputs;
+        self
+    }
+
+    /// Enable circular buffering of the period: at each update, `PER` and
+    /// `PERBUF` are swapped, so the period alternates between two values
+    pub fn circular_period(mut self, enable: bool) -> Self {
+        self.circular_period = enable;
+        self
+    }
+
+    /// Enable circular buffering of the compare channels set in `channels`,
+    /// among channels 0 to 3
+    ///
+    /// Bit `n` of the mask stands for channel `n`.
+    pub fn circular_cc(mut self, channels: u8) -> Self {
+        self.circular_cc = channels & 0xF;
+        self
+    }
+
+    /// Keep the TCC running in standby sleep mode
+    pub fn run_in_standby(mut self, run_in_standby: bool) -> Self {
+        self.run_in_standby = run_in_standby;
+        self
+    }
+}
+
+/// Check whether bit `n` of `mask` is set
+#[inline]
+fn bit(mask: u8, n: u8) -> bool {
+    mask & (1 << n) != 0
+}
+
+//==============================================================================
+// TccId
+//==============================================================================
+
+/// Type-level trait identifying a TCC instance
+#[hal_cfg("tcc0-d5x")]
+pub trait TccId: ApbId + Sealed {
+    /// PAC peripheral struct
+    type Tcc;
+
+    /// Peripheral channel clocking the TCC, which may be shared with another
+    /// instance
+    type PclkId: PclkId;
+
+    /// Number of compare channels
+    const CC_NUM: usize;
+
+    /// Number of waveform outputs
+    const WO_NUM: usize;
+
+    /// Largest counter value
+    const MAX: u32;
+
+    /// Interrupt source of the TCC
+    #[cfg(feature = "async")]
+    type Interrupt: crate::async_hal::interrupts::InterruptSource;
+
+    #[doc(hidden)]
+    fn regs() -> &'static RegisterBlock;
+
+    #[cfg(feature = "async")]
+    #[doc(hidden)]
+    fn waker() -> &'static embassy_sync::waitqueue::AtomicWaker;
+}
+
+/// Type-level trait identifying a TCC instance
+#[hal_cfg("tcc0-d21")]
+pub trait TccId: Sealed {
+    /// PAC peripheral struct
+    type Tcc;
+
+    /// Generic clock token clocking the TCC, which may be shared with another
+    /// peripheral
+    type Clock;
+
+    /// Number of compare channels
+    const CC_NUM: usize;
+
+    /// Number of waveform outputs
+    const WO_NUM: usize;
+
+    /// Largest counter value
+    const MAX: u32;
+
+    /// Interrupt source of the TCC
+    #[cfg(feature = "async")]
+    type Interrupt: crate::async_hal::interrupts::InterruptSource;
+
+    #[doc(hidden)]
+    fn regs() -> &'static RegisterBlock;
+
+    #[doc(hidden)]
+    fn clock_freq(clock: &Self::Clock) -> Hertz;
+
+    #[doc(hidden)]
+    fn enable_apb_clock(pm: &mut Pm);
+
+    #[cfg(feature = "async")]
+    #[doc(hidden)]
+    fn waker() -> &'static embassy_sync::waitqueue::AtomicWaker;
+}
+
+/// TCC instances supporting the output matrix, dead-time insertion, output
+/// swapping and pattern generation
+pub trait WexTccId: TccId {}
+
+/// TCC instances supporting dithering
+pub trait DitherTccId: TccId {}
+
+#[hal_cfg("tcc0-d5x")]
+macro_rules! tcc_id {
+    ($Id:ident, $Pac:ident, $Pclk:ident, $Irq:ident, $n:literal, $cc:literal, $wo:literal, $max:literal) => {
+        impl TccId for $Id {
+            type Tcc = pac::$Pac;
+            type PclkId = $Pclk;
+
+            const CC_NUM: usize = $cc;
+            const WO_NUM: usize = $wo;
+            const MAX: u32 = $max;
+
+            #[cfg(feature = "async")]
+            type Interrupt = crate::async_hal::interrupts::$Irq;
+
+            #[inline]
+            fn regs() -> &'static RegisterBlock {
+                unsafe { &*pac::$Pac::ptr() }
+            }
+
+            #[cfg(feature = "async")]
+            #[inline]
+            fn waker() -> &'static embassy_sync::waitqueue::AtomicWaker {
+                &async_api::WAKERS[$n]
+            }
+        }
+    };
+}
+
+#[hal_cfg("tcc0-d21")]
+macro_rules! tcc_id {
+    ($Id:ident, $Pac:ident, $Clock:ident, $apb:ident, $Irq:ident, $n:literal, $cc:literal, $wo:literal, $max:literal) => {
+        #[doc = concat!("Type-level variant of [`TccId`] for TCC", $n)]
+        pub enum $Id {}
+
+        impl Sealed for $Id {}
+
+        impl TccId for $Id {
+            type Tcc = pac::$Pac;
+            type Clock = clock::$Clock;
+
+            const CC_NUM: usize = $cc;
+            const WO_NUM: usize = $wo;
+            const MAX: u32 = $max;
+
+            #[cfg(feature = "async")]
+            type Interrupt = crate::async_hal::interrupts::$Irq;
+
+            #[inline]
+            fn regs() -> &'static RegisterBlock {
+                unsafe { &*pac::$Pac::ptr() }
+            }
+
+            #[inline]
+            fn clock_freq(clock: &Self::Clock) -> Hertz {
+                clock.freq()
+            }
+
+            #[inline]
+            fn enable_apb_clock(pm: &mut Pm) {
+                pm.apbcmask().modify(|_, w| w.$apb().set_bit());
+            }
+
+            #[cfg(feature = "async")]
+            #[inline]
+            fn waker() -> &'static embassy_sync::waitqueue::AtomicWaker {
+                &async_api::WAKERS[$n]
+            }
+        }
+    };
+}
+
+#[hal_cfg("tcc0-d5x")]
+tcc_id!(Tcc0, Tcc0, Tcc0Tcc1, TCC0, 0, 6, 8, 0xFF_FFFF);
+#[hal_cfg("tcc1-d5x")]
+tcc_id!(Tcc1, Tcc1, Tcc0Tcc1, TCC1, 1, 4, 8, 0xFF_FFFF);
+#[hal_cfg("tcc2-d5x")]
+tcc_id!(Tcc2, Tcc2, Tcc2Tcc3, TCC2, 2, 3, 3, 0xFFFF);
+#[hal_cfg("tcc3")]
+tcc_id!(Tcc3, Tcc3, Tcc2Tcc3, TCC3, 3, 2, 2, 0xFFFF);
+#[hal_cfg("tcc4")]
+tcc_id!(Tcc4, Tcc4, Tcc4, TCC4, 4, 2, 2, 0xFFFF);
+
+#[hal_cfg("tcc0-d21")]
+tcc_id!(Tcc0, Tcc0, Tcc0Tcc1Clock, tcc0_, TCC0, 0, 4, 8, 0xFF_FFFF);
+#[hal_cfg("tcc1-d21")]
+tcc_id!(Tcc1, Tcc1, Tcc0Tcc1Clock, tcc1_, TCC1, 1, 2, 4, 0xFF_FFFF);
+#[hal_cfg("tcc2-d21")]
+tcc_id!(Tcc2, Tcc2, Tcc2Tc3Clock, tcc2_, TCC2, 2, 2, 2, 0xFFFF);
+
+impl WexTccId for Tcc0 {}
+#[hal_cfg("tcc1-d5x")]
+impl WexTccId for Tcc1 {}
+
+impl DitherTccId for Tcc0 {}
+impl DitherTccId for Tcc1 {}
+#[hal_cfg("tcc2-d5x")]
+impl DitherTccId for Tcc2 {}
+
+//==============================================================================
+// Tcc
+//==============================================================================
+
+/// Timer/Counter for Control Applications
+#[hal_macro_helper]
+pub struct Tcc<T: TccId, P: OutputPins<T>> {
+    tcc: T::Tcc,
+    #[hal_cfg("tcc0-d5x")]
+    apbclk: ApbClk<T>,
+    pins: P,
+    freq: Hertz,
+}
+
+impl<T: TccId, P: OutputPins<T>> Tcc<T, P> {
+    /// Reset and configure the TCC, leaving it disabled
+    ///
+    /// The faults, dead-time insertion, output matrix and dithering can only
+    /// be changed while the TCC is disabled. They should be set up before
+    /// calling [`enable`](Self::enable), otherwise the setters briefly stop
+    /// the TCC.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the period doesn't fit in the counter.
+    #[hal_cfg("tcc0-d5x")]
+    pub fn new<PS: PclkSourceId>(
+        tcc: T::Tcc,
+        apbclk: ApbClk<T>,
+        pclk: &Pclk<T::PclkId, PS>,
+        pins: P,
+        config: Config,
+    ) -> Self {
+        assert!(config.period <= T::MAX, "period doesn't fit in the counter");
+        let tcc = Self {
+            tcc,
+            apbclk,
+            pins,
+            freq: pclk.freq(),
+        };
+        tcc.reset(config);
+        tcc
+    }
+
+    /// Enable the APB clock, then reset and configure the TCC, leaving it
+    /// disabled
+    ///
+    /// The faults, dead-time insertion, output matrix and dithering can only
+    /// be changed while the TCC is disabled. They should be set up before
+    /// calling [`enable`](Self::enable), otherwise the setters briefly stop
+    /// the TCC.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the period doesn't fit in the counter.
+    #[hal_cfg("tcc0-d21")]
+    pub fn new(tcc: T::Tcc, clock: &T::Clock, pm: &mut Pm, pins: P, config: Config) -> Self {
+        assert!(config.period <= T::MAX, "period doesn't fit in the counter");
+        T::enable_apb_clock(pm);
+        let tcc = Self {
+            tcc,
+            pins,
+            freq: T::clock_freq(clock),
+        };
+        tcc.reset(config);
+        tcc
+    }
+
+    /// Reset the TCC and apply `config`
+    #[hal_macro_helper]
+    fn reset(&self, config: Config) {
+        let regs = self.regs();
+        regs.ctrla().write(|w| w.swrst().set_bit());
+        while regs.syncbusy().read().swrst().bit_is_set() {}
+
+        regs.ctrla().write(|w| {
+            w.prescaler().variant(config.prescaler);
+            w.prescsync().presc();
+            w.runstdby().bit(config.run_in_standby)
+        });
+
+        let circular_cc = config.circular_cc;
+        let polarity = config.polarity;
+        regs.wave().write(|w| {
+            w.wavegen().variant(config.waveform.wavegen());
+            w.ramp().variant(config.ramp);
+            w.ciperen().bit(config.circular_period);
+            w.ciccen0().bit(bit(circular_cc, 0));
+            w.ciccen1().bit(bit(circular_cc, 1));
+            w.ciccen2().bit(bit(circular_cc, 2));
+            w.ciccen3().bit(bit(circular_cc, 3));
+            #[hal_cfg("tcc0-d5x")]
+            {
+                w.pol4().bit(bit(polarity, 4));
+                w.pol5().bit(bit(polarity, 5));
+            }
+            w.pol0().bit(bit(polarity, 0));
+            w.pol1().bit(bit(polarity, 1));
+            w.pol2().bit(bit(polarity, 2));
+            w.pol3().bit(bit(polarity, 3))
+        });
+        while regs.syncbusy().read().wave().bit_is_set() {}
+
+        let invert = config.invert_outputs;
+        regs.drvctrl().write(|w| {
+            w.inven0().bit(bit(invert, 0));
+            w.inven1().bit(bit(invert, 1));
+            w.inven2().bit(bit(invert, 2));
+            w.inven3().bit(bit(invert, 3));
+            w.inven4().bit(bit(invert, 4));
+            w.inven5().bit(bit(invert, 5));
+            w.inven6().bit(bit(invert, 6));
+            w.inven7().bit(bit(invert, 7))
+        });
+
+        // SAFETY: The period fits in the counter
+        regs.per().write(|w| unsafe { w.bits(config.period) });
+        while regs.syncbusy().read().per().bit_is_set() {}
+    }
+
+    #[inline]
+    fn regs(&self) -> &'static RegisterBlock {
+        T::regs()
+    }
+
+    /// Enable the TCC, starting the counter
+    #[inline]
+    pub fn enable(&mut self) {
+        let regs = self.regs();
+        regs.ctrla().modify(|_, w| w.enable().set_bit());
+        while regs.syncbusy().read().enable().bit_is_set() {}
+    }
+
+    /// Disable the TCC. The outputs keep their current level.
+    #[inline]
+    pub fn disable(&mut self) {
+        let regs = self.regs();
+        regs.ctrla().modify(|_, w| w.enable().clear_bit());
+        while regs.syncbusy().read().enable().bit_is_set() {}
+    }
+
+    /// Check whether the TCC is enabled
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.regs().ctrla().read().enable().bit_is_set()
+    }
+
+    /// Run `f` with the TCC disabled, to change enable-protected registers,
+    /// then restore its state
+    fn with_disabled(&mut self, f: impl FnOnce(&RegisterBlock)) {
+        let enabled = self.is_enabled();
+        if enabled {
+            self.disable();
+        }
+        f(self.regs());
+        if enabled {
+            self.enable();
+        }
+    }
+
+    /// Frequency of the clock counted by the TCC, after the prescaler
+    #[inline]
+    pub fn counter_freq(&self) -> Hertz {
+        let divider = match self.regs().ctrla().read().prescaler().variant() {
+            Prescaler::Div1 => 1,
+            Prescaler::Div2 => 2,
+            Prescaler::Div4 => 4,
+            Prescaler::Div8 => 8,
+            Prescaler::Div16 => 16,
+            Prescaler::Div64 => 64,
+            Prescaler::Div256 => 256,
+            Prescaler::Div1024 => 1024,
+        };
+        self.freq / divider
+    }
+
+    /// Frequency of the PWM, from the current period
+    ///
+    /// The dithering cycles, if any, are not taken into account.
+    pub fn frequency(&self) -> Hertz {
+        let regs = self.regs();
+        let wave = regs.wave().read();
+        let period = self.period() >> self.dither_bits();
+        let dual_slope =
+            !(wave.wavegen().is_nfrq() || wave.wavegen().is_mfrq() || wave.wavegen().is_npwm());
+        if dual_slope {
+            self.counter_freq() / (2 * period.max(1))
+        } else {
+            self.counter_freq() / (period + 1)
+        }
+    }
+
+    /// Number of dithering bits in the period and compare values
+    #[inline]
+    fn dither_bits(&self) -> u32 {
+        match self.regs().ctrla().read().resolution().variant() {
+            Resolution::None => 0,
+            Resolution::Dith4 => 4,
+            Resolution::Dith5 => 5,
+            Resolution::Dith6 => 6,
+        }
+    }
+
+    /// Read the current period value `PER`
+    #[inline]
+    pub fn period(&self) -> u32 {
+        self.regs().per().read().bits()
+    }
+
+    /// Set the period value, applied at the next update condition
+    ///
+    /// The value is written to `PERBUF`, and copied to `PER` at the end of
+    /// the current period, so the PWM doesn't glitch. With dithering enabled,
+    /// the lower bits of `period` hold the number of dithering cycles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the period doesn't fit in the counter.
+    #[inline]
+    #[hal_macro_helper]
+    pub fn set_period(&mut self, period: u32) {
+        assert!(period <= T::MAX, "period doesn't fit in the counter");
+        #[hal_cfg("tcc0-d5x")]
+        let perbuf = self.regs().perbuf();
+        #[hal_cfg("tcc0-d21")]
+        let perbuf = self.regs().perb();
+        // SAFETY: The period fits in the counter
+        perbuf.write(|w| unsafe { w.bits(period) });
+    }
+
+    /// Read the current compare value of `channel`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not a valid compare channel.
+    #[inline]
+    pub fn duty(&self, channel: usize) -> u32 {
+        assert!(channel < T::CC_NUM);
+        self.regs().cc(channel).read().bits()
+    }
+
+    /// Set the compare value of `channel`, applied at the next update
+    /// condition
+    ///
+    /// The value is written to `CCBUF`, and copied to `CC` at the end of the
+    /// current period, so the PWM doesn't glitch. With dithering enabled, the
+    /// lower bits of `duty` hold the number of dithering cycles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not a valid compare channel, or if the value
+    /// doesn't fit in the counter.
+    #[inline]
+    #[hal_macro_helper]
+    pub fn set_duty(&mut self, channel: usize, duty: u32) {
+        assert!(channel < T::CC_NUM);
+        assert!(duty <= T::MAX, "compare value doesn't fit in the counter");
+        #[hal_cfg("tcc0-d5x")]
+        let ccbuf = self.regs().ccbuf(channel);
+        #[hal_cfg("tcc0-d21")]
+        let ccbuf = self.regs().ccb(channel);
+        // SAFETY: The value fits in the counter
+        ccbuf.write(|w| unsafe { w.bits(duty) });
+    }
+
+    /// Hold the buffered values while `f` runs, so that the new period,
+    /// compare values and pattern written by `f` are all applied at the same
+    /// update condition
+    pub fn update_atomically<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let regs = self.regs();
+        regs.ctrlbset().write(|w| w.lupd().set_bit());
+        while regs.syncbusy().read().ctrlb().bit_is_set() {}
+        let result = f(self);
+        regs.ctrlbclr().write(|w| w.lupd().set_bit());
+        while regs.syncbusy().read().ctrlb().bit_is_set() {}
+        result
+    }
+
+    /// Copy the buffered values now, instead of at the end of the period
+    #[inline]
+    pub fn force_update(&mut self) {
+        self.command(Cmdselect::Update);
+    }
+
+    /// Restart the counter
+    #[inline]
+    pub fn retrigger(&mut self) {
+        self.command(Cmdselect::Retrigger);
+    }
+
+    /// Read the counter value
+    #[inline]
+    pub fn count(&mut self) -> u32 {
+        self.command(Cmdselect::Readsync);
+        let regs = self.regs();
+        while regs.syncbusy().read().count().bit_is_set() {}
+        regs.count().read().bits()
+    }
+
+    fn command(&mut self, cmd: Cmdselect) {
+        let regs = self.regs();
+        regs.ctrlbset().write(|w| w.cmd().variant(cmd));
+        while regs.syncbusy().read().ctrlb().bit_is_set() {}
+    }
+
+    /// Configure a recoverable fault, and enable the corresponding match or
+    /// capture event input
+    ///
+    /// The TCC is briefly disabled if it is running.
+    #[hal_macro_helper]
+    pub fn configure_recoverable_fault(
+        &mut self,
+        input: RecoverableInput,
+        fault: RecoverableFault,
+    ) {
+        // FCTRLA and FCTRLB have the same layout, but distinct PAC types
+        macro_rules! write_fctrl {
+            ($regs:ident.$fctrl:ident) => {
+                $regs.$fctrl().write(|w| {
+                    match fault.source {
+                        FaultSource::Event => w.src().enable(),
+                        FaultSource::InvertedEvent => w.src().invert(),
+                        FaultSource::AltEvent => w.src().altfault(),
+                    };
+                    w.keep().bit(fault.keep);
+                    w.qual().bit(fault.qualify);
+                    match fault.blanking {
+                        #[hal_cfg("tcc0-d5x")]
+                        Blanking::Start => w.blank().start(),
+                        #[hal_cfg("tcc0-d21")]
+                        Blanking::None => w.blank().none(),
+                        Blanking::Rise => w.blank().rise(),
+                        Blanking::Fall => w.blank().fall(),
+                        Blanking::Both => w.blank().both(),
+                    };
+                    w.restart().bit(fault.restart);
+                    match fault.halt {
+                        FaultHalt::Disabled => w.halt().disable(),
+                        FaultHalt::Hardware => w.halt().hw(),
+                        FaultHalt::Software => w.halt().sw(),
+                        FaultHalt::NonRecoverable => w.halt().nr(),
+                    };
+                    match fault.channel {
+                        0 => w.chsel().cc0(),
+                        1 => w.chsel().cc1(),
+                        2 => w.chsel().cc2(),
+                        _ => w.chsel().cc3(),
+                    };
+                    match fault.capture {
+                        FaultCapture::Disabled => w.capture().disable(),
+                        FaultCapture::Capture => w.capture().capt(),
+                        FaultCapture::Minimum => w.capture().captmin(),
+                        FaultCapture::Maximum => w.capture().captmax(),
+                        FaultCapture::LocalMinimum => w.capture().locmin(),
+                        FaultCapture::LocalMaximum => w.capture().locmax(),
+                        FaultCapture::LocalExtremum => w.capture().deriv0(),
+                    };
+                    #[hal_cfg("tcc0-d5x")]
+                    w.blankpresc().bit(fault.blanking_prescaler);
+                    // SAFETY: Every 8-bit blanking time is valid, and the
+                    // filter value is checked to fit in 4 bits
+                    unsafe {
+                        w.blankval().bits(fault.blanking_time);
+                        w.filterval().bits(fault.filter)
+                    }
+                })
+            };
+        }
+
+        self.with_disabled(|regs| match input {
+            RecoverableInput::A => {
+                write_fctrl!(regs.fctrla);
+                regs.evctrl().modify(|_, w| w.mcei0().set_bit());
+            }
+            RecoverableInput::B => {
+                write_fctrl!(regs.fctrlb);
+                regs.evctrl().modify(|_, w| w.mcei1().set_bit());
+            }
+        });
+    }
+
+    /// Disable a recoverable fault, and the corresponding event input
+    ///
+    /// The TCC is briefly disabled if it is running.
+    pub fn disable_recoverable_fault(&mut self, input: RecoverableInput) {
+        self.with_disabled(|regs| match input {
+            RecoverableInput::A => {
+                regs.fctrla().reset();
+                regs.evctrl().modify(|_, w| w.mcei0().clear_bit());
+            }
+            RecoverableInput::B => {
+                regs.fctrlb().reset();
+                regs.evctrl().modify(|_, w| w.mcei1().clear_bit());
+            }
+        });
+    }
+
+    /// Enable a non-recoverable fault, driven by the event input 0 or 1
+    ///
+    /// The fault is active when the event is high, or low with `invert`.
+    /// Fault pulses shorter than `filter` cycles, between 0 and 15, are
+    /// rejected. On a fault, the outputs take the values set with
+    /// [`set_fault_outputs`](Self::set_fault_outputs). The TCC is briefly
+    /// disabled if it is running.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `filter` is greater than 15.
+    pub fn enable_non_recoverable_fault(
+        &mut self,
+        input: NonRecoverableInput,
+        invert: bool,
+        filter: u8,
+    ) {
+        assert!(filter <= 15);
+        self.with_disabled(|regs| match input {
+            NonRecoverableInput::Nr0 => {
+                regs.evctrl().modify(|_, w| {
+                    w.evact0().fault();
+                    w.tcinv0().bit(invert);
+                    w.tcei0().set_bit()
+                });
+                // SAFETY: The filter value fits in 4 bits
+                regs.drvctrl()
+                    .modify(|_, w| unsafe { w.filterval0().bits(filter) });
+            }
+            NonRecoverableInput::Nr1 => {
+                regs.evctrl().modify(|_, w| {
+                    w.evact1().fault();
+                    w.tcinv1().bit(invert);
+                    w.tcei1().set_bit()
+                });
+                // SAFETY: The filter value fits in 4 bits
+                regs.drvctrl()
+                    .modify(|_, w| unsafe { w.filterval1().bits(filter) });
+            }
+        });
+    }
+
+    /// Disable a non-recoverable fault, and the corresponding event input
+    ///
+    /// The TCC is briefly disabled if it is running.
+    pub fn disable_non_recoverable_fault(&mut self, input: NonRecoverableInput) {
+        self.with_disabled(|regs| {
+            regs.evctrl().modify(|_, w| match input {
+                NonRecoverableInput::Nr0 => {
+                    w.evact0().off();
+                    w.tcinv0().clear_bit();
+                    w.tcei0().clear_bit()
+                }
+                NonRecoverableInput::Nr1 => {
+                    w.evact1().off();
+                    w.tcinv1().clear_bit();
+                    w.tcei1().clear_bit()
+                }
+            });
+        });
+    }
+
+    /// Set the outputs forced on a non-recoverable fault: the outputs set in
+    /// `enable` are driven to the corresponding bit of `value`, the others
+    /// keep running
+    ///
+    /// Bit `n` of each mask stands for `WO[n]`. The TCC is briefly disabled
+    /// if it is running.
+    pub fn set_fault_outputs(&mut self, enable: u8, value: u8) {
+        self.with_disabled(|regs| {
+            regs.drvctrl().modify(|_, w| {
+                w.nre0().bit(bit(enable, 0));
+                w.nre1().bit(bit(enable, 1));
+                w.nre2().bit(bit(enable, 2));
+                w.nre3().bit(bit(enable, 3));
+                w.nre4().bit(bit(enable, 4));
+                w.nre5().bit(bit(enable, 5));
+                w.nre6().bit(bit(enable, 6));
+                w.nre7().bit(bit(enable, 7));
+                w.nrv0().bit(bit(value, 0));
+                w.nrv1().bit(bit(value, 1));
+                w.nrv2().bit(bit(value, 2));
+                w.nrv3().bit(bit(value, 3));
+                w.nrv4().bit(bit(value, 4));
+                w.nrv5().bit(bit(value, 5));
+                w.nrv6().bit(bit(value, 6));
+                w.nrv7().bit(bit(value, 7))
+            });
+        });
+    }
+
+    /// Read the fault states
+    #[inline]
+    pub fn faults(&self) -> Faults {
+        Faults::from_bits_truncate(self.regs().status().read().bits())
+    }
+
+    /// Clear the fault states set in `faults`
+    ///
+    /// A recoverable fault with [`FaultHalt::Software`] releases the outputs,
+    /// and a non-recoverable fault restores normal operation, once the fault
+    /// input is inactive.
+    #[inline]
+    pub fn clear_faults(&mut self, faults: Faults) {
+        let regs = self.regs();
+        // SAFETY: Writing 1 to a fault state clears it, and 0 has no effect
+        regs.status().write(|w| unsafe { w.bits(faults.bits()) });
+        while regs.syncbusy().read().status().bit_is_set() {}
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.regs().intflag().read().bits())
+    }
+
+    /// Clear the interrupt flags set in `flags`
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        // SAFETY: Writing 1 to a flag clears it
+        self.regs()
+            .intflag()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable the interrupts set in `flags`
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        // SAFETY: Writing 1 to a bit enables the interrupt, and 0 has no effect
+        self.regs()
+            .intenset()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the interrupts set in `flags`
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        // SAFETY: Writing 1 to a bit disables the interrupt, and 0 has no effect
+        self.regs()
+            .intenclr()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the TCC and its interrupts, and return the underlying
+    /// resources
+    #[hal_cfg("tcc0-d5x")]
+    pub fn free(mut self) -> (T::Tcc, ApbClk<T>, P) {
+        self.disable_interrupts(Flags::all());
+        self.disable();
+        (self.tcc, self.apbclk, self.pins)
+    }
+
+    /// Disable the TCC and its interrupts, and return the underlying
+    /// resources
+    #[hal_cfg("tcc0-d21")]
+    pub fn free(mut self) -> (T::Tcc, P) {
+        self.disable_interrupts(Flags::all());
+        self.disable();
+        (self.tcc, self.pins)
+    }
+}
+
+impl<T: WexTccId, P: OutputPins<T>> Tcc<T, P> {
+    /// Select which compare channel drives each output
+    ///
+    /// The TCC is briefly disabled if it is running.
+    pub fn set_output_matrix(&mut self, matrix: OutputMatrix) {
+        self.with_disabled(|regs| {
+            // SAFETY: Every OutputMatrix is a valid OTMX value
+            regs.wexctrl()
+                .modify(|_, w| unsafe { w.otmx().bits(matrix as u8) });
+        });
+    }
+
+    /// Enable dead-time insertion on the output pairs set in `pairs`, with
+    /// `low` cycles of dead time on the low side and `high` cycles on the
+    /// high side
+    ///
+    /// Bit `n` of the mask stands for pair `n`, made of `WO[n]` and
+    /// `WO[n + 4]`. The pairs not set in `pairs` are disabled. The TCC is
+    /// briefly disabled if it is running.
+    pub fn set_dead_time(&mut self, pairs: u8, low: u8, high: u8) {
+        self.with_disabled(|regs| {
+            regs.wexctrl().modify(|_, w| {
+                w.dtien0().bit(bit(pairs, 0));
+                w.dtien1().bit(bit(pairs, 1));
+                w.dtien2().bit(bit(pairs, 2));
+                w.dtien3().bit(bit(pairs, 3));
+                // SAFETY: Every 8-bit dead time is valid
+                unsafe {
+                    w.dtls().bits(low);
+                    w.dths().bits(high)
+                }
+            });
+        });
+    }
+
+    /// Disable dead-time insertion on all the output pairs
+    ///
+    /// The TCC is briefly disabled if it is running.
+    #[inline]
+    pub fn disable_dead_time(&mut self) {
+        self.set_dead_time(0, 0, 0);
+    }
+
+    /// Swap the low and high side outputs of the pairs set in `pairs`,
+    /// including their dead times
+    ///
+    /// Bit `n` of the mask stands for pair `n`. The change is applied
+    /// immediately.
+    pub fn set_swap(&mut self, pairs: u8) {
+        let regs = self.regs();
+        regs.wave().modify(|_, w| {
+            w.swap0().bit(bit(pairs, 0));
+            w.swap1().bit(bit(pairs, 1));
+            w.swap2().bit(bit(pairs, 2));
+            w.swap3().bit(bit(pairs, 3))
+        });
+        while regs.syncbusy().read().wave().bit_is_set() {}
+    }
+
+    /// Override the outputs set in `enable` with the corresponding bit of
+    /// `value`, from the next update condition
+    ///
+    /// Bit `n` of each mask stands for `WO[n]`. The pattern is buffered like
+    /// the compare values, so it can be changed in sync with them in
+    /// [`update_atomically`](Self::update_atomically), for example to
+    /// commutate a brushless motor.
+    #[inline]
+    #[hal_macro_helper]
+    pub fn set_pattern(&mut self, enable: u8, value: u8) {
+        #[hal_cfg("tcc0-d5x")]
+        let pattbuf = self.regs().pattbuf();
+        #[hal_cfg("tcc0-d21")]
+        let pattbuf = self.regs().pattb();
+        // SAFETY: PGEB and PGVB are the two bytes of the pattern buffer
+        pattbuf.write(|w| unsafe { w.bits(enable as u16 | (value as u16) << 8) });
+    }
+
+    /// Stop overriding the outputs, from the next update condition
+    #[inline]
+    pub fn disable_pattern(&mut self) {
+        self.set_pattern(0, 0);
+    }
+}
+
+impl<T: DitherTccId, P: OutputPins<T>> Tcc<T, P> {
+    /// Set the dithering resolution
+    ///
+    /// With dithering, the lower 4, 5 or 6 bits of the period and compare
+    /// values set the number of periods, out of 16, 32 or 64, which are
+    /// extended by one cycle. This increases the average resolution of the
+    /// PWM. The TCC is briefly disabled if it is running.
+    pub fn set_dithering(&mut self, resolution: Resolution) {
+        self.with_disabled(|regs| {
+            regs.ctrla()
+                .modify(|_, w| w.resolution().variant(resolution));
+        });
+    }
+}
@@ -0,0 +1,400 @@
+//! TCC pin definitions
+
+use core::marker::PhantomData;
+
+use atsamd_hal_macros::hal_cfg;
+
+use super::{Tcc0, Tcc1, Tcc2, TccId};
+use crate::gpio::*;
+use crate::typelevel::NoneT;
+
+#[hal_cfg("tcc3")]
+use super::Tcc3;
+#[hal_cfg("tcc4")]
+use super::Tcc4;
+
+/// Type-level trait for [`PinId`]s which can be a waveform output of the TCC
+/// `T`
+pub trait WoPinId<T: TccId>: PinId {
+    /// Index `n` of the `WO[n]` output driven on the pin
+    const WO: usize;
+
+    /// Alternate function connecting the pin to the TCC
+    type Mode: AlternateConfig;
+}
+
+/// Waveform output `WO[n]` of the TCC `T`, with `n` given by
+/// [`WoPinId::WO`]
+pub struct WoPin<T: TccId, I: WoPinId<T>> {
+    pin: Pin<I, Alternate<I::Mode>>,
+    _tcc: PhantomData<T>,
+}
+
+impl<T: TccId, I: WoPinId<T>> WoPin<T, I> {
+    /// Configure `pin` as a waveform output
+    #[inline]
+    pub fn new(pin: impl AnyPin<Id = I>) -> Self {
+        Self {
+            pin: pin.into().into_alternate(),
+            _tcc: PhantomData,
+        }
+    }
+
+    /// Index `n` of the `WO[n]` output
+    #[inline]
+    pub const fn wo(&self) -> usize {
+        I::WO
+    }
+
+    /// Return the underlying pin
+    #[inline]
+    pub fn free(self) -> Pin<I, Alternate<I::Mode>> {
+        self.pin
+    }
+}
+
+/// Set of [`WoPin`]s owned by a [`Tcc`](super::Tcc)
+///
+/// This trait is implemented for [`NoneT`], a single [`WoPin`], and tuples of
+/// up to 8 [`WoPin`]s of the same TCC.
+pub trait OutputPins<T: TccId> {}
+
+impl<T: TccId> OutputPins<T> for NoneT {}
+
+impl<T: TccId, I: WoPinId<T>> OutputPins<T> for WoPin<T, I> {}
+
+macro_rules! impl_output_pins_tuple {
+    ($($P:ident),+) => {
+        impl<T: TccId, $($P: OutputPins<T>),+> OutputPins<T> for ($($P,)+) {}
+    };
+}
+
+impl_output_pins_tuple!(P0);
+impl_output_pins_tuple!(P0, P1);
+impl_output_pins_tuple!(P0, P1, P2);
+impl_output_pins_tuple!(P0, P1, P2, P3);
+impl_output_pins_tuple!(P0, P1, P2, P3, P4);
+impl_output_pins_tuple!(P0, P1, P2, P3, P4, P5);
+impl_output_pins_tuple!(P0, P1, P2, P3, P4, P5, P6);
+impl_output_pins_tuple!(P0, P1, P2, P3, P4, P5, P6, P7);
+
+macro_rules! wo_pins {
+    (
+        $Tcc:ident: [ $(
+            $( #[$attr:meta] )?
+            ($Id:ident, $WO:literal, $Mode:ident)
+        ),+ $(,)? ]
+    ) => {
+        $(
+            $( #[$attr] )?
+            impl WoPinId<$Tcc> for $Id {
+                const WO: usize = $WO;
+                type Mode = $Mode;
+            }
+        )+
+    };
+}
+
+#[hal_cfg("tcc0-d5x")]
+wo_pins!(Tcc0: [
+    #[hal_cfg("pa08")]
+    (PA08, 0, F),
+    #[hal_cfg("pa09")]
+    (PA09, 1, F),
+    #[hal_cfg("pa10")]
+    (PA10, 2, F),
+    #[hal_cfg("pa11")]
+    (PA11, 3, F),
+    #[hal_cfg("pa12")]
+    (PA12, 6, F),
+    #[hal_cfg("pa13")]
+    (PA13, 7, F),
+    #[hal_cfg("pa16")]
+    (PA16, 4, G),
+    #[hal_cfg("pa17")]
+    (PA17, 5, G),
+    #[hal_cfg("pa18")]
+    (PA18, 6, G),
+    #[hal_cfg("pa19")]
+    (PA19, 7, G),
+    #[hal_cfg("pa20")]
+    (PA20, 0, G),
+    #[hal_cfg("pa21")]
+    (PA21, 1, G),
+    #[hal_cfg("pa22")]
+    (PA22, 2, G),
+    #[hal_cfg("pa23")]
+    (PA23, 3, G),
+    #[hal_cfg("pb10")]
+    (PB10, 4, F),
+    #[hal_cfg("pb11")]
+    (PB11, 5, F),
+    #[hal_cfg("pb12")]
+    (PB12, 0, G),
+    #[hal_cfg("pb13")]
+    (PB13, 1, G),
+    #[hal_cfg("pb14")]
+    (PB14, 2, G),
+    #[hal_cfg("pb15")]
+    (PB15, 3, G),
+    #[hal_cfg("pb16")]
+    (PB16, 4, G),
+    #[hal_cfg("pb17")]
+    (PB17, 5, G),
+    #[hal_cfg("pb30")]
+    (PB30, 6, G),
+    #[hal_cfg("pb31")]
+    (PB31, 7, G),
+    #[hal_cfg("pc04")]
+    (PC04, 0, F),
+    #[hal_cfg("pc10")]
+    (PC10, 0, F),
+    #[hal_cfg("pc11")]
+    (PC11, 1, F),
+    #[hal_cfg("pc12")]
+    (PC12, 2, F),
+    #[hal_cfg("pc13")]
+    (PC13, 3, F),
+    #[hal_cfg("pc14")]
+    (PC14, 4, F),
+    #[hal_cfg("pc15")]
+    (PC15, 5, F),
+    #[hal_cfg("pc16")]
+    (PC16, 0, F),
+    #[hal_cfg("pc17")]
+    (PC17, 1, F),
+    #[hal_cfg("pc18")]
+    (PC18, 2, F),
+    #[hal_cfg("pc19")]
+    (PC19, 3, F),
+    #[hal_cfg("pc20")]
+    (PC20, 4, F),
+    #[hal_cfg("pc21")]
+    (PC21, 5, F),
+    #[hal_cfg("pc22")]
+    (PC22, 6, F),
+    #[hal_cfg("pc23")]
+    (PC23, 7, F),
+    #[hal_cfg("pd08")]
+    (PD08, 1, F),
+    #[hal_cfg("pd09")]
+    (PD09, 2, F),
+    #[hal_cfg("pd10")]
+    (PD10, 3, F),
+    #[hal_cfg("pd11")]
+    (PD11, 4, F),
+    #[hal_cfg("pd12")]
+    (PD12, 5, F),
+]);
+
+#[hal_cfg("tcc1-d5x")]
+wo_pins!(Tcc1: [
+    #[hal_cfg("pa08")]
+    (PA08, 4, G),
+    #[hal_cfg("pa09")]
+    (PA09, 5, G),
+    #[hal_cfg("pa10")]
+    (PA10, 6, G),
+    #[hal_cfg("pa11")]
+    (PA11, 7, G),
+    #[hal_cfg("pa12")]
+    (PA12, 2, G),
+    #[hal_cfg("pa13")]
+    (PA13, 3, G),
+    #[hal_cfg("pa14")]
+    (PA14, 2, G),
+    #[hal_cfg("pa15")]
+    (PA15, 3, G),
+    #[hal_cfg("pa16")]
+    (PA16, 0, F),
+    #[hal_cfg("pa17")]
+    (PA17, 1, F),
+    #[hal_cfg("pa18")]
+    (PA18, 2, F),
+    #[hal_cfg("pa19")]
+    (PA19, 3, F),
+    #[hal_cfg("pa20")]
+    (PA20, 4, F),
+    #[hal_cfg("pa21")]
+    (PA21, 5, F),
+    #[hal_cfg("pa22")]
+    (PA22, 6, F),
+    #[hal_cfg("pa23")]
+    (PA23, 7, F),
+    #[hal_cfg("pb10")]
+    (PB10, 0, G),
+    #[hal_cfg("pb11")]
+    (PB11, 1, G),
+    #[hal_cfg("pb18")]
+    (PB18, 0, F),
+    #[hal_cfg("pb19")]
+    (PB19, 1, F),
+    #[hal_cfg("pb20")]
+    (PB20, 2, F),
+    #[hal_cfg("pb21")]
+    (PB21, 3, F),
+    #[hal_cfg("pb26")]
+    (PB26, 2, F),
+    #[hal_cfg("pb27")]
+    (PB27, 3, F),
+    #[hal_cfg("pb28")]
+    (PB28, 4, F),
+    #[hal_cfg("pb29")]
+    (PB29, 5, F),
+    #[hal_cfg("pc10")]
+    (PC10, 4, G),
+    #[hal_cfg("pc11")]
+    (PC11, 5, G),
+    #[hal_cfg("pc12")]
+    (PC12, 6, G),
+    #[hal_cfg("pc13")]
+    (PC13, 7, G),
+    #[hal_cfg("pc14")]
+    (PC14, 0, G),
+    #[hal_cfg("pc15")]
+    (PC15, 1, G),
+    #[hal_cfg("pd20")]
+    (PD20, 0, F),
+    #[hal_cfg("pd21")]
+    (PD21, 1, F),
+]);
+
+#[hal_cfg("tcc2-d5x")]
+wo_pins!(Tcc2: [
+    #[hal_cfg("pa14")]
+    (PA14, 0, F),
+    #[hal_cfg("pa15")]
+    (PA15, 1, F),
+    #[hal_cfg("pa24")]
+    (PA24, 2, F),
+    #[hal_cfg("pa30")]
+    (PA30, 0, F),
+    #[hal_cfg("pa31")]
+    (PA31, 1, F),
+    #[hal_cfg("pb02")]
+    (PB02, 2, F),
+]);
+
+#[hal_cfg("tcc3")]
+wo_pins!(Tcc3: [
+    #[hal_cfg("pb12")]
+    (PB12, 0, F),
+    #[hal_cfg("pb13")]
+    (PB13, 1, F),
+    #[hal_cfg("pb16")]
+    (PB16, 0, F),
+    #[hal_cfg("pb17")]
+    (PB17, 1, F),
+]);
+
+#[hal_cfg("tcc4")]
+wo_pins!(Tcc4: [
+    #[hal_cfg("pb14")]
+    (PB14, 0, F),
+    #[hal_cfg("pb15")]
+    (PB15, 1, F),
+    #[hal_cfg("pb30")]
+    (PB30, 0, F),
+    #[hal_cfg("pb31")]
+    (PB31, 1, F),
+]);
+
+#[hal_cfg("tcc0-d21")]
+wo_pins!(Tcc0: [
+    #[hal_cfg("pa04")]
+    (PA04, 0, E),
+    #[hal_cfg("pa05")]
+    (PA05, 1, E),
+    #[hal_cfg("pa08")]
+    (PA08, 0, E),
+    #[hal_cfg("pa09")]
+    (PA09, 1, E),
+    #[hal_cfg("pa10")]
+    (PA10, 2, F),
+    #[hal_cfg("pa11")]
+    (PA11, 3, F),
+    #[hal_cfg("pa12")]
+    (PA12, 6, F),
+    #[hal_cfg("pa13")]
+    (PA13, 7, F),
+    #[hal_cfg("pa14")]
+    (PA14, 4, F),
+    #[hal_cfg("pa15")]
+    (PA15, 5, F),
+    #[hal_cfg("pa16")]
+    (PA16, 6, F),
+    #[hal_cfg("pa17")]
+    (PA17, 7, F),
+    #[hal_cfg("pa18")]
+    (PA18, 2, F),
+    #[hal_cfg("pa19")]
+    (PA19, 3, F),
+    #[hal_cfg("pa20")]
+    (PA20, 6, F),
+    #[hal_cfg("pa21")]
+    (PA21, 7, F),
+    #[hal_cfg("pa22")]
+    (PA22, 4, F),
+    #[hal_cfg("pa23")]
+    (PA23, 5, F),
+    #[hal_cfg("pb10")]
+    (PB10, 4, F),
+    #[hal_cfg("pb11")]
+    (PB11, 5, F),
+    #[hal_cfg("pb12")]
+    (PB12, 6, F),
+    #[hal_cfg("pb13")]
+    (PB13, 7, F),
+    #[hal_cfg("pb16")]
+    (PB16, 4, F),
+    #[hal_cfg("pb17")]
+    (PB17, 5, F),
+    #[hal_cfg("pb30")]
+    (PB30, 0, E),
+    #[hal_cfg("pb31")]
+    (PB31, 1, E),
+]);
+
+#[hal_cfg("tcc1-d21")]
+wo_pins!(Tcc1: [
+    #[hal_cfg("pa06")]
+    (PA06, 0, E),
+    #[hal_cfg("pa07")]
+    (PA07, 1, E),
+    #[hal_cfg("pa08")]
+    (PA08, 2, F),
+    #[hal_cfg("pa09")]
+    (PA09, 3, F),
+    #[hal_cfg("pa10")]
+    (PA10, 0, E),
+    #[hal_cfg("pa11")]
+    (PA11, 1, E),
+    #[hal_cfg("pa24")]
+    (PA24, 2, F),
+    #[hal_cfg("pa25")]
+    (PA25, 3, F),
+    #[hal_cfg("pa30")]
+    (PA30, 0, E),
+    #[hal_cfg("pa31")]
+    (PA31, 1, E),
+    #[hal_cfg("pb30")]
+    (PB30, 2, F),
+    #[hal_cfg("pb31")]
+    (PB31, 3, F),
+]);
+
+#[hal_cfg("tcc2-d21")]
+wo_pins!(Tcc2: [
+    #[hal_cfg("pa00")]
+    (PA00, 0, E),
+    #[hal_cfg("pa01")]
+    (PA01, 1, E),
+    #[hal_cfg("pa12")]
+    (PA12, 0, E),
+    #[hal_cfg("pa13")]
+    (PA13, 1, E),
+    #[hal_cfg("pa16")]
+    (PA16, 0, E),
+    #[hal_cfg("pa17")]
+    (PA17, 1, E),
+]);
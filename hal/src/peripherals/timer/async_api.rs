@@ -1,7 +1,8 @@
 //! Async APIs for timers.
 //!
 //! Use [`TimerCounter::into_future`] to convert a regular [`TimerCounter`] into
-//! an asynchronous [`TimerFuture`].
+//! an asynchronous [`TimerFuture`], and [`Capture::into_future`] to convert a
+//! [`Capture`] into a [`CaptureFuture`].
 
 use crate::{
     async_hal::interrupts::{Binding, Handler, Interrupt},
@@ -22,6 +23,10 @@ use portable_atomic::AtomicBool;
 
 use crate::peripherals::timer;
 
+#[hal_cfg("tc0")]
+#[allow(unused_imports)]
+use crate::pac::Tc0;
+
 #[hal_cfg("tc1")]
 #[allow(unused_imports)]
 use crate::pac::Tc1;
@@ -42,7 +47,17 @@ use crate::pac::Tc4;
 #[allow(unused_imports)]
 use crate::pac::Tc5;
 
-use timer::{Count16, TimerCounter};
+#[hal_cfg("tc6")]
+#[allow(unused_imports)]
+use crate::pac::Tc6;
+
+#[hal_cfg("tc7")]
+#[allow(unused_imports)]
+use crate::pac::Tc7;
+
+use timer::{
+    Capture, Count8, Count16, Count32Pair, Counter, Error, Measurement, Tc8, Tc32, TimerCounter,
+};
 
 #[hal_cfg("tc1-d11")]
 type RegBlock = pac::tc1::RegisterBlock;
@@ -68,6 +83,25 @@ pub trait AsyncCount16: Count16 + Sealed {
     type Interrupt: Interrupt;
 }
 
+/// A [`Counter`] mode which can be used in async mode: a TC in 16-bit mode,
+/// a [`Tc8`] or a [`Tc32`].
+pub trait AsyncCounter: Counter {
+    /// TC generating the interrupts of the counter
+    type Tc: AsyncCount16;
+}
+
+impl<T: AsyncCount16> AsyncCounter for T {
+    type Tc = T;
+}
+
+impl<T: AsyncCount16 + Count8> AsyncCounter for Tc8<T> {
+    type Tc = T;
+}
+
+impl<T: AsyncCount16 + Count32Pair> AsyncCounter for Tc32<T> {
+    type Tc = T;
+}
+
 /// Interrupt handler for async timer operarions
 pub struct InterruptHandler<T: AsyncCount16> {
     _private: (),
@@ -91,6 +125,20 @@ impl<A: AsyncCount16> Handler<A::Interrupt> for InterruptHandler<A> {
         let periph = unsafe { crate::pac::Peripherals::steal() };
         let tc = A::reg_block(&periph);
         let intflag = &tc.count16().intflag();
+        let intenset = tc.count16().intenset().read();
+
+        // A CaptureFuture is waiting: stop signalling, but don't clear the flags;
+        // they will be checked and cleared when the future is next polled.
+        if intenset.mc0().bit_is_set() || intenset.mc1().bit_is_set() {
+            let pending = intflag.read().bits() & intenset.bits();
+            if pending != 0 {
+                tc.count16()
+                    .intenclr()
+                    .write(|w| unsafe { w.bits(pending) });
+                STATE[A::STATE_ID].notify();
+            }
+            return;
+        }
 
         if intflag.read().ovf().bit_is_set() {
             // Clear the flag
@@ -121,6 +169,9 @@ macro_rules! impl_async_count16 {
 #[hal_cfg("tc1-d11")]
 impl_async_count16!(Tc1, 0);
 
+#[hal_cfg("tc2-d11")]
+impl_async_count16!(Tc2, 1);
+
 #[hal_cfg("tc3-d21")]
 impl_async_count16!(Tc3, 0);
 
@@ -130,6 +181,12 @@ impl_async_count16!(Tc4, 1);
 #[hal_cfg("tc5-d21")]
 impl_async_count16!(Tc5, 2);
 
+#[hal_cfg("tc6-d21")]
+impl_async_count16!(Tc6, 3);
+
+#[hal_cfg("tc7-d21")]
+impl_async_count16!(Tc7, 4);
+
 #[hal_cfg("tc2-d5x")]
 impl_async_count16!(Tc2, 0);
 
@@ -142,30 +199,42 @@ impl_async_count16!(Tc4, 2);
 #[hal_cfg("tc5-d5x")]
 impl_async_count16!(Tc5, 3);
 
+#[hal_cfg("tc0-d5x")]
+impl_async_count16!(Tc0, 4);
+
+#[hal_cfg("tc1-d5x")]
+impl_async_count16!(Tc1, 5);
+
+#[hal_cfg("tc6-d5x")]
+impl_async_count16!(Tc6, 6);
+
+#[hal_cfg("tc7-d5x")]
+impl_async_count16!(Tc7, 7);
+
 // Reserve space for the max number of timer peripherals based on chip type,
 // even though some wakers may not be used on some chips if they actually don't
 // exist on variant's hardware
 #[hal_cfg("tc1-d11")]
-const NUM_TIMERS: usize = 1;
+const NUM_TIMERS: usize = 2;
 
 #[hal_cfg("tc3-d21")]
-const NUM_TIMERS: usize = 3;
+const NUM_TIMERS: usize = 5;
 
 #[hal_cfg("tc3-d5x")]
-const NUM_TIMERS: usize = 4;
+const NUM_TIMERS: usize = 8;
 
 impl<T> TimerCounter<T>
 where
-    T: AsyncCount16,
+    T: AsyncCounter,
 {
     /// Transform a [`TimerCounter`] into an [`TimerFuture`]
     #[inline]
     pub fn into_future<I>(mut self, _irq: I) -> TimerFuture<T>
     where
-        I: Binding<T::Interrupt, InterruptHandler<T>>,
+        I: Binding<<T::Tc as AsyncCount16>::Interrupt, InterruptHandler<T::Tc>>,
     {
-        T::Interrupt::unpend();
-        unsafe { T::Interrupt::enable() };
+        <T::Tc as AsyncCount16>::Interrupt::unpend();
+        unsafe { <T::Tc as AsyncCount16>::Interrupt::enable() };
         self.enable_interrupt();
 
         TimerFuture { timer: self }
@@ -175,14 +244,14 @@ where
 /// Wrapper around a [`TimerCounter`] with an `async` interface
 pub struct TimerFuture<T>
 where
-    T: AsyncCount16,
+    T: AsyncCounter,
 {
     timer: TimerCounter<T>,
 }
 
 impl<T> TimerFuture<T>
 where
-    T: AsyncCount16,
+    T: AsyncCounter,
 {
     /// Delay asynchronously
     #[inline]
@@ -191,8 +260,8 @@ where
         self.timer.enable_interrupt();
 
         poll_fn(|cx| {
-            STATE[T::STATE_ID].register(cx.waker());
-            if STATE[T::STATE_ID].ready() {
+            STATE[T::Tc::STATE_ID].register(cx.waker());
+            if STATE[T::Tc::STATE_ID].ready() {
                 return Poll::Ready(());
             }
 
@@ -204,23 +273,90 @@ where
 
 impl<T> Drop for TimerFuture<T>
 where
-    T: AsyncCount16,
+    T: AsyncCounter,
 {
     #[inline]
     fn drop(&mut self) {
-        T::Interrupt::disable();
+        <T::Tc as AsyncCount16>::Interrupt::disable();
     }
 }
 
 impl<T> embedded_hal_async::delay::DelayNs for TimerFuture<T>
 where
-    T: AsyncCount16,
+    T: AsyncCounter,
 {
     async fn delay_ns(&mut self, ns: u32) {
         self.delay(NanosDurationU32::from_ticks(ns).convert()).await;
     }
 }
 
+impl<T> Capture<T>
+where
+    T: AsyncCount16,
+{
+    /// Transform a [`Capture`] into a [`CaptureFuture`]
+    #[inline]
+    pub fn into_future<I>(self, _irq: I) -> CaptureFuture<T>
+    where
+        I: Binding<T::Interrupt, InterruptHandler<T>>,
+    {
+        T::Interrupt::unpend();
+        unsafe { T::Interrupt::enable() };
+
+        CaptureFuture { capture: self }
+    }
+}
+
+/// Wrapper around a [`Capture`] with an `async` interface
+pub struct CaptureFuture<T>
+where
+    T: AsyncCount16,
+{
+    capture: Capture<T>,
+}
+
+impl<T> CaptureFuture<T>
+where
+    T: AsyncCount16,
+{
+    /// Wait for the next measurement, see [`Capture::read`]
+    ///
+    /// Returns [`Error::Overflow`] if no period edge occurs before the counter
+    /// overflows, which allows detecting a stopped signal.
+    pub async fn next(&mut self) -> Result<Measurement, Error> {
+        poll_fn(|cx| {
+            match self.capture.read() {
+                Ok(measurement) => return Poll::Ready(Ok(measurement)),
+                Err(nb::Error::Other(error)) => return Poll::Ready(Err(error)),
+                Err(nb::Error::WouldBlock) => (),
+            }
+
+            STATE[T::STATE_ID].register(cx.waker());
+            self.capture.enable_interrupt();
+
+            match self.capture.read() {
+                Ok(measurement) => {
+                    self.capture.disable_interrupt();
+                    Poll::Ready(Ok(measurement))
+                }
+                Err(nb::Error::Other(error)) => {
+                    self.capture.disable_interrupt();
+                    Poll::Ready(Err(error))
+                }
+                Err(nb::Error::WouldBlock) => Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Convert the [`CaptureFuture`] back into a blocking [`Capture`]
+    pub fn into_blocking(mut self) -> Capture<T> {
+        T::Interrupt::disable();
+        self.capture.disable_interrupt();
+        self.capture
+    }
+}
+
 // TODO instead of tracking the state manually, we could use ONESHOT
 // mode and check the STATUS.STOP bit
 struct State {
@@ -248,6 +384,12 @@ impl State {
         self.waker.wake()
     }
 
+    /// Wake the task without marking a delay as elapsed
+    #[inline]
+    fn notify(&self) {
+        self.waker.wake()
+    }
+
     #[inline]
     fn ready(&self) -> bool {
         self.ready.swap(false, Ordering::SeqCst)
@@ -0,0 +1,418 @@
+//! Period and pulse-width capture
+//!
+//! A [`TimerCounter`] can be turned into a [`Capture`] with
+//! [`TimerCounter::into_capture`]. The TC then measures the period and the
+//! pulse width of the signal on its event input, without any CPU
+//! intervention: the counter restarts on each period edge, and the period and
+//! pulse width are captured into the compare/capture channels.
+//!
+//! Any event generator can drive the TC event input through the
+//! [`evsys`](crate::evsys) module. For example, to measure the frequency of a
+//! tachometer wired to an external interrupt pin:
+//!
+//! ```no_run
+//! let mut extint = ExtInt::new(eic_channel, pin).into_event_generator();
+//! extint.sense(Sense::High);
+//! let mut ch0 = channels.0.with_generator::<generators::EicExtint3>(Path::Asynchronous);
+//! let _tc3_ev = ch0.attach(users.tc3);
+//!
+//! let mut capture = timer.into_capture(CaptureMode::PeriodPulseWidth, Edge::Rising, Prescaler::Div16);
+//! let measurement = nb::block!(capture.read())?;
+//! let rpm = capture.frequency(&measurement).to_Hz() * 60;
+//! ```
+//!
+//! The counter is 16 bits wide: signals with a period longer than 65535
+//! counter ticks make [`Capture::read`] return [`Error::Overflow`]. The
+//! [`Prescaler`] should be chosen to cover the slowest expected signal.
+//!
+//! With the `dma` feature, the captured periods can be copied into a buffer
+//! by the DMAC, see [`Capture::capture_with_dma`]. With the `async` feature,
+//! [`Capture::into_future`] returns a [`CaptureFuture`] which waits for the
+//! next measurement.
+
+use atsamd_hal_macros::hal_macro_helper;
+
+use super::{Count16, TimerCounter};
+use crate::time::Hertz;
+
+/// Prescaler of the counter clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Prescaler {
+    Div1,
+    Div2,
+    Div4,
+    Div8,
+    Div16,
+    Div64,
+    Div256,
+    Div1024,
+}
+
+impl Prescaler {
+    /// Division factor of the prescaler
+    #[inline]
+    pub const fn divider(self) -> u16 {
+        match self {
+            Prescaler::Div1 => 1,
+            Prescaler::Div2 => 2,
+            Prescaler::Div4 => 4,
+            Prescaler::Div8 => 8,
+            Prescaler::Div16 => 16,
+            Prescaler::Div64 => 64,
+            Prescaler::Div256 => 256,
+            Prescaler::Div1024 => 1024,
+        }
+    }
+}
+
+/// Signal edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Edge {
+    Rising,
+    Falling,
+}
+
+/// Assignment of the period and pulse width to the capture channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CaptureMode {
+    /// Capture the period in channel 0 and the pulse width in channel 1
+    PeriodPulseWidth,
+    /// Capture the pulse width in channel 0 and the period in channel 1
+    PulseWidthPeriod,
+}
+
+impl CaptureMode {
+    /// Capture channel holding the period
+    #[inline]
+    const fn period_channel(self) -> usize {
+        match self {
+            CaptureMode::PeriodPulseWidth => 0,
+            CaptureMode::PulseWidthPeriod => 1,
+        }
+    }
+}
+
+/// Capture error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// A new value was captured before the previous one was read. The values
+    /// returned next are still valid, but at least one measurement was lost.
+    Overrun,
+    /// The counter overflowed: the period of the signal is longer than the
+    /// counter range, or the signal stopped
+    Overflow,
+}
+
+/// Period and pulse width of a signal, in counter ticks
+///
+/// The pulse width is the time from the period edge to the opposite edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Measurement {
+    pub period: u16,
+    pub pulse_width: u16,
+}
+
+/// A [`TimerCounter`] measuring the period and pulse width of the signal on
+/// its event input
+pub struct Capture<TC> {
+    timer: TimerCounter<TC>,
+    mode: CaptureMode,
+    prescaler: Prescaler,
+    /// Whether the counter has been restarted by a period edge since it was
+    /// enabled or since it last overflowed, which makes the captured period
+    /// valid
+    synced: bool,
+}
+
+impl<TC> TimerCounter<TC>
+where
+    TC: Count16,
+{
+    /// Start measuring the period and pulse width of the signal on the event
+    /// input
+    ///
+    /// The period starts on `edge` of the event. The counter is clocked by the
+    /// TC clock divided by `prescaler`.
+    #[hal_macro_helper]
+    pub fn into_capture(
+        mut self,
+        mode: CaptureMode,
+        edge: Edge,
+        prescaler: Prescaler,
+    ) -> Capture<TC> {
+        self.disable();
+
+        let count = self.tc.count_16();
+
+        count.ctrla().write(|w| w.swrst().set_bit());
+        #[hal_cfg("clock-d5x")]
+        while count.syncbusy().read().swrst().bit_is_set() {}
+        #[hal_cfg(any("clock-d11", "clock-d21"))]
+        {
+            while count.status().read().syncbusy().bit_is_set() {}
+            // the SVD erroneously marks swrst as write-only, so we
+            // need to manually read the bit here
+            while count.ctrla().read().bits() & 1 != 0 {}
+        }
+
+        count.evctrl().write(|w| {
+            match mode {
+                CaptureMode::PeriodPulseWidth => w.evact().ppw(),
+                CaptureMode::PulseWidthPeriod => w.evact().pwp(),
+            };
+            w.tcinv().bit(edge == Edge::Falling);
+            w.tcei().set_bit()
+        });
+
+        #[hal_cfg(any("clock-d11", "clock-d21"))]
+        {
+            count.ctrlc().write(|w| {
+                w.cpten0().set_bit();
+                w.cpten1().set_bit()
+            });
+            while count.status().read().syncbusy().bit_is_set() {}
+        }
+
+        count.ctrla().modify(|_, w| {
+            match prescaler {
+                Prescaler::Div1 => w.prescaler().div1(),
+                Prescaler::Div2 => w.prescaler().div2(),
+                Prescaler::Div4 => w.prescaler().div4(),
+                Prescaler::Div8 => w.prescaler().div8(),
+                Prescaler::Div16 => w.prescaler().div16(),
+                Prescaler::Div64 => w.prescaler().div64(),
+                Prescaler::Div256 => w.prescaler().div256(),
+                Prescaler::Div1024 => w.prescaler().div1024(),
+            };
+            #[hal_cfg("clock-d5x")]
+            {
+                w.capten0().set_bit();
+                w.capten1().set_bit();
+            }
+            w.enable().set_bit();
+            w.runstdby().set_bit()
+        });
+
+        #[hal_cfg("clock-d5x")]
+        while count.syncbusy().read().enable().bit_is_set() {}
+        #[hal_cfg(any("clock-d11", "clock-d21"))]
+        while count.status().read().syncbusy().bit_is_set() {}
+
+        Capture {
+            timer: self,
+            mode,
+            prescaler,
+            synced: false,
+        }
+    }
+}
+
+impl<TC> Capture<TC>
+where
+    TC: Count16,
+{
+    /// Capture channel holding the period
+    #[inline]
+    fn period_channel(&self) -> usize {
+        self.mode.period_channel()
+    }
+
+    /// Read the value captured in `channel`
+    #[hal_macro_helper]
+    fn read_cc(&self, channel: usize) -> u16 {
+        let count = self.timer.tc.count_16();
+
+        // The CC registers must be synchronized before they are read. The
+        // channels are 16 bits wide, starting at offset 0x18.
+        #[hal_cfg(any("clock-d11", "clock-d21"))]
+        {
+            count.readreq().write(|w| unsafe {
+                w.rreq().set_bit();
+                w.addr().bits(0x18 + 2 * channel as u8)
+            });
+            while count.status().read().syncbusy().bit_is_set() {}
+        }
+
+        count.cc(channel).read().cc().bits()
+    }
+
+    /// Frequency of the counter clock, which is the resolution of the
+    /// measurements
+    #[inline]
+    pub fn tick_freq(&self) -> Hertz {
+        self.timer.freq / self.prescaler.divider() as u32
+    }
+
+    /// Frequency of the signal for `measurement`
+    #[inline]
+    pub fn frequency(&self, measurement: &Measurement) -> Hertz {
+        self.tick_freq() / (measurement.period.max(1) as u32)
+    }
+
+    /// Read the last measurement
+    ///
+    /// Returns [`nb::Error::WouldBlock`] until a new period has been
+    /// captured. The first period captured after the counter is enabled, or
+    /// after it overflowed, is incomplete and is discarded.
+    pub fn read(&mut self) -> nb::Result<Measurement, Error> {
+        let count = self.timer.tc.count_16();
+        let flags = count.intflag().read();
+
+        if flags.err().bit_is_set() {
+            count.intflag().write(|w| w.err().set_bit());
+            return Err(nb::Error::Other(Error::Overrun));
+        }
+
+        if flags.ovf().bit_is_set() {
+            count.intflag().write(|w| w.ovf().set_bit());
+            self.synced = false;
+            return Err(nb::Error::Other(Error::Overflow));
+        }
+
+        let captured = match self.period_channel() {
+            0 => flags.mc0().bit_is_set(),
+            _ => flags.mc1().bit_is_set(),
+        };
+        if !captured {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let period = self.read_cc(self.period_channel());
+        let pulse_width = self.read_cc(1 - self.period_channel());
+        count.intflag().write(|w| {
+            w.mc0().set_bit();
+            w.mc1().set_bit()
+        });
+
+        if !self.synced {
+            self.synced = true;
+            return Err(nb::Error::WouldBlock);
+        }
+
+        Ok(Measurement {
+            period,
+            pulse_width,
+        })
+    }
+
+    /// Enable the interrupt on each captured period, overrun and overflow.
+    /// This method does not configure the interrupt controller nor define an
+    /// interrupt handler.
+    pub fn enable_interrupt(&mut self) {
+        let period_channel = self.period_channel();
+        self.timer.tc.count_16().intenset().write(|w| {
+            match period_channel {
+                0 => w.mc0().set_bit(),
+                _ => w.mc1().set_bit(),
+            };
+            w.err().set_bit();
+            w.ovf().set_bit()
+        });
+    }
+
+    /// Disable the capture interrupts
+    pub fn disable_interrupt(&mut self) {
+        self.timer.tc.count_16().intenclr().write(|w| {
+            w.mc0().set_bit();
+            w.mc1().set_bit();
+            w.err().set_bit();
+            w.ovf().set_bit()
+        });
+    }
+
+    /// Stop the capture, and return the [`TimerCounter`]
+    pub fn free(mut self) -> TimerCounter<TC> {
+        self.disable_interrupt();
+        self.timer.disable();
+        self.timer
+    }
+}
+
+#[cfg(feature = "dma")]
+pub use dma::*;
+
+#[cfg(feature = "dma")]
+mod dma {
+    use super::*;
+    use crate::dmac::{
+        self, AnyChannel, Buffer, Busy, Channel as DmaChannel, InterruptFlags, Ready, Transfer,
+        TriggerAction, TriggerSource, transfer::BufferPair,
+    };
+
+    /// Timer/counter instances able to trigger DMA transfers on capture
+    pub trait CaptureDmaId: Count16 {
+        /// DMA trigger sources of the capture channels 0 and 1
+        const DMA_TRIGGERS: [TriggerSource; 2];
+    }
+
+    /// A [`Capture`] can be used as a non-incrementing DMA [`Buffer`], which
+    /// yields the captured period on each beat
+    unsafe impl<TC: CaptureDmaId> Buffer for Capture<TC> {
+        type Beat = u16;
+
+        #[inline]
+        fn dma_ptr(&mut self) -> *mut Self::Beat {
+            self.timer.tc.count_16().cc(self.period_channel()).as_ptr()
+        }
+
+        #[inline]
+        fn incrementing(&self) -> bool {
+            false
+        }
+
+        #[inline]
+        fn buffer_len(&self) -> usize {
+            1
+        }
+    }
+
+    impl<TC: CaptureDmaId> Capture<TC> {
+        /// Transform a [`Capture`] into a DMA [`Transfer`], and copy each
+        /// captured period into `buf`
+        ///
+        /// Set `circular` to loop over the buffer indefinitely. The first
+        /// period is incomplete, and the pulse widths are not copied.
+        #[hal_macro_helper]
+        pub fn capture_with_dma<Ch, B>(
+            self,
+            buf: B,
+            mut channel: Ch,
+            circular: bool,
+        ) -> Transfer<DmaChannel<Ch::Id, Busy>, BufferPair<Self, B>>
+        where
+            Ch: AnyChannel<Status = Ready>,
+            B: Buffer<Beat = u16> + 'static,
+        {
+            channel
+                .as_mut()
+                .enable_interrupts(InterruptFlags::new().with_tcmpl(true));
+
+            let period_channel = self.period_channel();
+            let trigger = TC::DMA_TRIGGERS[period_channel];
+
+            // The DMAC reads the period register without requesting its
+            // synchronization, so keep it synchronized continuously.
+            #[hal_cfg(any("clock-d11", "clock-d21"))]
+            self.timer.tc.count_16().readreq().write(|w| unsafe {
+                w.rcont().set_bit();
+                w.addr().bits(0x18 + 2 * period_channel as u8)
+            });
+
+            #[hal_cfg("clock-d5x")]
+            let trigger_action = TriggerAction::Burst;
+
+            #[hal_cfg(any("clock-d11", "clock-d21"))]
+            let trigger_action = TriggerAction::Beat;
+
+            // SAFETY: This is safe because of the `'static` bound check for `B`,
+            // and the fact that the buffer length of a `Capture` is always 1.
+            let xfer = unsafe { dmac::Transfer::new_unchecked(channel, self, buf, circular) };
+            xfer.begin(trigger, trigger_action)
+        }
+    }
+}
@@ -4,11 +4,24 @@ use fugit::NanosDurationU32;
 
 use crate::ehal::delay::DelayNs;
 use crate::ehal_02::timer::{CountDown, Periodic};
-use crate::time::Nanoseconds;
+use crate::time::{Hertz, Nanoseconds};
 use crate::timer_params::TimerParams;
 use crate::timer_traits::InterruptDrivenTimer;
 
-use super::{Count16Reg, TimerCounter};
+use super::{Count8Reg, Count16Reg, Count32Reg, Prescaler, TimerCounter};
+
+/// Run `$body` with `$count` bound to the register view of the counter mode,
+/// whichever it is. The register blocks of the three modes have the same
+/// methods, with different field widths.
+macro_rules! with_counter {
+    ($regs:expr, |$count:ident| $body:block) => {
+        match $regs {
+            CounterRegs::Count8($count) => $body,
+            CounterRegs::Count16($count) => $body,
+            CounterRegs::Count32($count) => $body,
+        }
+    };
+}
 
 /// This is a helper trait to make it easier to make most of the
 /// TimerCounter impl generic.  It doesn't make too much sense to
@@ -17,35 +30,286 @@ pub trait Count16 {
     fn count_16(&self) -> &Count16Reg;
 }
 
+/// Helper trait giving access to the 8-bit mode registers of a TC, see
+/// [`Count16`]
+pub trait Count8 {
+    fn count_8(&self) -> &Count8Reg;
+}
+
+/// Helper trait giving access to the 32-bit mode registers of a TC, see
+/// [`Count16`]
+pub trait Count32 {
+    fn count_32(&self) -> &Count32Reg;
+}
+
+/// A TC which can be the master of a 32-bit counter, paired with its
+/// [`Slave`](Count32Pair::Slave)
+pub trait Count32Pair: Count32 + Count16 {
+    /// TC paired with this one in 32-bit mode
+    type Slave: Count16;
+}
+
+/// A TC in 8-bit counter mode, see [`TimerCounter::into_8bit`]
+pub struct Tc8<TC>(TC);
+
+/// A pair of TCs forming a 32-bit counter, see [`TimerCounter::into_32bit`]
+///
+/// The slave TC is owned by the pair, so it can't be used on its own while
+/// the pair exists.
+pub struct Tc32<TC: Count32Pair> {
+    master: TC,
+    slave: TC::Slave,
+    slave_freq: Hertz,
+}
+
+#[doc(hidden)]
+pub enum CounterRegs<'a> {
+    Count8(&'a Count8Reg),
+    Count16(&'a Count16Reg),
+    Count32(&'a Count32Reg),
+}
+
+impl CounterRegs<'_> {
+    /// Read the COUNT register, which must have been synchronized beforehand
+    pub fn read_count(&self) -> u32 {
+        match self {
+            CounterRegs::Count8(count) => count.count().read().count().bits().into(),
+            CounterRegs::Count16(count) => count.count().read().count().bits().into(),
+            CounterRegs::Count32(count) => count.count().read().count().bits(),
+        }
+    }
+}
+
+/// Counter mode of a [`TimerCounter`]
+///
+/// This trait is implemented for the TCs themselves, in 16-bit mode, for
+/// [`Tc8`] and for [`Tc32`].
+pub trait Counter {
+    /// Largest value of the counter
+    const MAX: u32;
+
+    /// Value of the `CTRLA.MODE` field
+    #[doc(hidden)]
+    const MODE: u8;
+
+    #[doc(hidden)]
+    fn regs(&self) -> CounterRegs<'_>;
+}
+
+impl<TC: Count16> Counter for TC {
+    const MAX: u32 = u16::MAX as u32;
+    const MODE: u8 = 0;
+
+    #[inline]
+    fn regs(&self) -> CounterRegs<'_> {
+        CounterRegs::Count16(self.count_16())
+    }
+}
+
+impl<TC: Count8> Counter for Tc8<TC> {
+    const MAX: u32 = u8::MAX as u32;
+    const MODE: u8 = 1;
+
+    #[inline]
+    fn regs(&self) -> CounterRegs<'_> {
+        CounterRegs::Count8(self.0.count_8())
+    }
+}
+
+impl<TC: Count32Pair> Counter for Tc32<TC> {
+    const MAX: u32 = u32::MAX;
+    const MODE: u8 = 2;
+
+    #[inline]
+    fn regs(&self) -> CounterRegs<'_> {
+        CounterRegs::Count32(self.master.count_32())
+    }
+}
+
+/// Counting direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+/// Operation settings applied each time the timer is started
+#[derive(Clone, Copy)]
+pub(super) struct Settings {
+    pub(super) one_shot: bool,
+    pub(super) direction: Direction,
+    pub(super) retrigger_on_event: bool,
+}
+
+impl Settings {
+    pub(super) const fn new() -> Self {
+        Self {
+            one_shot: false,
+            direction: Direction::Up,
+            retrigger_on_event: false,
+        }
+    }
+}
+
+impl<TC> TimerCounter<TC>
+where
+    TC: Counter,
+{
+    /// Stop the timer after a single timeout, instead of restarting it.
+    ///
+    /// The setting is applied the next time the timer is started. A stopped
+    /// one-shot timer can be restarted with
+    /// [`retrigger`](TimerCounter::retrigger).
+    #[inline]
+    pub fn set_one_shot(&mut self, one_shot: bool) {
+        self.settings.one_shot = one_shot;
+    }
+
+    /// Set the counting direction.
+    ///
+    /// The setting is applied the next time the timer is started. When
+    /// counting down, the counter starts from the timeout value and the
+    /// timeout occurs when it reaches zero.
+    #[inline]
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.settings.direction = direction;
+    }
+
+    /// Restart the timer on each event on the TC event input, routed through
+    /// the [`evsys`](crate::evsys) module.
+    ///
+    /// The setting is applied the next time the timer is started. A one-shot
+    /// timer is started by the event.
+    #[inline]
+    pub fn set_retrigger_on_event(&mut self, retrigger: bool) {
+        self.settings.retrigger_on_event = retrigger;
+    }
+
+    /// Start the counter running freely through its whole range, to be read
+    /// with [`count`](TimerCounter::count). The counter is clocked by the TC
+    /// clock divided by `prescaler`.
+    #[inline]
+    pub fn start_free_running(&mut self, prescaler: Prescaler) {
+        self.start_timer(prescaler.divider(), TC::MAX);
+    }
+
+    /// Frequency of the clock of the TC, before the prescaler
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.freq
+    }
+}
+
+impl<TC> TimerCounter<TC>
+where
+    TC: Count8 + Count16,
+{
+    /// Switch the timer to 8-bit mode.
+    ///
+    /// The timer is stopped. It is configured in 8-bit mode the next time it
+    /// is started.
+    pub fn into_8bit(mut self) -> TimerCounter<Tc8<TC>> {
+        self.disable();
+        TimerCounter {
+            freq: self.freq,
+            tc: Tc8(self.tc),
+            settings: self.settings,
+        }
+    }
+}
+
+impl<TC> TimerCounter<Tc8<TC>>
+where
+    TC: Count8 + Count16,
+{
+    /// Switch the timer back to 16-bit mode.
+    pub fn into_16bit(mut self) -> TimerCounter<TC> {
+        self.disable();
+        TimerCounter {
+            freq: self.freq,
+            tc: self.tc.0,
+            settings: self.settings,
+        }
+    }
+}
+
+impl<TC> TimerCounter<TC>
+where
+    TC: Count32Pair,
+{
+    /// Pair this timer with its slave TC to form a 32-bit timer.
+    ///
+    /// Both timers are stopped. The 32-bit timer is clocked by the clock of
+    /// this TC, and is configured in 32-bit mode the next time it is started.
+    pub fn into_32bit(mut self, mut slave: TimerCounter<TC::Slave>) -> TimerCounter<Tc32<TC>> {
+        self.disable();
+        slave.disable();
+        TimerCounter {
+            freq: self.freq,
+            tc: Tc32 {
+                master: self.tc,
+                slave: slave.tc,
+                slave_freq: slave.freq,
+            },
+            settings: self.settings,
+        }
+    }
+}
+
+impl<TC> TimerCounter<Tc32<TC>>
+where
+    TC: Count32Pair,
+{
+    /// Split the 32-bit timer back into two 16-bit timers.
+    pub fn split(mut self) -> (TimerCounter<TC>, TimerCounter<TC::Slave>) {
+        self.disable();
+        let master = TimerCounter {
+            freq: self.freq,
+            tc: self.tc.master,
+            settings: self.settings,
+        };
+        let slave = TimerCounter {
+            freq: self.tc.slave_freq,
+            tc: self.tc.slave,
+            settings: Settings::new(),
+        };
+        (master, slave)
+    }
+}
+
 impl<TC> InterruptDrivenTimer for TimerCounter<TC>
 where
-    TC: Count16,
+    TC: Counter,
 {
     /// Enable the interrupt generation for this hardware timer.
     /// This method only sets the clock configuration to trigger
     /// the interrupt; it does not configure the interrupt controller
     /// or define an interrupt handler.
     fn enable_interrupt(&mut self) {
-        self.tc.count_16().intenset().write(|w| w.ovf().set_bit());
+        with_counter!(self.tc.regs(), |count| {
+            count.intenset().write(|w| w.ovf().set_bit());
+        });
     }
 
     fn start<T>(&mut self, timeout: T)
     where
         T: Into<NanosDurationU32>,
     {
-        let params = TimerParams::new_ns(timeout.into(), self.freq);
-        self.start_timer(params.divider, params.check_cycles_u16());
+        let params = TimerParams::new_ns_max(timeout.into(), self.freq, TC::MAX);
+        self.start_timer(params.divider, params.cycles);
     }
 
     fn wait(&mut self) -> nb::Result<(), Infallible> {
-        let count = self.tc.count_16();
-        if count.intflag().read().ovf().bit_is_set() {
-            // Writing a 1 clears the flag
-            count.intflag().modify(|_, w| w.ovf().set_bit());
-            Ok(())
-        } else {
-            Err(nb::Error::WouldBlock)
-        }
+        with_counter!(self.tc.regs(), |count| {
+            if count.intflag().read().ovf().bit_is_set() {
+                // Writing a 1 clears the flag
+                count.intflag().modify(|_, w| w.ovf().set_bit());
+                Ok(())
+            } else {
+                Err(nb::Error::WouldBlock)
+            }
+        })
     }
 
     /// Disables interrupt generation for this hardware timer.
@@ -53,14 +317,16 @@ where
     /// triggering the interrupt; it does not configure the interrupt
     /// controller.
     fn disable_interrupt(&mut self) {
-        self.tc.count_16().intenclr().write(|w| w.ovf().set_bit());
+        with_counter!(self.tc.regs(), |count| {
+            count.intenclr().write(|w| w.ovf().set_bit());
+        });
     }
 }
 
 impl<TC> Periodic for TimerCounter<TC> {}
 impl<TC> CountDown for TimerCounter<TC>
 where
-    TC: Count16,
+    TC: Counter,
 {
     type Time = Nanoseconds;
 
@@ -82,28 +348,33 @@ where
 
 impl<TC> DelayNs for TimerCounter<TC>
 where
-    TC: Count16,
+    TC: Counter,
 {
     fn delay_ns(&mut self, ns: u32) {
         let ticks: u32 = (ns as u64 * self.freq.to_Hz() as u64 / 1_000_000_000_u64) as u32;
-        let params = TimerParams::new_from_ticks(ticks);
+        let params = TimerParams::new_from_ticks_max(ticks, TC::MAX);
+
+        // The delay must run in periodic mode, counting up
+        let settings = self.settings;
+        self.settings = Settings::new();
 
-        // The timer is is only 16 bits, so we may need to run it multiple times.
+        // The timer may be too narrow, so we may need to run it multiple times.
         let mut cycles = params.cycles;
-        if cycles > u16::MAX as u32 {
-            self.start_timer(params.divider, u16::MAX);
-            while cycles > u16::MAX as u32 {
+        if cycles > TC::MAX {
+            self.start_timer(params.divider, TC::MAX);
+            while cycles > TC::MAX {
                 let _ = nb::block!(InterruptDrivenTimer::wait(self));
-                cycles -= u16::MAX as u32;
+                cycles -= TC::MAX;
             }
         }
 
         // Wait more if there are any leftover cycles
         if cycles > 0 {
-            self.start_timer(params.divider, cycles as u16);
+            self.start_timer(params.divider, cycles);
             let _ = nb::block!(InterruptDrivenTimer::wait(self));
         }
 
         self.disable();
+        self.settings = settings;
     }
 }
@@ -3,15 +3,30 @@ use atsamd_hal_macros::hal_cfg;
 
 use crate::pac::Pm;
 #[hal_cfg("tc1-d11")]
-use crate::pac::{tc1::Count16 as Count16Reg, Tc1};
+use crate::pac::{
+    Tc1, Tc2,
+    tc1::{Count8 as Count8Reg, Count16 as Count16Reg, Count32 as Count32Reg},
+};
 #[hal_cfg("tc3-d21")]
-use crate::pac::{tc3::Count16 as Count16Reg, Tc3, Tc4, Tc5};
+use crate::pac::{
+    Tc3, Tc4, Tc5,
+    tc3::{Count8 as Count8Reg, Count16 as Count16Reg, Count32 as Count32Reg},
+};
+#[hal_cfg(all("tc6", "tc7"))]
+use crate::pac::{Tc6, Tc7};
 
 use crate::clock;
 use crate::time::Hertz;
 
+#[macro_use]
 mod common;
-pub use common::Count16;
+use common::Settings;
+pub use common::{
+    Count8, Count16, Count32, Count32Pair, Counter, CounterRegs, Direction, Tc8, Tc32,
+};
+
+mod capture;
+pub use capture::*;
 
 #[cfg(feature = "async")]
 mod async_api;
@@ -20,16 +35,16 @@ mod async_api;
 pub use async_api::*;
 
 // Note:
+// TC1 + TC2 can be paired to make a 32-bit counter (samd11)
 // TC3 + TC4 can be paired to make a 32-bit counter
 // TC5 + TC6 can be paired to make a 32-bit counter
 
 /// A generic hardware timer counter.
 ///
-/// The counters are exposed in 16-bit mode only.
-/// The hardware allows configuring the 8-bit mode
-/// and pairing up some instances to run in 32-bit
-/// mode, but that functionality is not currently
-/// exposed by this hal implementation.
+/// The counters run in 16-bit mode by default. A timer can be switched to
+/// 8-bit mode with [`into_8bit`](TimerCounter::into_8bit), and some
+/// instances can be paired to run in 32-bit mode with
+/// [`into_32bit`](TimerCounter::into_32bit).
 /// TimerCounter implements both the `Periodic` and
 /// the `CountDown` embedded_hal timer traits.
 /// Before a hardware timer can be used, it must first
@@ -37,61 +52,112 @@ pub use async_api::*;
 pub struct TimerCounter<TC> {
     freq: Hertz,
     tc: TC,
+    settings: Settings,
 }
 impl<TC> TimerCounter<TC>
 where
-    TC: Count16,
+    TC: Counter,
 {
-    /// Starts the 16-bit timer, counting up in periodic mode.
-    fn start_timer(&mut self, divider: u16, cycles: u16) {
+    /// Starts the timer, counting up to `cycles` in periodic or one-shot
+    /// mode.
+    fn start_timer(&mut self, divider: u16, cycles: u32) {
         // Disable the timer while we reconfigure it
         self.disable();
 
-        let count = self.tc.count_16();
-
-        // Now that we have a clock routed to the peripheral, we
-        // can ask it to perform a reset.
-        count.ctrla().write(|w| w.swrst().set_bit());
-        while count.status().read().syncbusy().bit_is_set() {}
-        // the SVD erroneously marks swrst as write-only, so we
-        // need to manually read the bit here
-        while count.ctrla().read().bits() & 1 != 0 {}
-
-        count.ctrlbset().write(|w| {
-            // Count up when the direction bit is zero
-            w.dir().clear_bit();
-            // Periodic
-            w.oneshot().clear_bit()
-        });
+        let settings = self.settings;
+        with_counter!(self.tc.regs(), |count| {
+            // Now that we have a clock routed to the peripheral, we
+            // can ask it to perform a reset.
+            count.ctrla().write(|w| w.swrst().set_bit());
+            while count.status().read().syncbusy().bit_is_set() {}
+            // the SVD erroneously marks swrst as write-only, so we
+            // need to manually read the bit here
+            while count.ctrla().read().bits() & 1 != 0 {}
+
+            // Select the counter mode
+            count.ctrla().write(|w| unsafe { w.mode().bits(TC::MODE) });
+
+            count.ctrlbset().write(|w| {
+                // Count down when the direction bit is one
+                w.dir().bit(settings.direction == Direction::Down);
+                w.oneshot().bit(settings.one_shot)
+            });
+            while count.status().read().syncbusy().bit_is_set() {}
+
+            // Set TOP value for mfrq mode
+            count.cc(0).write(|w| unsafe { w.cc().bits(cycles as _) });
+
+            // When counting down, start from TOP so that the first period is
+            // complete
+            if settings.direction == Direction::Down {
+                count
+                    .count()
+                    .write(|w| unsafe { w.count().bits(cycles as _) });
+                while count.status().read().syncbusy().bit_is_set() {}
+            }
 
-        // Set TOP value for mfrq mode
-        count.cc(0).write(|w| unsafe { w.cc().bits(cycles) });
-
-        count.ctrla().modify(|_, w| {
-            match divider {
-                1 => w.prescaler().div1(),
-                2 => w.prescaler().div2(),
-                4 => w.prescaler().div4(),
-                8 => w.prescaler().div8(),
-                16 => w.prescaler().div16(),
-                64 => w.prescaler().div64(),
-                256 => w.prescaler().div256(),
-                1024 => w.prescaler().div1024(),
-                _ => unreachable!(),
-            };
-            // Enable Match Frequency Waveform generation
-            w.wavegen().mfrq();
-            w.enable().set_bit();
-            w.runstdby().set_bit()
+            if settings.retrigger_on_event {
+                count.evctrl().write(|w| {
+                    w.evact().retrigger();
+                    w.tcei().set_bit()
+                });
+            }
+
+            count.ctrla().modify(|_, w| {
+                match divider {
+                    1 => w.prescaler().div1(),
+                    2 => w.prescaler().div2(),
+                    4 => w.prescaler().div4(),
+                    8 => w.prescaler().div8(),
+                    16 => w.prescaler().div16(),
+                    64 => w.prescaler().div64(),
+                    256 => w.prescaler().div256(),
+                    1024 => w.prescaler().div1024(),
+                    _ => unreachable!(),
+                };
+                // Enable Match Frequency Waveform generation
+                w.wavegen().mfrq();
+                w.enable().set_bit();
+                w.runstdby().set_bit()
+            });
         });
     }
 
     /// Disable the timer
     fn disable(&mut self) {
-        let count = self.tc.count_16();
+        with_counter!(self.tc.regs(), |count| {
+            count.ctrla().modify(|_, w| w.enable().clear_bit());
+            while count.status().read().syncbusy().bit_is_set() {}
+        });
+    }
+
+    /// Read the current counter value
+    pub fn count(&mut self) -> u32 {
+        with_counter!(self.tc.regs(), |count| {
+            // COUNT is at the same offset in all counter modes
+            count.readreq().write(|w| unsafe {
+                w.rreq().set_bit();
+                w.addr().bits(0x10)
+            });
+            while count.status().read().syncbusy().bit_is_set() {}
+        });
+        self.tc.regs().read_count()
+    }
+
+    /// Restart the counter, or start a stopped one-shot timer
+    pub fn retrigger(&mut self) {
+        with_counter!(self.tc.regs(), |count| {
+            count.ctrlbset().write(|w| w.cmd().retrigger());
+            while count.status().read().syncbusy().bit_is_set() {}
+        });
+    }
 
-        count.ctrla().modify(|_, w| w.enable().clear_bit());
-        while count.status().read().syncbusy().bit_is_set() {}
+    /// Check whether the counter is stopped, which is the case once a one-shot
+    /// timer has timed out
+    pub fn is_stopped(&self) -> bool {
+        with_counter!(self.tc.regs(), |count| {
+            count.status().read().stop().bit_is_set()
+        })
     }
 }
 
@@ -106,6 +172,18 @@ impl Count16 for $TC {
     }
 }
 
+impl Count8 for $TC {
+    fn count_8(&self) -> &Count8Reg {
+        self.count8()
+    }
+}
+
+impl Count32 for $TC {
+    fn count_32(&self) -> &Count32Reg {
+        self.count32()
+    }
+}
+
 impl TimerCounter<$TC>
 {
     /// Configure this timer counter instance.
@@ -127,9 +205,20 @@ impl TimerCounter<$TC>
         Self {
             freq: clock.freq(),
             tc,
+            settings: Settings::new(),
         }
     }
 }
+
+#[cfg(feature = "dma")]
+paste::paste! {
+    impl CaptureDmaId for $TC {
+        const DMA_TRIGGERS: [crate::dmac::TriggerSource; 2] = [
+            crate::dmac::TriggerSource::[<$TC Mc0>],
+            crate::dmac::TriggerSource::[<$TC Mc1>],
+        ];
+    }
+}
         )+
     }
 }
@@ -138,6 +227,7 @@ impl TimerCounter<$TC>
 #[hal_cfg("tc1-d11")]
 tc! {
     TimerCounter1: (Tc1, tc1_, Tc1Tc2Clock),
+    TimerCounter2: (Tc2, tc2_, Tc1Tc2Clock),
 }
 // samd21
 #[hal_cfg("tc3-d21")]
@@ -146,3 +236,23 @@ tc! {
     TimerCounter4: (Tc4, tc4_, Tc4Tc5Clock),
     TimerCounter5: (Tc5, tc5_, Tc4Tc5Clock),
 }
+#[hal_cfg(all("tc6", "tc7"))]
+tc! {
+    TimerCounter6: (Tc6, tc6_, Tc6Tc7Clock),
+    TimerCounter7: (Tc7, tc7_, Tc6Tc7Clock),
+}
+
+#[hal_cfg("tc1-d11")]
+impl Count32Pair for Tc1 {
+    type Slave = Tc2;
+}
+
+#[hal_cfg("tc3-d21")]
+impl Count32Pair for Tc3 {
+    type Slave = Tc4;
+}
+
+#[hal_cfg("tc6")]
+impl Count32Pair for Tc5 {
+    type Slave = Tc6;
+}
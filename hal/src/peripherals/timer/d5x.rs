@@ -1,8 +1,8 @@
 //! Working with timer counter hardware
 use atsamd_hal_macros::hal_cfg;
 
-use crate::pac::tc0::Count16 as Count16Reg;
-use crate::pac::{Mclk, Tc2, Tc3};
+use crate::pac::tc0::{Count8 as Count8Reg, Count16 as Count16Reg, Count32 as Count32Reg};
+use crate::pac::{Mclk, Tc0, Tc1, Tc2, Tc3};
 #[hal_cfg(all("tc4", "tc5"))]
 use crate::pac::{Tc4, Tc5};
 #[hal_cfg(all("tc6", "tc7"))]
@@ -11,8 +11,18 @@ use crate::pac::{Tc6, Tc7};
 use crate::clock;
 use crate::time::Hertz;
 
+#[macro_use]
 mod common;
-pub use common::Count16;
+use common::Settings;
+pub use common::{
+    Count8, Count16, Count32, Count32Pair, Counter, CounterRegs, Direction, Tc8, Tc32,
+};
+
+mod capture;
+pub use capture::*;
+
+mod pin_capture;
+pub use pin_capture::*;
 
 #[cfg(feature = "async")]
 mod async_api;
@@ -21,16 +31,17 @@ mod async_api;
 pub use async_api::*;
 
 // Note:
-// TC3 + TC4 can be paired to make a 32-bit counter
-// TC5 + TC6 can be paired to make a 32-bit counter
+// TC0 + TC1 can be paired to make a 32-bit counter
+// TC2 + TC3 can be paired to make a 32-bit counter
+// TC4 + TC5 can be paired to make a 32-bit counter
+// TC6 + TC7 can be paired to make a 32-bit counter
 
 /// A generic hardware timer counter.
 ///
-/// The counters are exposed in 16-bit mode only.
-/// The hardware allows configuring the 8-bit mode
-/// and pairing up some instances to run in 32-bit
-/// mode, but that functionality is not currently
-/// exposed by this hal implementation.
+/// The counters run in 16-bit mode by default. A timer can be switched to
+/// 8-bit mode with [`into_8bit`](TimerCounter::into_8bit), and the even TCs
+/// can be paired with the next odd TC to run in 32-bit mode with
+/// [`into_32bit`](TimerCounter::into_32bit).
 /// TimerCounter implements both the `Periodic` and
 /// the `CountDown` embedded_hal timer traits.
 /// Before a hardware timer can be used, it must first
@@ -38,59 +49,107 @@ pub use async_api::*;
 pub struct TimerCounter<TC> {
     freq: Hertz,
     tc: TC,
+    settings: Settings,
 }
 impl<TC> TimerCounter<TC>
 where
-    TC: Count16,
+    TC: Counter,
 {
-    /// Starts the 16-bit timer, counting up in periodic mode.
-    fn start_timer(&mut self, divider: u16, cycles: u16) {
+    /// Starts the timer, counting up to `cycles` in periodic or one-shot
+    /// mode.
+    fn start_timer(&mut self, divider: u16, cycles: u32) {
         // Disable the timer while we reconfigure it
         self.disable();
 
-        let count = self.tc.count_16();
-
-        // Now that we have a clock routed to the peripheral, we
-        // can ask it to perform a reset.
-        count.ctrla().write(|w| w.swrst().set_bit());
-        while count.syncbusy().read().swrst().bit_is_set() {}
+        let settings = self.settings;
+        with_counter!(self.tc.regs(), |count| {
+            // Now that we have a clock routed to the peripheral, we
+            // can ask it to perform a reset.
+            count.ctrla().write(|w| w.swrst().set_bit());
+            while count.syncbusy().read().swrst().bit_is_set() {}
+
+            // Select the counter mode
+            count.ctrla().write(|w| unsafe { w.mode().bits(TC::MODE) });
+
+            count.ctrlbset().write(|w| {
+                // Count down when the direction bit is one
+                w.dir().bit(settings.direction == Direction::Down);
+                w.oneshot().bit(settings.one_shot)
+            });
+            while count.syncbusy().read().ctrlb().bit_is_set() {}
+
+            // Set TOP value for mfrq mode
+            count.cc(0).write(|w| unsafe { w.cc().bits(cycles as _) });
+
+            // When counting down, start from TOP so that the first period is
+            // complete
+            if settings.direction == Direction::Down {
+                count
+                    .count()
+                    .write(|w| unsafe { w.count().bits(cycles as _) });
+                while count.syncbusy().read().count().bit_is_set() {}
+            }
+
+            if settings.retrigger_on_event {
+                count.evctrl().write(|w| {
+                    w.evact().retrigger();
+                    w.tcei().set_bit()
+                });
+            }
+
+            // Enable Match Frequency Waveform generation
+            count.wave().modify(|_, w| w.wavegen().mfrq());
+
+            count.ctrla().modify(|_, w| {
+                match divider {
+                    1 => w.prescaler().div1(),
+                    2 => w.prescaler().div2(),
+                    4 => w.prescaler().div4(),
+                    8 => w.prescaler().div8(),
+                    16 => w.prescaler().div16(),
+                    64 => w.prescaler().div64(),
+                    256 => w.prescaler().div256(),
+                    1024 => w.prescaler().div1024(),
+                    _ => unreachable!(),
+                };
+                w.enable().set_bit();
+                w.runstdby().set_bit()
+            });
+        });
+    }
 
-        count.ctrlbset().write(|w| {
-            // Count up when the direction bit is zero
-            w.dir().clear_bit();
-            // Periodic
-            w.oneshot().clear_bit()
+    /// Disable the timer
+    fn disable(&mut self) {
+        with_counter!(self.tc.regs(), |count| {
+            count.ctrla().modify(|_, w| w.enable().clear_bit());
+            while count.syncbusy().read().enable().bit_is_set() {}
         });
+    }
 
-        // Set TOP value for mfrq mode
-        count.cc(0).write(|w| unsafe { w.cc().bits(cycles) });
-
-        // Enable Match Frequency Waveform generation
-        count.wave().modify(|_, w| w.wavegen().mfrq());
-
-        count.ctrla().modify(|_, w| {
-            match divider {
-                1 => w.prescaler().div1(),
-                2 => w.prescaler().div2(),
-                4 => w.prescaler().div4(),
-                8 => w.prescaler().div8(),
-                16 => w.prescaler().div16(),
-                64 => w.prescaler().div64(),
-                256 => w.prescaler().div256(),
-                1024 => w.prescaler().div1024(),
-                _ => unreachable!(),
-            };
-            w.enable().set_bit();
-            w.runstdby().set_bit()
+    /// Read the current counter value
+    pub fn count(&mut self) -> u32 {
+        with_counter!(self.tc.regs(), |count| {
+            count.ctrlbset().write(|w| w.cmd().readsync());
+            while count.ctrlbset().read().cmd().bits() != 0 {}
+            while count.syncbusy().read().count().bit_is_set() {}
         });
+        self.tc.regs().read_count()
     }
 
-    /// Disable the timer
-    fn disable(&mut self) {
-        let count = self.tc.count_16();
+    /// Restart the counter, or start a stopped one-shot timer
+    pub fn retrigger(&mut self) {
+        with_counter!(self.tc.regs(), |count| {
+            count.ctrlbset().write(|w| w.cmd().retrigger());
+            while count.syncbusy().read().ctrlb().bit_is_set() {}
+        });
+    }
 
-        count.ctrla().modify(|_, w| w.enable().clear_bit());
-        while count.syncbusy().read().enable().bit_is_set() {}
+    /// Check whether the counter is stopped, which is the case once a one-shot
+    /// timer has timed out
+    pub fn is_stopped(&self) -> bool {
+        with_counter!(self.tc.regs(), |count| {
+            count.status().read().stop().bit_is_set()
+        })
     }
 }
 
@@ -105,6 +164,18 @@ impl Count16 for $TC {
     }
 }
 
+impl Count8 for $TC {
+    fn count_8(&self) -> &Count8Reg {
+        self.count8()
+    }
+}
+
+impl Count32 for $TC {
+    fn count_32(&self) -> &Count32Reg {
+        self.count32()
+    }
+}
+
 impl TimerCounter<$TC>
 {
     /// Configure this timer counter instance.
@@ -126,14 +197,27 @@ impl TimerCounter<$TC>
         Self {
             freq: clock.freq(),
             tc,
+            settings: Settings::new(),
         }
     }
 }
+
+#[cfg(feature = "dma")]
+paste::paste! {
+    impl CaptureDmaId for $TC {
+        const DMA_TRIGGERS: [crate::dmac::TriggerSource; 2] = [
+            crate::dmac::TriggerSource::[<$TC Mc0>],
+            crate::dmac::TriggerSource::[<$TC Mc1>],
+        ];
+    }
+}
         )+
     }
 }
 
 tc! {
+    TimerCounter0: (Tc0, tc0_, Tc0Tc1Clock, apbamask),
+    TimerCounter1: (Tc1, tc1_, Tc0Tc1Clock, apbamask),
     TimerCounter2: (Tc2, tc2_, Tc2Tc3Clock, apbbmask),
     TimerCounter3: (Tc3, tc3_, Tc2Tc3Clock, apbbmask),
 }
@@ -149,3 +233,21 @@ tc! {
     TimerCounter6: (Tc6, tc6_, Tc6Tc7Clock, apbdmask),
     TimerCounter7: (Tc7, tc7_, Tc6Tc7Clock, apbdmask),
 }
+
+impl Count32Pair for Tc0 {
+    type Slave = Tc1;
+}
+
+impl Count32Pair for Tc2 {
+    type Slave = Tc3;
+}
+
+#[hal_cfg(all("tc4", "tc5"))]
+impl Count32Pair for Tc4 {
+    type Slave = Tc5;
+}
+
+#[hal_cfg(all("tc6", "tc7"))]
+impl Count32Pair for Tc6 {
+    type Slave = Tc7;
+}
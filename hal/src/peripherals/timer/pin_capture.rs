@@ -0,0 +1,310 @@
+//! I/O pin capture
+//!
+//! On SAMx5x chips, the capture channels of a TC can be triggered directly by
+//! its `WO[0]` and `WO[1]` pins, without going through the EIC and EVSYS. A
+//! [`PinCapture`] timestamps the edges of the signals on these pins, with a
+//! free-running 16-bit counter.
+//!
+//! ```no_run
+//! let pins = (CapturePin::new(pins.pa14, Edge::Rising), CapturePin::new(pins.pa15, Edge::Falling));
+//! let mut capture = timer.into_pin_capture(pins, Prescaler::Div8);
+//! let first = nb::block!(capture.read(0))?;
+//! let second = nb::block!(capture.read(0))?;
+//! let period = second.wrapping_sub(first);
+//! ```
+
+use core::marker::PhantomData;
+
+use atsamd_hal_macros::hal_cfg;
+
+use super::{Count16, Edge, Error, Prescaler, TimerCounter};
+use crate::gpio::{AlternateE, AnyPin, Pin, PinId};
+
+use crate::pac::{Tc2, Tc3};
+#[hal_cfg(all("tc4", "tc5"))]
+use crate::pac::{Tc4, Tc5};
+#[hal_cfg(all("tc6", "tc7"))]
+use crate::pac::{Tc6, Tc7};
+
+/// Type-level trait for [`PinId`]s which can be a capture input of the TC
+/// `TC`
+pub trait CapturePinId<TC>: PinId {
+    /// Capture channel triggered by the pin, which is `x` for the `WO[x]` pin
+    const CHANNEL: usize;
+}
+
+/// Capture input of the TC `TC`
+pub struct CapturePin<TC, I: CapturePinId<TC>> {
+    pin: Pin<I, AlternateE>,
+    edge: Edge,
+    _tc: PhantomData<TC>,
+}
+
+impl<TC, I: CapturePinId<TC>> CapturePin<TC, I> {
+    /// Configure `pin` as a capture input, triggered on `edge`
+    #[inline]
+    pub fn new(pin: impl AnyPin<Id = I>, edge: Edge) -> Self {
+        Self {
+            pin: pin.into().into_alternate(),
+            edge,
+            _tc: PhantomData,
+        }
+    }
+
+    /// Return the underlying pin
+    #[inline]
+    pub fn free(self) -> Pin<I, AlternateE> {
+        self.pin
+    }
+}
+
+/// Set of [`CapturePin`]s owned by a [`PinCapture`]
+///
+/// This trait is implemented for a single [`CapturePin`], and for a pair of
+/// [`CapturePin`]s of the same TC.
+pub trait CapturePins<TC> {
+    /// Mask of the capture channels triggered by the pins
+    fn channels(&self) -> u8;
+
+    /// Mask of the capture channels triggered on a falling edge
+    fn inverted(&self) -> u8;
+}
+
+impl<TC, I: CapturePinId<TC>> CapturePins<TC> for CapturePin<TC, I> {
+    #[inline]
+    fn channels(&self) -> u8 {
+        1 << I::CHANNEL
+    }
+
+    #[inline]
+    fn inverted(&self) -> u8 {
+        match self.edge {
+            Edge::Rising => 0,
+            Edge::Falling => 1 << I::CHANNEL,
+        }
+    }
+}
+
+impl<TC, P0: CapturePins<TC>, P1: CapturePins<TC>> CapturePins<TC> for (P0, P1) {
+    #[inline]
+    fn channels(&self) -> u8 {
+        self.0.channels() | self.1.channels()
+    }
+
+    #[inline]
+    fn inverted(&self) -> u8 {
+        self.0.inverted() | self.1.inverted()
+    }
+}
+
+/// A [`TimerCounter`] timestamping the edges on its `WO[0]` and `WO[1]` pins
+pub struct PinCapture<TC, P> {
+    timer: TimerCounter<TC>,
+    pins: P,
+}
+
+impl<TC> TimerCounter<TC>
+where
+    TC: Count16,
+{
+    /// Start timestamping the edges on `pins`
+    ///
+    /// The counter runs freely, and is clocked by the TC clock divided by
+    /// `prescaler`.
+    pub fn into_pin_capture<P: CapturePins<TC>>(
+        mut self,
+        pins: P,
+        prescaler: Prescaler,
+    ) -> PinCapture<TC, P> {
+        self.disable();
+
+        let count = self.tc.count_16();
+        let channels = pins.channels();
+        let inverted = pins.inverted();
+
+        count.ctrla().write(|w| w.swrst().set_bit());
+        while count.syncbusy().read().swrst().bit_is_set() {}
+
+        count.drvctrl().write(|w| {
+            w.inven0().bit(inverted & 0b01 != 0);
+            w.inven1().bit(inverted & 0b10 != 0)
+        });
+
+        count.ctrla().modify(|_, w| {
+            match prescaler {
+                Prescaler::Div1 => w.prescaler().div1(),
+                Prescaler::Div2 => w.prescaler().div2(),
+                Prescaler::Div4 => w.prescaler().div4(),
+                Prescaler::Div8 => w.prescaler().div8(),
+                Prescaler::Div16 => w.prescaler().div16(),
+                Prescaler::Div64 => w.prescaler().div64(),
+                Prescaler::Div256 => w.prescaler().div256(),
+                Prescaler::Div1024 => w.prescaler().div1024(),
+            };
+            w.capten0().bit(channels & 0b01 != 0);
+            w.copen0().bit(channels & 0b01 != 0);
+            w.capten1().bit(channels & 0b10 != 0);
+            w.copen1().bit(channels & 0b10 != 0);
+            w.enable().set_bit();
+            w.runstdby().set_bit()
+        });
+        while count.syncbusy().read().enable().bit_is_set() {}
+
+        PinCapture { timer: self, pins }
+    }
+}
+
+impl<TC, P> PinCapture<TC, P>
+where
+    TC: Count16,
+    P: CapturePins<TC>,
+{
+    /// Read the counter value captured on the last edge of the pin of
+    /// `channel`
+    ///
+    /// Returns [`nb::Error::WouldBlock`] until an edge has been captured, and
+    /// [`Error::Overrun`] if an edge on either channel was missed because the
+    /// previous value was not read in time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no pin of `channel` is owned by the [`PinCapture`].
+    pub fn read(&mut self, channel: usize) -> nb::Result<u16, Error> {
+        assert!(self.pins.channels() & (1 << channel) != 0);
+
+        let count = self.timer.tc.count_16();
+        let flags = count.intflag().read();
+
+        if flags.err().bit_is_set() {
+            count.intflag().write(|w| w.err().set_bit());
+            return Err(nb::Error::Other(Error::Overrun));
+        }
+
+        let captured = match channel {
+            0 => flags.mc0().bit_is_set(),
+            _ => flags.mc1().bit_is_set(),
+        };
+        if !captured {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let value = count.cc(channel).read().cc().bits();
+        count.intflag().write(|w| match channel {
+            0 => w.mc0().set_bit(),
+            _ => w.mc1().set_bit(),
+        });
+        Ok(value)
+    }
+
+    /// Stop the capture, and return the [`TimerCounter`] and the pins
+    pub fn free(mut self) -> (TimerCounter<TC>, P) {
+        self.timer.disable();
+        (self.timer, self.pins)
+    }
+}
+
+macro_rules! capture_pins {
+    (
+        $TC:ident: [ $(
+            $( #[$attr:meta] )?
+            ($Id:ident, $CHANNEL:literal)
+        ),+ $(,)? ]
+    ) => {
+        $(
+            $( #[$attr] )?
+            impl CapturePinId<$TC> for crate::gpio::$Id {
+                const CHANNEL: usize = $CHANNEL;
+            }
+        )+
+    };
+}
+
+capture_pins!(Tc2: [
+    #[hal_cfg("pa00")]
+    (PA00, 0),
+    #[hal_cfg("pa01")]
+    (PA01, 1),
+    #[hal_cfg("pa12")]
+    (PA12, 0),
+    #[hal_cfg("pa13")]
+    (PA13, 1),
+    #[hal_cfg("pa16")]
+    (PA16, 0),
+    #[hal_cfg("pa17")]
+    (PA17, 1),
+]);
+
+capture_pins!(Tc3: [
+    #[hal_cfg("pa14")]
+    (PA14, 0),
+    #[hal_cfg("pa15")]
+    (PA15, 1),
+    #[hal_cfg("pa18")]
+    (PA18, 0),
+    #[hal_cfg("pa19")]
+    (PA19, 1),
+]);
+
+#[hal_cfg(all("tc4", "tc5"))]
+capture_pins!(Tc4: [
+    #[hal_cfg("pa22")]
+    (PA22, 0),
+    #[hal_cfg("pa23")]
+    (PA23, 1),
+    #[hal_cfg("pb08")]
+    (PB08, 0),
+    #[hal_cfg("pb09")]
+    (PB09, 1),
+    #[hal_cfg("pb12")]
+    (PB12, 0),
+    #[hal_cfg("pb13")]
+    (PB13, 1),
+]);
+
+#[hal_cfg(all("tc4", "tc5"))]
+capture_pins!(Tc5: [
+    #[hal_cfg("pa24")]
+    (PA24, 0),
+    #[hal_cfg("pa25")]
+    (PA25, 1),
+    #[hal_cfg("pb10")]
+    (PB10, 0),
+    #[hal_cfg("pb11")]
+    (PB11, 1),
+    #[hal_cfg("pb14")]
+    (PB14, 0),
+    #[hal_cfg("pb15")]
+    (PB15, 1),
+]);
+
+#[hal_cfg(all("tc6", "tc7"))]
+capture_pins!(Tc6: [
+    #[hal_cfg("pb02")]
+    (PB02, 0),
+    #[hal_cfg("pb03")]
+    (PB03, 1),
+    #[hal_cfg("pb16")]
+    (PB16, 0),
+    #[hal_cfg("pb17")]
+    (PB17, 1),
+    #[hal_cfg("pa30")]
+    (PA30, 0),
+    #[hal_cfg("pa31")]
+    (PA31, 1),
+]);
+
+#[hal_cfg(all("tc6", "tc7"))]
+capture_pins!(Tc7: [
+    #[hal_cfg("pa20")]
+    (PA20, 0),
+    #[hal_cfg("pa21")]
+    (PA21, 1),
+    #[hal_cfg("pb22")]
+    (PB22, 0),
+    #[hal_cfg("pb23")]
+    (PB23, 1),
+    #[hal_cfg("pb00")]
+    (PB00, 0),
+    #[hal_cfg("pb01")]
+    (PB01, 1),
+]);
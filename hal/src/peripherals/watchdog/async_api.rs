@@ -0,0 +1,115 @@
+//! Async APIs for the watchdog.
+//!
+//! Use [`Watchdog::into_future`] to convert a [`Watchdog`] into an
+//! asynchronous [`FutureWatchdog`], which can wait for the early warning.
+
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::*;
+use crate::async_hal::interrupts::{Binding, Handler, InterruptSource, WDT};
+use crate::pac;
+use crate::typelevel::Sealed;
+
+static WAKER: AtomicWaker = AtomicWaker::new();
+
+/// Interrupt handler for the WDT peripheral.
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Sealed for InterruptHandler {}
+
+impl Handler<WDT> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        let wdt = unsafe { pac::Peripherals::steal().wdt };
+
+        // Disable the interrupt but don't clear the flag; it will be cleared
+        // when the future is next polled.
+        if wdt.intenset().read().ew().bit_is_set() && wdt.intflag().read().ew().bit_is_set() {
+            wdt.intenclr().write(|w| w.ew().set_bit());
+            WAKER.wake();
+        }
+    }
+}
+
+impl Watchdog {
+    /// Turn a [`Watchdog`] into a [`FutureWatchdog`], allowing for the use of
+    /// async methods. You are required to provide the struct created by the
+    /// [`bind_interrupts`](crate::bind_interrupts) macro to prove that the
+    /// interrupt source has been correctly configured. This function will
+    /// automatically enable the `WDT` NVIC interrupt source.
+    pub fn into_future<I>(self, irqs: I) -> FutureWatchdog<I>
+    where
+        I: Binding<WDT, InterruptHandler>,
+    {
+        WDT::unpend();
+        unsafe { WDT::enable() };
+        FutureWatchdog { inner: self, irqs }
+    }
+}
+
+/// A [`Watchdog`] capable of `async` operations
+pub struct FutureWatchdog<I> {
+    inner: Watchdog,
+    irqs: I,
+}
+
+impl<I> FutureWatchdog<I>
+where
+    I: Binding<WDT, InterruptHandler>,
+{
+    /// Convert the [`FutureWatchdog`] back into a blocking [`Watchdog`], and
+    /// return the IRQs
+    pub fn into_blocking(self) -> (Watchdog, I) {
+        WDT::disable();
+        (self.inner, self.irqs)
+    }
+
+    /// Returns the current configuration of the watchdog, see
+    /// [`Watchdog::config`]
+    #[inline]
+    pub fn config(&self) -> WatchdogConfig {
+        self.inner.config()
+    }
+
+    /// Feeds the watchdog, and clears the early warning
+    #[inline]
+    pub fn feed(&mut self) {
+        self.inner.clear_early_warning();
+        watchdog::Watchdog::feed(&mut self.inner);
+    }
+
+    /// Wait until the early warning is raised, which means the watchdog must
+    /// be fed soon.
+    ///
+    /// Returns immediately if the early warning was raised since the watchdog
+    /// was last fed with [`feed`](Self::feed).
+    pub async fn wait_early_warning(&mut self) {
+        core::future::poll_fn(|cx| {
+            if self.inner.is_early_warning() {
+                return Poll::Ready(());
+            }
+
+            WAKER.register(cx.waker());
+            self.inner.enable_early_warning_interrupt();
+
+            if self.inner.is_early_warning() {
+                return Poll::Ready(());
+            }
+
+            Poll::Pending
+        })
+        .await;
+    }
+}
+
+impl<I> watchdog::Watchdog for FutureWatchdog<I>
+where
+    I: Binding<WDT, InterruptHandler>,
+{
+    fn feed(&mut self) {
+        FutureWatchdog::feed(self);
+    }
+}
@@ -0,0 +1,282 @@
+//! # Watchdog Timer
+//!
+//! The [`Watchdog`] resets the device if it isn't fed before its timeout
+//! period elapses. It runs from a 1.024 kHz clock, and supports:
+//!
+//! * The window mode, started with [`Watchdog::start_windowed`], where
+//!   feeding the watchdog during the closed window also resets the device.
+//! * The early warning interrupt, raised some time before the timeout, see
+//!   [`Watchdog::set_early_warning`]. With the `async` feature, a
+//!   [`FutureWatchdog`] waits for it.
+//! * The always-on mode, started with [`Watchdog::start_always_on`], where
+//!   the watchdog can't be disabled until the next reset.
+//!
+//! The watchdog can also be enabled at boot by the fuses of the NVM user row.
+//! [`Watchdog::config`] returns the configuration currently applied.
+//!
+//! With the `async` feature, a [`TaskWatchdog`] feeds the watchdog only when
+//! several tasks have all checked in.
+
+use crate::ehal_02::watchdog;
+use crate::pac::Wdt;
+use atsamd_hal_macros::hal_macro_helper;
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
+#[cfg(feature = "async")]
+mod task;
+#[cfg(feature = "async")]
+pub use task::*;
+
+/// WatchdogTimeout enumerates usable values for configuring
+/// the timeout of the watchdog peripheral.
+#[repr(u8)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WatchdogTimeout {
+    Cycles8 = 0,
+    Cycles16,
+    Cycles32,
+    Cycles64,
+    Cycles128,
+    Cycles256,
+    Cycles512,
+    Cycles1K,
+    Cycles2K,
+    Cycles4K,
+    Cycles8K,
+    Cycles16K,
+}
+
+impl WatchdogTimeout {
+    /// Returns the number of watchdog clock cycles
+    #[inline]
+    pub const fn cycles(self) -> u32 {
+        8 << self as u32
+    }
+
+    /// Convert the value of a `PER`, `WINDOW` or `EWOFFSET` field. The
+    /// reserved values are clamped to [`Cycles16K`](Self::Cycles16K).
+    fn from_bits(bits: u8) -> Self {
+        match bits {
+            0 => Self::Cycles8,
+            1 => Self::Cycles16,
+            2 => Self::Cycles32,
+            3 => Self::Cycles64,
+            4 => Self::Cycles128,
+            5 => Self::Cycles256,
+            6 => Self::Cycles512,
+            7 => Self::Cycles1K,
+            8 => Self::Cycles2K,
+            9 => Self::Cycles4K,
+            10 => Self::Cycles8K,
+            _ => Self::Cycles16K,
+        }
+    }
+}
+
+/// Configuration of the watchdog, as returned by [`Watchdog::config`]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WatchdogConfig {
+    /// The watchdog is running
+    pub enabled: bool,
+    /// The watchdog runs in always-on mode, and can't be disabled
+    pub always_on: bool,
+    /// Timeout period, or open window period in window mode
+    pub period: WatchdogTimeout,
+    /// Closed window period, if the watchdog is in window mode
+    pub window: Option<WatchdogTimeout>,
+    /// Offset of the early warning interrupt
+    pub early_warning: WatchdogTimeout,
+}
+
+pub struct Watchdog {
+    wdt: Wdt,
+}
+
+impl Watchdog {
+    pub fn new(wdt: Wdt) -> Self {
+        Self { wdt }
+    }
+
+    /// Returns the current configuration of the watchdog.
+    ///
+    /// After a reset, this is the configuration loaded from the NVM user row,
+    /// which may have enabled the watchdog, or even put it in always-on mode.
+    #[hal_macro_helper]
+    pub fn config(&self) -> WatchdogConfig {
+        #[hal_cfg(any("wdt-d11", "wdt-d21"))]
+        let ctrl = self.wdt.ctrl().read();
+        #[hal_cfg("wdt-d5x")]
+        let ctrl = self.wdt.ctrla().read();
+        let config = self.wdt.config().read();
+
+        WatchdogConfig {
+            enabled: ctrl.enable().bit_is_set(),
+            always_on: ctrl.alwayson().bit_is_set(),
+            period: WatchdogTimeout::from_bits(config.per().bits()),
+            window: ctrl
+                .wen()
+                .bit_is_set()
+                .then(|| WatchdogTimeout::from_bits(config.window().bits())),
+            early_warning: WatchdogTimeout::from_bits(self.wdt.ewctrl().read().ewoffset().bits()),
+        }
+    }
+
+    /// Starts the watchdog in window mode.
+    ///
+    /// Feeding the watchdog during the `closed` period resets the device, as
+    /// does not feeding it during the following `open` period.
+    pub fn start_windowed(&mut self, closed: WatchdogTimeout, open: WatchdogTimeout) {
+        self.wdt.config().write(|w| unsafe {
+            w.per().bits(open as u8);
+            w.window().bits(closed as u8)
+        });
+        self.enable(true, false);
+    }
+
+    /// Starts the watchdog in always-on mode, with the closed window period
+    /// `window` if it is `Some`.
+    ///
+    /// The watchdog then can't be disabled until the next reset, and [`disable`](watchdog::WatchdogDisable::disable) has no
+    /// effect.
+    pub fn start_always_on(&mut self, period: WatchdogTimeout, window: Option<WatchdogTimeout>) {
+        self.wdt.config().write(|w| unsafe {
+            w.per().bits(period as u8);
+            w.window()
+                .bits(window.unwrap_or(WatchdogTimeout::Cycles8) as u8)
+        });
+        self.enable(window.is_some(), true);
+    }
+
+    /// Sets the offset of the early warning interrupt, raised `offset` cycles
+    /// after the start of the timeout period.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the watchdog is running, as its configuration can't be
+    /// changed.
+    pub fn set_early_warning(&mut self, offset: WatchdogTimeout) {
+        assert!(!self.config().enabled, "the watchdog is running");
+        self.wdt
+            .ewctrl()
+            .write(|w| unsafe { w.ewoffset().bits(offset as u8) });
+    }
+
+    /// Enables the early warning interrupt
+    #[inline]
+    pub fn enable_early_warning_interrupt(&mut self) {
+        self.wdt.intenset().write(|w| w.ew().set_bit());
+    }
+
+    /// Disables the early warning interrupt
+    #[inline]
+    pub fn disable_early_warning_interrupt(&mut self) {
+        self.wdt.intenclr().write(|w| w.ew().set_bit());
+    }
+
+    /// Returns whether the early warning has been raised since it was last
+    /// cleared
+    #[inline]
+    pub fn is_early_warning(&self) -> bool {
+        self.wdt.intflag().read().ew().bit_is_set()
+    }
+
+    /// Clears the early warning flag
+    #[inline]
+    pub fn clear_early_warning(&mut self) {
+        self.wdt.intflag().write(|w| w.ew().set_bit());
+    }
+
+    /// Returns the PAC [`Wdt`]
+    #[inline]
+    pub fn free(self) -> Wdt {
+        self.wdt
+    }
+
+    /// Enable the watchdog, in window and/or always-on mode
+    #[hal_macro_helper]
+    fn enable(&mut self, window: bool, always_on: bool) {
+        #[hal_cfg(any("wdt-d11", "wdt-d21"))]
+        {
+            // Enable the watchdog timer.
+            self.wdt.ctrl().write(|w| {
+                w.wen().bit(window);
+                w.alwayson().bit(always_on);
+                w.enable().set_bit()
+            });
+            // Wait for watchdog timer to be enabled.
+            while self.wdt.status().read().syncbusy().bit_is_set() {}
+        }
+
+        #[hal_cfg("wdt-d5x")]
+        {
+            // Enable the watchdog timer.
+            self.wdt.ctrla().write(|w| {
+                w.wen().bit(window);
+                w.alwayson().bit(always_on);
+                w.enable().set_bit()
+            });
+            // Wait for watchdog timer to be enabled.
+            while self.wdt.syncbusy().read().bits() != 0 {}
+        }
+    }
+}
+
+impl watchdog::Watchdog for Watchdog {
+    /// Feeds an existing watchdog to ensure the processor isn't reset.
+    /// Sometimes commonly referred to as "kicking" or "refreshing".
+    fn feed(&mut self) {
+        self.wdt.clear().write(|w| unsafe { w.clear().bits(0xA5) });
+    }
+}
+
+/// Disables a running watchdog timer so the processor won't be reset.
+///
+/// This has no effect in always-on mode.
+impl watchdog::WatchdogDisable for Watchdog {
+    #[hal_macro_helper]
+    fn disable(&mut self) {
+        #[hal_cfg(any("wdt-d11", "wdt-d21"))]
+        {
+            // Disable the watchdog timer.
+            self.wdt.ctrl().write(|w| w.enable().clear_bit());
+            // Wait for watchdog timer to be disabled.
+            while self.wdt.status().read().syncbusy().bit_is_set() {}
+        }
+        #[hal_cfg("wdt-d5x")]
+        {
+            // Disable the watchdog timer.
+            self.wdt.ctrla().write(|w| w.enable().clear_bit());
+            // Wait for watchdog timer to be disabled.
+            while self.wdt.syncbusy().read().enable().bit_is_set() {}
+        }
+    }
+}
+
+impl watchdog::WatchdogEnable for Watchdog {
+    type Time = u8;
+
+    /// Enables a watchdog timer to reset the processor if software is frozen
+    /// or stalled. Pass [`WatchdogTimeout`] as the period.
+    ///
+    /// As WDT is driven by a 1024Hz clock, the time until timeout can be calculated
+    /// as `(1 second/1024)*period`
+    ///
+    /// EG:
+    /// `Timeout of 2048 cycles = (1/1024)*2048 = 2 seconds`
+    fn start<T>(&mut self, period: T)
+    where
+        T: Into<Self::Time>,
+    {
+        // Write the timeout configuration.
+        self.wdt
+            .config()
+            .write(|w| unsafe { w.per().bits(period.into()) });
+        self.enable(false, false);
+    }
+}
@@ -0,0 +1,160 @@
+//! Task watchdog, feeding the hardware watchdog once several tasks have all
+//! checked in
+
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+use portable_atomic::{AtomicU32, Ordering};
+
+use super::{FutureWatchdog, InterruptHandler};
+use crate::async_hal::interrupts::{Binding, WDT};
+use crate::ehal_02::watchdog::Watchdog;
+
+/// Software watchdog feeding the hardware watchdog once all its tasks have
+/// checked in
+///
+/// A [`TaskWatchdog`] makes sure that several tasks are all making progress:
+/// the hardware watchdog is only fed once every registered task has checked
+/// in since it was last fed. A single stuck task therefore resets the device.
+///
+/// ```no_run
+/// static TASK_WDT: TaskWatchdog = TaskWatchdog::new();
+///
+/// async fn sensor_task() {
+///     let task = TASK_WDT.register().unwrap();
+///     loop {
+///         read_sensor().await;
+///         task.check_in();
+///     }
+/// }
+///
+/// async fn watchdog_task(mut wdt: FutureWatchdog<Irqs>) -> ! {
+///     TASK_WDT.supervise(&mut wdt).await
+/// }
+/// ```
+pub struct TaskWatchdog {
+    registered: AtomicU32,
+    checked_in: AtomicU32,
+    waker: AtomicWaker,
+}
+
+impl Default for TaskWatchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskWatchdog {
+    /// Maximum number of tasks registered at the same time
+    pub const MAX_TASKS: usize = 32;
+
+    /// Create a new [`TaskWatchdog`], without any registered task
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            registered: AtomicU32::new(0),
+            checked_in: AtomicU32::new(0),
+            waker: AtomicWaker::new(),
+        }
+    }
+
+    /// Register a new task, which must then check in before each feed.
+    ///
+    /// The task counts as checked in for the current round. It is
+    /// unregistered when the returned [`Task`] is dropped.
+    ///
+    /// Returns `None` if [`MAX_TASKS`](Self::MAX_TASKS) tasks are already
+    /// registered.
+    pub fn register(&self) -> Option<Task<'_>> {
+        let registered = self
+            .registered
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |r| {
+                (r != u32::MAX).then(|| r | Self::first_free(r))
+            })
+            .ok()?;
+        let mask = Self::first_free(registered);
+        self.checked_in.fetch_or(mask, Ordering::AcqRel);
+        Some(Task { wdt: self, mask })
+    }
+
+    /// Returns `true` if all the registered tasks have checked in since the
+    /// last feed
+    #[inline]
+    pub fn all_checked_in(&self) -> bool {
+        let registered = self.registered.load(Ordering::Acquire);
+        self.checked_in.load(Ordering::Acquire) & registered == registered
+    }
+
+    /// Feed `wdt` if all the registered tasks have checked in since the last
+    /// feed, and start a new round.
+    ///
+    /// Returns `false` if a task hasn't checked in, in which case `wdt` is not
+    /// fed.
+    pub fn feed_if_ready<W: Watchdog>(&self, wdt: &mut W) -> bool {
+        let registered = self.registered.load(Ordering::Acquire);
+        let ready = self
+            .checked_in
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |c| {
+                (c & registered == registered).then_some(0)
+            })
+            .is_ok();
+
+        if ready {
+            wdt.feed();
+        }
+        ready
+    }
+
+    /// Feed `wdt` on each early warning, as long as all the registered tasks
+    /// keep checking in. Otherwise, the device is reset by the watchdog.
+    ///
+    /// The watchdog must be running in normal mode, with its early warning
+    /// configured to leave the tasks enough time to check in.
+    pub async fn supervise<I>(&self, wdt: &mut FutureWatchdog<I>) -> !
+    where
+        I: Binding<WDT, InterruptHandler>,
+    {
+        loop {
+            wdt.wait_early_warning().await;
+            // If a task is late, wait for check-ins until the watchdog times out
+            core::future::poll_fn(|cx| {
+                self.waker.register(cx.waker());
+                if self.feed_if_ready(wdt) {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            })
+            .await;
+        }
+    }
+
+    /// Returns the lowest bit which is cleared in `registered`
+    #[inline]
+    fn first_free(registered: u32) -> u32 {
+        !registered & registered.wrapping_add(1)
+    }
+}
+
+/// A task registered to a [`TaskWatchdog`]
+pub struct Task<'a> {
+    wdt: &'a TaskWatchdog,
+    mask: u32,
+}
+
+impl Task<'_> {
+    /// Signal that the task is making progress
+    #[inline]
+    pub fn check_in(&self) {
+        self.wdt.checked_in.fetch_or(self.mask, Ordering::AcqRel);
+        self.wdt.waker.wake();
+    }
+}
+
+impl Drop for Task<'_> {
+    fn drop(&mut self) {
+        self.wdt.registered.fetch_and(!self.mask, Ordering::AcqRel);
+        self.wdt.checked_in.fetch_and(!self.mask, Ordering::AcqRel);
+        self.wdt.waker.wake();
+    }
+}
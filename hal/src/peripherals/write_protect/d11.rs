@@ -0,0 +1,208 @@
+//! # Peripheral Access Controller
+//!
+//! This module provides a driver for the Peripheral Access Controllers (PAC0,
+//! PAC1 and PAC2) found on SAMD11/SAMD21 chips. Each PAC can write-protect
+//! the registers of the peripherals on its APB bridge. A write to a protected
+//! peripheral is discarded, and raises a hard fault.
+//!
+//! Safety-relevant firmware can use it to freeze the clock and port
+//! configuration once the system is set up:
+//!
+//! ```no_run
+//! let mut wp = WriteProtect::new(peripherals.pac0, peripherals.pac1, peripherals.pac2);
+//! wp.lock(Peripheral::Gclk);
+//! wp.lock(Peripheral::SysCtrl);
+//! wp.lock(Peripheral::Port);
+//! ```
+//!
+//! The HAL drivers are not aware of the protection. A peripheral must be
+//! unlocked before its driver is used to change its configuration.
+
+use atsamd_hal_macros::hal_macro_helper;
+
+use crate::pac;
+use crate::pac::pac0::RegisterBlock;
+
+macro_rules! define_peripherals {
+    (
+        $(
+            $Bridge:ident = $BASE:literal {
+                $(
+                    $( #[$( $cfg:tt )+] )?
+                    $Name:ident = $BIT:literal,
+                )+
+            }
+        )+
+    ) => {
+        /// Peripheral which can be write-protected
+        ///
+        /// The discriminant of each variant is `32 * bridge + bit`, where
+        /// `bridge` is the index of the PAC and `bit` the index of the
+        /// peripheral in its `WPSET` register.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        #[repr(u8)]
+        pub enum Peripheral {
+            $(
+                $(
+                    $( #[$( $cfg )+] )?
+                    $Name = $BASE + $BIT,
+                )+
+            )+
+        }
+    };
+}
+
+#[hal_macro_helper]
+define_peripherals!(
+    A = 0 {
+        Pm = 1,
+        SysCtrl = 2,
+        Gclk = 3,
+        Wdt = 4,
+        Rtc = 5,
+        Eic = 6,
+    }
+    B = 32 {
+        Dsu = 1,
+        NvmCtrl = 2,
+        Port = 3,
+        Dmac = 4,
+        #[hal_cfg("usb")]
+        Usb = 5,
+        Mtb = 6,
+    }
+    C = 64 {
+        EvSys = 1,
+        Sercom0 = 2,
+        Sercom1 = 3,
+        #[hal_cfg("sercom2")]
+        Sercom2 = 4,
+        #[hal_cfg("sercom3")]
+        Sercom3 = 5,
+        #[hal_cfg("sercom4")]
+        Sercom4 = 6,
+        #[hal_cfg("sercom5")]
+        Sercom5 = 7,
+        #[hal_cfg("tcc0-d11")]
+        Tcc0 = 5,
+        #[hal_cfg("tcc0-d21")]
+        Tcc0 = 8,
+        #[hal_cfg("tcc1")]
+        Tcc1 = 9,
+        #[hal_cfg("tcc2")]
+        Tcc2 = 10,
+        #[hal_cfg("tc1")]
+        Tc1 = 6,
+        #[hal_cfg("tc2")]
+        Tc2 = 7,
+        #[hal_cfg("tc3")]
+        Tc3 = 11,
+        #[hal_cfg("tc4")]
+        Tc4 = 12,
+        #[hal_cfg("tc5")]
+        Tc5 = 13,
+        #[hal_cfg("tc6")]
+        Tc6 = 14,
+        #[hal_cfg("tc7")]
+        Tc7 = 15,
+        #[hal_cfg("adc-d11")]
+        Adc = 8,
+        #[hal_cfg("adc-d21")]
+        Adc = 16,
+        #[hal_cfg("ac-d11")]
+        Ac = 9,
+        #[hal_cfg("ac-d21")]
+        Ac = 17,
+        #[hal_cfg("dac-d11")]
+        Dac = 10,
+        #[hal_cfg("dac-d21")]
+        Dac = 18,
+        #[hal_cfg("ptc-d11")]
+        Ptc = 11,
+        #[hal_cfg("ptc-d21")]
+        Ptc = 19,
+        #[hal_cfg("i2s")]
+        I2S = 20,
+    }
+);
+
+impl Peripheral {
+    /// Index of the PAC
+    #[inline]
+    const fn bridge(self) -> u8 {
+        self as u8 / 32
+    }
+
+    /// Bit of the peripheral in the `WPSET` and `WPCLR` registers
+    #[inline]
+    const fn mask(self) -> u32 {
+        1 << (self as u8 % 32)
+    }
+}
+
+/// Peripheral Access Controllers
+pub struct WriteProtect {
+    pac0: pac::Pac0,
+    pac1: pac::Pac1,
+    pac2: pac::Pac2,
+}
+
+impl WriteProtect {
+    /// Create the PAC driver. The protection of the peripherals is not
+    /// changed.
+    pub fn new(pac0: pac::Pac0, pac1: pac::Pac1, pac2: pac::Pac2) -> Self {
+        Self { pac0, pac1, pac2 }
+    }
+
+    #[inline]
+    fn regs(&self, peripheral: Peripheral) -> &RegisterBlock {
+        match peripheral.bridge() {
+            0 => &self.pac0,
+            1 => &self.pac1,
+            _ => &self.pac2,
+        }
+    }
+
+    /// Check whether the registers of `peripheral` are write-protected
+    #[inline]
+    pub fn is_locked(&self, peripheral: Peripheral) -> bool {
+        self.regs(peripheral).wpset().read().bits() & peripheral.mask() != 0
+    }
+
+    /// Write-protect the registers of `peripheral`
+    ///
+    /// Protecting an already protected peripheral raises a hard fault, so
+    /// this method does nothing in that case.
+    #[inline]
+    pub fn lock(&mut self, peripheral: Peripheral) {
+        if !self.is_locked(peripheral) {
+            // SAFETY: Writing 1 to a bit protects the peripheral, and 0 has no
+            // effect
+            self.regs(peripheral)
+                .wpset()
+                .write(|w| unsafe { w.bits(peripheral.mask()) });
+        }
+    }
+
+    /// Remove the write protection of `peripheral`
+    ///
+    /// Unprotecting an already unprotected peripheral raises a hard fault, so
+    /// this method does nothing in that case.
+    #[inline]
+    pub fn unlock(&mut self, peripheral: Peripheral) {
+        if self.is_locked(peripheral) {
+            // SAFETY: Writing 1 to a bit unprotects the peripheral, and 0 has
+            // no effect
+            self.regs(peripheral)
+                .wpclr()
+                .write(|w| unsafe { w.bits(peripheral.mask()) });
+        }
+    }
+
+    /// Return the underlying PAC structs. The protection of the peripherals
+    /// is not changed.
+    pub fn free(self) -> (pac::Pac0, pac::Pac1, pac::Pac2) {
+        (self.pac0, self.pac1, self.pac2)
+    }
+}
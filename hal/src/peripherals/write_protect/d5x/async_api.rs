@@ -0,0 +1,103 @@
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::*;
+use crate::async_hal::interrupts::{Binding, Handler, InterruptSource, PAC};
+use crate::typelevel::Sealed;
+
+static WAKER: AtomicWaker = AtomicWaker::new();
+
+/// Interrupt handler for the PAC peripheral.
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Sealed for InterruptHandler {}
+
+impl Handler<PAC> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        let pac = unsafe { pac::Peripherals::steal().pac };
+
+        if pac.intenset().read().err().bit_is_clear() {
+            return;
+        }
+
+        let pending = pac.intflagahb().read().bits() != 0
+            || pac.intflaga().read().bits() != 0
+            || pac.intflagb().read().bits() != 0
+            || pac.intflagc().read().bits() != 0
+            || pac.intflagd().read().bits() != 0;
+
+        // Disable the interrupt but don't clear the flags; they will be cleared
+        // when the error is read.
+        if pending {
+            pac.intenclr().write(|w| w.err().set_bit());
+            WAKER.wake();
+        }
+    }
+}
+
+impl WriteProtect {
+    /// Turn a [`WriteProtect`] into a [`FutureWriteProtect`], allowing for
+    /// the use of async methods. You are required to provide the struct
+    /// created by the [`bind_interrupts`](crate::bind_interrupts) macro to
+    /// prove that the interrupt source has been correctly configured. This
+    /// function will automatically enable the `PAC` NVIC interrupt source.
+    pub fn into_future<F>(self, irqs: F) -> FutureWriteProtect<F>
+    where
+        F: Binding<PAC, InterruptHandler>,
+    {
+        PAC::unpend();
+        unsafe { PAC::enable() };
+        FutureWriteProtect { inner: self, irqs }
+    }
+}
+
+/// A [`WriteProtect`] capable of `async` operations
+pub struct FutureWriteProtect<F> {
+    inner: WriteProtect,
+    irqs: F,
+}
+
+impl<F> FutureWriteProtect<F>
+where
+    F: Binding<PAC, InterruptHandler>,
+{
+    /// Convert the [`FutureWriteProtect`] back into a blocking
+    /// [`WriteProtect`], and return the IRQs
+    pub fn into_blocking(mut self) -> (WriteProtect, F) {
+        PAC::disable();
+        self.inner.disable_error_interrupt();
+        (self.inner, self.irqs)
+    }
+
+    /// Borrow the underlying [`WriteProtect`], to change the protection of
+    /// the peripherals
+    #[inline]
+    pub fn write_protect(&mut self) -> &mut WriteProtect {
+        &mut self.inner
+    }
+
+    /// Wait for the next illegal access, see [`WriteProtect::read_error`]
+    ///
+    /// An access which occurred since the last error was read is returned
+    /// immediately.
+    pub async fn wait_error(&mut self) -> AccessError {
+        core::future::poll_fn(|cx| {
+            if let Some(error) = self.inner.read_error() {
+                return Poll::Ready(error);
+            }
+
+            WAKER.register(cx.waker());
+            self.inner.enable_error_interrupt();
+
+            if let Some(error) = self.inner.read_error() {
+                return Poll::Ready(error);
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+}
@@ -0,0 +1,476 @@
+//! # Peripheral Access Controller
+//!
+//! This module provides a driver for the Peripheral Access Controller (PAC)
+//! found on SAMD5x/SAME5x chips. The PAC can write-protect the registers of
+//! each peripheral, and reports the illegal accesses to the peripherals and
+//! to the AHB slaves.
+//!
+//! Safety-relevant firmware can use it to freeze the clock and port
+//! configuration once the system is set up. A permanent lock can only be
+//! removed by a reset.
+//!
+//! ```no_run
+//! let mut wp = WriteProtect::new(peripherals.pac, clocks.apbs.pac);
+//! wp.lock_permanently(Peripheral::Gclk);
+//! wp.lock_permanently(Peripheral::Mclk);
+//! wp.lock_permanently(Peripheral::OscCtrl);
+//! wp.lock_permanently(Peripheral::Osc32kCtrl);
+//! wp.lock_permanently(Peripheral::Port);
+//!
+//! if let Some(error) = wp.read_error() {
+//!     // Some code tried to write to a protected peripheral
+//! }
+//! ```
+//!
+//! The HAL drivers are not aware of the protection. A peripheral must be
+//! unlocked before its driver is used to change its configuration.
+//!
+//! ## Errors
+//!
+//! A write to a protected peripheral is discarded, and raises the error flag
+//! of the peripheral. A CPU access also raises a bus fault. The flags are
+//! read with [`WriteProtect::read_error`], which decodes the offending
+//! peripheral into an [`AccessError`].
+//!
+//! ## Async
+//!
+//! With the `async` feature enabled, [`WriteProtect::into_future`] returns a
+//! [`FutureWriteProtect`], whose
+//! [`wait_error`](FutureWriteProtect::wait_error) method waits for the next
+//! illegal access.
+
+use atsamd_hal_macros::hal_macro_helper;
+
+use crate::clock::v2::{apb::ApbClk, types::Pac as PacId};
+use crate::pac;
+use crate::pac::pac::RegisterBlock;
+use crate::pac::pac::wrctrl::Keyselect;
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
+macro_rules! define_peripherals {
+    (
+        $(
+            $Bridge:ident = $BASE:literal {
+                $(
+                    $( #[$( $cfg:tt )+] )?
+                    $Name:ident = $BIT:literal,
+                )+
+            }
+        )+
+    ) => {
+        /// Peripheral which can be write-protected
+        ///
+        /// The discriminant of each variant is the peripheral identifier used
+        /// by the PAC, `32 * bridge + bit`, where `bridge` is the index of the
+        /// APB bridge and `bit` the index of the peripheral in its `STATUS`
+        /// and `INTFLAG` registers.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        #[repr(u8)]
+        pub enum Peripheral {
+            $(
+                $(
+                    $( #[$( $cfg )+] )?
+                    $Name = $BASE + $BIT,
+                )+
+            )+
+        }
+
+        impl Peripheral {
+            /// Return the peripheral with the PAC identifier `id`
+            fn from_id(id: u8) -> Option<Self> {
+                match id {
+                    $(
+                        $(
+                            $( #[$( $cfg )+] )?
+                            id if id == Self::$Name as u8 => Some(Self::$Name),
+                        )+
+                    )+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+#[hal_macro_helper]
+define_peripherals!(
+    A = 0 {
+        Pac = 0,
+        Pm = 1,
+        Mclk = 2,
+        RstC = 3,
+        OscCtrl = 4,
+        Osc32kCtrl = 5,
+        SupC = 6,
+        Gclk = 7,
+        Wdt = 8,
+        Rtc = 9,
+        Eic = 10,
+        FreqM = 11,
+        Sercom0 = 12,
+        Sercom1 = 13,
+        Tc0 = 14,
+        Tc1 = 15,
+    }
+    B = 32 {
+        Usb = 0,
+        Dsu = 1,
+        NvmCtrl = 2,
+        Cmcc = 3,
+        Port = 4,
+        Dmac = 5,
+        EvSys = 7,
+        Sercom2 = 9,
+        Sercom3 = 10,
+        Tcc0 = 11,
+        Tcc1 = 12,
+        Tc2 = 13,
+        Tc3 = 14,
+        RamEcc = 16,
+    }
+    C = 64 {
+        #[hal_cfg("can0")]
+        Can0 = 0,
+        #[hal_cfg("can1")]
+        Can1 = 1,
+        #[hal_cfg("gmac")]
+        Gmac = 2,
+        Tcc2 = 3,
+        #[hal_cfg("tcc3")]
+        Tcc3 = 4,
+        #[hal_cfg("tc4")]
+        Tc4 = 5,
+        #[hal_cfg("tc5")]
+        Tc5 = 6,
+        PDec = 7,
+        Ac = 8,
+        Aes = 9,
+        Trng = 10,
+        Icm = 11,
+        Pukcc = 12,
+        Qspi = 13,
+        Ccl = 14,
+    }
+    D = 96 {
+        Sercom4 = 0,
+        Sercom5 = 1,
+        #[hal_cfg("sercom6")]
+        Sercom6 = 2,
+        #[hal_cfg("sercom7")]
+        Sercom7 = 3,
+        #[hal_cfg("tcc4")]
+        Tcc4 = 4,
+        #[hal_cfg("tc6")]
+        Tc6 = 5,
+        #[hal_cfg("tc7")]
+        Tc7 = 6,
+        Adc0 = 7,
+        Adc1 = 8,
+        Dac = 9,
+        #[hal_cfg("i2s")]
+        I2S = 10,
+        Pcc = 11,
+    }
+);
+
+impl Peripheral {
+    /// Index of the APB bridge
+    #[inline]
+    const fn bridge(self) -> u8 {
+        self as u8 / 32
+    }
+
+    /// Bit of the peripheral in the `STATUS` and `INTFLAG` registers
+    #[inline]
+    const fn mask(self) -> u32 {
+        1 << (self as u8 % 32)
+    }
+}
+
+/// AHB slave whose illegal accesses are reported by the PAC
+#[hal_macro_helper]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum AhbSlave {
+    /// Flash
+    Flash = 0,
+    /// Flash, alternate access
+    FlashAlt = 1,
+    /// SmartEEPROM
+    SeeProm = 2,
+    /// SRAM, Cortex-M4 port
+    RamCm4s = 3,
+    /// SRAM, PPP and DSU port
+    RamPpPdsu = 4,
+    /// SRAM, DMAC write port
+    RamDmaWr = 5,
+    /// SRAM, DMAC and ICM read port
+    RamDmacIcm = 6,
+    /// APB bridge A
+    Hpb0 = 7,
+    /// APB bridge B
+    Hpb1 = 8,
+    /// APB bridge C
+    Hpb2 = 9,
+    /// APB bridge D
+    Hpb3 = 10,
+    /// PUKCC
+    Pukcc = 11,
+    /// SDHC0
+    Sdhc0 = 12,
+    /// SDHC1
+    #[hal_cfg("sdhc1")]
+    Sdhc1 = 13,
+    /// QSPI
+    Qspi = 14,
+}
+
+impl AhbSlave {
+    /// Return the slave reported by bit `bit` of `INTFLAGAHB`
+    #[hal_macro_helper]
+    fn from_bit(bit: u32) -> Option<Self> {
+        use AhbSlave::*;
+        let slave = match bit {
+            0 => Flash,
+            1 => FlashAlt,
+            2 => SeeProm,
+            3 => RamCm4s,
+            4 => RamPpPdsu,
+            5 => RamDmaWr,
+            6 => RamDmacIcm,
+            7 => Hpb0,
+            8 => Hpb1,
+            9 => Hpb2,
+            10 => Hpb3,
+            11 => Pukcc,
+            12 => Sdhc0,
+            #[hal_cfg("sdhc1")]
+            13 => Sdhc1,
+            14 => Qspi,
+            _ => return None,
+        };
+        Some(slave)
+    }
+}
+
+/// Illegal access reported by the PAC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccessError {
+    /// Write to a protected peripheral, or invalid change of its protection
+    Peripheral(Peripheral),
+    /// Illegal access to an AHB slave
+    Ahb(AhbSlave),
+}
+
+/// Error changing the protection of a peripheral
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The protection was locked with
+    /// [`lock_permanently`](WriteProtect::lock_permanently), and can't be
+    /// changed until the next reset
+    Locked,
+}
+
+/// Peripheral Access Controller
+pub struct WriteProtect {
+    pac: pac::Pac,
+    apbclk: ApbClk<PacId>,
+}
+
+impl WriteProtect {
+    /// Create the PAC driver, with the error interrupt disabled. The
+    /// protection of the peripherals is not changed.
+    pub fn new(pac: pac::Pac, apbclk: ApbClk<PacId>) -> Self {
+        let mut wp = Self { pac, apbclk };
+        wp.disable_error_interrupt();
+        wp
+    }
+
+    #[inline]
+    fn regs(&self) -> &RegisterBlock {
+        &self.pac
+    }
+
+    /// Check whether the registers of `peripheral` are write-protected
+    #[inline]
+    pub fn is_locked(&self, peripheral: Peripheral) -> bool {
+        let regs = self.regs();
+        let status = match peripheral.bridge() {
+            0 => regs.statusa().read().bits(),
+            1 => regs.statusb().read().bits(),
+            2 => regs.statusc().read().bits(),
+            _ => regs.statusd().read().bits(),
+        };
+        status & peripheral.mask() != 0
+    }
+
+    /// Write-protect the registers of `peripheral`
+    ///
+    /// Does nothing if the peripheral is already protected.
+    #[inline]
+    pub fn lock(&mut self, peripheral: Peripheral) {
+        if !self.is_locked(peripheral) {
+            self.write_key(peripheral, Keyselect::Set);
+        }
+    }
+
+    /// Remove the write protection of `peripheral`
+    ///
+    /// Does nothing if the peripheral is not protected. Returns
+    /// [`Error::Locked`] if the protection was locked with
+    /// [`lock_permanently`](Self::lock_permanently).
+    pub fn unlock(&mut self, peripheral: Peripheral) -> Result<(), Error> {
+        if !self.is_locked(peripheral) {
+            return Ok(());
+        }
+
+        self.write_key(peripheral, Keyselect::Clr);
+        if self.is_locked(peripheral) {
+            // The rejected request raised the error flag of the PAC itself
+            self.clear_error(AccessError::Peripheral(Peripheral::Pac));
+            Err(Error::Locked)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Write-protect the registers of `peripheral`, and lock the protection
+    /// until the next reset
+    ///
+    /// Does nothing if the protection is already locked.
+    pub fn lock_permanently(&mut self, peripheral: Peripheral) {
+        // Protecting an already protected peripheral is an error, so the
+        // protection is removed first
+        if self.unlock(peripheral).is_ok() {
+            self.write_key(peripheral, Keyselect::Setlck);
+        }
+    }
+
+    #[inline]
+    fn write_key(&mut self, peripheral: Peripheral, key: Keyselect) {
+        // SAFETY: The identifier of a Peripheral is a valid PERID
+        self.regs()
+            .wrctrl()
+            .write(|w| unsafe { w.perid().bits(peripheral as u16).key().variant(key) });
+    }
+
+    /// Read and clear the next illegal access reported by the PAC, if any
+    ///
+    /// The PAC only keeps one flag per peripheral, so repeated accesses to a
+    /// peripheral before its error is read are reported once. Errors on AHB
+    /// slaves are returned first.
+    pub fn read_error(&mut self) -> Option<AccessError> {
+        let error = self.pending_error()?;
+        self.clear_error(error);
+        Some(error)
+    }
+
+    /// Return the next illegal access reported by the PAC, without clearing
+    /// it
+    ///
+    /// Flags which can't be decoded, because they belong to a peripheral this
+    /// chip doesn't have, are cleared.
+    fn pending_error(&self) -> Option<AccessError> {
+        let regs = self.regs();
+        loop {
+            let ahb = regs.intflagahb().read().bits();
+            if ahb != 0 {
+                let bit = ahb.trailing_zeros();
+                if let Some(slave) = AhbSlave::from_bit(bit) {
+                    return Some(AccessError::Ahb(slave));
+                }
+                // SAFETY: Writing 1 to a flag clears it
+                regs.intflagahb().write(|w| unsafe { w.bits(1 << bit) });
+                continue;
+            }
+
+            let flags = [
+                regs.intflaga().read().bits(),
+                regs.intflagb().read().bits(),
+                regs.intflagc().read().bits(),
+                regs.intflagd().read().bits(),
+            ];
+            let (bridge, &bits) = flags.iter().enumerate().find(|&(_, &bits)| bits != 0)?;
+            let bit = bits.trailing_zeros();
+            if let Some(peripheral) = Peripheral::from_id(bridge as u8 * 32 + bit as u8) {
+                return Some(AccessError::Peripheral(peripheral));
+            }
+            self.clear_flag(bridge as u8, 1 << bit);
+        }
+    }
+
+    /// Clear the flag reporting `error`
+    fn clear_error(&self, error: AccessError) {
+        match error {
+            AccessError::Peripheral(peripheral) => {
+                self.clear_flag(peripheral.bridge(), peripheral.mask())
+            }
+            // SAFETY: Writing 1 to a flag clears it
+            AccessError::Ahb(slave) => self
+                .regs()
+                .intflagahb()
+                .write(|w| unsafe { w.bits(1 << slave as u32) }),
+        }
+    }
+
+    #[inline]
+    fn clear_flag(&self, bridge: u8, mask: u32) {
+        let regs = self.regs();
+        // SAFETY: Writing 1 to a flag clears it
+        unsafe {
+            match bridge {
+                0 => regs.intflaga().write(|w| w.bits(mask)),
+                1 => regs.intflagb().write(|w| w.bits(mask)),
+                2 => regs.intflagc().write(|w| w.bits(mask)),
+                _ => regs.intflagd().write(|w| w.bits(mask)),
+            }
+        }
+    }
+
+    /// Clear all the error flags
+    pub fn clear_errors(&mut self) {
+        let regs = self.regs();
+        // SAFETY: Writing 1 to a flag clears it
+        unsafe {
+            regs.intflagahb().write(|w| w.bits(u32::MAX));
+            regs.intflaga().write(|w| w.bits(u32::MAX));
+            regs.intflagb().write(|w| w.bits(u32::MAX));
+            regs.intflagc().write(|w| w.bits(u32::MAX));
+            regs.intflagd().write(|w| w.bits(u32::MAX));
+        }
+    }
+
+    /// Enable the error interrupt, raised on any illegal access
+    #[inline]
+    pub fn enable_error_interrupt(&mut self) {
+        self.regs().intenset().write(|w| w.err().set_bit());
+    }
+
+    /// Disable the error interrupt
+    #[inline]
+    pub fn disable_error_interrupt(&mut self) {
+        self.regs().intenclr().write(|w| w.err().set_bit());
+    }
+
+    /// Enable or disable the error event output, generated on any illegal
+    /// access
+    #[inline]
+    pub fn set_error_event(&mut self, enabled: bool) {
+        self.regs().evctrl().write(|w| w.erreo().bit(enabled));
+    }
+
+    /// Disable the error interrupt, and return the underlying resources. The
+    /// protection of the peripherals is not changed.
+    pub fn free(mut self) -> (pac::Pac, ApbClk<PacId>) {
+        self.disable_error_interrupt();
+        (self.pac, self.apbclk)
+    }
+}
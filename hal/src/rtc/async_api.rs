@@ -0,0 +1,107 @@
+//! Async APIs for the RTC.
+//!
+//! Use [`Rtc::into_future`] to convert an [`Rtc`] in clock mode into an
+//! asynchronous [`FutureRtc`], which can wait for an alarm.
+
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::{Alarm0, AlarmMask, ClockMode, Datetime, Rtc, RtcMode2, modes::RtcMode as _};
+use crate::async_hal::interrupts::{Binding, Handler, InterruptSource, RTC};
+use crate::pac;
+use crate::typelevel::Sealed;
+
+static WAKER: AtomicWaker = AtomicWaker::new();
+
+/// Interrupt handler for the RTC peripheral.
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl Sealed for InterruptHandler {}
+
+impl Handler<RTC> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        let rtc = unsafe { pac::Peripherals::steal().rtc };
+
+        // Disable the interrupt but don't clear the flag; it will be cleared
+        // when the future is next polled.
+        // SYNC: None
+        if rtc.mode2().intenset().read().alarm0().bit_is_set()
+            && RtcMode2::check_interrupt_flag::<Alarm0>(&rtc)
+        {
+            RtcMode2::disable_interrupt::<Alarm0>(&rtc);
+            WAKER.wake();
+        }
+    }
+}
+
+impl Rtc<ClockMode> {
+    /// Turn an [`Rtc`] into a [`FutureRtc`], allowing for the use of async
+    /// methods. You are required to provide the struct created by the
+    /// [`bind_interrupts`](crate::bind_interrupts) macro to prove that the
+    /// interrupt source has been correctly configured. This function will
+    /// automatically enable the `RTC` NVIC interrupt source.
+    pub fn into_future<I>(self, irqs: I) -> FutureRtc<I>
+    where
+        I: Binding<RTC, InterruptHandler>,
+    {
+        RTC::unpend();
+        unsafe { RTC::enable() };
+        FutureRtc { inner: self, irqs }
+    }
+}
+
+/// An [`Rtc`] in clock mode capable of `async` operations
+pub struct FutureRtc<I> {
+    inner: Rtc<ClockMode>,
+    irqs: I,
+}
+
+impl<I> FutureRtc<I>
+where
+    I: Binding<RTC, InterruptHandler>,
+{
+    /// Convert the [`FutureRtc`] back into a blocking [`Rtc`], and return the
+    /// IRQs
+    pub fn into_blocking(self) -> (Rtc<ClockMode>, I) {
+        RTC::disable();
+        (self.inner, self.irqs)
+    }
+
+    /// Returns the current clock/calendar value.
+    #[inline]
+    pub fn current_time(&self) -> Datetime {
+        self.inner.current_time()
+    }
+
+    /// Updates the current clock/calendar value.
+    #[inline]
+    pub fn set_time(&mut self, time: Datetime) {
+        self.inner.set_time(time);
+    }
+
+    /// Wait until the fields of `time` selected by `mask` match the current
+    /// time, see [`Rtc::set_alarm`].
+    pub async fn wait_alarm(&mut self, time: Datetime, mask: AlarmMask) {
+        self.inner.set_alarm(time, mask);
+        core::future::poll_fn(|cx| {
+            if self.inner.alarm_triggered() {
+                return Poll::Ready(());
+            }
+
+            WAKER.register(cx.waker());
+            self.inner.enable_alarm_interrupt();
+
+            if self.inner.alarm_triggered() {
+                return Poll::Ready(());
+            }
+
+            Poll::Pending
+        })
+        .await;
+        self.inner.disable_alarm_interrupt();
+        self.inner.clear_alarm();
+    }
+}
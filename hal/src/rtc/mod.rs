@@ -1,5 +1,5 @@
 //! Real-time clock/counter
-use atsamd_hal_macros::hal_cfg;
+use atsamd_hal_macros::{hal_cfg, hal_macro_helper, hal_module};
 use fugit::ExtU32;
 
 use crate::ehal;
@@ -13,7 +13,8 @@ use core::marker::PhantomData;
 use modes::{
     RtcMode as _,
     mode0::{Compare0, RtcMode0},
-    mode2::RtcMode2,
+    mode1::{Overflow, RtcMode1},
+    mode2::{Alarm0, RtcMode2},
 };
 
 #[cfg(feature = "sdmmc")]
@@ -21,6 +22,16 @@ use embedded_sdmmc::{TimeSource, Timestamp};
 
 mod modes;
 
+#[hal_module("rtc-d5x")]
+mod tamper {}
+#[hal_cfg("rtc-d5x")]
+pub use tamper::*;
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::*;
+
 #[cfg(feature = "rtic")]
 pub mod rtic;
 
@@ -32,7 +43,7 @@ use crate::pac::{Mclk as Pm, rtc::mode0::ctrla::Prescalerselect};
 #[hal_cfg(any("rtc-d11", "rtc-d21"))]
 use crate::pac::{Pm, rtc::mode0::ctrl::Prescalerselect};
 
-pub use modes::mode2::Datetime;
+pub use modes::mode2::{AlarmMask, Datetime};
 
 /// RtcMode represents the mode of the RTC
 pub trait RtcMode: Sealed {}
@@ -53,6 +64,57 @@ pub enum Count32Mode {}
 impl RtcMode for Count32Mode {}
 impl Sealed for Count32Mode {}
 
+/// Count16Mode represents the 16-bit counter mode.
+///
+/// This is a free running count-up timer, which overflows and restarts from
+/// zero when it reaches its period. When used in Periodic/CountDown mode with
+/// the embedded-hal trait(s), the period is set to the specified timeout.
+pub enum Count16Mode {}
+
+impl RtcMode for Count16Mode {}
+impl Sealed for Count16Mode {}
+
+/// Periodic interval of the RTC, obtained by dividing the RTC clock frequency.
+///
+/// With a 1.024 kHz RTC clock, [`Div1024`](PeriodicInterval::Div1024) is a
+/// 1 Hz interval. The intervals don't depend on the RTC mode nor on the
+/// prescaler division. On SAMx5x chips, they are only generated when the
+/// prescaler is enabled, as in clock mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PeriodicInterval {
+    /// `PER0`, the RTC clock frequency divided by 8
+    Div8,
+    /// `PER1`, the RTC clock frequency divided by 16
+    Div16,
+    /// `PER2`, the RTC clock frequency divided by 32
+    Div32,
+    /// `PER3`, the RTC clock frequency divided by 64
+    Div64,
+    /// `PER4`, the RTC clock frequency divided by 128
+    Div128,
+    /// `PER5`, the RTC clock frequency divided by 256
+    Div256,
+    /// `PER6`, the RTC clock frequency divided by 512
+    Div512,
+    /// `PER7`, the RTC clock frequency divided by 1024
+    Div1024,
+}
+
+#[hal_cfg("rtc-d5x")]
+impl PeriodicInterval {
+    /// Bit of the interval in the `INTENSET` and `INTFLAG` registers, which is
+    /// the same in all modes
+    #[inline]
+    fn mask(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// Maximum frequency correction, in ppm, see
+/// [`Rtc::set_frequency_correction`]
+pub const MAX_FREQUENCY_CORRECTION_PPM: i32 = 127;
+
 #[cfg(feature = "sdmmc")]
 impl From<Datetime> for Timestamp {
     fn from(clock: Datetime) -> Timestamp {
@@ -95,6 +157,13 @@ impl<Mode: RtcMode> Rtc<Mode> {
         RtcMode2::start_and_initialize(rtc);
     }
 
+    fn set_count16_mode(rtc: &pac::Rtc) {
+        RtcMode1::disable(rtc);
+        RtcMode1::reset(rtc);
+        RtcMode1::set_mode(rtc);
+        RtcMode1::start_and_initialize(rtc);
+    }
+
     fn create(rtc: pac::Rtc, rtc_clock_freq: Hertz) -> Self {
         Self {
             rtc,
@@ -114,6 +183,14 @@ impl<Mode: RtcMode> Rtc<Mode> {
         self.into_mode()
     }
 
+    /// Reconfigures the RTC for 16-bit counter mode with no prescaler, the
+    /// period set to its maximum and the counter initialized to zero and
+    /// started.
+    pub fn into_count16_mode(self) -> Rtc<Count16Mode> {
+        Self::set_count16_mode(&self.rtc);
+        self.into_mode()
+    }
+
     /// Reconfigures the peripheral for clock/calendar mode. Requires the source
     /// clock to be running at 1024 Hz.
     pub fn into_clock_mode(self) -> Rtc<ClockMode> {
@@ -121,10 +198,134 @@ impl<Mode: RtcMode> Rtc<Mode> {
         self.into_mode()
     }
 
+    /// Enables the event output of a periodic interval, to be used with the
+    /// [event system](crate::evsys).
+    ///
+    /// On SAMx5x chips, the RTC is briefly stopped to change its
+    /// configuration.
+    #[inline]
+    pub fn enable_periodic_event(&mut self, interval: PeriodicInterval) {
+        self.set_periodic_event(interval, true);
+    }
+
+    /// Disables the event output of a periodic interval.
+    ///
+    /// On SAMx5x chips, the RTC is briefly stopped to change its
+    /// configuration.
+    #[inline]
+    pub fn disable_periodic_event(&mut self, interval: PeriodicInterval) {
+        self.set_periodic_event(interval, false);
+    }
+
+    /// Enables the interrupt of a periodic interval.
+    #[hal_cfg("rtc-d5x")]
+    #[inline]
+    pub fn enable_periodic_interrupt(&mut self, interval: PeriodicInterval) {
+        // SYNC: None
+        self.rtc
+            .mode0()
+            .intenset()
+            .write(|w| unsafe { w.bits(interval.mask()) });
+    }
+
+    /// Disables the interrupt of a periodic interval.
+    #[hal_cfg("rtc-d5x")]
+    #[inline]
+    pub fn disable_periodic_interrupt(&mut self, interval: PeriodicInterval) {
+        // SYNC: None
+        self.rtc
+            .mode0()
+            .intenclr()
+            .write(|w| unsafe { w.bits(interval.mask()) });
+    }
+
+    /// Returns whether the interrupt of a periodic interval has been
+    /// triggered.
+    #[hal_cfg("rtc-d5x")]
+    #[inline]
+    pub fn check_periodic_interrupt_flag(&self, interval: PeriodicInterval) -> bool {
+        // SYNC: None
+        self.rtc.mode0().intflag().read().bits() & interval.mask() != 0
+    }
+
+    /// Clears the interrupt flag of a periodic interval.
+    #[hal_cfg("rtc-d5x")]
+    #[inline]
+    pub fn clear_periodic_interrupt_flag(&mut self, interval: PeriodicInterval) {
+        // SYNC: None
+        self.rtc
+            .mode0()
+            .intflag()
+            .write(|w| unsafe { w.bits(interval.mask()) });
+    }
+
+    /// Corrects the frequency of the RTC oscillator by `ppm` parts per
+    /// million, to compensate for its drift.
+    ///
+    /// A positive correction speeds the RTC up, and a negative one slows it
+    /// down. The correction is applied in steps of approximately 1.0006 ppm,
+    /// and requires an RTC prescaler greater than 1, as used in clock mode.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ppm` is larger than [`MAX_FREQUENCY_CORRECTION_PPM`] in
+    /// absolute value.
+    pub fn set_frequency_correction(&mut self, ppm: i32) {
+        assert!(
+            ppm.abs() <= MAX_FREQUENCY_CORRECTION_PPM,
+            "the RTC frequency correction is limited to {MAX_FREQUENCY_CORRECTION_PPM} ppm"
+        );
+        // A correction step is one count every 1024 * 976 RTC clock cycles
+        let value = ((ppm.unsigned_abs() * 1024 * 976 + 500_000) / 1_000_000) as u8;
+
+        // SYNC: Write
+        RtcMode0::sync(&self.rtc);
+        self.rtc.mode0().freqcorr().write(|w| {
+            // A cleared sign bit increases the frequency
+            w.sign().bit(ppm < 0);
+            unsafe { w.value().bits(value) }
+        });
+    }
+
     /// Releases the RTC resource
     pub fn free(self) -> pac::Rtc {
         self.rtc
     }
+
+    fn set_periodic_event(&mut self, interval: PeriodicInterval, enable: bool) {
+        self.with_disabled(|rtc| {
+            // SYNC: None
+            rtc.evctrl().modify(|_, w| match interval {
+                PeriodicInterval::Div8 => w.pereo0().bit(enable),
+                PeriodicInterval::Div16 => w.pereo1().bit(enable),
+                PeriodicInterval::Div32 => w.pereo2().bit(enable),
+                PeriodicInterval::Div64 => w.pereo3().bit(enable),
+                PeriodicInterval::Div128 => w.pereo4().bit(enable),
+                PeriodicInterval::Div256 => w.pereo5().bit(enable),
+                PeriodicInterval::Div512 => w.pereo6().bit(enable),
+                PeriodicInterval::Div1024 => w.pereo7().bit(enable),
+            });
+        });
+    }
+
+    /// Run `f` with the RTC disabled, to write its enable-protected registers.
+    ///
+    /// The SAMD11/SAMD21 chips have no such registers, so the RTC keeps
+    /// running.
+    #[hal_macro_helper]
+    fn with_disabled(&mut self, f: impl FnOnce(&pac::rtc::Mode0)) {
+        #[hal_cfg("rtc-d5x")]
+        {
+            RtcMode0::disable(&self.rtc);
+            RtcMode0::sync(&self.rtc);
+        }
+        f(self.rtc.mode0());
+        #[hal_cfg("rtc-d5x")]
+        {
+            RtcMode0::enable(&self.rtc);
+            RtcMode0::sync(&self.rtc);
+        }
+    }
 }
 
 impl Rtc<Count32Mode> {
@@ -184,6 +385,57 @@ impl Rtc<Count32Mode> {
     }
 }
 
+impl Rtc<Count16Mode> {
+    /// Configures the RTC in 16-bit counter mode with no prescaler, the period
+    /// set to its maximum and the counter initialized to zero and started.
+    pub fn count16_mode(rtc: pac::Rtc, rtc_clock_freq: Hertz, pm: &mut Pm) -> Self {
+        Rtc::count32_mode(rtc, rtc_clock_freq, pm).into_count16_mode()
+    }
+
+    /// Returns the internal counter value.
+    #[inline]
+    pub fn count16(&self) -> u16 {
+        RtcMode1::count(&self.rtc)
+    }
+
+    /// Sets the internal counter value.
+    #[inline]
+    pub fn set_count16(&mut self, count: u16) {
+        RtcMode1::disable(&self.rtc);
+        RtcMode1::set_count(&self.rtc, count);
+        RtcMode1::enable(&self.rtc);
+    }
+
+    /// Sets the period, the value at which the counter overflows and restarts
+    /// from zero.
+    #[inline]
+    pub fn set_period(&mut self, period: u16) {
+        RtcMode1::set_period(&self.rtc, period);
+    }
+
+    /// This resets the internal counter and sets the prescaler to match the
+    /// provided timeout.
+    fn reset_and_set_prescaler(&mut self, divider: Prescalerselect) {
+        RtcMode1::disable(&self.rtc);
+        RtcMode1::reset(&self.rtc);
+        RtcMode1::set_mode(&self.rtc);
+        RtcMode1::set_prescaler(&self.rtc, divider);
+    }
+
+    /// This resets the internal counter, sets the prescaler to match the
+    /// provided timeout, and starts the counter. You should configure the
+    /// prescaler using the longest timeout you plan to measure.
+    pub fn reset_and_compute_prescaler<T: Into<Nanoseconds>>(&mut self, timeout: T) -> &Self {
+        let params = TimerParams::new_us(timeout, self.rtc_clock_freq);
+        let divider = params.divider;
+
+        self.reset_and_set_prescaler(divider);
+        RtcMode1::start_and_initialize(&self.rtc);
+
+        self
+    }
+}
+
 impl Rtc<ClockMode> {
     pub fn clock_mode(rtc: pac::Rtc, rtc_clock_freq: Hertz, pm: &mut Pm) -> Self {
         Rtc::count32_mode(rtc, rtc_clock_freq, pm).into_clock_mode()
@@ -198,6 +450,42 @@ impl Rtc<ClockMode> {
     pub fn set_time(&mut self, time: Datetime) {
         RtcMode2::set_count(&self.rtc, time);
     }
+
+    /// Sets the alarm to trigger when the fields of `time` selected by `mask`
+    /// match the current time.
+    ///
+    /// For example, [`AlarmMask::Mmss`] triggers the alarm every hour, when
+    /// the minutes and seconds match. The alarm flag is cleared.
+    pub fn set_alarm(&mut self, time: Datetime, mask: AlarmMask) {
+        RtcMode2::set_compare(&self.rtc, 0, time);
+        RtcMode2::set_alarm_mask(&self.rtc, 0, mask);
+        RtcMode2::clear_interrupt_flag::<Alarm0>(&self.rtc);
+    }
+
+    /// Enables the alarm interrupt.
+    #[inline]
+    pub fn enable_alarm_interrupt(&mut self) {
+        RtcMode2::enable_interrupt::<Alarm0>(&self.rtc);
+    }
+
+    /// Disables the alarm interrupt.
+    #[inline]
+    pub fn disable_alarm_interrupt(&mut self) {
+        RtcMode2::disable_interrupt::<Alarm0>(&self.rtc);
+    }
+
+    /// Returns whether the alarm has triggered since its flag was last
+    /// cleared.
+    #[inline]
+    pub fn alarm_triggered(&self) -> bool {
+        RtcMode2::check_interrupt_flag::<Alarm0>(&self.rtc)
+    }
+
+    /// Clears the alarm flag.
+    #[inline]
+    pub fn clear_alarm(&mut self) {
+        RtcMode2::clear_interrupt_flag::<Alarm0>(&self.rtc);
+    }
 }
 
 // --- Timer / Counter Functionality
@@ -278,6 +566,113 @@ impl InterruptDrivenTimer for Rtc<Count32Mode> {
     }
 }
 
+impl ehal_02::timer::Periodic for Rtc<Count16Mode> {}
+impl ehal_02::timer::CountDown for Rtc<Count16Mode> {
+    type Time = Nanoseconds;
+
+    /// Starts the timer and puts it in periodic mode in which the counter
+    /// counts up to the specified `timeout` and then overflows repeatedly
+    /// back to zero.
+    fn start<T>(&mut self, timeout: T)
+    where
+        T: Into<Self::Time>,
+    {
+        <Self as InterruptDrivenTimer>::start(self, timeout);
+    }
+
+    fn wait(&mut self) -> nb::Result<(), void::Void> {
+        <Self as InterruptDrivenTimer>::wait(self).map_err(|e| e.map(|_| panic!()))
+    }
+}
+
+impl ehal::delay::DelayNs for Rtc<Count16Mode> {
+    fn delay_ns(&mut self, ns: u32) {
+        <Self as InterruptDrivenTimer>::start(self, ns.nanos());
+        // Note that this cannot error since the error type is `Infallible`
+        let _ = nb::block!(<Self as InterruptDrivenTimer>::wait(self));
+    }
+}
+
+impl InterruptDrivenTimer for Rtc<Count16Mode> {
+    /// Enable the interrupt generation for this hardware timer.
+    /// This method only sets the clock configuration to trigger
+    /// the interrupt; it does not configure the interrupt controller
+    /// or define an interrupt handler.
+    fn enable_interrupt(&mut self) {
+        RtcMode1::enable_interrupt::<Overflow>(&self.rtc);
+    }
+
+    /// Starts the timer and puts it in periodic mode in which the counter
+    /// counts up to the specified `timeout` and then overflows repeatedly
+    /// back to zero.
+    fn start<T>(&mut self, timeout: T)
+    where
+        T: Into<Nanoseconds>,
+    {
+        let params = TimerParams::new_us(timeout, self.rtc_clock_freq);
+        let divider = params.divider;
+        let cycles: u16 = params
+            .cycles
+            .try_into()
+            .expect("cannot achieve the timeout even with the maximum RTC prescaler");
+
+        // Reset and set prescaler, keeping the counter disabled
+        self.reset_and_set_prescaler(divider);
+        // Overflow at the desired time for periodicity
+        RtcMode1::set_period(&self.rtc, cycles);
+
+        // Start the counter
+        RtcMode1::start_and_initialize(&self.rtc);
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Infallible> {
+        if RtcMode1::check_interrupt_flag::<Overflow>(&self.rtc) {
+            // Clear the flag
+            RtcMode1::clear_interrupt_flag::<Overflow>(&self.rtc);
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    /// Disables interrupt generation for this hardware timer.
+    /// This method only sets the clock configuration to prevent
+    /// triggering the interrupt; it does not configure the interrupt
+    /// controller.
+    fn disable_interrupt(&mut self) {
+        RtcMode1::disable_interrupt::<Overflow>(&self.rtc);
+    }
+}
+
+impl<Mode: RtcMode> crate::pm::WakeupSource for Rtc<Mode> {
+    #[inline]
+    fn interrupt(&self) -> u16 {
+        use cortex_m::interrupt::InterruptNumber;
+        pac::Interrupt::RTC.number()
+    }
+
+    /// The RTC is armed as soon as one of its interrupts is enabled.
+    #[inline]
+    fn is_armed(&self) -> bool {
+        // The INTENSET register is at the same place in all modes
+        self.rtc.mode0().intenset().read().bits() != 0
+    }
+
+    /// On SAMx5x chips, the RTC is in the backup domain and always runs. On
+    /// SAMD11/21 chips, its generic clock generator must run in standby.
+    #[hal_macro_helper]
+    #[inline]
+    fn runs_in_standby(&self) -> bool {
+        #[hal_cfg("rtc-d5x")]
+        return true;
+        #[hal_cfg(any("rtc-d11", "rtc-d21"))]
+        return crate::clock::runs_in_standby(crate::clock::ClockId::Rtc);
+    }
+}
+
+#[hal_cfg("rtc-d5x")]
+impl<Mode: RtcMode> crate::pm::BackupWakeupSource for Rtc<Mode> {}
+
 #[cfg(feature = "sdmmc")]
 impl TimeSource for Rtc<ClockMode> {
     fn get_timestamp(&self) -> Timestamp {
@@ -356,6 +356,8 @@ pub mod mode0 {
     #[cfg(feature = "rtic")]
     #[hal_cfg("rtc-d5x")]
     create_rtc_interrupt!(mode0, Overflow, ovf);
+    #[hal_cfg("rtc-d5x")]
+    create_rtc_interrupt!(mode0, Tamper, tamper);
 
     /// The RTC operating in MODE0 (32-bit COUNT)
     pub struct RtcMode0;
@@ -433,20 +435,36 @@ pub mod mode0 {
 }
 
 /// Interface for using the RTC in MODE1 (16-bit COUNT)
-#[hal_cfg(any("rtc-d11", "rtc-d21"))]
-#[cfg(feature = "rtic")]
 pub mod mode1 {
     use super::*;
 
+    #[cfg(feature = "rtic")]
+    #[hal_cfg(any("rtc-d11", "rtc-d21"))]
     create_rtc_interrupt!(mode1, Compare0, cmp0);
     #[cfg(feature = "rtic")]
+    #[hal_cfg(any("rtc-d11", "rtc-d21"))]
     create_rtc_interrupt!(mode1, Compare1, cmp1);
-    #[cfg(feature = "rtic")]
     create_rtc_interrupt!(mode1, Overflow, ovf);
 
     /// The RTC operating in MODE1 (16-bit COUNT)
     pub struct RtcMode1;
 
+    impl RtcMode1 {
+        /// Sets the period, the value at which the counter overflows and
+        /// restarts from zero.
+        ///
+        /// # Safety
+        ///
+        /// Should be called only after setting the RTC mode using
+        /// [`set_mode`](RtcMode::set_mode).
+        #[inline]
+        pub fn set_period(rtc: &Rtc, period: u16) {
+            // SYNC: Write
+            Self::sync(rtc);
+            unsafe { rtc.mode1().per().write(|w| w.per().bits(period)) };
+        }
+    }
+
     impl RtcMode for RtcMode1 {
         type Count = u16;
 
@@ -462,9 +480,7 @@ pub mod mode1 {
             rtc.mode0().ctrla().modify(|_, w| w.mode().count16());
 
             // Set the mode 1 period
-            // SYNC: Write
-            Self::sync(rtc);
-            unsafe { rtc.mode1().per().write(|w| w.bits(0xFFFF)) };
+            Self::set_period(rtc, 0xFFFF);
         }
 
         #[inline]
@@ -509,10 +525,16 @@ pub mod mode1 {
 pub mod mode2 {
     use super::*;
 
-    // These actually aren't needed for anything right now
-    //create_rtc_interrupt!(mode2, Alarm0, alarm0);
+    create_rtc_interrupt!(mode2, Alarm0, alarm0);
+    // This actually isn't needed for anything right now
     //create_rtc_interrupt!(mode2, Alarm1, alarm1);
 
+    // Import alarm mask enum
+    #[hal_cfg(any("rtc-d11", "rtc-d21"))]
+    pub use crate::pac::rtc::mode2::mask::Selselect as AlarmMask;
+    #[hal_cfg("rtc-d5x")]
+    pub use crate::pac::rtc::mode2::mask0::Selselect as AlarmMask;
+
     /// Datetime represents an RTC clock/calendar value.
     #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
     pub struct Datetime {
@@ -549,6 +571,8 @@ pub mod mode2 {
     from_reg_datetime!(alarm0);
     #[hal_cfg("rtc-d5x")]
     from_reg_datetime!(alarm1);
+    #[hal_cfg("rtc-d5x")]
+    from_reg_datetime!(timestamp);
 
     /// Macro to write to the clock or alarm registers.
     macro_rules! write_datetime {
@@ -574,6 +598,34 @@ pub mod mode2 {
     /// The RTC operating in MODE2 (Clock/Calendar)
     pub struct RtcMode2;
 
+    impl RtcMode2 {
+        /// Sets the fields of an alarm which must match the clock for the
+        /// alarm to trigger.
+        ///
+        /// # Safety
+        ///
+        /// Should be called only after setting the RTC mode using
+        /// [`set_mode`](RtcMode::set_mode).
+        #[inline]
+        #[hal_macro_helper]
+        pub fn set_alarm_mask(rtc: &Rtc, _number: usize, mask: AlarmMask) {
+            // SYNC: Write
+            Self::sync(rtc);
+
+            #[hal_cfg(any("rtc-d11", "rtc-d21"))]
+            rtc.mode2().mask(0).write(|w| w.sel().variant(mask));
+            #[hal_cfg("rtc-d5x")]
+            if _number == 0 {
+                rtc.mode2().mask0().write(|w| w.sel().variant(mask));
+            } else {
+                // The MASK1 field has its own, identical, enum type
+                rtc.mode2()
+                    .mask1()
+                    .write(|w| unsafe { w.sel().bits(mask as u8) });
+            }
+        }
+    }
+
     impl RtcMode for RtcMode2 {
         type Count = Datetime;
 
@@ -0,0 +1,375 @@
+//! Tamper detection and backup registers (SAMx5x only)
+//!
+//! The RTC monitors up to five tamper inputs, `IN[0..4]`. On a tamper event,
+//! it raises the tamper interrupt, which wakes the device from any sleep mode,
+//! records which inputs were tampered, and optionally captures the time of
+//! the event and erases the backup registers.
+//!
+//! ```no_run
+//! let input = TamperPin::new(pins.pb00);
+//! rtc.set_tamper_debounce(Debounce::Stable, DebounceFreq::Div64);
+//! rtc.set_erase_on_tamper(true);
+//! rtc.configure_tamper(&input, TamperConfig::new(TamperAction::Capture).edge(TamperEdge::Rising));
+//! rtc.enable_tamper_interrupt();
+//!
+//! // Later, in the RTC interrupt handler
+//! if rtc.tampered().contains(TamperInputs::IN0) {
+//!     let time = rtc.tamper_timestamp();
+//!     rtc.clear_tamper(TamperInputs::all());
+//! }
+//! ```
+//!
+//! In active layer mode, the RTC drives a random pattern on its `OUT` pin,
+//! which is routed through a protective mesh back to a tamper input. A tamper
+//! event is detected when the input doesn't match the pattern, see
+//! [`Rtc::configure_active_layer`].
+//!
+//! The tamper inputs and the `OUT` pin are in the backup domain, and are
+//! controlled by the RTC instead of the PORT. The pins are disabled in the
+//! PORT as long as they are owned by a [`TamperPin`] or a [`TamperOut`].
+//!
+//! Resetting the RTC clears its tamper configuration, so the tamper inputs
+//! must be configured after selecting the RTC mode.
+//!
+//! ## Backup registers
+//!
+//! The eight 32-bit backup registers, `BKUP[0..7]`, keep their value in
+//! backup mode, and can be erased on a tamper event. See
+//! [`Rtc::backup_register`].
+
+use atsamd_hal_macros::hal_cfg;
+
+use super::modes::{
+    RtcMode as _,
+    mode0::{RtcMode0, Tamper},
+};
+use super::{ClockMode, Count32Mode, Datetime, Rtc, RtcMode};
+use crate::gpio::{AnyPin, FloatingDisabled, Pin, PinId};
+use crate::pac;
+
+pub use pac::rtc::mode0::ctrlb::Actfselect as ActiveLayerFreq;
+pub use pac::rtc::mode0::ctrlb::Debfselect as DebounceFreq;
+
+/// Number of backup registers
+pub const NUM_BACKUP_REGISTERS: usize = 8;
+
+bitflags::bitflags! {
+    /// Tamper inputs
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct TamperInputs: u32 {
+        const IN0 = 1 << 0;
+        const IN1 = 1 << 1;
+        const IN2 = 1 << 2;
+        const IN3 = 1 << 3;
+        const IN4 = 1 << 4;
+    }
+}
+
+/// Type-level trait for the [`PinId`]s of the tamper inputs
+pub trait TamperPinId: PinId {
+    /// Index `n` of the `IN[n]` input
+    const INPUT: usize;
+}
+
+/// A pin used as a tamper input
+pub struct TamperPin<I: TamperPinId> {
+    pin: Pin<I, FloatingDisabled>,
+}
+
+impl<I: TamperPinId> TamperPin<I> {
+    /// Hand `pin` over to the RTC
+    #[inline]
+    pub fn new(pin: impl AnyPin<Id = I>) -> Self {
+        Self {
+            pin: pin.into().into_floating_disabled(),
+        }
+    }
+
+    /// Return the underlying pin
+    #[inline]
+    pub fn free(self) -> Pin<I, FloatingDisabled> {
+        self.pin
+    }
+}
+
+/// The `OUT` pin of the RTC, driving the active layer pattern
+#[hal_cfg("pb01")]
+pub struct TamperOut {
+    pin: Pin<crate::gpio::PB01, FloatingDisabled>,
+}
+
+#[hal_cfg("pb01")]
+impl TamperOut {
+    /// Hand `pin` over to the RTC
+    #[inline]
+    pub fn new(pin: impl AnyPin<Id = crate::gpio::PB01>) -> Self {
+        Self {
+            pin: pin.into().into_floating_disabled(),
+        }
+    }
+
+    /// Return the underlying pin
+    #[inline]
+    pub fn free(self) -> Pin<crate::gpio::PB01, FloatingDisabled> {
+        self.pin
+    }
+}
+
+#[hal_cfg("pb00")]
+impl TamperPinId for crate::gpio::PB00 {
+    const INPUT: usize = 0;
+}
+
+#[hal_cfg("pb02")]
+impl TamperPinId for crate::gpio::PB02 {
+    const INPUT: usize = 1;
+}
+
+#[hal_cfg("pa02")]
+impl TamperPinId for crate::gpio::PA02 {
+    const INPUT: usize = 2;
+}
+
+#[hal_cfg("pc00")]
+impl TamperPinId for crate::gpio::PC00 {
+    const INPUT: usize = 3;
+}
+
+#[hal_cfg("pc01")]
+impl TamperPinId for crate::gpio::PC01 {
+    const INPUT: usize = 4;
+}
+
+/// Action taken on a tamper event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TamperAction {
+    /// Raise the tamper interrupt, which wakes the device
+    Wake,
+    /// Raise the tamper interrupt and capture the time of the event, see
+    /// [`Rtc::tamper_timestamp`]
+    Capture,
+}
+
+/// Edge of a tamper input which triggers a tamper event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TamperEdge {
+    /// Trigger on a falling edge of the input
+    Falling,
+    /// Trigger on a rising edge of the input
+    Rising,
+}
+
+/// Debouncing of the tamper inputs, when enabled with
+/// [`TamperConfig::debounce`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Debounce {
+    /// An edge is detected once the input is stable for three samples
+    Stable,
+    /// An edge is detected once two of three samples agree
+    Majority,
+    /// An edge is detected immediately, and the following edges are ignored
+    /// until the input is stable for three samples
+    Asynchronous,
+}
+
+/// Configuration of a tamper input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TamperConfig {
+    action: TamperAction,
+    edge: TamperEdge,
+    debounce: bool,
+}
+
+impl TamperConfig {
+    /// Create a configuration with `action`, triggered on a falling edge
+    /// without debouncing
+    #[inline]
+    pub const fn new(action: TamperAction) -> Self {
+        Self {
+            action,
+            edge: TamperEdge::Falling,
+            debounce: false,
+        }
+    }
+
+    /// Set the edge triggering a tamper event
+    #[inline]
+    pub const fn edge(mut self, edge: TamperEdge) -> Self {
+        self.edge = edge;
+        self
+    }
+
+    /// Debounce the input, as configured with [`Rtc::set_tamper_debounce`]
+    #[inline]
+    pub const fn debounce(mut self, debounce: bool) -> Self {
+        self.debounce = debounce;
+        self
+    }
+}
+
+/// Value of the `TAMPCTRL.INnACT` fields
+const ACT_OFF: u32 = 0;
+const ACT_WAKE: u32 = 1;
+const ACT_CAPTURE: u32 = 2;
+#[hal_cfg("pb01")]
+const ACT_ACTL: u32 = 3;
+
+impl<Mode: RtcMode> Rtc<Mode> {
+    /// Write the action, level and debouncing fields of tamper input `n`
+    fn write_tampctrl(&mut self, n: usize, action: u32, rising: bool, debounce: bool) {
+        let mask = (0b11 << (2 * n)) | (1 << (16 + n)) | (1 << (24 + n));
+        let value =
+            (action << (2 * n)) | ((rising as u32) << (16 + n)) | ((debounce as u32) << (24 + n));
+        self.with_disabled(|rtc| {
+            // SYNC: None
+            rtc.tampctrl()
+                .modify(|r, w| unsafe { w.bits((r.bits() & !mask) | value) });
+        });
+    }
+
+    /// Configure the tamper input of `pin`.
+    ///
+    /// The RTC is briefly stopped to change its configuration.
+    pub fn configure_tamper<I: TamperPinId>(&mut self, _pin: &TamperPin<I>, config: TamperConfig) {
+        let action = match config.action {
+            TamperAction::Wake => ACT_WAKE,
+            TamperAction::Capture => ACT_CAPTURE,
+        };
+        self.write_tampctrl(
+            I::INPUT,
+            action,
+            config.edge == TamperEdge::Rising,
+            config.debounce,
+        );
+    }
+
+    /// Compare the tamper input of `pin` with the active layer pattern driven
+    /// on `out`, toggling at the RTC clock frequency divided by `freq`.
+    ///
+    /// The RTC is briefly stopped to change its configuration.
+    #[hal_cfg("pb01")]
+    pub fn configure_active_layer<I: TamperPinId>(
+        &mut self,
+        _pin: &TamperPin<I>,
+        _out: &TamperOut,
+        freq: ActiveLayerFreq,
+        debounce: bool,
+    ) {
+        self.with_disabled(|rtc| {
+            // SYNC: None
+            rtc.ctrlb().modify(|_, w| {
+                w.actf().variant(freq);
+                w.rtcout().set_bit()
+            });
+        });
+        self.write_tampctrl(I::INPUT, ACT_ACTL, false, debounce);
+    }
+
+    /// Stop monitoring the tamper input of `pin`.
+    pub fn disable_tamper<I: TamperPinId>(&mut self, _pin: &TamperPin<I>) {
+        self.write_tampctrl(I::INPUT, ACT_OFF, false, false);
+    }
+
+    /// Configure the debouncing of the tamper inputs, sampled at the RTC clock
+    /// frequency divided by `freq`.
+    ///
+    /// Debouncing is enabled for each input with [`TamperConfig::debounce`].
+    pub fn set_tamper_debounce(&mut self, debounce: Debounce, freq: DebounceFreq) {
+        self.with_disabled(|rtc| {
+            // SYNC: None
+            rtc.ctrlb().modify(|_, w| {
+                w.debf().variant(freq);
+                w.debmaj().bit(debounce == Debounce::Majority);
+                w.debasync().bit(debounce == Debounce::Asynchronous)
+            });
+        });
+    }
+
+    /// Erase the backup registers on a tamper event
+    pub fn set_erase_on_tamper(&mut self, erase: bool) {
+        self.with_disabled(|rtc| {
+            // SYNC: None
+            rtc.ctrla().modify(|_, w| w.bktrst().bit(erase));
+        });
+    }
+
+    /// Enable the tamper interrupt
+    #[inline]
+    pub fn enable_tamper_interrupt(&mut self) {
+        RtcMode0::enable_interrupt::<Tamper>(&self.rtc);
+    }
+
+    /// Disable the tamper interrupt
+    #[inline]
+    pub fn disable_tamper_interrupt(&mut self) {
+        RtcMode0::disable_interrupt::<Tamper>(&self.rtc);
+    }
+
+    /// Returns the inputs on which a tamper event was detected since they were
+    /// last cleared
+    #[inline]
+    pub fn tampered(&self) -> TamperInputs {
+        // SYNC: None
+        TamperInputs::from_bits_truncate(self.rtc.mode0().tampid().read().bits())
+    }
+
+    /// Clear the tamper events of `inputs`, and the tamper interrupt flag
+    #[inline]
+    pub fn clear_tamper(&mut self, inputs: TamperInputs) {
+        // SYNC: None
+        self.rtc
+            .mode0()
+            .tampid()
+            .write(|w| unsafe { w.bits(inputs.bits()) });
+        RtcMode0::clear_interrupt_flag::<Tamper>(&self.rtc);
+    }
+
+    /// Read the backup register `BKUP[index]`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not lower than [`NUM_BACKUP_REGISTERS`].
+    #[inline]
+    pub fn backup_register(&self, index: usize) -> u32 {
+        // SYNC: None
+        self.rtc.mode0().bkup(index).read().bits()
+    }
+
+    /// Write the backup register `BKUP[index]`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not lower than [`NUM_BACKUP_REGISTERS`].
+    #[inline]
+    pub fn set_backup_register(&mut self, index: usize, value: u32) {
+        // SYNC: None
+        self.rtc
+            .mode0()
+            .bkup(index)
+            .write(|w| unsafe { w.bits(value) });
+    }
+}
+
+impl Rtc<Count32Mode> {
+    /// Returns the counter value captured on the last tamper event with the
+    /// [`Capture`](TamperAction::Capture) action
+    #[inline]
+    pub fn tamper_timestamp(&self) -> u32 {
+        // SYNC: None
+        self.rtc.mode0().timestamp().read().bits()
+    }
+}
+
+impl Rtc<ClockMode> {
+    /// Returns the time captured on the last tamper event with the
+    /// [`Capture`](TamperAction::Capture) action
+    #[inline]
+    pub fn tamper_timestamp(&self) -> Datetime {
+        // SYNC: None
+        self.rtc.mode2().timestamp().read().into()
+    }
+}
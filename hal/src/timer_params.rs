@@ -25,15 +25,30 @@ impl TimerParams {
     /// Panics if the combination of `timeout` and `src_freq` cannot be done
     /// with a 16-bit timer.
     pub fn new_ns(timeout: Nanoseconds, src_freq: Hertz) -> Self {
+        Self::new_ns_max(timeout, src_freq, u16::MAX as u32)
+    }
+
+    /// Calculates the [`TimerParams`] from a given period based timeout, for
+    /// a counter whose largest value is `max`.
+    ///
+    /// Panics if the combination of `timeout` and `src_freq` cannot be done
+    /// with a counter up to `max`.
+    pub fn new_ns_max(timeout: Nanoseconds, src_freq: Hertz, max: u32) -> Self {
         let ticks: u32 =
             (timeout.to_nanos() as u64 * src_freq.to_Hz() as u64 / 1_000_000_000_u64) as u32;
-        let ret = Self::new_from_ticks(ticks);
-        ret.check_cycles_u16();
+        let ret = Self::new_from_ticks_max(ticks, max);
+        ret.check_cycles(max);
         ret
     }
 
     pub(crate) fn new_from_ticks(ticks: u32) -> Self {
-        let divider = ((ticks >> 16) + 1).next_power_of_two();
+        Self::new_from_ticks_max(ticks, u16::MAX as u32)
+    }
+
+    /// Calculates the [`TimerParams`] for a counter whose largest value is
+    /// `max`, using the smallest divider which makes `cycles` fit.
+    pub(crate) fn new_from_ticks_max(ticks: u32, max: u32) -> Self {
+        let divider = (ticks as u64 / (max as u64 + 1) + 1).next_power_of_two();
         let divider = match divider {
             1 | 2 | 4 | 8 | 16 | 64 | 256 | 1024 => divider,
             // There are a couple of gaps, so we round up to the next largest
@@ -46,7 +61,7 @@ impl TimerParams {
             _ => 1024,
         };
 
-        let cycles: u32 = ticks / divider;
+        let cycles: u32 = ticks / divider as u32;
 
         TimerParams {
             divider: divider as u16,
@@ -54,6 +69,18 @@ impl TimerParams {
         }
     }
 
+    /// Returns the number of required `cycles`, and panics if the number is
+    /// greater than `max`.
+    pub(crate) fn check_cycles(&self, max: u32) -> u32 {
+        if self.cycles > max {
+            panic!(
+                "cycles {} is out of range for a counter up to {}",
+                self.cycles, max
+            );
+        }
+        self.cycles
+    }
+
     /// Returns the number of required `cycles` as a `u16` and panics if the
     /// number is too high to fit.
     pub(crate) fn check_cycles_u16(&self) -> u16 {
@@ -91,4 +118,38 @@ mod tests {
         assert_eq!(tp_from_hz.divider, tp_from_us.divider);
         assert!((tp_from_hz.cycles as i32 - tp_from_us.cycles as i32).abs() <= 1);
     }
+
+    #[test]
+    fn timer_params_counter_width() {
+        // 1 s at 48 MHz needs a 1024 divider with 16 bits, but none with 32 bits
+        let tp_16 = TimerParams::new_from_ticks_max(48_000_000, u16::MAX as u32);
+        assert_eq!(tp_16.divider, 1024);
+        assert_eq!(tp_16.cycles, 46_875);
+
+        let tp_32 = TimerParams::new_from_ticks_max(48_000_000, u32::MAX);
+        assert_eq!(tp_32.divider, 1);
+        assert_eq!(tp_32.cycles, 48_000_000);
+
+        let tp_8 = TimerParams::new_from_ticks_max(1000, u8::MAX as u32);
+        assert_eq!(tp_8.divider, 4);
+        assert_eq!(tp_8.cycles, 250);
+    }
+
+    #[test]
+    fn timer_params_ns_counter_width() {
+        let tp_16 = TimerParams::new_ns_max(1_000_000.micros(), 48.MHz(), u16::MAX as u32);
+        assert_eq!(tp_16.divider, 1024);
+        assert_eq!(tp_16.cycles, 46_875);
+
+        let tp_32 = TimerParams::new_ns_max(1_000_000.micros(), 48.MHz(), u32::MAX);
+        assert_eq!(tp_32.divider, 1);
+        assert_eq!(tp_32.cycles, 48_000_000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn timer_params_ns_out_of_range() {
+        // 1 s at 48 MHz doesn't fit an 8-bit counter, even with a 1024 divider
+        TimerParams::new_ns_max(1_000_000.micros(), 48.MHz(), u8::MAX as u32);
+    }
 }